@@ -2,12 +2,12 @@
 // Lightweight git status display for skretchpad status bar
 // Runs in deno_core V8 sandbox — uses globalThis.skretchpad API
 
-onActivate(function () {
+onActivate(async function () {
   // Show a simple status indicator
   skretchpad.ui.setStatusBarItem('git-status', 'git: ...', 'Loading git status');
 
   try {
-    var result = skretchpad.commands.execute('git', ['branch', '--show-current']);
+    var result = await skretchpad.commands.executeAsync('git', ['branch', '--show-current']);
     if (result?.stdout) {
       var branch = result.stdout.trim();
       if (branch) {
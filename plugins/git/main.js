@@ -3,13 +3,13 @@
 // Runs in deno_core V8 sandbox — uses globalThis.skretchpad API
 
 // Register activation hook
-onActivate(function () {
+onActivate(async function () {
   // Set status bar with current branch info
   skretchpad.ui.setStatusBarItem('git-branch', 'git: main', 'Git branch');
 
   // Try to get current branch via git command
   try {
-    var result = skretchpad.commands.execute('git', ['branch', '--show-current']);
+    var result = await skretchpad.commands.executeAsync('git', ['branch', '--show-current']);
     if (result?.stdout) {
       var branch = result.stdout.trim();
       if (branch) {
@@ -28,10 +28,10 @@ onDeactivate(function () {
 });
 
 // Register hooks for file events
-registerHook('on_file_save', function () {
+registerHook('on_file_save', async function () {
   // Update git status when a file is saved
   try {
-    var result = skretchpad.commands.execute('git', ['status', '--porcelain']);
+    var result = await skretchpad.commands.executeAsync('git', ['status', '--porcelain']);
     if (result?.stdout) {
       var lines = result.stdout.split('\n').filter(function (l) { return l.trim(); });
       var changes = lines.length;
@@ -48,9 +48,9 @@ registerHook('on_file_open', function () {
   // Could show git blame info in the future
 });
 
-registerHook('command:git.status', function () {
+registerHook('command:git.status', async function () {
   try {
-    var result = skretchpad.commands.execute('git', ['status', '--short', '--branch']);
+    var result = await skretchpad.commands.executeAsync('git', ['status', '--short', '--branch']);
     var summary = result?.stdout ? result.stdout.split('\n')[0] : 'status unavailable';
     skretchpad.ui.showNotification(summary || 'status unavailable', 'info');
   } catch (e) {
@@ -58,9 +58,9 @@ registerHook('command:git.status', function () {
   }
 });
 
-registerHook('command:git.diff', function () {
+registerHook('command:git.diff', async function () {
   try {
-    var result = skretchpad.commands.execute('git', ['diff', '--stat']);
+    var result = await skretchpad.commands.executeAsync('git', ['diff', '--stat']);
     var summary = result?.stdout ? result.stdout.split('\n')[0] : 'no diff output';
     skretchpad.ui.showNotification(summary || 'no diff output', 'info');
   } catch (e) {
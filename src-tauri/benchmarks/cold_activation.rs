@@ -0,0 +1,140 @@
+// src-tauri/benchmarks/cold_activation.rs
+//
+// Cold activation of the bundled plugins, from the plugin startup snapshot and
+// from a fresh runtime that executes js/plugin_api.js (the pre-snapshot path).
+//
+// Run with `cargo bench --bench cold_activation`. Set SKRETCHPAD_BENCH_ITERATIONS
+// to change the sample count (default 50).
+
+use skretchpad_lib::plugin_system::module_loader::PluginModuleLoader;
+use skretchpad_lib::plugin_system::ops::skretchpad_plugin_ops;
+use skretchpad_lib::plugin_system::snapshot::{extensions, startup_snapshot};
+use skretchpad_lib::plugin_system::transpile::SourceMapStore;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ITERATIONS: usize = 50;
+const PLUGINS: [&str; 2] = ["git", "git-status"];
+
+fn loader(root: &Path) -> Rc<dyn deno_core::ModuleLoader> {
+    Rc::new(PluginModuleLoader::new(
+        root.to_path_buf(),
+        SourceMapStore::default(),
+    ))
+}
+
+fn snapshot_runtime(root: &Path) -> deno_core::JsRuntime {
+    deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+        module_loader: Some(loader(root)),
+        startup_snapshot: Some(startup_snapshot()),
+        extensions: extensions(),
+        ..Default::default()
+    })
+}
+
+fn scripted_runtime(root: &Path) -> deno_core::JsRuntime {
+    let mut runtime = deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+        module_loader: Some(loader(root)),
+        extensions: vec![skretchpad_plugin_ops::init_ops_and_esm()],
+        ..Default::default()
+    });
+    runtime
+        .execute_script(
+            "plugin_api.js",
+            deno_core::FastString::Static(include_str!("../js/plugin_api.js")),
+        )
+        .expect("plugin_api.js failed to evaluate");
+    runtime
+}
+
+/// Create a runtime and evaluate the plugin's entry module; returns the wall time.
+fn activate(
+    tokio_rt: &tokio::runtime::Runtime,
+    new_runtime: fn(&Path) -> deno_core::JsRuntime,
+    root: &Path,
+) -> Duration {
+    let specifier = deno_core::ModuleSpecifier::from_file_path(root.join("main.js")).unwrap();
+    let started = Instant::now();
+    let mut runtime = new_runtime(root);
+    tokio_rt
+        .block_on(async {
+            let id = runtime.load_main_module(&specifier, None).await?;
+            let evaluation = runtime.mod_evaluate(id);
+            runtime.run_event_loop(false).await?;
+            evaluation.await
+        })
+        .expect("plugin activation failed");
+    drop(runtime);
+    started.elapsed()
+}
+
+struct Summary {
+    mean: Duration,
+    median: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl Summary {
+    fn of(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let total: Duration = samples.iter().sum();
+        Self {
+            mean: total / samples.len() as u32,
+            median: samples[samples.len() / 2],
+            min: samples[0],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+fn main() {
+    let iterations = std::env::var("SKRETCHPAD_BENCH_ITERATIONS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|&count: &usize| count > 0)
+        .unwrap_or(DEFAULT_ITERATIONS);
+    let plugins_dir: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../plugins")
+        .canonicalize()
+        .expect("bundled plugins directory not found");
+    let tokio_rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let _guard = tokio_rt.enter();
+
+    println!(
+        "{:<12} {:<14} {:>10} {:>10} {:>10} {:>10}",
+        "plugin", "startup", "mean", "median", "min", "max"
+    );
+    for plugin in PLUGINS {
+        let root = plugins_dir.join(plugin);
+        // One untimed activation of each kind so file caches are warm for both.
+        activate(&tokio_rt, scripted_runtime, &root);
+        activate(&tokio_rt, snapshot_runtime, &root);
+
+        let mut scripted = Vec::with_capacity(iterations);
+        let mut snapshotted = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            scripted.push(activate(&tokio_rt, scripted_runtime, &root));
+            snapshotted.push(activate(&tokio_rt, snapshot_runtime, &root));
+        }
+
+        let scripted = Summary::of(scripted);
+        let snapshotted = Summary::of(snapshotted);
+        for (startup, summary) in [("plugin_api.js", &scripted), ("snapshot", &snapshotted)] {
+            println!(
+                "{:<12} {:<14} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+                plugin, startup, summary.mean, summary.median, summary.min, summary.max
+            );
+        }
+        println!(
+            "{:<12} {:<14} {:>10.2}x",
+            plugin,
+            "speedup",
+            scripted.mean.as_secs_f64() / snapshotted.mean.as_secs_f64()
+        );
+    }
+}
@@ -1,3 +1,29 @@
+use std::path::PathBuf;
+
+#[path = "src/plugin_system/plugin_api_ext.rs"]
+mod plugin_api_ext;
+
 fn main() {
+    build_plugin_snapshot();
     tauri_build::build()
 }
+
+/// Run js/plugin_api.js once and save the resulting V8 heap, so plugin isolates
+/// start with the API already in place (see `plugin_system::snapshot`).
+fn build_plugin_snapshot() {
+    println!("cargo:rerun-if-changed=js/plugin_api.js");
+    println!("cargo:rerun-if-changed=src/plugin_system/plugin_api_ext.rs");
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    deno_core::snapshot_util::create_snapshot(deno_core::snapshot_util::CreateSnapshotOptions {
+        cargo_manifest_dir: env!("CARGO_MANIFEST_DIR"),
+        snapshot_path: out_dir.join("PLUGIN_SNAPSHOT.bin"),
+        startup_snapshot: None,
+        // Ops are bound when each plugin runtime starts; plugin_api.js only
+        // reaches them through Deno.core.ops at call time.
+        skip_op_registration: true,
+        extensions: vec![plugin_api_ext::skretchpad_plugin_api::init_ops_and_esm()],
+        compression_cb: None,
+        with_runtime_cb: None,
+    });
+}
@@ -3,9 +3,11 @@
 // This API is available to plugins running in the V8 sandbox.
 // API calls use deno_core ops to execute actual Rust operations
 // with capability validation enforced on the Rust side.
+// `*Async` variants return Promises and do not block the plugin's worker thread.
 
 // Hook registration system
-globalThis.__hooks__ = {};
+// Null-prototype so inherited members are never mistaken for hooks
+globalThis.__hooks__ = Object.create(null);
 
 // Plugin lifecycle registration
 globalThis.registerHook = function(hookName, handler) {
@@ -32,6 +34,14 @@ globalThis.skretchpad = {
       }
     },
 
+    async readFileAsync(path) {
+      try {
+        return await Deno.core.opAsync('op_plugin_read_file_async', path);
+      } catch (e) {
+        throw new Error(`fs.readFileAsync: ${e?.message ?? e}`);
+      }
+    },
+
     writeFile(path, content) {
       try {
         Deno.core.ops.op_plugin_write_file(path, content);
@@ -62,6 +72,19 @@ globalThis.skretchpad = {
         throw new Error(`network.fetch: ${e?.message ?? e}`);
       }
     },
+
+    async fetchAsync(url, options) {
+      try {
+        return await Deno.core.opAsync('op_plugin_fetch_async', {
+          url,
+          method: options?.method,
+          headers: options?.headers,
+          body: options?.body,
+        });
+      } catch (e) {
+        throw new Error(`network.fetchAsync: ${e?.message ?? e}`);
+      }
+    },
   },
 
   commands: {
@@ -72,6 +95,29 @@ globalThis.skretchpad = {
         throw new Error(`commands.execute: ${e?.message ?? e}`);
       }
     },
+
+    async executeAsync(command, args) {
+      try {
+        return await Deno.core.opAsync('op_plugin_execute_command_async', command, args || []);
+      } catch (e) {
+        throw new Error(`commands.executeAsync: ${e?.message ?? e}`);
+      }
+    },
+  },
+
+  permissions: {
+    // kind: 'filesystem_read' | 'filesystem_write' | 'network' | 'command' | 'ui'
+    async request(kind, target, reason) {
+      try {
+        return await Deno.core.opAsync(
+          'op_plugin_request_permission',
+          { kind, target },
+          reason || ''
+        );
+      } catch (e) {
+        throw new Error(`permissions.request: ${e?.message ?? e}`);
+      }
+    },
   },
 
   ui: {
@@ -92,6 +138,31 @@ globalThis.skretchpad = {
     },
   },
 
+  // Live audit events of every plugin; needs `audit = ["read"]` under [permissions]
+  audit: {
+    // `onEvent` (or a hook registered as 'audit') is called with each event
+    // from other plugins as it is recorded
+    subscribe(onEvent) {
+      try {
+        Deno.core.ops.op_plugin_audit_subscribe();
+      } catch (e) {
+        throw new Error(`audit.subscribe: ${e?.message ?? e}`);
+      }
+      if (onEvent) {
+        globalThis.__hooks__.audit = onEvent;
+      }
+    },
+
+    // Returns { events, dropped } with the events recorded since the last poll
+    poll() {
+      try {
+        return Deno.core.ops.op_plugin_audit_poll();
+      } catch (e) {
+        throw new Error(`audit.poll: ${e?.message ?? e}`);
+      }
+    },
+  },
+
   editor: {
     getActiveFile() {
       try {
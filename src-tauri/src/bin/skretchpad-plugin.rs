@@ -0,0 +1,801 @@
+// src-tauri/src/bin/skretchpad-plugin.rs
+//
+// Command-line tooling for plugin authors: scaffold a plugin, check its manifest
+// the way the app will, sign it for `trust = "verified"`, and run its hooks
+// headless against a fixture workspace. Built on the app's plugin system library.
+
+use base64::Engine;
+use ed25519_dalek::SigningKey;
+use serde::Serialize;
+use skretchpad_lib::plugin_system::{
+    audit::{AuditLogger, DEFAULT_MAX_EVENTS},
+    capabilities::{FilesystemCapability, NetworkCapability, PluginCapabilities},
+    consent::ConsentBroker,
+    host::RecordingHost,
+    loader::{PluginLoader, PluginManifest},
+    manager::PluginManager,
+    policy::PolicyEngine,
+    sandbox::{LimitsPolicy, SandboxRegistry},
+    trust::{
+        build_plugin_signature_payload, encode_public_key, sign_plugin, PluginSignature,
+        TrustLevel, TrustVerifier,
+    },
+};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: skretchpad-plugin <command> [options]
+
+Commands:
+  new <dir> [--name <name>]              Scaffold plugin.toml and main.js in <dir>
+  validate <plugin-dir>                  Parse the manifest and summarize its capabilities
+  keygen <key-file>                      Write a new Ed25519 signing key and print its public key
+  sign <plugin-dir> --key <key-file>     Sign the plugin and store the signature in plugin.toml
+  verify <plugin-dir> [--trusted-keys <file>]
+                                         Check the plugin's signature (against a trusted key list)
+  run <plugin-dir> --workspace <dir> [--trusted-keys <file>] [--hook <name>[=<json>]]...
+                                         Activate the plugin headless and call its hooks";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    };
+
+    let result = Args::parse(rest).and_then(|args| match command.as_str() {
+        "new" => cmd_new(&args),
+        "validate" => cmd_validate(&args),
+        "keygen" => cmd_keygen(&args),
+        "sign" => cmd_sign(&args),
+        "verify" => cmd_verify(&args),
+        "run" => cmd_run(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    });
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+// ============================================================================
+// ARGUMENTS
+// ============================================================================
+
+/// Positional arguments plus `--name value` options (options may repeat).
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("Option --{} needs a value", name))?;
+                options.push((name.to_string(), value.clone()));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn positional(&self, index: usize, what: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Missing {}\n\n{}", what, USAGE))
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn options(&self, name: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+}
+
+/// A plugin directory split into the loader's plugins dir and the plugin id.
+struct PluginDir {
+    plugins_dir: PathBuf,
+    plugin_id: String,
+    path: PathBuf,
+}
+
+impl PluginDir {
+    fn open(dir: &str) -> Result<Self, String> {
+        let path = Path::new(dir)
+            .canonicalize()
+            .map_err(|e| format!("Cannot open plugin directory '{}': {}", dir, e))?;
+        let plugin_id = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| format!("'{}' has no usable directory name", path.display()))?
+            .to_string();
+        let plugins_dir = path
+            .parent()
+            .ok_or_else(|| format!("'{}' has no parent directory", path.display()))?
+            .to_path_buf();
+        Ok(Self {
+            plugins_dir,
+            plugin_id,
+            path,
+        })
+    }
+
+    /// Parse plugin.toml exactly as the app does at startup.
+    fn load_manifest(&self) -> Result<PluginManifest, String> {
+        PluginLoader::new(self.plugins_dir.clone())
+            .load_manifest(&self.plugin_id)
+            .map_err(|e| format!("{}: {}", self.path.join("plugin.toml").display(), e))
+    }
+}
+
+// ============================================================================
+// NEW
+// ============================================================================
+
+fn cmd_new(args: &Args) -> Result<(), String> {
+    let dir = PathBuf::from(args.positional(0, "plugin directory")?);
+    let name = match args.option("name") {
+        Some(name) => name.to_string(),
+        None => dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| format!("Cannot derive a plugin name from '{}'", dir.display()))?
+            .to_string(),
+    };
+    // The name is interpolated into both scaffold files
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(format!(
+            "Invalid plugin name '{}': use letters, digits, '-', '_' and '.'",
+            name.escape_default()
+        ));
+    }
+
+    for file in ["plugin.toml", "main.js"] {
+        if dir.join(file).exists() {
+            return Err(format!("{} already exists", dir.join(file).display()));
+        }
+    }
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    write_file(&dir.join("plugin.toml"), &scaffold_manifest(&name))?;
+    write_file(&dir.join("main.js"), &scaffold_script(&name))?;
+
+    println!("Created plugin '{}' in {}", name, dir.display());
+    println!(
+        "Next: skretchpad-plugin validate {0} && skretchpad-plugin run {0} --workspace <dir>",
+        dir.display()
+    );
+    Ok(())
+}
+
+fn scaffold_manifest(name: &str) -> String {
+    format!(
+        r#"# {name}/plugin.toml
+name = "{name}"
+version = "0.1.0"
+author = ""
+description = ""
+main = "main.js"
+trust = "community"
+
+# Everything is denied unless requested here; see plugins/git/plugin.toml
+[permissions]
+filesystem = "None"
+network = "None"
+
+[ui]
+status_bar = false
+sidebar = false
+notifications = true
+"#
+    )
+}
+
+fn scaffold_script(name: &str) -> String {
+    format!(
+        r#"// {name}/main.js
+// Runs in the skretchpad V8 sandbox; the plugin API is on globalThis.skretchpad
+
+onActivate(function () {{
+  skretchpad.ui.showNotification('{name} activated', 'info');
+}});
+
+registerHook('on_file_save', function (event) {{
+  return {{ saved: event?.path ?? null }};
+}});
+
+onDeactivate(function () {{}});
+"#
+    )
+}
+
+// ============================================================================
+// VALIDATE
+// ============================================================================
+
+fn cmd_validate(args: &Args) -> Result<(), String> {
+    let plugin = PluginDir::open(args.positional(0, "plugin directory")?)?;
+    let manifest = plugin.load_manifest()?;
+
+    let entrypoint = plugin.path.join(&manifest.main);
+    if !entrypoint.is_file() {
+        return Err(format!(
+            "Entrypoint '{}' does not exist",
+            entrypoint.display()
+        ));
+    }
+
+    println!(
+        "{} {} ({})",
+        manifest.name,
+        manifest.version,
+        trust_label(&manifest.trust)
+    );
+    println!("  entrypoint:    {}", manifest.main);
+    for line in capability_summary(&manifest.capabilities) {
+        println!("  {}", line);
+    }
+    let limits = LimitsPolicy::default().effective_limits(&manifest);
+    println!(
+        "  limits:        {} MB memory, {} ms per call, {} ops/s, {} MB fetch bodies, {} concurrent commands",
+        limits.max_memory / (1024 * 1024),
+        limits.max_cpu_time.as_millis(),
+        limits.max_operations,
+        limits.max_fetch_body_size / (1024 * 1024),
+        limits.max_concurrent_commands
+    );
+    if !manifest.dependencies.is_empty() {
+        println!("  dependencies:  {}", manifest.dependencies.join(", "));
+    }
+
+    match (&manifest.signature, manifest.trust.requires_signature()) {
+        (Some(_), _) => println!("  signature:     present (check with `verify`)"),
+        (None, true) => {
+            return Err("trust = \"verified\" requires a signature; run `sign` first".to_string())
+        }
+        (None, false) => println!("  signature:     none"),
+    }
+    println!("OK");
+    Ok(())
+}
+
+fn trust_label(trust: &TrustLevel) -> &'static str {
+    match trust {
+        TrustLevel::FirstParty => "first-party",
+        TrustLevel::Verified => "verified",
+        TrustLevel::Community => "community",
+        TrustLevel::Local => "local",
+    }
+}
+
+fn capability_summary(caps: &PluginCapabilities) -> Vec<String> {
+    let filesystem = match &caps.filesystem {
+        FilesystemCapability::None => "none".to_string(),
+        FilesystemCapability::WorkspaceRead => "read workspace".to_string(),
+        FilesystemCapability::WorkspaceReadWrite => "read/write workspace".to_string(),
+        FilesystemCapability::Scoped { read, write, deny } => format!(
+            "read [{}], write [{}], deny [{}]",
+            sorted(read).join(", "),
+            sorted(write).join(", "),
+            sorted(deny).join(", ")
+        ),
+    };
+    let network = match &caps.network {
+        NetworkCapability::None => "none".to_string(),
+        NetworkCapability::DomainAllowlist(rules) => sorted(rules).join(", "),
+        NetworkCapability::Unrestricted => "UNRESTRICTED".to_string(),
+    };
+    let commands = if caps.commands.allowlist.is_empty() {
+        "none".to_string()
+    } else {
+        format!(
+            "{}{}",
+            sorted(&caps.commands.allowlist).join(", "),
+            if caps.commands.require_confirmation {
+                " (asks for confirmation)"
+            } else {
+                ""
+            }
+        )
+    };
+    let ui: Vec<&str> = [
+        ("status_bar", caps.ui.status_bar),
+        ("sidebar", caps.ui.sidebar),
+        ("notifications", caps.ui.notifications),
+        ("webview", caps.ui.webview),
+    ]
+    .into_iter()
+    .filter(|(_, on)| *on)
+    .map(|(name, _)| name)
+    .collect();
+
+    vec![
+        format!("filesystem:    {}", filesystem),
+        format!("network:       {}", network),
+        format!("commands:      {}", commands),
+        format!(
+            "ui:            {}",
+            if ui.is_empty() {
+                "none".to_string()
+            } else {
+                ui.join(", ")
+            }
+        ),
+        format!(
+            "audit:         {}",
+            if caps.audit.read { "read" } else { "none" }
+        ),
+    ]
+}
+
+fn sorted(set: &std::collections::HashSet<String>) -> Vec<String> {
+    let mut items: Vec<String> = set.iter().cloned().collect();
+    items.sort();
+    items
+}
+
+// ============================================================================
+// KEYGEN / SIGN / VERIFY
+// ============================================================================
+
+fn cmd_keygen(args: &Args) -> Result<(), String> {
+    let path = PathBuf::from(args.positional(0, "key file")?);
+
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed).map_err(|e| format!("Failed to generate key: {}", e))?;
+    let signing_key = SigningKey::from_bytes(&seed);
+
+    write_secret_file(
+        &path,
+        &format!(
+            "{}\n",
+            base64::engine::general_purpose::STANDARD.encode(signing_key.to_bytes())
+        ),
+    )?;
+
+    println!("Wrote signing key to {} (keep it secret)", path.display());
+    println!(
+        "Public key: {}",
+        encode_public_key(&signing_key.verifying_key())
+    );
+    Ok(())
+}
+
+fn read_signing_key(path: &Path) -> Result<SigningKey, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read key '{}': {}", path.display(), e))?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(content.trim())
+        .map_err(|e| format!("Key '{}' is not base64: {}", path.display(), e))?;
+    let seed: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| format!("Key '{}' is not a 32-byte Ed25519 key", path.display()))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+fn cmd_sign(args: &Args) -> Result<(), String> {
+    let plugin = PluginDir::open(args.positional(0, "plugin directory")?)?;
+    let key_path = args
+        .option("key")
+        .ok_or_else(|| "sign needs --key <key-file>".to_string())?;
+    let signing_key = read_signing_key(Path::new(key_path))?;
+
+    let manifest = plugin.load_manifest()?;
+    let signature = sign_plugin(&plugin.plugin_id, &plugin.path, &manifest, &signing_key)?;
+
+    #[derive(Serialize)]
+    struct SignatureTable<'a> {
+        signature: &'a PluginSignature,
+    }
+    let table = toml::to_string(&SignatureTable {
+        signature: &signature,
+    })
+    .map_err(|e| format!("Failed to serialize signature: {}", e))?;
+
+    let manifest_path = plugin.path.join("plugin.toml");
+    let content = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read '{}': {}", manifest_path.display(), e))?;
+    let unsigned = strip_signature_table(&content);
+    write_file(
+        &manifest_path,
+        &format!("{}\n\n{}", unsigned.trim_end(), table),
+    )?;
+
+    println!("Signed {} with {}", plugin.plugin_id, signature.public_key);
+    if !manifest.trust.requires_signature() {
+        println!(
+            "note: the app only checks signatures of plugins with trust = \"verified\" (this one is {})",
+            trust_label(&manifest.trust)
+        );
+    }
+    Ok(())
+}
+
+/// Drop a previous `[signature]` table (and its subtables) from plugin.toml text,
+/// keeping the rest of the file and its comments as written. Root-level
+/// `signature = { ... }` and dotted `signature.key = ...` lines go too.
+fn strip_signature_table(content: &str) -> String {
+    let mut out = Vec::new();
+    let mut in_signature = false;
+    let mut in_root = true;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            let header = trimmed.trim_matches(|c| c == '[' || c == ']');
+            in_signature = is_signature_key(header);
+            in_root = false;
+        } else if in_root
+            && !trimmed.starts_with('#')
+            && trimmed
+                .split_once('=')
+                .is_some_and(|(key, _)| is_signature_key(key))
+        {
+            continue;
+        }
+        if !in_signature {
+            out.push(line);
+        }
+    }
+    out.join("\n")
+}
+
+/// Whether a (possibly dotted or quoted) TOML key starts with `signature`.
+fn is_signature_key(key: &str) -> bool {
+    key.split('.')
+        .next()
+        .is_some_and(|first| first.trim().trim_matches(|c| c == '"' || c == '\'') == "signature")
+}
+
+fn cmd_verify(args: &Args) -> Result<(), String> {
+    let plugin = PluginDir::open(args.positional(0, "plugin directory")?)?;
+    let manifest = plugin.load_manifest()?;
+    let signature = manifest
+        .signature
+        .as_ref()
+        .ok_or_else(|| format!("Plugin '{}' is not signed", plugin.plugin_id))?;
+    signature
+        .check_payload_version()
+        .map_err(|e| format!("Plugin '{}': {}", plugin.plugin_id, e))?;
+
+    let trusted_keys = args.option("trusted-keys");
+    let verifier = load_verifier(trusted_keys, Some(signature))?;
+
+    let payload = build_plugin_signature_payload(
+        &plugin.plugin_id,
+        &plugin.path,
+        &manifest,
+        signature.timestamp,
+    )?;
+    if !verifier.verify_signature(signature, &payload) {
+        return Err(match trusted_keys {
+            Some(_) if !verifier.trusted_keys().contains(&signature.public_key) => format!(
+                "Plugin '{}' is signed by {}, which is not a trusted key",
+                plugin.plugin_id, signature.public_key
+            ),
+            _ => format!(
+                "Signature of plugin '{}' does not match its files",
+                plugin.plugin_id
+            ),
+        });
+    }
+
+    println!(
+        "Signature OK: {} signed by {}",
+        plugin.plugin_id, signature.public_key
+    );
+    if trusted_keys.is_none() {
+        println!("note: the key itself was not checked; pass --trusted-keys to do so");
+    }
+    Ok(())
+}
+
+/// Keys from `trusted_keys`, or else the key of `signature` itself, which
+/// only checks that the files match the signature.
+fn load_verifier(
+    trusted_keys: Option<&str>,
+    signature: Option<&PluginSignature>,
+) -> Result<TrustVerifier, String> {
+    let mut verifier = TrustVerifier::new();
+    match (trusted_keys, signature) {
+        (Some(path), _) => {
+            let path = Path::new(path);
+            if !path.exists() {
+                return Err(format!("Trusted key file '{}' not found", path.display()));
+            }
+            verifier.load_from_file(path)?;
+        }
+        (None, Some(signature)) => verifier.add_trusted_key(signature.public_key.clone())?,
+        (None, None) => {}
+    }
+    Ok(verifier)
+}
+
+// ============================================================================
+// RUN
+// ============================================================================
+
+fn cmd_run(args: &Args) -> Result<(), String> {
+    let plugin = PluginDir::open(args.positional(0, "plugin directory")?)?;
+    let workspace = args
+        .option("workspace")
+        .ok_or_else(|| "run needs --workspace <dir>".to_string())?;
+    let workspace = Path::new(workspace)
+        .canonicalize()
+        .map_err(|e| format!("Cannot open workspace '{}': {}", workspace, e))?;
+    let hooks = args
+        .options("hook")
+        .into_iter()
+        .map(parse_hook)
+        .collect::<Result<Vec<_>, _>>()?;
+    // Verified plugins only activate with a valid signature, as in the app
+    let manifest = plugin.load_manifest()?;
+    let verifier = load_verifier(args.option("trusted-keys"), manifest.signature.as_ref())?;
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to start async runtime: {}", e))?;
+    runtime.block_on(run_headless(plugin, workspace, verifier, hooks))
+}
+
+/// `name` (no arguments) or `name=<json argument>`
+fn parse_hook(spec: &str) -> Result<(String, Vec<serde_json::Value>), String> {
+    match spec.split_once('=') {
+        Some((name, json)) => {
+            let arg = serde_json::from_str(json)
+                .map_err(|e| format!("Hook '{}' argument is not JSON: {}", name, e))?;
+            Ok((name.to_string(), vec![arg]))
+        }
+        None => Ok((spec.to_string(), Vec::new())),
+    }
+}
+
+async fn run_headless(
+    plugin: PluginDir,
+    workspace: PathBuf,
+    verifier: TrustVerifier,
+    hooks: Vec<(String, Vec<serde_json::Value>)>,
+) -> Result<(), String> {
+    let audit = Arc::new(AuditLogger::new(DEFAULT_MAX_EVENTS));
+    let host = Arc::new(
+        RecordingHost::new().with_policy_engine(Arc::new(PolicyEngine::new(
+            audit.clone(),
+            // Nobody can answer consent requests here, so they are denied at once
+            Arc::new(ConsentBroker::new(Duration::ZERO)),
+        ))),
+    );
+    let mut manager = PluginManager::new(
+        plugin.plugins_dir.clone(),
+        Arc::new(SandboxRegistry::new()),
+        workspace,
+        host.clone(),
+        Default::default(),
+    );
+    manager.set_trust_verifier(Arc::new(RwLock::new(verifier)));
+
+    manager.load(&plugin.plugin_id).map_err(|e| e.to_string())?;
+    manager
+        .activate(&plugin.plugin_id)
+        .await
+        .map_err(|e| format!("Activation failed: {}", e))?;
+    println!("activated {}", plugin.plugin_id);
+
+    let mut failed = false;
+    if let Some(sandbox) = manager
+        .sandbox_registry()
+        .get_sandbox(&plugin.plugin_id)
+        .await
+    {
+        for (name, hook_args) in hooks {
+            let result = sandbox.read().await.call_hook(&name, hook_args).await;
+            match result {
+                Ok(value) => println!("hook {} -> {}", name, value),
+                Err(e) => {
+                    failed = true;
+                    println!("hook {} failed: {}", name, e);
+                }
+            }
+        }
+    }
+
+    manager
+        .deactivate(&plugin.plugin_id)
+        .await
+        .map_err(|e| format!("Deactivation failed: {}", e))?;
+
+    println!("\nevents:");
+    for event in host.events() {
+        println!("  {} {}", event.event, event.payload);
+    }
+    println!("\naudit:");
+    for event in audit.get_events_for_plugin(&plugin.plugin_id).await {
+        if event.success {
+            println!("  ok     {} {}", event.operation, event.resource);
+        } else {
+            println!(
+                "  DENIED {} {}: {}",
+                event.operation,
+                event.resource,
+                event.error.unwrap_or_default()
+            );
+        }
+    }
+
+    if failed {
+        return Err("one or more hooks failed".to_string());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    std::fs::write(path, content)
+        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Create `path` readable only by its owner, never replacing an existing file.
+/// The mode is set at creation, so the secret is never readable by others.
+fn write_secret_file(path: &Path, content: &str) -> Result<(), String> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => format!("{} already exists", path.display()),
+        _ => format!("Failed to create '{}': {}", path.display(), e),
+    })?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_args_parse_splits_positionals_and_options() {
+        let parsed = Args::parse(&args(&[
+            "plugins/demo",
+            "--hook",
+            "activate",
+            "--workspace",
+            "/tmp/ws",
+            "--hook",
+            "on_file_save={\"path\":\"a.rs\"}",
+        ]))
+        .unwrap();
+        assert_eq!(
+            parsed.positional(0, "plugin directory").unwrap(),
+            "plugins/demo"
+        );
+        assert!(parsed.positional(1, "key file").is_err());
+        assert_eq!(parsed.option("workspace"), Some("/tmp/ws"));
+        assert_eq!(parsed.option("key"), None);
+        assert_eq!(
+            parsed.options("hook"),
+            vec!["activate", "on_file_save={\"path\":\"a.rs\"}"]
+        );
+    }
+
+    #[test]
+    fn test_args_parse_last_option_wins_and_values_are_required() {
+        let parsed = Args::parse(&args(&["--key", "a.key", "--key", "b.key"])).unwrap();
+        assert_eq!(parsed.option("key"), Some("b.key"));
+        assert!(Args::parse(&args(&["demo", "--workspace"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_hook() {
+        assert_eq!(
+            parse_hook("activate").unwrap(),
+            ("activate".to_string(), vec![])
+        );
+        assert_eq!(
+            parse_hook("on_file_save={\"path\":\"a=b.rs\"}").unwrap(),
+            (
+                "on_file_save".to_string(),
+                vec![serde_json::json!({"path": "a=b.rs"})]
+            )
+        );
+        assert!(parse_hook("on_file_save={not json").is_err());
+    }
+
+    #[test]
+    fn test_strip_signature_table() {
+        let manifest = "\
+name = \"demo\"
+# signature = kept because it is a comment
+signature.public_key = \"old\"
+\"signature\".timestamp = 1
+signature = { public_key = \"older\" }
+signatures_seen = 2
+
+[permissions]
+filesystem = \"WorkspaceRead\"
+
+[signature]
+public_key = \"abc\"
+
+[signature.timestamp]
+secs_since_epoch = 1
+
+[ui]
+notifications = true
+";
+        let stripped = strip_signature_table(manifest);
+        assert_eq!(
+            stripped,
+            "\
+name = \"demo\"
+# signature = kept because it is a comment
+signatures_seen = 2
+
+[permissions]
+filesystem = \"WorkspaceRead\"
+
+[ui]
+notifications = true"
+        );
+        let table: toml::Table = toml::from_str(&stripped).unwrap();
+        assert!(!table.contains_key("signature"));
+    }
+
+    #[test]
+    fn test_write_secret_file_is_private_and_never_replaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing.key");
+        write_secret_file(&path, "secret\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "secret\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let err = write_secret_file(&path, "other\n").unwrap_err();
+        assert!(err.contains("already exists"), "{}", err);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "secret\n");
+    }
+}
@@ -0,0 +1,8 @@
+// src-tauri/src/lib.rs
+//
+// The plugin system as a library, shared by the `skretchpad` app and the
+// `skretchpad-plugin` CLI.
+
+pub mod plugin_system;
+#[cfg(test)]
+mod security;
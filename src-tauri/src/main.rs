@@ -5,23 +5,25 @@
 
 #[allow(dead_code)]
 mod git;
-mod plugin_system;
 mod theme_engine;
 
-use plugin_system::{
-    api::{
-        clear_audit_logs, get_audit_logs, plugin_add_status_bar_item, plugin_emit_event,
-        plugin_execute_command, plugin_execute_hook, plugin_fetch, plugin_get_active_file,
-        plugin_get_editor_content, plugin_hide_panel, plugin_list_directory, plugin_read_file,
-        plugin_register_event, plugin_remove_status_bar_item, plugin_set_editor_content,
-        plugin_show_notification, plugin_show_panel, plugin_unwatch_path, plugin_watch_path,
-        plugin_write_file, AuditLogger, FileWatcherRegistry,
-    },
+use skretchpad_lib::plugin_system::{
+    self,
+    api::{self, FileWatcherRegistry},
+    audit::{AuditLogger, AUDIT_EVENT, DEFAULT_MAX_EVENTS},
+    audit_store::{AuditStore, RotationPolicy},
     capabilities::PluginCapabilities,
+    consent::{AllowedCommand, ConsentBroker, ConsentDecision, CONSENT_TIMEOUT},
+    escalation::{ApprovalStore, CapabilityDiff},
+    grants::{Grant, GrantStore, Permission},
+    host::{PluginHost, TauriHost},
     manager::PluginManager,
     ops::EditorStateHandle,
-    sandbox::SandboxRegistry,
-    trust::{build_plugin_signature_payload, TrustVerifier},
+    policy::PolicyEngine,
+    pool::WorkerPool,
+    sandbox::{LimitsPolicy, SandboxRegistry},
+    supervisor::SUPERVISE_INTERVAL,
+    trust::TrustVerifier,
     worker::WorkerRegistry,
 };
 use std::collections::HashMap;
@@ -69,40 +71,8 @@ async fn load_plugin(
 async fn activate_plugin(
     plugin_id: String,
     state: State<'_, Arc<RwLock<PluginManager>>>,
-    verifier: State<'_, Arc<RwLock<TrustVerifier>>>,
     worker_registry: State<'_, Arc<RwLock<WorkerRegistry>>>,
 ) -> Result<(), String> {
-    // Trust verification before activation
-    {
-        let manager = state.read().await;
-        if let Some(info) = manager.loader().get(&plugin_id) {
-            if info.manifest.trust.requires_signature() {
-                if let Some(ref sig) = info.manifest.signature {
-                    let payload = build_plugin_signature_payload(
-                        &plugin_id,
-                        &info.path,
-                        &info.manifest,
-                        sig.timestamp,
-                    )?;
-                    let v = verifier.read().await;
-                    if !v.verify_signature(sig, &payload) {
-                        return Err(format!(
-                            "Plugin '{}' requires a trusted signature but verification failed",
-                            plugin_id
-                        ));
-                    }
-                } else {
-                    return Err(format!(
-                        "Plugin '{}' requires a signature but none was provided",
-                        plugin_id
-                    ));
-                }
-            }
-        } else {
-            return Err(format!("Plugin not loaded: {}", plugin_id));
-        }
-    }
-
     // Clean up old worker if exists (from previous activation)
     {
         let mut wr = worker_registry.write().await;
@@ -226,7 +196,7 @@ async fn get_plugin_resource_stats(
     // Use sandbox getters for metadata
     let sandbox_id = sandbox.id().to_string();
     let caps = sandbox.capabilities().clone();
-    let limits = sandbox.resource_limits().clone();
+    let limits = sandbox.resource_limits().to_json();
 
     // Check limits (will error if exceeded)
     sandbox.check_resource_limits().map_err(|e| e.to_string())?;
@@ -235,11 +205,7 @@ async fn get_plugin_resource_stats(
     serde_json::to_value(serde_json::json!({
         "sandbox_id": sandbox_id,
         "capabilities": caps,
-        "limits": {
-            "max_memory": limits.max_memory,
-            "max_cpu_time_ms": limits.max_cpu_time.as_millis() as u64,
-            "max_operations": limits.max_operations,
-        },
+        "limits": limits,
         "stats": stats,
     }))
     .map_err(|e| e.to_string())
@@ -252,51 +218,74 @@ async fn grant_plugin_capability(
     allow_command: Option<String>,
     disallow_command: Option<String>,
     ui_preset: Option<String>,
+    ttl_secs: Option<u64>,
     state: State<'_, Arc<RwLock<PluginManager>>>,
 ) -> Result<serde_json::Value, String> {
     let mut manager = state.write().await;
-    let mut caps = manager
-        .get_plugin_capabilities(&plugin_id)
+    let manifest_caps = manager
+        .loader()
+        .get(&plugin_id)
+        .map(|info| info.manifest.capabilities.clone())
         .ok_or_else(|| format!("Plugin not found: {}", plugin_id))?;
+    let workspace = manager.workspace_root().to_path_buf();
+    let grants = manager.grant_store().clone();
+    let ttl = ttl_secs.map(std::time::Duration::from_secs);
 
-    // Apply domain addition
+    // Record each granted permission in the persistent grant store
+    let mut permissions = Vec::new();
     if let Some(d) = domain {
-        caps.network.add_domain(d);
+        permissions.push(Permission::Network(d));
     }
-
-    // Apply command changes
     if let Some(cmd) = allow_command {
-        // Use CommandCapability::new for fresh construction when needed
-        if caps.commands.allowlist.is_empty() {
-            caps.commands = crate::plugin_system::capabilities::CommandCapability::new(vec![cmd]);
-        } else {
-            caps.commands.allow_command(cmd);
-        }
-    }
-    if let Some(cmd) = disallow_command {
-        caps.commands.disallow_command(&cmd);
+        permissions.push(Permission::Command(cmd));
     }
-
-    // Apply UI preset
     if let Some(preset) = ui_preset {
-        use crate::plugin_system::capabilities::UiCapability;
+        use plugin_system::capabilities::UiCapability;
         let ui = match preset.as_str() {
             "all" => UiCapability::all(),
             "basic" => UiCapability::basic(),
             _ => UiCapability::default(),
         };
-        caps.ui = ui;
+        for (feature, enabled) in [
+            ("status_bar", ui.status_bar),
+            ("sidebar", ui.sidebar),
+            ("notifications", ui.notifications),
+            ("webview", ui.webview),
+        ] {
+            if enabled {
+                permissions.push(Permission::Ui(feature.to_string()));
+            }
+        }
+    }
+    for permission in permissions {
+        grants
+            .grant(&plugin_id, &workspace, permission, ttl)
+            .map_err(|e| e.to_string())?;
     }
 
-    // Merge with existing capabilities (most permissive)
-    let existing = manager
-        .get_plugin_capabilities(&plugin_id)
-        .unwrap_or_default();
-    let merged = existing.merge(&caps);
+    // Disallowing revokes grants for the command; a command declared in
+    // plugin.toml is also dropped for this session
+    if let Some(cmd) = disallow_command {
+        let permission = Permission::Command(cmd.clone());
+        grants
+            .revoke_permission(&plugin_id, &workspace, &permission)
+            .map_err(|e| e.to_string())?;
+        if manifest_caps.commands.can_execute(&cmd) {
+            let mut caps = manifest_caps;
+            caps.commands.disallow_command(&cmd);
+            manager
+                .set_plugin_capabilities(&plugin_id, caps)
+                .map_err(|e| e.to_string())?;
+        }
+    }
 
     manager
-        .set_plugin_capabilities(&plugin_id, merged.clone())
+        .refresh_capabilities(&plugin_id)
+        .await
         .map_err(|e| e.to_string())?;
+    let merged = manager
+        .get_plugin_capabilities(&plugin_id)
+        .unwrap_or_default();
 
     // Compute tier for response
     let tier = if merged.is_subset_of(&PluginCapabilities::none()) {
@@ -309,9 +298,6 @@ async fn grant_plugin_capability(
         "full"
     };
 
-    // Also check first_party preset for reference
-    let _first_party_caps = PluginCapabilities::first_party();
-
     serde_json::to_value(serde_json::json!({
         "plugin_id": plugin_id,
         "capabilities": merged,
@@ -320,11 +306,65 @@ async fn grant_plugin_capability(
     .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn list_plugin_grants(
+    plugin_id: Option<String>,
+    state: State<'_, Arc<RwLock<PluginManager>>>,
+) -> Result<Vec<Grant>, String> {
+    let manager = state.read().await;
+    Ok(manager.grant_store().list(plugin_id.as_deref()))
+}
+
+#[tauri::command]
+async fn revoke_plugin_grant(
+    grant_id: String,
+    state: State<'_, Arc<RwLock<PluginManager>>>,
+) -> Result<bool, String> {
+    let manager = state.read().await;
+    let Some(grant) = manager
+        .grant_store()
+        .revoke(&grant_id)
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(false);
+    };
+    if manager.loader().get(&grant.plugin_id).is_some() {
+        manager
+            .refresh_capabilities(&grant.plugin_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(true)
+}
+
+#[tauri::command]
+async fn get_plugin_capability_diff(
+    plugin_id: String,
+    state: State<'_, Arc<RwLock<PluginManager>>>,
+) -> Result<Option<CapabilityDiff>, String> {
+    let manager = state.read().await;
+    Ok(manager.capability_escalation(&plugin_id).cloned())
+}
+
+#[tauri::command]
+async fn approve_plugin_capabilities(
+    plugin_id: String,
+    diff: CapabilityDiff,
+    state: State<'_, Arc<RwLock<PluginManager>>>,
+) -> Result<(), String> {
+    let mut manager = state.write().await;
+    manager
+        .approve_capabilities(&plugin_id, &diff)
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn get_worker_info(
     plugin_id: String,
     worker_registry: State<'_, Arc<RwLock<WorkerRegistry>>>,
 ) -> Result<serde_json::Value, String> {
+    // Thread, isolate and heap figures for the shared pool all plugins run on
+    let pool = WorkerPool::global().stats().await;
     let mut registry = worker_registry.write().await;
 
     // Use get_worker_mut for mutable access (e.g. future resource tracking)
@@ -332,11 +372,9 @@ async fn get_worker_info(
         let info = serde_json::json!({
             "id": worker.id(),
             "capabilities": worker.capabilities(),
-            "limits": {
-                "max_memory": worker.resource_limits().max_memory,
-                "max_cpu_time_ms": worker.resource_limits().max_cpu_time.as_millis() as u64,
-                "max_operations": worker.resource_limits().max_operations,
-            },
+            "limits": worker.resource_limits().to_json(),
+            "memory_used": worker.get_memory_usage().await.ok(),
+            "pool": pool,
         });
         Ok(info)
     } else if let Some(worker) = registry.get_worker(&plugin_id) {
@@ -344,11 +382,8 @@ async fn get_worker_info(
         Ok(serde_json::json!({
             "id": worker.id(),
             "capabilities": worker.capabilities(),
-            "limits": {
-                "max_memory": worker.resource_limits().max_memory,
-                "max_cpu_time_ms": worker.resource_limits().max_cpu_time.as_millis() as u64,
-                "max_operations": worker.resource_limits().max_operations,
-            },
+            "limits": worker.resource_limits().to_json(),
+            "pool": pool,
         }))
     } else {
         Err(format!("No worker found for plugin: {}", plugin_id))
@@ -361,6 +396,7 @@ async fn register_plugin_worker(
     state: State<'_, Arc<RwLock<PluginManager>>>,
     worker_registry: State<'_, Arc<RwLock<WorkerRegistry>>>,
     app_handle: AppHandle,
+    plugin_host: State<'_, Arc<dyn PluginHost>>,
     editor_state_handle: State<'_, EditorStateHandle>,
 ) -> Result<(), String> {
     let manager = state.read().await;
@@ -368,6 +404,16 @@ async fn register_plugin_worker(
         .loader()
         .get(&plugin_id)
         .ok_or_else(|| format!("Plugin not loaded: {}", plugin_id))?;
+    let limits = manager
+        .effective_limits(&plugin_id)
+        .ok_or_else(|| format!("Plugin not loaded: {}", plugin_id))?;
+    let plugin_root = info.path.canonicalize().map_err(|e| {
+        format!(
+            "Failed to canonicalize plugin root '{}': {}",
+            info.path.display(),
+            e
+        )
+    })?;
 
     let workspace_root = if cfg!(debug_assertions) {
         let manifest_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -380,11 +426,16 @@ async fn register_plugin_worker(
     };
 
     let mut wr = worker_registry.write().await;
+    let capabilities = manager
+        .get_plugin_capabilities(&plugin_id)
+        .ok_or_else(|| format!("Plugin not loaded: {}", plugin_id))?;
     wr.create_worker(
         plugin_id.clone(),
-        info.manifest.capabilities.clone(),
+        capabilities,
+        limits,
+        plugin_root,
         workspace_root,
-        app_handle.clone(),
+        plugin_host.inner().clone(),
         editor_state_handle.inner().clone(),
     )
     .map_err(|e| e.to_string())
@@ -394,9 +445,9 @@ async fn register_plugin_worker(
 async fn add_trusted_key(
     key: String,
     app: AppHandle,
-    verifier: State<'_, Arc<RwLock<TrustVerifier>>>,
+    verifier: State<'_, Arc<std::sync::RwLock<TrustVerifier>>>,
 ) -> Result<(), String> {
-    let mut v = verifier.write().await;
+    let mut v = verifier.write().unwrap_or_else(|e| e.into_inner());
     v.add_trusted_key(key)?;
     let path = trusted_keys_file(&app)?;
     v.save_to_file(&path)
@@ -406,9 +457,9 @@ async fn add_trusted_key(
 async fn remove_trusted_key(
     key: String,
     app: AppHandle,
-    verifier: State<'_, Arc<RwLock<TrustVerifier>>>,
+    verifier: State<'_, Arc<std::sync::RwLock<TrustVerifier>>>,
 ) -> Result<bool, String> {
-    let mut v = verifier.write().await;
+    let mut v = verifier.write().unwrap_or_else(|e| e.into_inner());
     let removed = v.remove_trusted_key(&key);
     let path = trusted_keys_file(&app)?;
     v.save_to_file(&path)?;
@@ -417,9 +468,9 @@ async fn remove_trusted_key(
 
 #[tauri::command]
 async fn list_trusted_keys(
-    verifier: State<'_, Arc<RwLock<TrustVerifier>>>,
+    verifier: State<'_, Arc<std::sync::RwLock<TrustVerifier>>>,
 ) -> Result<Vec<String>, String> {
-    let v = verifier.read().await;
+    let v = verifier.read().unwrap_or_else(|e| e.into_inner());
     Ok(v.trusted_keys())
 }
 
@@ -427,14 +478,41 @@ async fn list_trusted_keys(
 async fn set_trusted_keys(
     keys: Vec<String>,
     app: AppHandle,
-    verifier: State<'_, Arc<RwLock<TrustVerifier>>>,
+    verifier: State<'_, Arc<std::sync::RwLock<TrustVerifier>>>,
 ) -> Result<(), String> {
-    let mut v = verifier.write().await;
+    let mut v = verifier.write().unwrap_or_else(|e| e.into_inner());
     v.set_trusted_keys(keys)?;
     let path = trusted_keys_file(&app)?;
     v.save_to_file(&path)
 }
 
+#[tauri::command]
+async fn respond_plugin_consent(
+    request_id: String,
+    decision: ConsentDecision,
+    consent: State<'_, Arc<ConsentBroker>>,
+) -> Result<bool, String> {
+    Ok(consent.respond(&request_id, decision))
+}
+
+#[tauri::command]
+async fn list_plugin_consents(
+    plugin_id: String,
+    consent: State<'_, Arc<ConsentBroker>>,
+) -> Result<Vec<AllowedCommand>, String> {
+    Ok(consent.always_allowed(&plugin_id))
+}
+
+#[tauri::command]
+async fn revoke_plugin_consent(
+    plugin_id: String,
+    command: Option<String>,
+    args: Option<Vec<String>>,
+    consent: State<'_, Arc<ConsentBroker>>,
+) -> Result<(), String> {
+    consent.revoke(&plugin_id, command.as_deref(), args.as_deref())
+}
+
 fn trusted_keys_file(app: &AppHandle) -> Result<PathBuf, String> {
     let app_dir = app
         .path()
@@ -443,6 +521,46 @@ fn trusted_keys_file(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_dir.join("trusted_keys.json"))
 }
 
+fn consent_store_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(app_dir.join("plugin_consent.json"))
+}
+
+fn grants_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(app_dir.join("plugin_grants.json"))
+}
+
+fn approvals_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(app_dir.join("plugin_approvals.json"))
+}
+
+fn audit_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(app_dir.join("plugin_audit"))
+}
+
+fn limits_policy_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(app_dir.join("plugin_limits.toml"))
+}
+
 fn is_legacy_git_plugin(plugin_id: &str) -> bool {
     matches!(plugin_id, "git" | "git-status")
 }
@@ -937,17 +1055,51 @@ fn main() {
 
             // Initialize plugin system
             let sandbox_registry = Arc::new(SandboxRegistry::new());
-            let plugin_manager = Arc::new(RwLock::new(PluginManager::new(
+            let plugin_host: Arc<dyn PluginHost> = Arc::new(TauriHost::new(app.handle().clone()));
+            let mut manager = PluginManager::new(
                 plugins_dir,
                 sandbox_registry.clone(),
                 workspace_root,
-                app.handle().clone(),
+                plugin_host.clone(),
                 editor_state.clone(),
-            )));
-            let audit_logger = Arc::new(AuditLogger::new(10000));
-            let watcher_registry = Arc::new(FileWatcherRegistry::new());
-            let hot_reload_registry = Arc::new(HotReloadRegistry::new());
-            let workspace_watcher_registry = Arc::new(WorkspaceWatcherRegistry::new());
+            );
+            if let Ok(path) = limits_policy_file(app.handle()) {
+                match LimitsPolicy::load_from_file(&path) {
+                    Ok(policy) => manager.set_limits_policy(policy),
+                    Err(e) => eprintln!(
+                        "Failed to load plugin limits policy from '{}': {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+            // An empty store in place of an unreadable one would overwrite the
+            // user's grants on the next save, so startup fails instead
+            let grant_store = Arc::new(
+                grants_file(app.handle())
+                    .and_then(GrantStore::with_file)
+                    .map_err(|e| format!("Failed to load plugin grants: {}", e))?,
+            );
+            manager.set_grant_store(grant_store.clone());
+            // An empty approval store would take every plugin for a first
+            // sighting and wave its escalations through, so startup fails too
+            manager.set_approval_store(Arc::new(
+                approvals_file(app.handle())
+                    .and_then(ApprovalStore::with_file)
+                    .map_err(|e| format!("Failed to load plugin capability approvals: {}", e))?,
+            ));
+            let audit_logger = AuditLogger::new(DEFAULT_MAX_EVENTS);
+            let audit_logger = Arc::new(
+                match audit_dir(app.handle()).and_then(|dir| {
+                    AuditStore::open(dir, RotationPolicy::default()).map_err(|e| e.to_string())
+                }) {
+                    Ok(store) => audit_logger.with_store(Arc::new(store)),
+                    Err(e) => {
+                        eprintln!("Failed to open plugin audit log: {}", e);
+                        audit_logger
+                    }
+                },
+            );
             let mut tv = TrustVerifier::new();
             if let Ok(path) = trusted_keys_file(app.handle()) {
                 if let Err(e) = tv.load_from_file(&path) {
@@ -958,19 +1110,126 @@ fn main() {
                     );
                 }
             }
-            let trust_verifier = Arc::new(RwLock::new(tv));
+            let trust_verifier = Arc::new(std::sync::RwLock::new(tv));
+            manager.set_trust_verifier(trust_verifier.clone());
+            let anomaly_detector = manager.anomaly_detector();
+            let plugin_manager = Arc::new(RwLock::new(manager));
+            let watcher_registry = Arc::new(FileWatcherRegistry::new());
+            let hot_reload_registry = Arc::new(HotReloadRegistry::new());
+            let workspace_watcher_registry = Arc::new(WorkspaceWatcherRegistry::new());
+            let consent_broker = Arc::new(
+                consent_store_file(app.handle())
+                    .and_then(|path| ConsentBroker::with_store(path, CONSENT_TIMEOUT))
+                    .unwrap_or_else(|e| {
+                        eprintln!("Failed to load plugin consent decisions: {}", e);
+                        ConsentBroker::new(CONSENT_TIMEOUT)
+                    }),
+            );
+            let policy_engine = Arc::new(PolicyEngine::new(
+                audit_logger.clone(),
+                consent_broker.clone(),
+            ));
             let worker_registry = Arc::new(RwLock::new(WorkerRegistry::new()));
 
             // Store state
             app.manage(plugin_manager.clone());
             app.manage(sandbox_registry.clone());
             app.manage(audit_logger.clone());
+            app.manage(plugin_host);
             app.manage(watcher_registry.clone());
             app.manage(editor_state.clone());
             app.manage(hot_reload_registry.clone());
             app.manage(workspace_watcher_registry.clone());
             app.manage(trust_verifier.clone());
             app.manage(worker_registry.clone());
+            app.manage(consent_broker);
+            app.manage(policy_engine.clone());
+            app.manage(grant_store);
+
+            // Stream audit events to the frontend as they are recorded
+            let mut audit_events = audit_logger.subscribe();
+            let audit_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tokio::sync::broadcast::error::RecvError;
+                loop {
+                    match audit_events.recv().await {
+                        Ok(event) => {
+                            let _ = audit_handle.emit(AUDIT_EVENT, &event);
+                        }
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+
+            // Pass audit events to the `audit` hook of monitor plugins
+            let mut dispatched_events = audit_logger.subscribe();
+            let dispatching_manager = plugin_manager.clone();
+            let dispatching_policy = policy_engine.clone();
+            tauri::async_runtime::spawn(async move {
+                use tokio::sync::broadcast::error::RecvError;
+                loop {
+                    match dispatched_events.recv().await {
+                        // Only lock the manager when some plugin is listening
+                        Ok(event) if !dispatching_policy.audit_hook_subscribers().is_empty() => {
+                            dispatching_manager
+                                .read()
+                                .await
+                                .dispatch_audit_event(&event)
+                                .await;
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(missed)) => {
+                            eprintln!("Audit hooks missed {} audit events", missed);
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+
+            // Suspend plugins whose audit events match an anomaly rule. Every
+            // event is checked (the channel never drops any) under the
+            // detector's own lock; the manager is only locked on a match.
+            let mut inspected_events = audit_logger.inspect();
+            let inspecting_manager = plugin_manager.clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(event) = inspected_events.recv().await {
+                    let anomaly = anomaly_detector.lock().unwrap_or_else(|e| e.into_inner()).inspect(&event);
+                    if let Some(anomaly) = anomaly {
+                        inspecting_manager.write().await.suspend(anomaly).await;
+                    }
+                }
+            });
+
+            // Restart plugins whose worker crashed
+            let supervised_manager = plugin_manager.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(SUPERVISE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    // Check under a read lock and only take the write lock when a
+                    // worker died or a restart is due; restarted plugins run their
+                    // entry point and activate hook without the manager locked.
+                    let now = std::time::Instant::now();
+                    let work = supervised_manager.read().await.supervision_work(now).await;
+                    if !work.is_empty() {
+                        let restarts = supervised_manager
+                            .write()
+                            .await
+                            .begin_supervision(work, now)
+                            .await;
+                        for pending in restarts {
+                            let run = pending.run().await;
+                            supervised_manager.write().await.finish_restart(run).await;
+                        }
+                    }
+                    supervised_manager
+                        .read()
+                        .await
+                        .refresh_expired_grants()
+                        .await;
+                }
+            });
 
             // Auto-discover and load plugins
             let hr_registry = hot_reload_registry.clone();
@@ -1135,37 +1394,47 @@ fn main() {
             remove_trusted_key,
             list_trusted_keys,
             set_trusted_keys,
+            respond_plugin_consent,
+            list_plugin_consents,
+            revoke_plugin_consent,
+            list_plugin_grants,
+            revoke_plugin_grant,
+            get_plugin_capability_diff,
+            approve_plugin_capabilities,
             get_worker_info,
             register_plugin_worker,
             // Filesystem operations
-            plugin_read_file,
-            plugin_write_file,
-            plugin_list_directory,
-            plugin_watch_path,
-            plugin_unwatch_path,
+            api::plugin_read_file,
+            api::plugin_write_file,
+            api::plugin_list_directory,
+            api::plugin_watch_path,
+            api::plugin_unwatch_path,
             // Network operations
-            plugin_fetch,
+            api::plugin_fetch,
             // Command execution
-            plugin_execute_command,
+            api::plugin_execute_command,
             // UI operations
-            plugin_show_notification,
-            plugin_add_status_bar_item,
-            plugin_remove_status_bar_item,
-            plugin_show_panel,
-            plugin_hide_panel,
+            api::plugin_show_notification,
+            api::plugin_add_status_bar_item,
+            api::plugin_remove_status_bar_item,
+            api::plugin_show_panel,
+            api::plugin_hide_panel,
             // Editor operations
-            plugin_get_editor_content,
-            plugin_set_editor_content,
-            plugin_get_active_file,
+            api::plugin_get_editor_content,
+            api::plugin_set_editor_content,
+            api::plugin_get_active_file,
             update_editor_state,
             // Event system
-            plugin_register_event,
-            plugin_emit_event,
+            api::plugin_register_event,
+            api::plugin_emit_event,
             // Plugin hooks
-            plugin_execute_hook,
+            api::plugin_execute_hook,
             // Audit logs
-            get_audit_logs,
-            clear_audit_logs,
+            api::get_audit_logs,
+            api::clear_audit_logs,
+            api::query_audit_log,
+            api::export_audit_log,
+            api::verify_audit_log,
             // Theme engine
             theme_engine::load_theme,
             theme_engine::load_theme_data,
@@ -0,0 +1,462 @@
+// src-tauri/src/plugin_system/anomaly.rs
+//
+// Anomaly detection over the plugin audit stream. `AnomalyDetector` keeps a
+// short window of each plugin's recent audit events and checks every new event
+// against the configured rules; `PluginManager::suspend` deactivates the plugin
+// when one matches. The detector has its own lock, so the app checks each event
+// without locking the manager and only takes it to suspend a plugin.
+
+use crate::plugin_system::{audit::AuditEvent, capabilities::NetworkCapability};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Tauri event emitted when a plugin is suspended by an anomaly rule
+pub const PLUGIN_SUSPENDED_EVENT: &str = "plugin:suspended";
+
+/// A pattern of plugin operations that suspends the plugin when seen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "kebab-case")]
+pub enum AnomalyRule {
+    /// A file read followed within `window_secs` by a fetch to a domain that is
+    /// neither in `known_domains` nor granted by the plugin's network capability.
+    /// Plugins with unrestricted network access are never flagged, since every
+    /// domain is one they were granted.
+    ReadThenFetch {
+        window_secs: u64,
+        #[serde(default)]
+        known_domains: Vec<String>,
+    },
+    /// More than `max_denials` denied operations within `window_secs`
+    DeniedBurst {
+        max_denials: usize,
+        window_secs: u64,
+    },
+    /// Writes to more than `max_files` distinct files within `window_secs`
+    WriteBurst { max_files: usize, window_secs: u64 },
+}
+
+impl AnomalyRule {
+    /// Name recorded in the plugin's status when the rule matches.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnomalyRule::ReadThenFetch { .. } => "read-then-fetch",
+            AnomalyRule::DeniedBurst { .. } => "denied-burst",
+            AnomalyRule::WriteBurst { .. } => "write-burst",
+        }
+    }
+
+    fn window(&self) -> Duration {
+        let secs = match self {
+            AnomalyRule::ReadThenFetch { window_secs, .. }
+            | AnomalyRule::DeniedBurst { window_secs, .. }
+            | AnomalyRule::WriteBurst { window_secs, .. } => *window_secs,
+        };
+        Duration::from_secs(secs)
+    }
+
+    /// Check `event` against the plugin's earlier events (oldest first).
+    /// Returns what was detected.
+    fn check(
+        &self,
+        history: &VecDeque<AuditEvent>,
+        event: &AuditEvent,
+        network: &NetworkCapability,
+    ) -> Option<String> {
+        let mut recent = history
+            .iter()
+            .chain(std::iter::once(event))
+            .filter(|e| within(e, event, self.window()));
+
+        match self {
+            AnomalyRule::ReadThenFetch { known_domains, .. } => {
+                if event.operation != "network.fetch" || !event.success {
+                    return None;
+                }
+                let host = url::Url::parse(&event.resource)
+                    .ok()?
+                    .host_str()?
+                    .to_ascii_lowercase();
+                if network.can_access(&host)
+                    || known_domains.iter().any(|d| d.eq_ignore_ascii_case(&host))
+                {
+                    return None;
+                }
+                let read = recent.rfind(|e| e.operation == "fs.read" && e.success)?;
+                Some(format!(
+                    "read '{}' then fetched unknown domain '{}'",
+                    read.resource, host
+                ))
+            }
+            AnomalyRule::DeniedBurst { max_denials, .. } => {
+                if event.success {
+                    return None;
+                }
+                let denials = recent.filter(|e| !e.success).count();
+                (denials > *max_denials).then(|| {
+                    format!(
+                        "{} denied operations within {}s",
+                        denials,
+                        self.window().as_secs()
+                    )
+                })
+            }
+            AnomalyRule::WriteBurst { max_files, .. } => {
+                if event.operation != "fs.write" || !event.success {
+                    return None;
+                }
+                let files: HashSet<&str> = recent
+                    .filter(|e| e.operation == "fs.write" && e.success)
+                    .map(|e| e.resource.as_str())
+                    .collect();
+                (files.len() > *max_files).then(|| {
+                    format!(
+                        "wrote {} files within {}s",
+                        files.len(),
+                        self.window().as_secs()
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Whether `earlier` happened no more than `window` before `event`.
+fn within(earlier: &AuditEvent, event: &AuditEvent, window: Duration) -> bool {
+    event
+        .timestamp
+        .duration_since(earlier.timestamp)
+        .map_or(true, |elapsed| elapsed <= window)
+}
+
+/// The rules checked against every audit event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnomalyRules {
+    pub rules: Vec<AnomalyRule>,
+}
+
+impl Default for AnomalyRules {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                AnomalyRule::ReadThenFetch {
+                    window_secs: 5,
+                    known_domains: Vec::new(),
+                },
+                AnomalyRule::DeniedBurst {
+                    max_denials: 10,
+                    window_secs: 10,
+                },
+                AnomalyRule::WriteBurst {
+                    max_files: 20,
+                    window_secs: 10,
+                },
+            ],
+        }
+    }
+}
+
+/// A rule match that suspended a plugin.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub plugin_id: String,
+    /// Name of the rule that matched (see [`AnomalyRule::name`])
+    pub rule: String,
+    pub detail: String,
+    /// When the matching event was recorded (Unix seconds)
+    pub detected_at: u64,
+}
+
+/// Checks audit events against [`AnomalyRules`], one window per plugin.
+#[derive(Debug, Default)]
+pub struct AnomalyDetector {
+    rules: AnomalyRules,
+    history: HashMap<String, VecDeque<AuditEvent>>,
+    /// Network capability of each watched (active) plugin
+    watched: HashMap<String, NetworkCapability>,
+}
+
+impl AnomalyDetector {
+    pub fn new(rules: AnomalyRules) -> Self {
+        Self {
+            rules,
+            history: HashMap::new(),
+            watched: HashMap::new(),
+        }
+    }
+
+    /// Check the events of `plugin_id`, whose fetches are allowed by `network`,
+    /// in [`AnomalyDetector::inspect`]; replaces an earlier capability.
+    pub fn watch(&mut self, plugin_id: &str, network: NetworkCapability) {
+        self.watched.insert(plugin_id.to_string(), network);
+    }
+
+    /// Check `event` if its plugin is watched (see [`AnomalyDetector::observe`]).
+    pub fn inspect(&mut self, event: &AuditEvent) -> Option<Anomaly> {
+        let network = self.watched.get(&event.plugin_id)?.clone();
+        self.observe(event, &network)
+    }
+
+    /// Replace the rules; windows already collected are kept.
+    pub fn set_rules(&mut self, rules: AnomalyRules) {
+        self.rules = rules;
+    }
+
+    /// Check `event` against every rule, given the plugin's network capability.
+    ///
+    /// On a match the plugin's window is cleared, so one burst is reported once.
+    pub fn observe(&mut self, event: &AuditEvent, network: &NetworkCapability) -> Option<Anomaly> {
+        let longest = self
+            .rules
+            .rules
+            .iter()
+            .map(AnomalyRule::window)
+            .max()
+            .unwrap_or_default();
+        let history = self.history.entry(event.plugin_id.clone()).or_default();
+        while history
+            .front()
+            .is_some_and(|oldest| !within(oldest, event, longest))
+        {
+            history.pop_front();
+        }
+
+        let matched = self.rules.rules.iter().find_map(|rule| {
+            rule.check(history, event, network)
+                .map(|detail| (rule.name(), detail))
+        });
+        match matched {
+            Some((rule, detail)) => {
+                self.history.remove(&event.plugin_id);
+                Some(Anomaly {
+                    plugin_id: event.plugin_id.clone(),
+                    rule: rule.to_string(),
+                    detail,
+                    detected_at: event
+                        .timestamp
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs()),
+                })
+            }
+            None => {
+                history.push_back(event.clone());
+                None
+            }
+        }
+    }
+
+    /// Stop watching a plugin and drop its window (e.g. once it is deactivated).
+    pub fn forget(&mut self, plugin_id: &str) {
+        self.history.remove(plugin_id);
+        self.watched.remove(plugin_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_system::capabilities::PluginCapabilities;
+
+    fn event(offset_secs: u64, operation: &str, resource: &str, success: bool) -> AuditEvent {
+        AuditEvent {
+            plugin_id: "suspicious".to_string(),
+            operation: operation.to_string(),
+            resource: resource.to_string(),
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000 + offset_secs),
+            success,
+            error: (!success).then(|| "denied".to_string()),
+        }
+    }
+
+    /// A network capability that does not reach the domains these tests fetch.
+    fn allowlist() -> NetworkCapability {
+        NetworkCapability::DomainAllowlist(["api.example.com".to_string()].into())
+    }
+
+    fn observe_all(
+        detector: &mut AnomalyDetector,
+        events: &[AuditEvent],
+        network: &NetworkCapability,
+    ) -> Option<Anomaly> {
+        events
+            .iter()
+            .find_map(|event| detector.observe(event, network))
+    }
+
+    #[test]
+    fn test_read_then_fetch_unknown_domain() {
+        let mut detector = AnomalyDetector::default();
+        let anomaly = observe_all(
+            &mut detector,
+            &[
+                event(0, "fs.read", "/home/user/.ssh/id_rsa", true),
+                event(2, "network.fetch", "https://evil.example/upload", true),
+            ],
+            &allowlist(),
+        )
+        .unwrap();
+        assert_eq!(anomaly.plugin_id, "suspicious");
+        assert_eq!(anomaly.rule, "read-then-fetch");
+        assert!(anomaly.detail.contains("evil.example"));
+        assert_eq!(anomaly.detected_at, 1_700_000_002);
+    }
+
+    #[test]
+    fn test_read_then_fetch_ignores_known_and_late_fetches() {
+        let allowlist = allowlist();
+        let mut detector = AnomalyDetector::default();
+        assert!(observe_all(
+            &mut detector,
+            &[
+                event(0, "fs.read", "src/main.rs", true),
+                event(1, "network.fetch", "https://api.example.com/lint", true),
+            ],
+            &allowlist,
+        )
+        .is_none());
+
+        let mut detector = AnomalyDetector::default();
+        assert!(observe_all(
+            &mut detector,
+            &[
+                event(0, "fs.read", "src/main.rs", true),
+                event(30, "network.fetch", "https://evil.example/upload", true),
+            ],
+            &allowlist,
+        )
+        .is_none());
+
+        let mut detector = AnomalyDetector::new(AnomalyRules {
+            rules: vec![AnomalyRule::ReadThenFetch {
+                window_secs: 5,
+                known_domains: vec!["registry.example".to_string()],
+            }],
+        });
+        assert!(observe_all(
+            &mut detector,
+            &[
+                event(0, "fs.read", "package.json", true),
+                event(1, "network.fetch", "https://registry.example/pkg", true),
+            ],
+            &allowlist,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_read_then_fetch_ignores_unrestricted_plugins() {
+        let mut detector = AnomalyDetector::default();
+        assert!(observe_all(
+            &mut detector,
+            &[
+                event(0, "fs.read", "src/main.rs", true),
+                event(1, "network.fetch", "https://any.example/upload", true),
+            ],
+            &NetworkCapability::Unrestricted,
+        )
+        .is_none());
+
+        // First-party plugins are watched with their unrestricted access
+        let mut detector = AnomalyDetector::default();
+        detector.watch("suspicious", PluginCapabilities::first_party().network);
+        assert!([
+            event(0, "fs.read", "src/main.rs", true),
+            event(1, "network.fetch", "https://any.example/upload", true),
+        ]
+        .iter()
+        .all(|e| detector.inspect(e).is_none()));
+    }
+
+    #[test]
+    fn test_denied_burst() {
+        let mut detector = AnomalyDetector::default();
+        let denials: Vec<AuditEvent> = (0..10)
+            .map(|i| event(i / 2, "command.execute", "rm", false))
+            .collect();
+        assert!(observe_all(&mut detector, &denials, &NetworkCapability::None).is_none());
+
+        let anomaly = detector
+            .observe(
+                &event(6, "fs.read", "/etc/shadow", false),
+                &NetworkCapability::None,
+            )
+            .unwrap();
+        assert_eq!(anomaly.rule, "denied-burst");
+        assert!(anomaly.detail.starts_with("11 denied"));
+
+        // The window was cleared, so the same burst is not reported twice
+        assert!(detector
+            .observe(
+                &event(7, "fs.read", "/etc/shadow", false),
+                &NetworkCapability::None
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_denials_spread_out_are_not_a_burst() {
+        let mut detector = AnomalyDetector::default();
+        let denials: Vec<AuditEvent> = (0..30)
+            .map(|i| event(i * 2, "command.execute", "rm", false))
+            .collect();
+        assert!(observe_all(&mut detector, &denials, &NetworkCapability::None).is_none());
+    }
+
+    #[test]
+    fn test_write_burst_counts_distinct_files() {
+        let mut detector = AnomalyDetector::default();
+        let same_file: Vec<AuditEvent> = (0..40)
+            .map(|_| event(1, "fs.write", "notes.md", true))
+            .collect();
+        assert!(observe_all(&mut detector, &same_file, &NetworkCapability::None).is_none());
+
+        let many_files: Vec<AuditEvent> = (0..21)
+            .map(|i| event(2, "fs.write", &format!("src/{}.rs", i), true))
+            .collect();
+        let anomaly = observe_all(&mut detector, &many_files, &NetworkCapability::None).unwrap();
+        assert_eq!(anomaly.rule, "write-burst");
+    }
+
+    #[test]
+    fn test_windows_are_per_plugin() {
+        let mut detector = AnomalyDetector::default();
+        detector.observe(
+            &event(0, "fs.read", "secret.txt", true),
+            &NetworkCapability::None,
+        );
+        let mut fetch = event(1, "network.fetch", "https://evil.example/", true);
+        fetch.plugin_id = "other".to_string();
+        assert!(detector.observe(&fetch, &allowlist()).is_none());
+    }
+
+    #[test]
+    fn test_inspect_only_checks_watched_plugins() {
+        let events = [
+            event(0, "fs.read", "/home/user/.ssh/id_rsa", true),
+            event(2, "network.fetch", "https://evil.example/upload", true),
+        ];
+        let mut detector = AnomalyDetector::default();
+        assert!(events.iter().all(|e| detector.inspect(e).is_none()));
+
+        detector.watch("suspicious", allowlist());
+        assert!(events.iter().find_map(|e| detector.inspect(e)).is_some());
+
+        detector.forget("suspicious");
+        assert!(events.iter().all(|e| detector.inspect(e).is_none()));
+    }
+
+    #[test]
+    fn test_rules_deserialize() {
+        let rules: AnomalyRules = serde_json::from_str(
+            r#"{"rules": [{"rule": "write-burst", "max_files": 5, "window_secs": 2}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules.rules,
+            vec![AnomalyRule::WriteBurst {
+                max_files: 5,
+                window_secs: 2
+            }]
+        );
+    }
+}
@@ -1,85 +1,25 @@
 // src-tauri/src/plugin_system/api.rs
 
 use crate::plugin_system::{
-    capabilities::{FilesystemCapability, NetworkCapability, PluginCapabilities},
+    audit::{AuditEvent, AuditLogger},
+    audit_store::{AuditQuery, AuditRecord, AuditStore, AuditStoreError, ExportFormat},
+    capabilities::PluginCapabilities,
+    consent::{ConsentError, ConsentRequest, CONSENT_REQUEST_EVENT},
+    host::{Notification, Panel, PluginHost, StatusBarItem},
     manager::PluginManager,
     ops::EditorStateHandle,
-    sandbox::SandboxRegistry,
+    policy::{PolicyContext, PolicyEngine, PolicyError},
+    sandbox::{ResourceLimits, SandboxRegistry},
+    worker::validate_hook_name,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::SystemTime;
 use tauri::{Emitter, State, WebviewWindow};
 use tokio::sync::RwLock;
 
-// ============================================================================
-// AUDIT LOGGING
-// ============================================================================
-
-/// Audit event for tracking plugin operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AuditEvent {
-    pub plugin_id: String,
-    pub operation: String,
-    pub resource: String,
-    pub timestamp: SystemTime,
-    pub success: bool,
-    pub error: Option<String>,
-}
-
-/// Audit logger for tracking plugin operations
-pub struct AuditLogger {
-    events: Arc<RwLock<Vec<AuditEvent>>>,
-    max_events: usize,
-}
-
-impl AuditLogger {
-    pub fn new(max_events: usize) -> Self {
-        Self {
-            events: Arc::new(RwLock::new(Vec::new())),
-            max_events,
-        }
-    }
-
-    pub async fn log(&self, event: AuditEvent) {
-        let mut events = self.events.write().await;
-
-        // Rotate logs if exceeding max
-        if events.len() >= self.max_events {
-            events.remove(0);
-        }
-
-        events.push(event.clone());
-
-        // Also log to console in debug mode
-        #[cfg(debug_assertions)]
-        println!(
-            "[AUDIT] Plugin: {}, Op: {}, Resource: {}, Success: {}",
-            event.plugin_id, event.operation, event.resource, event.success
-        );
-    }
-
-    pub async fn get_events(&self) -> Vec<AuditEvent> {
-        self.events.read().await.clone()
-    }
-
-    pub async fn get_events_for_plugin(&self, plugin_id: &str) -> Vec<AuditEvent> {
-        self.events
-            .read()
-            .await
-            .iter()
-            .filter(|e| e.plugin_id == plugin_id)
-            .cloned()
-            .collect()
-    }
-
-    pub async fn clear(&self) {
-        self.events.write().await.clear();
-    }
-}
-
 // ============================================================================
 // FILE WATCHER REGISTRY
 // ============================================================================
@@ -169,6 +109,12 @@ pub enum ApiError {
     #[error("Serialization failed: {0}")]
     SerializationError(String),
 
+    #[error("Invalid event name: {name}")]
+    InvalidEventName { name: String },
+
+    #[error("{0}")]
+    ResourceLimit(String),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 }
@@ -185,8 +131,35 @@ impl From<serde_json::Error> for ApiError {
     }
 }
 
+impl From<PolicyError> for ApiError {
+    fn from(err: PolicyError) -> Self {
+        match err {
+            PolicyError::PermissionDenied {
+                operation,
+                capability,
+            } => ApiError::PermissionDenied {
+                operation,
+                capability,
+            },
+            PolicyError::InvalidPath { path, .. } => ApiError::InvalidPath { path },
+            PolicyError::PathNotAllowed { path } => ApiError::PathNotAllowed { path },
+            PolicyError::DomainNotAllowed(domain) => ApiError::DomainNotAllowed { domain },
+            PolicyError::Network(e) => ApiError::NetworkError(e.to_string()),
+            PolicyError::Fetch(e) => ApiError::NetworkError(e),
+            PolicyError::CommandNotAllowed(command) => ApiError::CommandNotAllowed { command },
+            PolicyError::Command(e) => ApiError::CommandError(e.to_string()),
+            PolicyError::Resource(e) => ApiError::ResourceLimit(e.to_string()),
+            PolicyError::Consent(ConsentError::Emit(reason)) => ApiError::UiError(reason),
+            PolicyError::Consent(_) => ApiError::PermissionDenied {
+                operation: "command execution".to_string(),
+                capability: "user confirmation".to_string(),
+            },
+        }
+    }
+}
+
 // ============================================================================
-// CAPABILITY VALIDATION HELPERS
+// CALLER CONTEXT
 // ============================================================================
 
 /// Get plugin capabilities
@@ -202,138 +175,38 @@ async fn get_plugin_capabilities(
         })
 }
 
-async fn get_workspace_root(
-    manager: &State<'_, Arc<RwLock<PluginManager>>>,
-) -> Result<PathBuf, ApiError> {
-    let manager = manager.read().await;
-    Ok(manager.workspace_root().to_path_buf())
-}
-
-/// Validate filesystem read permission
-fn validate_fs_read(
-    capabilities: &PluginCapabilities,
-    path: &Path,
-    workspace_root: &Path,
-) -> Result<(), ApiError> {
-    match &capabilities.filesystem {
-        FilesystemCapability::None => Err(ApiError::PermissionDenied {
-            operation: "read".to_string(),
-            capability: "filesystem read".to_string(),
-        }),
-        FilesystemCapability::WorkspaceRead | FilesystemCapability::WorkspaceReadWrite => {
-            if path.starts_with(workspace_root) {
-                Ok(())
-            } else {
-                Err(ApiError::PathNotAllowed {
-                    path: path.display().to_string(),
-                })
-            }
-        }
-        FilesystemCapability::Scoped { read, .. } => {
-            if read.iter().any(|allowed| path.starts_with(allowed)) {
-                Ok(())
-            } else {
-                Err(ApiError::PathNotAllowed {
-                    path: path.display().to_string(),
-                })
-            }
-        }
-    }
-}
-
-/// Validate filesystem write permission
-fn validate_fs_write(
-    capabilities: &PluginCapabilities,
-    path: &Path,
-    workspace_root: &Path,
-) -> Result<(), ApiError> {
-    match &capabilities.filesystem {
-        FilesystemCapability::None | FilesystemCapability::WorkspaceRead => {
-            Err(ApiError::PermissionDenied {
-                operation: "write".to_string(),
-                capability: "filesystem write".to_string(),
-            })
-        }
-        FilesystemCapability::WorkspaceReadWrite => {
-            if path.starts_with(workspace_root) {
-                Ok(())
-            } else {
-                Err(ApiError::PathNotAllowed {
-                    path: path.display().to_string(),
-                })
-            }
-        }
-        FilesystemCapability::Scoped { write, .. } => {
-            if write.iter().any(|allowed| path.starts_with(allowed)) {
-                Ok(())
-            } else {
-                Err(ApiError::PathNotAllowed {
-                    path: path.display().to_string(),
-                })
-            }
-        }
-    }
-}
-
-/// Validate network permission
-fn validate_network(capabilities: &PluginCapabilities, url: &str) -> Result<(), ApiError> {
-    match &capabilities.network {
-        NetworkCapability::None => Err(ApiError::PermissionDenied {
-            operation: "network request".to_string(),
-            capability: "network access".to_string(),
-        }),
-        NetworkCapability::DomainAllowlist(domains) => {
-            let url_parsed =
-                url::Url::parse(url).map_err(|e| ApiError::NetworkError(e.to_string()))?;
-            let domain = url_parsed
-                .host_str()
-                .ok_or_else(|| ApiError::NetworkError("Invalid URL".to_string()))?;
-
-            if domains.contains(domain) {
-                Ok(())
-            } else {
-                Err(ApiError::DomainNotAllowed {
-                    domain: domain.to_string(),
-                })
-            }
-        }
-        NetworkCapability::Unrestricted => Ok(()),
-    }
+/// The calling plugin, as the policy engine needs to see it
+struct Caller {
+    plugin_id: String,
+    capabilities: PluginCapabilities,
+    workspace_root: PathBuf,
+    limits: ResourceLimits,
 }
 
-/// Validate command execution permission
-fn validate_command(capabilities: &PluginCapabilities, command: &str) -> Result<(), ApiError> {
-    if command.contains('/') || command.contains('\\') || command.contains(':') {
-        return Err(ApiError::CommandNotAllowed {
-            command: command.to_string(),
-        });
-    }
-    if capabilities.commands.allowlist.contains(command) {
-        Ok(())
-    } else {
-        Err(ApiError::CommandNotAllowed {
-            command: command.to_string(),
+impl Caller {
+    async fn load(plugin_id: &str, manager: &RwLock<PluginManager>) -> Result<Self, ApiError> {
+        let manager = manager.read().await;
+        let capabilities =
+            manager
+                .get_plugin_capabilities(plugin_id)
+                .ok_or_else(|| ApiError::PluginNotFound {
+                    plugin_id: plugin_id.to_string(),
+                })?;
+        Ok(Self {
+            plugin_id: plugin_id.to_string(),
+            capabilities,
+            workspace_root: manager.workspace_root().to_path_buf(),
+            limits: manager.effective_limits(plugin_id).unwrap_or_default(),
         })
     }
-}
-
-/// Validate UI permission
-fn validate_ui(capabilities: &PluginCapabilities, operation: &str) -> Result<(), ApiError> {
-    let allowed = match operation {
-        "status_bar" => capabilities.ui.status_bar,
-        "sidebar" => capabilities.ui.sidebar,
-        "notification" => capabilities.ui.notifications,
-        "webview" => capabilities.ui.webview,
-        _ => false,
-    };
 
-    if allowed {
-        Ok(())
-    } else {
-        Err(ApiError::PermissionDenied {
-            operation: operation.to_string(),
-            capability: format!("UI {}", operation),
-        })
+    fn context(&self) -> PolicyContext<'_> {
+        PolicyContext {
+            plugin_id: &self.plugin_id,
+            capabilities: &self.capabilities,
+            workspace_root: &self.workspace_root,
+            limits: &self.limits,
+        }
     }
 }
 
@@ -351,39 +224,42 @@ pub struct ReadFileParams {
 pub async fn plugin_read_file(
     params: ReadFileParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    policy: State<'_, Arc<PolicyEngine>>,
 ) -> Result<String, ApiError> {
-    let start_time = SystemTime::now();
-    let path = PathBuf::from(&params.path)
-        .canonicalize()
-        .map_err(|_| ApiError::InvalidPath {
-            path: params.path.clone(),
-        })?;
-
-    let workspace_root = get_workspace_root(&manager).await?;
-
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
+    read_file(params, &manager, &policy).await
+}
 
-    // Validate permission
-    validate_fs_read(&capabilities, &path, &workspace_root)?;
+async fn read_file(
+    params: ReadFileParams,
+    manager: &RwLock<PluginManager>,
+    policy: &PolicyEngine,
+) -> Result<String, ApiError> {
+    let start_time = SystemTime::now();
+    let caller = Caller::load(&params.plugin_id, manager).await?;
+    let mut resource = params.path.clone();
 
-    // Perform operation
-    let result = tokio::fs::read_to_string(&path).await;
+    let result = async {
+        // Validate permission
+        let path = policy.authorize_read(&caller.context(), &params.path)?;
+        resource = path.display().to_string();
 
-    // Audit log
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: "fs.read".to_string(),
-            resource: path.display().to_string(),
-            timestamp: start_time,
-            success: result.is_ok(),
-            error: result.as_ref().err().map(|e| e.to_string()),
-        })
-        .await;
-
-    result.map_err(|e| ApiError::FileError(e.to_string()))
+        // Perform operation
+        tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| ApiError::FileError(e.to_string()))
+    }
+    .await;
+
+    // Audit log, denials included
+    policy.record(
+        &params.plugin_id,
+        "fs.read",
+        &resource,
+        start_time,
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+
+    result
 }
 
 #[derive(Debug, Deserialize)]
@@ -397,64 +273,42 @@ pub struct WriteFileParams {
 pub async fn plugin_write_file(
     params: WriteFileParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    policy: State<'_, Arc<PolicyEngine>>,
 ) -> Result<(), ApiError> {
-    let start_time = SystemTime::now();
-
-    // Ensure parent directory exists before canonicalizing
-    let raw_path = PathBuf::from(&params.path);
-    if let Some(parent) = raw_path.parent() {
-        if !parent.exists() {
-            return Err(ApiError::InvalidPath {
-                path: params.path.clone(),
-            });
-        }
-    }
-
-    // Canonicalize the parent and re-attach the filename for new files
-    let path = if raw_path.exists() {
-        raw_path.canonicalize().map_err(|_| ApiError::InvalidPath {
-            path: params.path.clone(),
-        })?
-    } else {
-        let parent = raw_path
-            .parent()
-            .ok_or_else(|| ApiError::InvalidPath {
-                path: params.path.clone(),
-            })?
-            .canonicalize()
-            .map_err(|_| ApiError::InvalidPath {
-                path: params.path.clone(),
-            })?;
-        parent.join(raw_path.file_name().ok_or_else(|| ApiError::InvalidPath {
-            path: params.path.clone(),
-        })?)
-    };
-
-    let workspace_root = get_workspace_root(&manager).await?;
-
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-
-    // Validate permission
-    validate_fs_write(&capabilities, &path, &workspace_root)?;
+    write_file(params, &manager, &policy).await
+}
 
-    // Perform operation
-    let result = tokio::fs::write(&path, &params.content).await;
+async fn write_file(
+    params: WriteFileParams,
+    manager: &RwLock<PluginManager>,
+    policy: &PolicyEngine,
+) -> Result<(), ApiError> {
+    let start_time = SystemTime::now();
+    let caller = Caller::load(&params.plugin_id, manager).await?;
+    let mut resource = params.path.clone();
 
-    // Audit log
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: "fs.write".to_string(),
-            resource: path.display().to_string(),
-            timestamp: start_time,
-            success: result.is_ok(),
-            error: result.as_ref().err().map(|e| e.to_string()),
-        })
-        .await;
+    let result = async {
+        // Validate permission; new files resolve through their deepest existing ancestor
+        let path = policy.authorize_write(&caller.context(), &params.path)?;
+        resource = path.display().to_string();
 
-    result.map_err(|e| ApiError::FileError(e.to_string()))
+        // Perform operation
+        tokio::fs::write(&path, &params.content)
+            .await
+            .map_err(|e| ApiError::FileError(e.to_string()))
+    }
+    .await;
+
+    // Audit log, denials included
+    policy.record(
+        &params.plugin_id,
+        "fs.write",
+        &resource,
+        start_time,
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+
+    result
 }
 
 #[derive(Debug, Deserialize)]
@@ -476,57 +330,59 @@ pub struct DirectoryEntry {
 pub async fn plugin_list_directory(
     params: ListDirectoryParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    policy: State<'_, Arc<PolicyEngine>>,
 ) -> Result<Vec<DirectoryEntry>, ApiError> {
     let start_time = SystemTime::now();
-    let path = PathBuf::from(&params.path)
-        .canonicalize()
-        .map_err(|_| ApiError::InvalidPath {
-            path: params.path.clone(),
-        })?;
-
-    let workspace_root = get_workspace_root(&manager).await?;
-
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-
-    // Validate permission
-    validate_fs_read(&capabilities, &path, &workspace_root)?;
-
-    // Perform operation
-    let mut entries = Vec::new();
-    let mut read_dir = tokio::fs::read_dir(&path).await?;
-
-    while let Some(entry) = read_dir.next_entry().await? {
-        let metadata = entry.metadata().await?;
-        let modified = metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
-
-        entries.push(DirectoryEntry {
-            name: entry.file_name().to_string_lossy().to_string(),
-            path: entry.path().display().to_string(),
-            is_dir: metadata.is_dir(),
-            size: metadata.len(),
-            modified,
-        });
+    let caller = Caller::load(&params.plugin_id, &manager).await?;
+    let mut resource = params.path.clone();
+
+    let result: Result<Vec<DirectoryEntry>, ApiError> = async {
+        // Validate permission
+        let path = policy.authorize_read(&caller.context(), &params.path)?;
+        resource = path.display().to_string();
+
+        // Perform operation
+        let mut entries = Vec::new();
+        let mut read_dir = tokio::fs::read_dir(&path).await?;
+
+        let workspace = caller.workspace_root.to_string_lossy();
+        while let Some(entry) = read_dir.next_entry().await? {
+            if caller
+                .capabilities
+                .filesystem
+                .denies(&entry.path().to_string_lossy(), &workspace)
+            {
+                continue;
+            }
+            let metadata = entry.metadata().await?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+
+            entries.push(DirectoryEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: entry.path().display().to_string(),
+                is_dir: metadata.is_dir(),
+                size: metadata.len(),
+                modified,
+            });
+        }
+        Ok(entries)
     }
-
-    // Audit log
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: "fs.list".to_string(),
-            resource: path.display().to_string(),
-            timestamp: start_time,
-            success: true,
-            error: None,
-        })
-        .await;
-
-    Ok(entries)
+    .await;
+
+    // Audit log, denials included
+    policy.record(
+        &params.plugin_id,
+        "fs.list",
+        &resource,
+        start_time,
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+
+    result
 }
 
 #[derive(Debug, Deserialize)]
@@ -540,71 +396,79 @@ pub async fn plugin_watch_path(
     params: WatchPathParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
     watcher_registry: State<'_, Arc<FileWatcherRegistry>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    policy: State<'_, Arc<PolicyEngine>>,
     window: WebviewWindow,
 ) -> Result<String, ApiError> {
-    let path = PathBuf::from(&params.path)
-        .canonicalize()
-        .map_err(|_| ApiError::InvalidPath {
-            path: params.path.clone(),
-        })?;
-
-    let workspace_root = get_workspace_root(&manager).await?;
-
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-
-    // Validate permission
-    validate_fs_read(&capabilities, &path, &workspace_root)?;
-
-    // Set up file watcher
-    use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-    use std::sync::mpsc::channel;
-
-    let (tx, rx) = channel();
-    let mut watcher: RecommendedWatcher = Watcher::new(tx, notify::Config::default())
-        .map_err(|e| ApiError::FileError(e.to_string()))?;
-
-    watcher
-        .watch(&path, RecursiveMode::Recursive)
-        .map_err(|e| ApiError::FileError(e.to_string()))?;
-
-    let watch_id = format!("watch_{}", uuid::Uuid::new_v4());
-    let plugin_id_clone = params.plugin_id.clone();
-    let window_clone = window.clone();
-
-    // Store watcher in registry so it persists and can be cleaned up
-    watcher_registry
-        .register(watch_id.clone(), params.plugin_id.clone(), watcher)
-        .await;
+    let start_time = SystemTime::now();
+    let caller = Caller::load(&params.plugin_id, &manager).await?;
+    let mut resource = params.path.clone();
+
+    let result: Result<String, ApiError> = async {
+        // Validate permission
+        let path = policy.authorize_read(&caller.context(), &params.path)?;
+        resource = path.display().to_string();
+
+        // Set up file watcher
+        use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+        use std::sync::mpsc::channel;
+
+        let (tx, rx) = channel();
+        let mut watcher: RecommendedWatcher = Watcher::new(tx, notify::Config::default())
+            .map_err(|e| ApiError::FileError(e.to_string()))?;
+
+        watcher
+            .watch(&path, RecursiveMode::Recursive)
+            .map_err(|e| ApiError::FileError(e.to_string()))?;
+
+        let watch_id = format!("watch_{}", uuid::Uuid::new_v4());
+        let plugin_id_clone = params.plugin_id.clone();
+        let window_clone = window.clone();
+        let filesystem = caller.capabilities.filesystem.clone();
+        let workspace = caller.workspace_root.to_string_lossy().to_string();
+
+        // Store watcher in registry so it persists and can be cleaned up
+        watcher_registry
+            .register(watch_id.clone(), params.plugin_id.clone(), watcher)
+            .await;
 
-    // Spawn event relay task
-    tokio::spawn(async move {
-        while let Ok(event) = rx.recv() {
-            if let Ok(event) = event {
-                let payload = serde_json::json!({
-                    "kind": format!("{:?}", event.kind),
-                    "paths": event.paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
-                });
-                let _ =
-                    window_clone.emit(&format!("plugin:{}:file_change", plugin_id_clone), payload);
+        // Spawn event relay task
+        tokio::spawn(async move {
+            while let Ok(event) = rx.recv() {
+                if let Ok(event) = event {
+                    // Denied files (e.g. `.env`) are not reported, even by name
+                    let paths: Vec<String> = event
+                        .paths
+                        .iter()
+                        .map(|p| p.display().to_string())
+                        .filter(|p| !filesystem.denies(p, &workspace))
+                        .collect();
+                    if paths.is_empty() {
+                        continue;
+                    }
+                    let payload = serde_json::json!({
+                        "kind": format!("{:?}", event.kind),
+                        "paths": paths,
+                    });
+                    let _ =
+                        window_clone.emit(&format!("plugin:{}:file_change", plugin_id_clone), payload);
+                }
             }
-        }
-    });
-
-    // Audit log
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: "fs.watch".to_string(),
-            resource: path.display().to_string(),
-            timestamp: SystemTime::now(),
-            success: true,
-            error: None,
-        })
-        .await;
+        });
 
-    Ok(watch_id)
+        Ok(watch_id)
+    }
+    .await;
+
+    // Audit log, denials included
+    policy.record(
+        &params.plugin_id,
+        "fs.watch",
+        &resource,
+        start_time,
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+
+    result
 }
 
 #[derive(Debug, Deserialize)]
@@ -617,26 +481,19 @@ pub struct UnwatchPathParams {
 pub async fn plugin_unwatch_path(
     params: UnwatchPathParams,
     watcher_registry: State<'_, Arc<FileWatcherRegistry>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    policy: State<'_, Arc<PolicyEngine>>,
 ) -> Result<(), ApiError> {
     let removed = watcher_registry
         .unregister(&params.watch_id, &params.plugin_id)
         .await;
 
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: "fs.unwatch".to_string(),
-            resource: params.watch_id.clone(),
-            timestamp: SystemTime::now(),
-            success: removed,
-            error: if removed {
-                None
-            } else {
-                Some(format!("Watch ID not found: {}", params.watch_id))
-            },
-        })
-        .await;
+    policy.record(
+        &params.plugin_id,
+        "fs.unwatch",
+        &params.watch_id,
+        SystemTime::now(),
+        (!removed).then(|| format!("Watch ID not found: {}", params.watch_id)),
+    );
 
     if removed {
         Ok(())
@@ -672,73 +529,84 @@ pub struct FetchResponse {
 pub async fn plugin_fetch(
     params: FetchParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    policy: State<'_, Arc<PolicyEngine>>,
 ) -> Result<FetchResponse, ApiError> {
-    let start_time = SystemTime::now();
-
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
+    fetch(params, &manager, &policy).await
+}
 
-    // Validate permission
-    validate_network(&capabilities, &params.url)?;
-
-    // Build request
-    let client = reqwest::Client::new();
-    let method = params
-        .method
-        .as_deref()
-        .unwrap_or("GET")
-        .parse::<reqwest::Method>()
-        .map_err(|e| ApiError::NetworkError(e.to_string()))?;
-
-    let mut request = client.request(method, &params.url);
-
-    // Add headers
-    if let Some(headers) = params.headers {
-        for (key, value) in headers {
-            request = request.header(&key, &value);
+async fn fetch(
+    params: FetchParams,
+    manager: &RwLock<PluginManager>,
+    policy: &PolicyEngine,
+) -> Result<FetchResponse, ApiError> {
+    let start_time = SystemTime::now();
+    let caller = Caller::load(&params.plugin_id, manager).await?;
+    let (request_headers, request_body) = (params.headers, params.body);
+
+    let result: Result<FetchResponse, ApiError> = async {
+        // Validate permission, then pin the request to the vetted addresses
+        let target = policy
+            .authorize_fetch(
+                &caller.context(),
+                &params.url,
+                params.method.as_deref().unwrap_or("GET"),
+            )?
+            .resolve()
+            .await
+            .map_err(|e| ApiError::NetworkError(e.to_string()))?;
+
+        // Build request
+        let client = target
+            .client(&caller.capabilities.network)
+            .map_err(|e| ApiError::NetworkError(e.to_string()))?;
+        let mut request = client
+            .request(target.method(), target.url.clone())
+            .timeout(policy.timeout());
+
+        // Add headers
+        if let Some(headers) = request_headers {
+            for (key, value) in headers {
+                request = request.header(&key, &value);
+            }
         }
-    }
 
-    // Add body
-    if let Some(body) = params.body {
-        request = request.body(body);
-    }
+        // Add body
+        if let Some(body) = request_body {
+            request = request.body(body);
+        }
 
-    // Execute request
-    let response = request
-        .send()
-        .await
-        .map_err(|e| ApiError::NetworkError(e.to_string()))?;
-
-    let status = response.status().as_u16();
-    let headers = response
-        .headers()
-        .iter()
-        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-        .collect();
-    let body = response
-        .text()
-        .await
-        .map_err(|e| ApiError::NetworkError(e.to_string()))?;
+        // Execute request
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ApiError::NetworkError(e.to_string()))?;
 
-    // Audit log
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: "network.fetch".to_string(),
-            resource: params.url.clone(),
-            timestamp: start_time,
-            success: true,
-            error: None,
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+            .collect();
+        let body = policy.read_body(&caller.context(), response).await?;
+
+        Ok(FetchResponse {
+            status,
+            headers,
+            body,
         })
-        .await;
-
-    Ok(FetchResponse {
-        status,
-        headers,
-        body,
-    })
+    }
+    .await;
+
+    // Audit log, denials included
+    policy.record(
+        &params.plugin_id,
+        "network.fetch",
+        &params.url,
+        start_time,
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+
+    result
 }
 
 // ============================================================================
@@ -764,78 +632,67 @@ pub struct CommandOutput {
 pub async fn plugin_execute_command(
     params: ExecuteCommandParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    policy: State<'_, Arc<PolicyEngine>>,
+    window: WebviewWindow,
 ) -> Result<CommandOutput, ApiError> {
-    let start_time = SystemTime::now();
+    execute_command(params, &manager, &policy, |req| {
+        window
+            .emit(CONSENT_REQUEST_EVENT, req)
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
 
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
+/// `emit` shows the consent request when the command needs confirmation.
+async fn execute_command(
+    params: ExecuteCommandParams,
+    manager: &RwLock<PluginManager>,
+    policy: &PolicyEngine,
+    emit: impl FnOnce(&ConsentRequest) -> Result<(), String>,
+) -> Result<CommandOutput, ApiError> {
+    let start_time = SystemTime::now();
+    let caller = Caller::load(&params.plugin_id, manager).await?;
+    let ctx = caller.context();
+
+    let result: Result<CommandOutput, ApiError> = async {
+        // Validate permission; the raw argv is checked and runs unchanged, without a shell
+        let authorized =
+            policy.authorize_command(&ctx, &params.command, &params.args, params.cwd.as_deref())?;
+        let slot = policy.command_slot(&ctx)?;
+
+        // Ask the user first when the capability requires confirmation
+        policy
+            .confirm(&ctx, &authorized, &params.command, emit)
+            .await?;
+
+        // Execute command
+        let output = policy
+            .output(&authorized, &slot)
+            .await
+            .map_err(|e| ApiError::CommandError(e.to_string()))?;
 
-    // Validate permission
-    validate_command(&capabilities, &params.command)?;
-
-    // Sanitize arguments (prevent injection)
-    let sanitized_args: Vec<String> = params
-        .args
-        .iter()
-        .map(|arg| {
-            // Remove any shell metacharacters
-            arg.replace(&['|', '&', ';', '>', '<', '`', '$', '\n', '\r'][..], "")
+        Ok(CommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            status: output.status.code().unwrap_or(-1),
         })
-        .collect();
-
-    if capabilities.commands.require_confirmation {
-        return Err(ApiError::PermissionDenied {
-            operation: "command execution".to_string(),
-            capability: "user confirmation".to_string(),
-        });
-    }
-
-    let workspace_root = get_workspace_root(&manager).await?;
-
-    // Execute command
-    let mut cmd = tokio::process::Command::new(&params.command);
-    cmd.args(&sanitized_args);
-    cmd.kill_on_drop(true);
-
-    if let Some(cwd) = params.cwd {
-        let cwd_path = PathBuf::from(cwd)
-            .canonicalize()
-            .map_err(|_| ApiError::InvalidPath {
-                path: "cwd".to_string(),
-            })?;
-        validate_fs_read(&capabilities, &cwd_path, &workspace_root)?;
-        cmd.current_dir(cwd_path);
     }
+    .await;
 
-    let output = tokio::time::timeout(std::time::Duration::from_secs(30), cmd.output())
-        .await
-        .map_err(|_| ApiError::CommandError("Command timed out after 30s".to_string()))?
-        .map_err(|e| ApiError::CommandError(e.to_string()))?;
-
-    let result = CommandOutput {
-        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-        status: output.status.code().unwrap_or(-1),
+    // Audit log, denials included; a failing command records its stderr
+    let error = match &result {
+        Ok(output) => (output.status != 0).then(|| output.stderr.clone()),
+        Err(e) => Some(e.to_string()),
     };
-
-    // Audit log
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: "command.execute".to_string(),
-            resource: format!("{} {:?}", params.command, sanitized_args),
-            timestamp: start_time,
-            success: output.status.success(),
-            error: if !output.status.success() {
-                Some(result.stderr.clone())
-            } else {
-                None
-            },
-        })
-        .await;
-
-    Ok(result)
+    policy.record(
+        &params.plugin_id,
+        "command.execute",
+        &format!("{} {:?}", params.command, params.args),
+        start_time,
+        error,
+    );
+
+    result
 }
 
 // ============================================================================
@@ -854,26 +711,30 @@ pub struct ShowNotificationParams {
 pub async fn plugin_show_notification(
     params: ShowNotificationParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    window: WebviewWindow,
+    policy: State<'_, Arc<PolicyEngine>>,
+    host: State<'_, Arc<dyn PluginHost>>,
 ) -> Result<(), ApiError> {
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
+    show_notification(params, &manager, &policy, host.inner().as_ref()).await
+}
 
+async fn show_notification(
+    params: ShowNotificationParams,
+    manager: &RwLock<PluginManager>,
+    policy: &PolicyEngine,
+    host: &dyn PluginHost,
+) -> Result<(), ApiError> {
     // Validate permission
-    validate_ui(&capabilities, "notification")?;
+    let caller = Caller::load(&params.plugin_id, manager).await?;
+    policy.authorize_ui(&caller.context(), "notification")?;
 
     // Emit notification event to frontend
-    window
-        .emit(
-            "plugin:notification",
-            serde_json::json!({
-                "plugin_id": params.plugin_id,
-                "title": params.title,
-                "message": params.message,
-                "level": params.level.unwrap_or_else(|| "info".to_string()),
-            }),
-        )
-        .map_err(|e| ApiError::UiError(e.to_string()))?;
+    host.show_notification(&Notification {
+        plugin_id: params.plugin_id,
+        title: params.title,
+        message: params.message,
+        level: params.level.unwrap_or_else(|| "info".to_string()),
+    })
+    .map_err(ApiError::UiError)?;
 
     Ok(())
 }
@@ -891,27 +752,22 @@ pub struct AddStatusBarItemParams {
 pub async fn plugin_add_status_bar_item(
     params: AddStatusBarItemParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    window: WebviewWindow,
+    policy: State<'_, Arc<PolicyEngine>>,
+    host: State<'_, Arc<dyn PluginHost>>,
 ) -> Result<String, ApiError> {
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-
     // Validate permission
-    validate_ui(&capabilities, "status_bar")?;
+    let caller = Caller::load(&params.plugin_id, &manager).await?;
+    policy.authorize_ui(&caller.context(), "status_bar")?;
 
     // Emit event to frontend
-    window
-        .emit(
-            "plugin:status_bar:add",
-            serde_json::json!({
-                "plugin_id": params.plugin_id,
-                "id": params.id,
-                "text": params.text,
-                "tooltip": params.tooltip,
-                "priority": params.priority.unwrap_or(0),
-            }),
-        )
-        .map_err(|e| ApiError::UiError(e.to_string()))?;
+    host.add_status_bar_item(&StatusBarItem {
+        plugin_id: params.plugin_id,
+        id: params.id.clone(),
+        text: params.text,
+        tooltip: params.tooltip,
+        priority: params.priority.unwrap_or(0),
+    })
+    .map_err(ApiError::UiError)?;
 
     Ok(params.id)
 }
@@ -926,24 +782,16 @@ pub struct RemoveStatusBarItemParams {
 pub async fn plugin_remove_status_bar_item(
     params: RemoveStatusBarItemParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    window: WebviewWindow,
+    policy: State<'_, Arc<PolicyEngine>>,
+    host: State<'_, Arc<dyn PluginHost>>,
 ) -> Result<(), ApiError> {
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-
     // Validate permission
-    validate_ui(&capabilities, "status_bar")?;
+    let caller = Caller::load(&params.plugin_id, &manager).await?;
+    policy.authorize_ui(&caller.context(), "status_bar")?;
 
     // Emit event to frontend
-    window
-        .emit(
-            "plugin:status_bar:remove",
-            serde_json::json!({
-                "plugin_id": params.plugin_id,
-                "id": params.id,
-            }),
-        )
-        .map_err(|e| ApiError::UiError(e.to_string()))?;
+    host.remove_status_bar_item(&params.plugin_id, &params.id)
+        .map_err(ApiError::UiError)?;
 
     Ok(())
 }
@@ -961,27 +809,22 @@ pub struct ShowPanelParams {
 pub async fn plugin_show_panel(
     params: ShowPanelParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    window: WebviewWindow,
+    policy: State<'_, Arc<PolicyEngine>>,
+    host: State<'_, Arc<dyn PluginHost>>,
 ) -> Result<(), ApiError> {
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-
     // Validate permission
-    validate_ui(&capabilities, "sidebar")?;
+    let caller = Caller::load(&params.plugin_id, &manager).await?;
+    policy.authorize_ui(&caller.context(), "sidebar")?;
 
     // Emit event to frontend
-    window
-        .emit(
-            "plugin:panel:show",
-            serde_json::json!({
-                "plugin_id": params.plugin_id,
-                "id": params.id,
-                "title": params.title,
-                "content": params.content,
-                "position": params.position.unwrap_or_else(|| "sidebar".to_string()),
-            }),
-        )
-        .map_err(|e| ApiError::UiError(e.to_string()))?;
+    host.show_panel(&Panel {
+        plugin_id: params.plugin_id,
+        id: params.id,
+        title: params.title,
+        content: params.content,
+        position: params.position.unwrap_or_else(|| "sidebar".to_string()),
+    })
+    .map_err(ApiError::UiError)?;
 
     Ok(())
 }
@@ -996,24 +839,16 @@ pub struct HidePanelParams {
 pub async fn plugin_hide_panel(
     params: HidePanelParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
-    window: WebviewWindow,
+    policy: State<'_, Arc<PolicyEngine>>,
+    host: State<'_, Arc<dyn PluginHost>>,
 ) -> Result<(), ApiError> {
-    // Get capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-
     // Validate permission
-    validate_ui(&capabilities, "sidebar")?;
+    let caller = Caller::load(&params.plugin_id, &manager).await?;
+    policy.authorize_ui(&caller.context(), "sidebar")?;
 
     // Emit event to frontend
-    window
-        .emit(
-            "plugin:panel:hide",
-            serde_json::json!({
-                "plugin_id": params.plugin_id,
-                "id": params.id,
-            }),
-        )
-        .map_err(|e| ApiError::UiError(e.to_string()))?;
+    host.hide_panel(&params.plugin_id, &params.id)
+        .map_err(ApiError::UiError)?;
 
     Ok(())
 }
@@ -1031,11 +866,21 @@ pub struct GetEditorContentParams {
 pub async fn plugin_get_editor_content(
     params: GetEditorContentParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
+    policy: State<'_, Arc<PolicyEngine>>,
     editor_state: State<'_, EditorStateHandle>,
 ) -> Result<String, ApiError> {
-    // Get and validate capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-    validate_ui(&capabilities, "webview")?;
+    get_editor_content(params, &manager, &policy, &editor_state).await
+}
+
+async fn get_editor_content(
+    params: GetEditorContentParams,
+    manager: &RwLock<PluginManager>,
+    policy: &PolicyEngine,
+    editor_state: &EditorStateHandle,
+) -> Result<String, ApiError> {
+    // Validate capabilities
+    let caller = Caller::load(&params.plugin_id, manager).await?;
+    policy.authorize_editor(&caller.context())?;
 
     let state = editor_state
         .lock()
@@ -1053,11 +898,12 @@ pub struct SetEditorContentParams {
 pub async fn plugin_set_editor_content(
     params: SetEditorContentParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
+    policy: State<'_, Arc<PolicyEngine>>,
     window: WebviewWindow,
 ) -> Result<(), ApiError> {
-    // Get and validate capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-    validate_ui(&capabilities, "webview")?;
+    // Validate capabilities
+    let caller = Caller::load(&params.plugin_id, &manager).await?;
+    policy.authorize_editor(&caller.context())?;
 
     // Send content to frontend
     window
@@ -1088,11 +934,21 @@ pub struct FileInfo {
 pub async fn plugin_get_active_file(
     params: GetActiveFileParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
+    policy: State<'_, Arc<PolicyEngine>>,
     editor_state: State<'_, EditorStateHandle>,
 ) -> Result<Option<FileInfo>, ApiError> {
-    // Get and validate capabilities
-    let capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
-    validate_ui(&capabilities, "webview")?;
+    get_active_file(params, &manager, &policy, &editor_state).await
+}
+
+async fn get_active_file(
+    params: GetActiveFileParams,
+    manager: &RwLock<PluginManager>,
+    policy: &PolicyEngine,
+    editor_state: &EditorStateHandle,
+) -> Result<Option<FileInfo>, ApiError> {
+    // Validate capabilities
+    let caller = Caller::load(&params.plugin_id, manager).await?;
+    policy.authorize_editor(&caller.context())?;
 
     let state = editor_state
         .lock()
@@ -1109,6 +965,13 @@ pub async fn plugin_get_active_file(
 // EVENT COMMANDS
 // ============================================================================
 
+/// Reject event and hook names that are not safe to dispatch into a plugin.
+fn validate_event_name(name: &str) -> Result<(), ApiError> {
+    validate_hook_name(name).map_err(|_| ApiError::InvalidEventName {
+        name: name.to_string(),
+    })
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RegisterEventParams {
     plugin_id: String,
@@ -1120,6 +983,8 @@ pub async fn plugin_register_event(
     params: RegisterEventParams,
     manager: State<'_, Arc<RwLock<PluginManager>>>,
 ) -> Result<(), ApiError> {
+    validate_event_name(&params.event_name)?;
+
     // Validate plugin exists and is loaded
     let _capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
 
@@ -1143,6 +1008,8 @@ pub async fn plugin_emit_event(
     manager: State<'_, Arc<RwLock<PluginManager>>>,
     window: WebviewWindow,
 ) -> Result<(), ApiError> {
+    validate_event_name(&params.event_name)?;
+
     // Validate plugin exists and is loaded
     let _capabilities = get_plugin_capabilities(&params.plugin_id, &manager).await?;
 
@@ -1172,9 +1039,11 @@ pub struct ExecuteHookParams {
 pub async fn plugin_execute_hook(
     params: ExecuteHookParams,
     registry: State<'_, Arc<SandboxRegistry>>,
-    audit: State<'_, Arc<AuditLogger>>,
+    manager: State<'_, Arc<RwLock<PluginManager>>>,
+    policy: State<'_, Arc<PolicyEngine>>,
 ) -> Result<serde_json::Value, ApiError> {
     let start_time = SystemTime::now();
+    validate_event_name(&params.hook_name)?;
 
     // Get sandbox
     let sandbox = registry
@@ -1189,18 +1058,23 @@ pub async fn plugin_execute_hook(
     let result = sandbox
         .call_hook(&params.hook_name, vec![params.data.clone()])
         .await;
+    drop(sandbox);
+
+    // A plugin that blew its CPU or memory budget is no longer considered healthy
+    if let Err(e) = &result {
+        if e.is_resource_violation() {
+            manager.write().await.mark_failed(&params.plugin_id, e);
+        }
+    }
 
     // Audit log
-    audit
-        .log(AuditEvent {
-            plugin_id: params.plugin_id.clone(),
-            operation: format!("hook.{}", params.hook_name),
-            resource: "plugin_hook".to_string(),
-            timestamp: start_time,
-            success: result.is_ok(),
-            error: result.as_ref().err().map(|e| e.to_string()),
-        })
-        .await;
+    policy.record(
+        &params.plugin_id,
+        &format!("hook.{}", params.hook_name),
+        "plugin_hook",
+        start_time,
+        result.as_ref().err().map(|e| e.to_string()),
+    );
 
     result.map_err(|e| ApiError::InternalError(e.to_string()))
 }
@@ -1232,467 +1106,446 @@ pub async fn clear_audit_logs(audit: State<'_, Arc<AuditLogger>>) -> Result<(),
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::plugin_system::capabilities::{CommandCapability, UiCapability};
-    use std::collections::HashSet;
-
-    // ============================================================================
-    // validate_fs_read tests
-    // ============================================================================
-
-    #[test]
-    fn test_validate_fs_read_workspace_read_allows_in_workspace() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::WorkspaceRead,
-            ..Default::default()
-        };
-        assert!(validate_fs_read(&capabilities, &workspace.join("file.txt"), &workspace).is_ok());
-    }
+/// Run `f` against the persistent audit store on a blocking thread, since it
+/// reads every file of the trail.
+async fn with_audit_store<T: Send + 'static>(
+    audit: &AuditLogger,
+    f: impl FnOnce(&AuditStore) -> Result<T, ApiError> + Send + 'static,
+) -> Result<T, ApiError> {
+    let store = audit
+        .store()
+        .cloned()
+        .ok_or_else(|| ApiError::InternalError("No persistent audit log".to_string()))?;
+    tokio::task::spawn_blocking(move || f(&store))
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?
+}
 
-    #[test]
-    fn test_validate_fs_read_workspace_read_denies_outside() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::WorkspaceRead,
-            ..Default::default()
-        };
-        assert!(
-            validate_fs_read(&capabilities, &PathBuf::from("/etc/passwd"), &workspace).is_err()
-        );
-    }
+fn store_error(e: AuditStoreError) -> ApiError {
+    ApiError::InternalError(e.to_string())
+}
 
-    #[test]
-    fn test_validate_fs_read_none_denies_all() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::None,
-            ..Default::default()
-        };
-        assert!(validate_fs_read(&capabilities, &workspace.join("file.txt"), &workspace).is_err());
-    }
+/// Query the on-disk audit trail, including events from earlier sessions.
+#[tauri::command]
+pub async fn query_audit_log(
+    query: AuditQuery,
+    audit: State<'_, Arc<AuditLogger>>,
+) -> Result<Vec<AuditRecord>, ApiError> {
+    with_audit_store(&audit, move |store| {
+        store.query(&query).map_err(store_error)
+    })
+    .await
+}
 
-    #[test]
-    fn test_validate_fs_read_scoped_allows_in_scope() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::Scoped {
-                read: vec!["/workspace/src".to_string()].into_iter().collect(),
-                write: HashSet::new(),
-            },
-            ..Default::default()
-        };
-        assert!(validate_fs_read(
-            &capabilities,
-            &PathBuf::from("/workspace/src/main.rs"),
-            &workspace
-        )
-        .is_ok());
-    }
+/// Export the matching records to `path` as JSON Lines or CSV; returns the count.
+#[tauri::command]
+pub async fn export_audit_log(
+    query: AuditQuery,
+    format: ExportFormat,
+    path: String,
+    audit: State<'_, Arc<AuditLogger>>,
+) -> Result<usize, ApiError> {
+    with_audit_store(&audit, move |store| {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        let count = store
+            .export(&query, format, &mut out)
+            .map_err(store_error)?;
+        std::io::Write::flush(&mut out)?;
+        Ok(count)
+    })
+    .await
+}
 
-    #[test]
-    fn test_validate_fs_read_scoped_denies_out_of_scope() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::Scoped {
-                read: vec!["/workspace/src".to_string()].into_iter().collect(),
-                write: HashSet::new(),
-            },
-            ..Default::default()
-        };
-        assert!(validate_fs_read(
-            &capabilities,
-            &PathBuf::from("/workspace/secrets/key.pem"),
-            &workspace
-        )
-        .is_err());
-    }
+/// Check the audit trail's hash chain; returns how many records were verified.
+#[tauri::command]
+pub async fn verify_audit_log(audit: State<'_, Arc<AuditLogger>>) -> Result<usize, ApiError> {
+    with_audit_store(&audit, |store| store.verify().map_err(store_error)).await
+}
 
-    #[test]
-    fn test_validate_fs_read_readwrite_allows() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::WorkspaceReadWrite,
-            ..Default::default()
-        };
-        assert!(validate_fs_read(&capabilities, &workspace.join("file.txt"), &workspace).is_ok());
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     // ============================================================================
-    // validate_fs_write tests
+    // ApiError tests
     // ============================================================================
 
     #[test]
-    fn test_validate_fs_write_none_denies() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::None,
-            ..Default::default()
-        };
-        assert!(validate_fs_write(&capabilities, &workspace.join("file.txt"), &workspace).is_err());
-    }
-
-    #[test]
-    fn test_validate_fs_write_workspace_read_denies() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::WorkspaceRead,
-            ..Default::default()
+    fn test_api_error_display() {
+        let err = ApiError::PermissionDenied {
+            operation: "read".to_string(),
+            capability: "filesystem".to_string(),
         };
-        assert!(validate_fs_write(&capabilities, &workspace.join("file.txt"), &workspace).is_err());
+        assert_eq!(
+            err.to_string(),
+            "Permission denied: read requires filesystem"
+        );
     }
 
     #[test]
-    fn test_validate_fs_write_readwrite_allows_in_workspace() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::WorkspaceReadWrite,
-            ..Default::default()
-        };
-        assert!(validate_fs_write(&capabilities, &workspace.join("file.txt"), &workspace).is_ok());
+    fn test_validate_event_name() {
+        assert!(validate_event_name("file-saved").is_ok());
+        assert!(validate_event_name("command:git.status").is_ok());
+        assert!(matches!(
+            validate_event_name("x'); globalThis.pwned = 1; ('"),
+            Err(ApiError::InvalidEventName { .. })
+        ));
+        assert!(validate_event_name("").is_err());
     }
 
     #[test]
-    fn test_validate_fs_write_readwrite_denies_outside() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::WorkspaceReadWrite,
-            ..Default::default()
-        };
-        assert!(
-            validate_fs_write(&capabilities, &PathBuf::from("/etc/passwd"), &workspace).is_err()
-        );
+    fn test_api_error_from_policy_error() {
+        assert!(matches!(
+            ApiError::from(PolicyError::CommandNotAllowed("rm".to_string())),
+            ApiError::CommandNotAllowed { command } if command == "rm"
+        ));
+        assert!(matches!(
+            ApiError::from(PolicyError::Consent(ConsentError::TimedOut {
+                timeout: std::time::Duration::from_secs(60)
+            })),
+            ApiError::PermissionDenied { .. }
+        ));
+        assert!(matches!(
+            ApiError::from(PolicyError::Consent(ConsentError::Emit(
+                "no window".to_string()
+            ))),
+            ApiError::UiError(_)
+        ));
     }
 
     #[test]
-    fn test_validate_fs_write_scoped_allows_in_scope() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::Scoped {
-                read: HashSet::new(),
-                write: vec!["/workspace/out".to_string()].into_iter().collect(),
-            },
-            ..Default::default()
-        };
-        assert!(validate_fs_write(
-            &capabilities,
-            &PathBuf::from("/workspace/out/result.txt"),
-            &workspace
-        )
-        .is_ok());
+    fn test_api_error_from_io_error() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "not found");
+        let api_err: ApiError = io_err.into();
+        assert!(matches!(api_err, ApiError::FileError(_)));
     }
 
     #[test]
-    fn test_validate_fs_write_scoped_denies_out_of_scope() {
-        let workspace = PathBuf::from("/workspace");
-        let capabilities = PluginCapabilities {
-            filesystem: FilesystemCapability::Scoped {
-                read: HashSet::new(),
-                write: vec!["/workspace/out".to_string()].into_iter().collect(),
-            },
-            ..Default::default()
+    fn test_api_error_serializable() {
+        let err = ApiError::PluginNotFound {
+            plugin_id: "test".to_string(),
         };
-        assert!(validate_fs_write(
-            &capabilities,
-            &PathBuf::from("/workspace/src/main.rs"),
-            &workspace
-        )
-        .is_err());
+        let json = serde_json::to_string(&err).unwrap();
+        assert!(json.contains("test"));
     }
 
     // ============================================================================
-    // validate_network tests
+    // Command / op parity
     // ============================================================================
 
-    #[test]
-    fn test_validate_network_none_denies() {
-        let capabilities = PluginCapabilities {
-            network: NetworkCapability::None,
-            ..Default::default()
+    use crate::plugin_system::consent::{ConsentBroker, CONSENT_TIMEOUT};
+    use crate::plugin_system::host::RecordingHost;
+    use std::collections::BTreeMap;
+
+    /// Capability tiers as plugin.toml tables
+    const TIERS: &[(&str, &str)] = &[
+        ("sandboxed", ""),
+        (
+            "read-only",
+            "[permissions]\nfilesystem = \"WorkspaceRead\"\n\n[ui]\nnotifications = true\n",
+        ),
+        (
+            "read-write",
+            "[permissions]\nfilesystem = \"WorkspaceReadWrite\"\n\n[ui]\nnotifications = true\n",
+        ),
+        (
+            "network",
+            "[permissions.network]\ntype = \"DomainAllowlist\"\ndomains = [\"api.example.com\"]\n",
+        ),
+        (
+            "commands",
+            "[permissions]\ncommands = { allowlist = [\"echo\"], require_confirmation = false }\n",
+        ),
+        ("editor", "[ui]\nwebview = true\n"),
+    ];
+
+    /// Every operation from the plugin's activation hook; `__outcomes` records
+    /// which ones succeeded. `OUTSIDE` is replaced with a path outside the workspace.
+    const PARITY_SCRIPT: &str = r#"
+globalThis.__outcomes = {};
+async function attempt(name, op) {
+  try {
+    await op();
+    __outcomes[name] = true;
+  } catch (e) {
+    __outcomes[name] = false;
+  }
+}
+onActivate(async function () {
+  await attempt('read', () => skretchpad.fs.readFile('notes.txt'));
+  await attempt('read outside', () => skretchpad.fs.readFile(OUTSIDE));
+  await attempt('write', () => skretchpad.fs.writeFile('new.txt', 'x'));
+  await attempt('fetch', () => skretchpad.network.fetch('https://evil.example/', { method: 'GET' }));
+  await attempt('echo', () => skretchpad.commands.execute('echo', ['hi']));
+  await attempt('rm', () => skretchpad.commands.execute('rm', ['-rf', 'src']));
+  await attempt('notification', () => skretchpad.ui.showNotification('hi', 'info'));
+  await attempt('editor content', () => skretchpad.editor.getContent());
+  await attempt('active file', () => skretchpad.editor.getActiveFile());
+});
+"#;
+
+    /// The same operations through the Tauri command functions.
+    async fn command_outcomes(
+        plugin_id: &str,
+        outside: &str,
+        manager: &RwLock<PluginManager>,
+        policy: &PolicyEngine,
+        host: &dyn PluginHost,
+        editor_state: &EditorStateHandle,
+    ) -> BTreeMap<String, bool> {
+        let id = || plugin_id.to_string();
+        let read = |path: &str| ReadFileParams {
+            plugin_id: id(),
+            path: path.to_string(),
         };
-        assert!(validate_network(&capabilities, "https://example.com").is_err());
-    }
-
-    #[test]
-    fn test_validate_network_unrestricted_allows() {
-        let capabilities = PluginCapabilities {
-            network: NetworkCapability::Unrestricted,
-            ..Default::default()
+        let command = |command: &str, args: &[&str]| ExecuteCommandParams {
+            plugin_id: id(),
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            cwd: None,
         };
-        assert!(validate_network(&capabilities, "https://anything.com/data").is_ok());
-    }
+        let no_window = |_: &ConsentRequest| Err("no window".to_string());
 
-    #[test]
-    fn test_validate_network_allowlist_allows() {
-        let capabilities = PluginCapabilities {
-            network: NetworkCapability::DomainAllowlist(
-                vec!["api.example.com".to_string()].into_iter().collect(),
+        let outcomes = [
+            (
+                "read",
+                read_file(read("notes.txt"), manager, policy).await.is_ok(),
             ),
-            ..Default::default()
-        };
-        assert!(validate_network(&capabilities, "https://api.example.com/data").is_ok());
-    }
-
-    #[test]
-    fn test_validate_network_allowlist_denies() {
-        let capabilities = PluginCapabilities {
-            network: NetworkCapability::DomainAllowlist(
-                vec!["api.example.com".to_string()].into_iter().collect(),
+            (
+                "read outside",
+                read_file(read(outside), manager, policy).await.is_ok(),
             ),
-            ..Default::default()
-        };
-        assert!(validate_network(&capabilities, "https://evil.com/data").is_err());
-    }
-
-    #[test]
-    fn test_validate_network_invalid_url_errors() {
-        let capabilities = PluginCapabilities {
-            network: NetworkCapability::DomainAllowlist(HashSet::new()),
-            ..Default::default()
-        };
-        assert!(validate_network(&capabilities, "not-a-url").is_err());
-    }
-
-    // ============================================================================
-    // validate_command tests
-    // ============================================================================
-
-    #[test]
-    fn test_validate_command_allows_allowlisted() {
-        let capabilities = PluginCapabilities {
-            commands: CommandCapability {
-                allowlist: vec!["git".to_string()].into_iter().collect(),
-                require_confirmation: false,
-            },
-            ..Default::default()
-        };
-        assert!(validate_command(&capabilities, "git").is_ok());
-    }
-
-    #[test]
-    fn test_validate_command_denies_unlisted() {
-        let capabilities = PluginCapabilities {
-            commands: CommandCapability {
-                allowlist: vec!["git".to_string()].into_iter().collect(),
-                require_confirmation: false,
-            },
-            ..Default::default()
-        };
-        assert!(validate_command(&capabilities, "rm").is_err());
-    }
-
-    #[test]
-    fn test_validate_command_denies_path_like_binary() {
-        let capabilities = PluginCapabilities {
-            commands: CommandCapability {
-                allowlist: vec!["git".to_string()].into_iter().collect(),
-                require_confirmation: false,
-            },
-            ..Default::default()
-        };
-        assert!(validate_command(&capabilities, "/usr/bin/git").is_err());
-    }
-
-    // ============================================================================
-    // validate_ui tests
-    // ============================================================================
-
-    #[test]
-    fn test_validate_ui_status_bar() {
-        let capabilities = PluginCapabilities {
-            ui: UiCapability {
-                status_bar: true,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-        assert!(validate_ui(&capabilities, "status_bar").is_ok());
-    }
-
-    #[test]
-    fn test_validate_ui_sidebar() {
-        let capabilities = PluginCapabilities {
-            ui: UiCapability {
-                sidebar: true,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-        assert!(validate_ui(&capabilities, "sidebar").is_ok());
-    }
-
-    #[test]
-    fn test_validate_ui_notification() {
-        let capabilities = PluginCapabilities {
-            ui: UiCapability {
-                notifications: true,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-        assert!(validate_ui(&capabilities, "notification").is_ok());
-    }
-
-    #[test]
-    fn test_validate_ui_webview() {
-        let capabilities = PluginCapabilities {
-            ui: UiCapability {
-                webview: true,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-        assert!(validate_ui(&capabilities, "webview").is_ok());
-    }
-
-    #[test]
-    fn test_validate_ui_denied_when_false() {
-        let capabilities = PluginCapabilities::default();
-        assert!(validate_ui(&capabilities, "status_bar").is_err());
-        assert!(validate_ui(&capabilities, "sidebar").is_err());
-        assert!(validate_ui(&capabilities, "notification").is_err());
-        assert!(validate_ui(&capabilities, "webview").is_err());
-    }
-
-    #[test]
-    fn test_validate_ui_unknown_operation() {
-        let capabilities = PluginCapabilities {
-            ui: UiCapability::all(),
-            ..Default::default()
-        };
-        assert!(validate_ui(&capabilities, "unknown_op").is_err());
-    }
-
-    // ============================================================================
-    // AuditLogger tests
-    // ============================================================================
-
-    #[tokio::test]
-    async fn test_audit_logger_creation() {
-        let logger = AuditLogger::new(100);
-        let events = logger.get_events().await;
-        assert!(events.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_audit_logger_log_and_retrieve() {
-        let logger = AuditLogger::new(100);
-        logger
-            .log(AuditEvent {
-                plugin_id: "test".to_string(),
-                operation: "fs.read".to_string(),
-                resource: "/file.txt".to_string(),
-                timestamp: SystemTime::now(),
-                success: true,
-                error: None,
-            })
-            .await;
-
-        let events = logger.get_events().await;
-        assert_eq!(events.len(), 1);
-        assert_eq!(events[0].plugin_id, "test");
+            (
+                "write",
+                write_file(
+                    WriteFileParams {
+                        plugin_id: id(),
+                        path: "new.txt".to_string(),
+                        content: "x".to_string(),
+                    },
+                    manager,
+                    policy,
+                )
+                .await
+                .is_ok(),
+            ),
+            (
+                "fetch",
+                fetch(
+                    FetchParams {
+                        plugin_id: id(),
+                        url: "https://evil.example/".to_string(),
+                        method: Some("GET".to_string()),
+                        headers: None,
+                        body: None,
+                    },
+                    manager,
+                    policy,
+                )
+                .await
+                .is_ok(),
+            ),
+            (
+                "echo",
+                execute_command(command("echo", &["hi"]), manager, policy, no_window)
+                    .await
+                    .is_ok(),
+            ),
+            (
+                "rm",
+                execute_command(command("rm", &["-rf", "src"]), manager, policy, no_window)
+                    .await
+                    .is_ok(),
+            ),
+            (
+                "notification",
+                show_notification(
+                    ShowNotificationParams {
+                        plugin_id: id(),
+                        title: id(),
+                        message: "hi".to_string(),
+                        level: None,
+                    },
+                    manager,
+                    policy,
+                    host,
+                )
+                .await
+                .is_ok(),
+            ),
+            (
+                "editor content",
+                get_editor_content(
+                    GetEditorContentParams { plugin_id: id() },
+                    manager,
+                    policy,
+                    editor_state,
+                )
+                .await
+                .is_ok(),
+            ),
+            (
+                "active file",
+                get_active_file(
+                    GetActiveFileParams { plugin_id: id() },
+                    manager,
+                    policy,
+                    editor_state,
+                )
+                .await
+                .is_ok(),
+            ),
+        ];
+        outcomes
+            .into_iter()
+            .map(|(name, ok)| (name.to_string(), ok))
+            .collect()
     }
 
+    #[cfg(unix)]
     #[tokio::test]
-    async fn test_audit_logger_rotation() {
-        let logger = AuditLogger::new(3);
-        for i in 0..5 {
-            logger
-                .log(AuditEvent {
-                    plugin_id: format!("plugin_{}", i),
-                    operation: "op".to_string(),
-                    resource: "r".to_string(),
-                    timestamp: SystemTime::now(),
-                    success: true,
-                    error: None,
-                })
-                .await;
+    async fn test_commands_and_ops_agree_for_every_tier() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::write(workspace.join("notes.txt"), "notes").unwrap();
+        let workspace = workspace.canonicalize().unwrap();
+        let outside = dir.path().join("secret.txt");
+        std::fs::write(&outside, "secret").unwrap();
+        let outside = outside.display().to_string();
+        let script = PARITY_SCRIPT.replace("OUTSIDE", &serde_json::to_string(&outside).unwrap());
+        for (tier, tables) in TIERS {
+            let plugin_dir = dir.path().join("plugins").join(tier);
+            std::fs::create_dir_all(&plugin_dir).unwrap();
+            std::fs::write(
+                plugin_dir.join("plugin.toml"),
+                format!(
+                    "name = \"{}\"\nversion = \"1.0.0\"\nauthor = \"test\"\n\n{}",
+                    tier, tables
+                ),
+            )
+            .unwrap();
+            std::fs::write(plugin_dir.join("main.js"), &script).unwrap();
         }
 
-        let events = logger.get_events().await;
-        assert_eq!(events.len(), 3);
-        // Should have the last 3 events
-        assert_eq!(events[0].plugin_id, "plugin_2");
-        assert_eq!(events[2].plugin_id, "plugin_4");
-    }
-
-    #[tokio::test]
-    async fn test_audit_logger_filter_by_plugin() {
-        let logger = AuditLogger::new(100);
-        logger
-            .log(AuditEvent {
-                plugin_id: "git".to_string(),
-                operation: "op1".to_string(),
-                resource: "r".to_string(),
-                timestamp: SystemTime::now(),
-                success: true,
-                error: None,
-            })
-            .await;
-        logger
-            .log(AuditEvent {
-                plugin_id: "other".to_string(),
-                operation: "op2".to_string(),
-                resource: "r".to_string(),
-                timestamp: SystemTime::now(),
-                success: true,
-                error: None,
-            })
+        let policy = Arc::new(PolicyEngine::new(
+            Arc::new(AuditLogger::new(1000)),
+            Arc::new(ConsentBroker::new(CONSENT_TIMEOUT)),
+        ));
+        let host = Arc::new(RecordingHost::new().with_policy_engine(policy.clone()));
+        let registry = Arc::new(SandboxRegistry::new());
+        let editor_state: EditorStateHandle = Default::default();
+        let mut manager = PluginManager::new(
+            dir.path().join("plugins"),
+            registry.clone(),
+            workspace,
+            host.clone(),
+            editor_state.clone(),
+        );
+        for (tier, _) in TIERS {
+            manager.load(tier).unwrap();
+            manager.activate(tier).await.unwrap();
+        }
+        let manager = RwLock::new(manager);
+
+        let expected: &[(&str, &[&str])] = &[
+            ("sandboxed", &[]),
+            ("read-only", &["read", "notification"]),
+            ("read-write", &["read", "write", "notification"]),
+            ("network", &[]),
+            ("commands", &["echo"]),
+            ("editor", &["editor content", "active file"]),
+        ];
+        for (tier, allowed) in expected {
+            let sandbox = registry.get_sandbox(tier).await.unwrap();
+            let from_ops: BTreeMap<String, bool> = serde_json::from_value(
+                sandbox
+                    .read()
+                    .await
+                    .execute("globalThis.__outcomes".to_string())
+                    .await
+                    .unwrap(),
+            )
+            .unwrap();
+            let from_commands = command_outcomes(
+                tier,
+                &outside,
+                &manager,
+                &policy,
+                host.as_ref(),
+                &editor_state,
+            )
             .await;
 
-        let git_events = logger.get_events_for_plugin("git").await;
-        assert_eq!(git_events.len(), 1);
-        assert_eq!(git_events[0].operation, "op1");
+            assert_eq!(
+                from_ops, from_commands,
+                "{}: ops and commands disagree",
+                tier
+            );
+            let granted: Vec<&str> = from_commands
+                .iter()
+                .filter(|(_, ok)| **ok)
+                .map(|(name, _)| name.as_str())
+                .collect();
+            let mut allowed = allowed.to_vec();
+            allowed.sort();
+            assert_eq!(granted, allowed, "{}", tier);
+        }
     }
 
     #[tokio::test]
-    async fn test_audit_logger_clear() {
-        let logger = AuditLogger::new(100);
-        logger
-            .log(AuditEvent {
-                plugin_id: "test".to_string(),
-                operation: "op".to_string(),
-                resource: "r".to_string(),
-                timestamp: SystemTime::now(),
-                success: true,
-                error: None,
-            })
-            .await;
-        logger.clear().await;
-        assert!(logger.get_events().await.is_empty());
-    }
-
-    // ============================================================================
-    // ApiError tests
-    // ============================================================================
+    async fn test_denied_commands_are_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin_dir = dir.path().join("plugins").join("sandboxed");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            "name = \"sandboxed\"\nversion = \"1.0.0\"\nauthor = \"test\"\n",
+        )
+        .unwrap();
+        std::fs::write(plugin_dir.join("main.js"), "").unwrap();
+
+        let policy = Arc::new(PolicyEngine::new(
+            Arc::new(AuditLogger::new(1000)),
+            Arc::new(ConsentBroker::new(CONSENT_TIMEOUT)),
+        ));
+        let mut manager = PluginManager::new(
+            dir.path().join("plugins"),
+            Arc::new(SandboxRegistry::new()),
+            dir.path().to_path_buf(),
+            Arc::new(RecordingHost::new().with_policy_engine(policy.clone())),
+            Default::default(),
+        );
+        manager.load("sandboxed").unwrap();
+        let manager = RwLock::new(manager);
+        let mut events = policy.subscribe();
 
-    #[test]
-    fn test_api_error_display() {
-        let err = ApiError::PermissionDenied {
-            operation: "read".to_string(),
-            capability: "filesystem".to_string(),
+        let read = ReadFileParams {
+            plugin_id: "sandboxed".to_string(),
+            path: "notes.txt".to_string(),
         };
+        assert!(read_file(read, &manager, &policy).await.is_err());
+        let execute = ExecuteCommandParams {
+            plugin_id: "sandboxed".to_string(),
+            command: "rm".to_string(),
+            args: vec!["-rf".to_string(), "src".to_string()],
+            cwd: None,
+        };
+        let no_window = |_: &ConsentRequest| Err("no window".to_string());
+        assert!(execute_command(execute, &manager, &policy, no_window)
+            .await
+            .is_err());
+
+        let read = events.try_recv().unwrap();
         assert_eq!(
-            err.to_string(),
-            "Permission denied: read requires filesystem"
+            (read.operation.as_str(), read.resource.as_str()),
+            ("fs.read", "notes.txt")
         );
-    }
-
-    #[test]
-    fn test_api_error_from_io_error() {
-        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "not found");
-        let api_err: ApiError = io_err.into();
-        assert!(matches!(api_err, ApiError::FileError(_)));
-    }
-
-    #[test]
-    fn test_api_error_serializable() {
-        let err = ApiError::PluginNotFound {
-            plugin_id: "test".to_string(),
-        };
-        let json = serde_json::to_string(&err).unwrap();
-        assert!(json.contains("test"));
+        assert!(!read.success);
+        assert!(read.error.unwrap().starts_with("Permission denied: "));
+        let execute = events.try_recv().unwrap();
+        assert_eq!(execute.operation, "command.execute");
+        assert!(!execute.success);
+        assert!(execute.error.unwrap().starts_with("Command not allowed: "));
     }
 }
@@ -0,0 +1,320 @@
+// src-tauri/src/plugin_system/audit.rs
+//
+// Audit trail of plugin operations. Both the Tauri command path and the deno
+// ops record through the policy engine (see `policy.rs`); synchronous ops use
+// `record`, so the log is guarded by a std mutex rather than an async lock.
+// Recent events are kept in memory; with an `AuditStore` attached every event
+// is also appended to the on-disk, hash-chained trail. Each event is broadcast
+// as it is recorded, for the frontend (`plugin:audit`) and security monitor
+// plugins; the anomaly detector gets every event on a channel that never drops
+// any, so flooding the log cannot hide events from it.
+
+use crate::plugin_system::audit_store::AuditStore;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::{broadcast, mpsc};
+
+/// Events kept in memory before the oldest are dropped
+pub const DEFAULT_MAX_EVENTS: usize = 10_000;
+
+/// Tauri event carrying each [`AuditEvent`] as it is recorded
+pub const AUDIT_EVENT: &str = "plugin:audit";
+
+/// Plugin hook called with each [`AuditEvent`] once the plugin subscribed
+pub const AUDIT_HOOK: &str = "audit";
+
+/// Events a live subscriber can fall behind by before it misses some
+const STREAM_CAPACITY: usize = 1024;
+
+/// Audit event for tracking plugin operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub plugin_id: String,
+    pub operation: String,
+    pub resource: String,
+    pub timestamp: SystemTime,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Audit logger for tracking plugin operations
+pub struct AuditLogger {
+    events: Mutex<Vec<AuditEvent>>,
+    max_events: usize,
+    store: Option<Arc<AuditStore>>,
+    stream: broadcast::Sender<AuditEvent>,
+    inspectors: Mutex<Vec<mpsc::UnboundedSender<AuditEvent>>>,
+}
+
+impl AuditLogger {
+    pub fn new(max_events: usize) -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            max_events,
+            store: None,
+            stream: broadcast::channel(STREAM_CAPACITY).0,
+            inspectors: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Receive every event recorded from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AuditEvent> {
+        self.stream.subscribe()
+    }
+
+    /// Receive every event recorded from now on. Unlike [`AuditLogger::subscribe`]
+    /// no event is dropped when the receiver falls behind.
+    pub fn inspect(&self) -> mpsc::UnboundedReceiver<AuditEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.inspectors.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Also persist every event to `store`.
+    pub fn with_store(mut self, store: Arc<AuditStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// The on-disk trail, if one is attached.
+    pub fn store(&self) -> Option<&Arc<AuditStore>> {
+        self.store.as_ref()
+    }
+
+    pub async fn log(&self, event: AuditEvent) {
+        self.record(event);
+    }
+
+    /// Record an event without awaiting (for synchronous ops).
+    pub fn record(&self, event: AuditEvent) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append(&event) {
+                eprintln!("Failed to persist audit event: {}", e);
+            }
+        }
+
+        // No subscribers is not an error
+        let _ = self.stream.send(event.clone());
+        self.inspectors
+            .lock()
+            .unwrap()
+            .retain(|inspector| inspector.send(event.clone()).is_ok());
+
+        let mut events = self.events.lock().unwrap();
+
+        // Rotate logs if exceeding max
+        if events.len() >= self.max_events {
+            events.remove(0);
+        }
+
+        events.push(event.clone());
+
+        // Also log to console in debug mode
+        #[cfg(debug_assertions)]
+        println!(
+            "[AUDIT] Plugin: {}, Op: {}, Resource: {}, Success: {}",
+            event.plugin_id, event.operation, event.resource, event.success
+        );
+    }
+
+    pub async fn get_events(&self) -> Vec<AuditEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub async fn get_events_for_plugin(&self, plugin_id: &str) -> Vec<AuditEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.plugin_id == plugin_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Clear the in-memory events; the on-disk trail is append-only.
+    pub async fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_audit_logger_creation() {
+        let logger = AuditLogger::new(100);
+        let events = logger.get_events().await;
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_log_and_retrieve() {
+        let logger = AuditLogger::new(100);
+        logger
+            .log(AuditEvent {
+                plugin_id: "test".to_string(),
+                operation: "fs.read".to_string(),
+                resource: "/file.txt".to_string(),
+                timestamp: SystemTime::now(),
+                success: true,
+                error: None,
+            })
+            .await;
+
+        let events = logger.get_events().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].plugin_id, "test");
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_rotation() {
+        let logger = AuditLogger::new(3);
+        for i in 0..5 {
+            logger
+                .log(AuditEvent {
+                    plugin_id: format!("plugin_{}", i),
+                    operation: "op".to_string(),
+                    resource: "r".to_string(),
+                    timestamp: SystemTime::now(),
+                    success: true,
+                    error: None,
+                })
+                .await;
+        }
+
+        let events = logger.get_events().await;
+        assert_eq!(events.len(), 3);
+        // Should have the last 3 events
+        assert_eq!(events[0].plugin_id, "plugin_2");
+        assert_eq!(events[2].plugin_id, "plugin_4");
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_filter_by_plugin() {
+        let logger = AuditLogger::new(100);
+        logger
+            .log(AuditEvent {
+                plugin_id: "git".to_string(),
+                operation: "op1".to_string(),
+                resource: "r".to_string(),
+                timestamp: SystemTime::now(),
+                success: true,
+                error: None,
+            })
+            .await;
+        logger
+            .log(AuditEvent {
+                plugin_id: "other".to_string(),
+                operation: "op2".to_string(),
+                resource: "r".to_string(),
+                timestamp: SystemTime::now(),
+                success: true,
+                error: None,
+            })
+            .await;
+
+        let git_events = logger.get_events_for_plugin("git").await;
+        assert_eq!(git_events.len(), 1);
+        assert_eq!(git_events[0].operation, "op1");
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_clear() {
+        let logger = AuditLogger::new(100);
+        logger
+            .log(AuditEvent {
+                plugin_id: "test".to_string(),
+                operation: "op".to_string(),
+                resource: "r".to_string(),
+                timestamp: SystemTime::now(),
+                success: true,
+                error: None,
+            })
+            .await;
+        logger.clear().await;
+        assert!(logger.get_events().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_persists_to_store() {
+        use crate::plugin_system::audit_store::{AuditQuery, RotationPolicy};
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(AuditStore::open(dir.path(), RotationPolicy::default()).unwrap());
+        let logger = AuditLogger::new(100).with_store(store.clone());
+        logger.record(AuditEvent {
+            plugin_id: "test".to_string(),
+            operation: "fs.read".to_string(),
+            resource: "r".to_string(),
+            timestamp: SystemTime::now(),
+            success: false,
+            error: Some("Permission denied".to_string()),
+        });
+
+        // Clearing the in-memory view leaves the on-disk trail alone
+        logger.clear().await;
+        let records = store.query(&AuditQuery::default()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].event.operation, "fs.read");
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_broadcasts_events() {
+        let logger = AuditLogger::new(100);
+        let mut first = logger.subscribe();
+        let mut second = logger.subscribe();
+        logger
+            .log(AuditEvent {
+                plugin_id: "test".to_string(),
+                operation: "fs.read".to_string(),
+                resource: "r".to_string(),
+                timestamp: SystemTime::now(),
+                success: true,
+                error: None,
+            })
+            .await;
+
+        assert_eq!(first.recv().await.unwrap().operation, "fs.read");
+        assert_eq!(second.recv().await.unwrap().plugin_id, "test");
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_inspector_never_lags() {
+        let logger = AuditLogger::new(10);
+        let mut inspector = logger.inspect();
+        let mut subscriber = logger.subscribe();
+        let events = STREAM_CAPACITY + 10;
+        for i in 0..events {
+            logger.record(AuditEvent {
+                plugin_id: "flood".to_string(),
+                operation: "ui.notification".to_string(),
+                resource: i.to_string(),
+                timestamp: SystemTime::now(),
+                success: true,
+                error: None,
+            });
+        }
+
+        assert!(matches!(
+            subscriber.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(_))
+        ));
+        for i in 0..events {
+            assert_eq!(inspector.try_recv().unwrap().resource, i.to_string());
+        }
+
+        // A dropped inspector is removed on the next event
+        drop(inspector);
+        logger.record(AuditEvent {
+            plugin_id: "flood".to_string(),
+            operation: "ui.notification".to_string(),
+            resource: "last".to_string(),
+            timestamp: SystemTime::now(),
+            success: true,
+            error: None,
+        });
+        assert!(logger.inspectors.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,834 @@
+// src-tauri/src/plugin_system/audit_store.rs
+//
+// Append-only, on-disk audit trail. Events are written as JSON Lines to
+// `audit.jsonl` in the store directory; once that file would grow past
+// `max_file_bytes`, or was started more than `max_age` ago, it is renamed to
+// `audit-<first seq>.jsonl` and a new one is started. Rotated files last
+// written more than `max_age` ago are deleted.
+//
+// Every record carries the SHA-256 of the record before it, so editing,
+// removing or reordering lines breaks the chain and `verify` reports where.
+// Files dropped by age rotation are expected: verification anchors on the
+// oldest record still on disk.
+//
+// A crash in the middle of an append leaves a torn last line. `open` moves it
+// to `torn-<unix secs>.partial`, truncates the file to its last complete record
+// and starts a new file whose first record notes the break.
+
+use crate::plugin_system::audit::AuditEvent;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const CURRENT_FILE: &str = "audit.jsonl";
+
+/// Operation of the record that notes a torn write was discarded
+pub const TORN_WRITE_OPERATION: &str = "audit.torn_write";
+
+/// `prev_hash` of the very first record
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditStoreError {
+    #[error("Audit store I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Corrupt audit record in '{file}' line {line}: {reason}")]
+    Corrupt {
+        file: String,
+        line: usize,
+        reason: String,
+    },
+
+    #[error("Audit chain broken at record {seq}: {reason}")]
+    Tampered { seq: u64, reason: String },
+}
+
+/// When the current file is rotated and how long rotated files are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    pub max_file_bytes: u64,
+    pub max_age: Duration,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 10 * 1024 * 1024,
+            max_age: Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// One line of the store: an event and its place in the hash chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub event: AuditEvent,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    fn new(seq: u64, event: AuditEvent, prev_hash: String) -> Self {
+        let hash = Self::digest(seq, &prev_hash, &event);
+        Self {
+            seq,
+            event,
+            prev_hash,
+            hash,
+        }
+    }
+
+    fn digest(seq: u64, prev_hash: &str, event: &AuditEvent) -> String {
+        // Serializing an AuditEvent cannot fail
+        let event = serde_json::to_string(event).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(seq.to_string());
+        hasher.update(b"\n");
+        hasher.update(prev_hash);
+        hasher.update(b"\n");
+        hasher.update(event);
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// Filter for [`AuditStore::query`]; unset fields match every record
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditQuery {
+    pub plugin_id: Option<String>,
+    pub operation: Option<String>,
+    /// Unix seconds, inclusive
+    pub since: Option<u64>,
+    /// Unix seconds, inclusive
+    pub until: Option<u64>,
+    pub success: Option<bool>,
+    pub resource_prefix: Option<String>,
+    /// Keep only the newest `limit` matches
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        let secs = unix_secs(event.timestamp);
+        self.plugin_id
+            .as_ref()
+            .is_none_or(|id| &event.plugin_id == id)
+            && self
+                .operation
+                .as_ref()
+                .is_none_or(|op| &event.operation == op)
+            && self.since.is_none_or(|since| secs >= since)
+            && self.until.is_none_or(|until| secs <= until)
+            && self.success.is_none_or(|success| event.success == success)
+            && self
+                .resource_prefix
+                .as_ref()
+                .is_none_or(|prefix| event.resource.starts_with(prefix.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+}
+
+/// The end of the chain, where the next record is appended
+struct Head {
+    file: File,
+    size: u64,
+    /// Sequence number of the first record in the current file
+    first_seq: u64,
+    /// When the current file's first record was written
+    started: SystemTime,
+    next_seq: u64,
+    last_hash: String,
+}
+
+pub struct AuditStore {
+    dir: PathBuf,
+    policy: RotationPolicy,
+    head: Mutex<Head>,
+}
+
+impl AuditStore {
+    /// Open (or create) the store in `dir`, continuing the existing chain.
+    pub fn open(dir: impl Into<PathBuf>, policy: RotationPolicy) -> Result<Self, AuditStoreError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        prune(&dir, policy.max_age)?;
+        let torn = discard_torn_tail(&dir)?;
+
+        let mut last = None;
+        let mut first_in_current = None;
+        for path in files(&dir)? {
+            let records = read_records(&path)?;
+            if path.ends_with(CURRENT_FILE) {
+                first_in_current = records.first().map(|r| (r.seq, r.event.timestamp));
+            }
+            if let Some(record) = records.into_iter().last() {
+                last = Some(record);
+            }
+        }
+        let (next_seq, last_hash) = match last {
+            Some(record) => (record.seq + 1, record.hash),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+
+        let current = dir.join(CURRENT_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&current)?;
+        let size = file.metadata()?.len();
+        let store = Self {
+            dir,
+            policy,
+            head: Mutex::new(Head {
+                file,
+                size,
+                first_seq: first_in_current.map_or(next_seq, |(seq, _)| seq),
+                started: first_in_current.map_or_else(SystemTime::now, |(_, time)| time),
+                next_seq,
+                last_hash,
+            }),
+        };
+        if let Some(quarantined) = torn {
+            store.record_break(&quarantined)?;
+        }
+        Ok(store)
+    }
+
+    /// Append `event` to the chain, rotating first if the current file is full
+    /// or too old.
+    pub fn append(&self, event: &AuditEvent) -> Result<AuditRecord, AuditStoreError> {
+        let mut head = self.head.lock().unwrap();
+        let record = AuditRecord::new(head.next_seq, event.clone(), head.last_hash.clone());
+        let mut line = serde_json::to_string(&record).map_err(|e| AuditStoreError::Corrupt {
+            file: CURRENT_FILE.to_string(),
+            line: 0,
+            reason: e.to_string(),
+        })?;
+        line.push('\n');
+
+        let full = head.size + line.len() as u64 > self.policy.max_file_bytes;
+        let expired = SystemTime::now()
+            .duration_since(head.started)
+            .is_ok_and(|age| age >= self.policy.max_age);
+        if head.size > 0 && (full || expired) {
+            self.rotate(&mut head)?;
+        }
+        head.file.write_all(line.as_bytes())?;
+        head.size += line.len() as u64;
+        head.next_seq += 1;
+        head.last_hash = record.hash.clone();
+        Ok(record)
+    }
+
+    /// Every record on disk, oldest first.
+    pub fn records(&self) -> Result<Vec<AuditRecord>, AuditStoreError> {
+        // Only open the files under the head, so appends are not held up while
+        // they are read; open handles survive a rotation, and the current file
+        // is read up to its last complete record
+        let (files, current_size) = {
+            let head = self.head.lock().unwrap();
+            let files = files(&self.dir)?
+                .into_iter()
+                .map(|path| File::open(&path).map(|file| (path, file)))
+                .collect::<Result<Vec<_>, _>>()?;
+            (files, head.size)
+        };
+
+        let mut records = Vec::new();
+        for (path, file) in files {
+            if path.ends_with(CURRENT_FILE) {
+                records.extend(parse_records(&path, file.take(current_size))?);
+            } else {
+                records.extend(parse_records(&path, file)?);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Records matching `query`, oldest first.
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, AuditStoreError> {
+        let mut records: Vec<_> = self
+            .records()?
+            .into_iter()
+            .filter(|r| query.matches(&r.event))
+            .collect();
+        if let Some(limit) = query.limit {
+            let skip = records.len().saturating_sub(limit);
+            records.drain(..skip);
+        }
+        Ok(records)
+    }
+
+    /// Write the records matching `query` to `out`; returns how many were written.
+    pub fn export(
+        &self,
+        query: &AuditQuery,
+        format: ExportFormat,
+        out: &mut impl Write,
+    ) -> Result<usize, AuditStoreError> {
+        let records = self.query(query)?;
+        match format {
+            ExportFormat::Jsonl => {
+                for record in &records {
+                    // Serializing an AuditRecord cannot fail
+                    let line = serde_json::to_string(record).unwrap_or_default();
+                    writeln!(out, "{}", line)?;
+                }
+            }
+            ExportFormat::Csv => {
+                writeln!(
+                    out,
+                    "seq,timestamp,plugin_id,operation,resource,success,error,hash"
+                )?;
+                for record in &records {
+                    let event = &record.event;
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{},{}",
+                        record.seq,
+                        unix_secs(event.timestamp),
+                        csv_field(&event.plugin_id),
+                        csv_field(&event.operation),
+                        csv_field(&event.resource),
+                        event.success,
+                        csv_field(event.error.as_deref().unwrap_or("")),
+                        record.hash
+                    )?;
+                }
+            }
+        }
+        Ok(records.len())
+    }
+
+    /// Check the hash chain; returns how many records were verified.
+    pub fn verify(&self) -> Result<usize, AuditStoreError> {
+        let records = self.records()?;
+        let mut previous: Option<&AuditRecord> = None;
+        for record in &records {
+            if let Some(previous) = previous {
+                if record.seq != previous.seq + 1 {
+                    return Err(AuditStoreError::Tampered {
+                        seq: record.seq,
+                        reason: format!("expected record {}", previous.seq + 1),
+                    });
+                }
+                if record.prev_hash != previous.hash {
+                    return Err(AuditStoreError::Tampered {
+                        seq: record.seq,
+                        reason: "does not follow the previous record".to_string(),
+                    });
+                }
+            }
+            if record.hash != AuditRecord::digest(record.seq, &record.prev_hash, &record.event) {
+                return Err(AuditStoreError::Tampered {
+                    seq: record.seq,
+                    reason: "contents do not match its hash".to_string(),
+                });
+            }
+            previous = Some(record);
+        }
+        Ok(records.len())
+    }
+
+    /// Start a new file whose first record notes that a torn write was moved to
+    /// `quarantined`; the chain continues from the last complete record.
+    fn record_break(&self, quarantined: &Path) -> Result<(), AuditStoreError> {
+        {
+            let mut head = self.head.lock().unwrap();
+            if head.size > 0 {
+                self.rotate(&mut head)?;
+            }
+        }
+        let file = quarantined
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.append(&AuditEvent {
+            // Written by the store itself, not on behalf of a plugin
+            plugin_id: String::new(),
+            operation: TORN_WRITE_OPERATION.to_string(),
+            resource: file,
+            timestamp: SystemTime::now(),
+            success: false,
+            error: Some("Discarded a partially written record".to_string()),
+        })?;
+        Ok(())
+    }
+
+    fn rotate(&self, head: &mut Head) -> Result<(), AuditStoreError> {
+        let rotated = self.dir.join(format!("audit-{:020}.jsonl", head.first_seq));
+        fs::rename(self.dir.join(CURRENT_FILE), rotated)?;
+        head.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(CURRENT_FILE))?;
+        head.size = 0;
+        head.first_seq = head.next_seq;
+        head.started = SystemTime::now();
+        prune(&self.dir, self.policy.max_age)
+    }
+}
+
+/// Store files oldest first: rotated files by sequence, then the current one.
+fn files(dir: &Path) -> Result<Vec<PathBuf>, AuditStoreError> {
+    let mut rotated = rotated_files(dir)?;
+    rotated.sort();
+    let current = dir.join(CURRENT_FILE);
+    if current.exists() {
+        rotated.push(current);
+    }
+    Ok(rotated)
+}
+
+fn rotated_files(dir: &Path) -> Result<Vec<PathBuf>, AuditStoreError> {
+    let mut rotated = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with("audit-") && name.ends_with(".jsonl") {
+            rotated.push(path);
+        }
+    }
+    Ok(rotated)
+}
+
+/// Delete rotated files last written at least `max_age` ago.
+fn prune(dir: &Path, max_age: Duration) -> Result<(), AuditStoreError> {
+    let now = SystemTime::now();
+    for path in rotated_files(dir)? {
+        let modified = fs::metadata(&path)?.modified()?;
+        if now.duration_since(modified).is_ok_and(|age| age >= max_age) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Move a torn last line of the current file (one cut off by a crash) to
+/// `torn-<unix secs>.partial` and truncate the file to its complete records.
+/// Returns the quarantine file, if there was a torn line.
+fn discard_torn_tail(dir: &Path) -> Result<Option<PathBuf>, AuditStoreError> {
+    let current = dir.join(CURRENT_FILE);
+    let bytes = match fs::read(&current) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if bytes.is_empty() || bytes.ends_with(b"\n") {
+        return Ok(None);
+    }
+
+    let start = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let tail = &bytes[start..];
+    if serde_json::from_slice::<AuditRecord>(tail).is_ok() {
+        // The record was written in full; only its newline is missing
+        OpenOptions::new()
+            .append(true)
+            .open(&current)?
+            .write_all(b"\n")?;
+        return Ok(None);
+    }
+
+    let quarantined = dir.join(format!("torn-{}.partial", unix_secs(SystemTime::now())));
+    fs::write(&quarantined, tail)?;
+    OpenOptions::new()
+        .write(true)
+        .open(&current)?
+        .set_len(start as u64)?;
+    Ok(Some(quarantined))
+}
+
+fn read_records(path: &Path) -> Result<Vec<AuditRecord>, AuditStoreError> {
+    parse_records(path, File::open(path)?)
+}
+
+/// Parse JSON Lines read from `source`; `path` is only used in errors.
+fn parse_records(path: &Path, source: impl Read) -> Result<Vec<AuditRecord>, AuditStoreError> {
+    let reader = BufReader::new(source);
+    let mut records = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| AuditStoreError::Corrupt {
+            file: path.display().to_string(),
+            line: index + 1,
+            reason: e.to_string(),
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(plugin_id: &str, operation: &str, resource: &str, success: bool) -> AuditEvent {
+        AuditEvent {
+            plugin_id: plugin_id.to_string(),
+            operation: operation.to_string(),
+            resource: resource.to_string(),
+            timestamp: SystemTime::now(),
+            success,
+            error: (!success).then(|| "Permission denied".to_string()),
+        }
+    }
+
+    fn open(dir: &Path) -> AuditStore {
+        AuditStore::open(dir, RotationPolicy::default()).unwrap()
+    }
+
+    #[test]
+    fn test_chain_continues_after_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = open(dir.path())
+            .append(&event("a", "fs.read", "/w/a.txt", true))
+            .unwrap();
+        assert_eq!(first.seq, 0);
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+
+        let store = open(dir.path());
+        let second = store
+            .append(&event("a", "fs.write", "/w/a.txt", true))
+            .unwrap();
+        assert_eq!(second.seq, 1);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(store.verify().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rotation_by_size_keeps_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RotationPolicy {
+            max_file_bytes: 600,
+            ..RotationPolicy::default()
+        };
+        let store = AuditStore::open(dir.path(), policy).unwrap();
+        for i in 0..10 {
+            store
+                .append(&event("a", "fs.read", &format!("/w/{}", i), true))
+                .unwrap();
+        }
+
+        assert!(rotated_files(dir.path()).unwrap().len() > 1);
+        let records = store.records().unwrap();
+        let seqs: Vec<u64> = records.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, (0..10).collect::<Vec<_>>());
+        assert_eq!(store.verify().unwrap(), 10);
+
+        // Reopening mid-rotation picks up the chain from the current file
+        let store = AuditStore::open(dir.path(), policy).unwrap();
+        assert_eq!(
+            store
+                .append(&event("a", "fs.read", "/w/x", true))
+                .unwrap()
+                .seq,
+            10
+        );
+        assert_eq!(store.verify().unwrap(), 11);
+    }
+
+    #[test]
+    fn test_rotation_by_age_prunes_old_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RotationPolicy {
+            max_file_bytes: 1,
+            max_age: Duration::ZERO,
+        };
+        let store = AuditStore::open(dir.path(), policy).unwrap();
+        for i in 0..3 {
+            store
+                .append(&event("a", "fs.read", &format!("/w/{}", i), true))
+                .unwrap();
+        }
+
+        // Only the current file survives; the chain anchors on what is left
+        assert!(rotated_files(dir.path()).unwrap().is_empty());
+        let records = store.records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].seq, 2);
+        assert_eq!(store.verify().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_rotation_by_age_rotates_the_current_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RotationPolicy {
+            max_file_bytes: u64::MAX,
+            max_age: Duration::from_secs(60 * 60),
+        };
+        let store = AuditStore::open(dir.path(), policy).unwrap();
+        store.append(&event("a", "fs.read", "/w/0", true)).unwrap();
+        store.append(&event("a", "fs.read", "/w/1", true)).unwrap();
+        assert!(rotated_files(dir.path()).unwrap().is_empty());
+
+        // The current file was started two hours ago
+        store.head.lock().unwrap().started -= Duration::from_secs(2 * 60 * 60);
+        store.append(&event("a", "fs.read", "/w/2", true)).unwrap();
+        assert_eq!(rotated_files(dir.path()).unwrap().len(), 1);
+        assert_eq!(store.verify().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(dir.path());
+        store
+            .append(&event("a", "fs.read", "/w/src/main.rs", true))
+            .unwrap();
+        store
+            .append(&event("a", "command.execute", "rm", false))
+            .unwrap();
+        store
+            .append(&event("b", "fs.read", "/w/docs/a.md", true))
+            .unwrap();
+        store
+            .append(&event("b", "fs.read", "/w/src/lib.rs", false))
+            .unwrap();
+
+        let by_plugin = AuditQuery {
+            plugin_id: Some("a".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(store.query(&by_plugin).unwrap().len(), 2);
+
+        let denied_reads = AuditQuery {
+            operation: Some("fs.read".to_string()),
+            success: Some(false),
+            ..Default::default()
+        };
+        let records = store.query(&denied_reads).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].event.plugin_id, "b");
+
+        let under_src = AuditQuery {
+            resource_prefix: Some("/w/src/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(store.query(&under_src).unwrap().len(), 2);
+
+        let now = unix_secs(SystemTime::now());
+        let future = AuditQuery {
+            since: Some(now + 3600),
+            ..Default::default()
+        };
+        assert!(store.query(&future).unwrap().is_empty());
+        let past = AuditQuery {
+            until: Some(now + 1),
+            ..Default::default()
+        };
+        assert_eq!(store.query(&past).unwrap().len(), 4);
+
+        let newest = AuditQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let records = store.query(&newest).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].seq, 3);
+    }
+
+    #[test]
+    fn test_export_jsonl_and_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(dir.path());
+        store
+            .append(&event("a", "fs.read", "/w/a,b.txt", true))
+            .unwrap();
+        let mut denied = event("a", "fs.write", "/w/c.txt", false);
+        denied.error = Some("Path not allowed: \"/w/c.txt\"".to_string());
+        store.append(&denied).unwrap();
+
+        let mut jsonl = Vec::new();
+        let count = store
+            .export(&AuditQuery::default(), ExportFormat::Jsonl, &mut jsonl)
+            .unwrap();
+        assert_eq!(count, 2);
+        let lines: Vec<AuditRecord> = String::from_utf8(jsonl)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[1].event.operation, "fs.write");
+
+        let mut csv = Vec::new();
+        store
+            .export(&AuditQuery::default(), ExportFormat::Csv, &mut csv)
+            .unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            rows[0],
+            "seq,timestamp,plugin_id,operation,resource,success,error,hash"
+        );
+        assert!(rows[1].contains(",\"/w/a,b.txt\",true,,"));
+        assert!(rows[2].contains(",false,\"Path not allowed: \"\"/w/c.txt\"\"\","));
+    }
+
+    #[test]
+    fn test_verify_detects_edited_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(dir.path());
+        store
+            .append(&event("a", "fs.read", "/w/a.txt", true))
+            .unwrap();
+        store
+            .append(&event("a", "fs.read", "/w/secret.txt", false))
+            .unwrap();
+        store
+            .append(&event("a", "fs.read", "/w/b.txt", true))
+            .unwrap();
+
+        let path = dir.path().join(CURRENT_FILE);
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace("secret.txt", "public.txt")).unwrap();
+
+        match store.verify() {
+            Err(AuditStoreError::Tampered { seq, .. }) => assert_eq!(seq, 1),
+            other => panic!("expected tampering, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_verify_detects_removed_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(dir.path());
+        for i in 0..3 {
+            store
+                .append(&event("a", "fs.read", &format!("/w/{}", i), true))
+                .unwrap();
+        }
+
+        let path = dir.path().join(CURRENT_FILE);
+        let content = fs::read_to_string(&path).unwrap();
+        let kept: Vec<&str> = content
+            .lines()
+            .enumerate()
+            .filter(|(i, _)| *i != 1)
+            .map(|(_, l)| l)
+            .collect();
+        fs::write(&path, kept.join("\n") + "\n").unwrap();
+
+        match store.verify() {
+            Err(AuditStoreError::Tampered { seq, .. }) => assert_eq!(seq, 2),
+            other => panic!("expected tampering, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_open_discards_a_torn_last_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(dir.path());
+        for i in 0..2 {
+            store
+                .append(&event("a", "fs.read", &format!("/w/{}", i), true))
+                .unwrap();
+        }
+        drop(store);
+        // A crash cut the third append short
+        let path = dir.path().join(CURRENT_FILE);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"seq":2,"event":{"plugin_id":"a","oper"#)
+            .unwrap();
+
+        let store = open(dir.path());
+
+        // The torn line is kept aside, and a new file opens with the break
+        let partial: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "partial"))
+            .collect();
+        assert_eq!(partial.len(), 1);
+        assert!(fs::read_to_string(&partial[0])
+            .unwrap()
+            .starts_with(r#"{"seq":2"#));
+        assert_eq!(rotated_files(dir.path()).unwrap().len(), 1);
+        let records = store.records().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].seq, 2);
+        assert_eq!(records[2].event.operation, TORN_WRITE_OPERATION);
+        assert!(!records[2].event.success);
+
+        // The chain carries on across the break
+        let next = store.append(&event("a", "fs.read", "/w/2", true)).unwrap();
+        assert_eq!(next.seq, 3);
+        assert_eq!(store.verify().unwrap(), 4);
+        assert_eq!(open(dir.path()).verify().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_open_keeps_a_complete_record_missing_its_newline() {
+        let dir = tempfile::tempdir().unwrap();
+        open(dir.path())
+            .append(&event("a", "fs.read", "/w/a.txt", true))
+            .unwrap();
+        let path = dir.path().join(CURRENT_FILE);
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.trim_end()).unwrap();
+
+        let store = open(dir.path());
+        store
+            .append(&event("a", "fs.read", "/w/b.txt", true))
+            .unwrap();
+        assert!(rotated_files(dir.path()).unwrap().is_empty());
+        assert_eq!(store.verify().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_records_skip_an_append_in_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(dir.path());
+        store
+            .append(&event("a", "fs.read", "/w/a.txt", true))
+            .unwrap();
+        // Bytes past the last complete append are not read yet
+        let path = dir.path().join(CURRENT_FILE);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"seq":1,"#).unwrap();
+
+        assert_eq!(store.records().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_open_rejects_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(CURRENT_FILE), "not json\n").unwrap();
+        assert!(matches!(
+            AuditStore::open(dir.path(), RotationPolicy::default()),
+            Err(AuditStoreError::Corrupt { line: 1, .. })
+        ));
+    }
+}
@@ -1,13 +1,64 @@
 // src-tauri/src/plugin_system/capabilities.rs
 
+use crate::plugin_system::commands::{self, CommandRule};
+use crate::plugin_system::network::NetworkRule;
+use crate::plugin_system::paths;
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
 
 // ============================================================================
 // FILESYSTEM CAPABILITIES
 // ============================================================================
 
+/// Files no plugin may read or write, whatever its filesystem capability.
+/// Matched against both the workspace-relative and the absolute path, ignoring
+/// case like every deny rule.
+pub const DEFAULT_DENY_PATTERNS: &[&str] = &[
+    "**/.env",
+    "**/.env.*",
+    "**/*.pem",
+    "**/*.key",
+    "**/*.p12",
+    "**/*.pfx",
+    "**/id_rsa*",
+    "**/id_ecdsa*",
+    "**/id_ed25519*",
+    "**/.ssh/**",
+    "**/.aws/**",
+    "**/.git/config",
+    "**/.git-credentials",
+    "**/.netrc",
+    "**/.npmrc",
+];
+
+/// Paths no plugin may write: repository metadata, where a planted hook or
+/// config entry runs code the next time the user runs git.
+pub const DEFAULT_WRITE_DENY_PATTERNS: &[&str] = &["**/.git", "**/.git/**"];
+
+static DEFAULT_DENY: LazyLock<GlobSet> = LazyLock::new(|| deny_set(DEFAULT_DENY_PATTERNS));
+
+static DEFAULT_WRITE_DENY: LazyLock<GlobSet> =
+    LazyLock::new(|| deny_set(DEFAULT_WRITE_DENY_PATTERNS));
+
+/// Compiled `Scoped` patterns by (pattern, ignore case); `None` for an invalid
+/// glob. Capabilities are checked on every file op, so each glob compiles once.
+static MATCHERS: LazyLock<MatcherCache> = LazyLock::new(Default::default);
+
+type MatcherCache = RwLock<HashMap<(String, bool), Option<GlobMatcher>>>;
+
+fn deny_set(patterns: &[&str]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(compile_glob(pattern, true).expect("default deny pattern is a valid glob"));
+    }
+    builder
+        .build()
+        .expect("default deny patterns build a glob set")
+}
+
 /// Filesystem access capabilities for plugins
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum FilesystemCapability {
@@ -21,45 +72,207 @@ pub enum FilesystemCapability {
     /// Read and write access to workspace files
     WorkspaceReadWrite,
 
-    /// Scoped access to specific paths
+    /// Scoped access to specific paths.
+    ///
+    /// Each entry is either a directory or file prefix (`src`, `/opt/data`) or a
+    /// glob (`src/**/*.rs`). Relative entries are taken from the workspace root.
     Scoped {
         /// Paths that can be read
         read: HashSet<String>,
         /// Paths that can be written
         write: HashSet<String>,
+        /// Paths that can never be accessed; overrides `read` and `write`
+        #[serde(default)]
+        deny: HashSet<String>,
     },
 }
 
 impl FilesystemCapability {
     /// Check if capability allows reading from a path
+    ///
+    /// `path` must already be resolved with [`paths::resolve`]; paths that still
+    /// contain `..` or are relative are refused.
     pub fn can_read(&self, path: &str, workspace_root: &str) -> bool {
         let candidate = Path::new(path);
         let workspace = Path::new(workspace_root);
+        if self.denies(path, workspace_root) {
+            return false;
+        }
         match self {
             FilesystemCapability::None => false,
             FilesystemCapability::WorkspaceRead | FilesystemCapability::WorkspaceReadWrite => {
-                candidate.starts_with(workspace)
+                paths::is_within(candidate, workspace)
             }
             FilesystemCapability::Scoped { read, .. } => read
                 .iter()
-                .map(Path::new)
-                .any(|allowed| candidate.starts_with(allowed)),
+                .any(|pattern| allows(pattern, candidate, workspace)),
         }
     }
 
-    /// Check if capability allows writing to a path
+    /// Check if capability allows writing to a path (resolved like [`Self::can_read`])
     pub fn can_write(&self, path: &str, workspace_root: &str) -> bool {
         let candidate = Path::new(path);
         let workspace = Path::new(workspace_root);
+        if self.denies(path, workspace_root)
+            || match_targets(candidate, workspace)
+                .iter()
+                .any(|target| DEFAULT_WRITE_DENY.is_match(target))
+        {
+            return false;
+        }
         match self {
             FilesystemCapability::None | FilesystemCapability::WorkspaceRead => false,
-            FilesystemCapability::WorkspaceReadWrite => candidate.starts_with(workspace),
+            FilesystemCapability::WorkspaceReadWrite => paths::is_within(candidate, workspace),
             FilesystemCapability::Scoped { write, .. } => write
                 .iter()
-                .map(Path::new)
-                .any(|allowed| candidate.starts_with(allowed)),
+                .any(|pattern| allows(pattern, candidate, workspace)),
+        }
+    }
+
+    /// Whether a deny rule (the defaults or the capability's own) covers `path`.
+    /// Directory listings use this to hide denied entries.
+    ///
+    /// Deny rules ignore case: the path may live on a case-insensitive
+    /// filesystem (macOS and Windows by default, casefolded directories
+    /// elsewhere), where `.ENV` opens `.env`.
+    pub fn denies(&self, path: &str, workspace_root: &str) -> bool {
+        let candidate = Path::new(path);
+        let workspace = Path::new(workspace_root);
+        let targets = match_targets(candidate, workspace);
+        if targets.iter().any(|target| DEFAULT_DENY.is_match(target)) {
+            return true;
+        }
+        match self {
+            FilesystemCapability::Scoped { deny, .. } => deny
+                .iter()
+                .any(|pattern| denied_by(pattern, candidate, workspace)),
+            _ => false,
+        }
+    }
+
+    /// Add read (and with `write`, write) access to one path or glob, keeping
+    /// everything already allowed. Workspace-wide access becomes a scope that
+    /// lists the workspace root, since the variants cannot express "workspace
+    /// plus one outside path" otherwise.
+    pub fn grant(&mut self, pattern: &str, write: bool, workspace_root: &str) {
+        let inside_workspace = !Path::new(pattern).is_absolute();
+        let (mut read_set, mut write_set, deny_set) = match std::mem::take(self) {
+            FilesystemCapability::None => (HashSet::new(), HashSet::new(), HashSet::new()),
+            FilesystemCapability::WorkspaceRead => {
+                if inside_workspace && !write {
+                    *self = FilesystemCapability::WorkspaceRead;
+                    return;
+                }
+                let workspace: HashSet<String> = [workspace_root.to_string()].into();
+                (workspace, HashSet::new(), HashSet::new())
+            }
+            FilesystemCapability::WorkspaceReadWrite => {
+                if inside_workspace {
+                    *self = FilesystemCapability::WorkspaceReadWrite;
+                    return;
+                }
+                let workspace: HashSet<String> = [workspace_root.to_string()].into();
+                (workspace.clone(), workspace, HashSet::new())
+            }
+            FilesystemCapability::Scoped { read, write, deny } => (read, write, deny),
+        };
+        read_set.insert(pattern.to_string());
+        if write {
+            write_set.insert(pattern.to_string());
+        }
+        *self = FilesystemCapability::Scoped {
+            read: read_set,
+            write: write_set,
+            deny: deny_set,
+        };
+    }
+
+    /// Check that every glob in a scoped capability compiles.
+    pub fn validate_patterns(&self) -> Result<(), String> {
+        if let FilesystemCapability::Scoped { read, write, deny } = self {
+            for pattern in read.iter().chain(write).chain(deny) {
+                if is_glob(pattern) {
+                    compile_glob(pattern, false)
+                        .map_err(|e| format!("invalid filesystem pattern '{}': {}", pattern, e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+/// `*` stops at separators; only `**` crosses directories.
+fn compile_glob(pattern: &str, ignore_case: bool) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(ignore_case)
+        .build()
+}
+
+/// Cached matcher for a `Scoped` glob; `None` if it does not compile.
+fn matcher(pattern: &str, ignore_case: bool) -> Option<GlobMatcher> {
+    let key = (pattern.to_string(), ignore_case);
+    if let Some(matcher) = MATCHERS.read().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return matcher.clone();
+    }
+    let compiled = compile_glob(pattern, ignore_case)
+        .ok()
+        .map(|glob| glob.compile_matcher());
+    MATCHERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(key)
+        .or_insert(compiled)
+        .clone()
+}
+
+/// Workspace-relative form of `path` (when inside the workspace) followed by
+/// the absolute form, both with `/` separators.
+fn match_targets(path: &Path, workspace: &Path) -> Vec<String> {
+    let mut targets = Vec::with_capacity(2);
+    if paths::is_within(path, workspace) {
+        if let Ok(relative) = path.strip_prefix(paths::canonical_root(workspace)) {
+            targets.push(relative.to_string_lossy().replace('\\', "/"));
         }
     }
+    targets.push(path.to_string_lossy().replace('\\', "/"));
+    targets
+}
+
+/// Whether an allow entry covers `path`. Relative globs only match inside the
+/// workspace; an invalid glob matches nothing.
+fn allows(pattern: &str, path: &Path, workspace: &Path) -> bool {
+    if !is_glob(pattern) {
+        return paths::is_within(path, &workspace.join(pattern));
+    }
+    let Some(matcher) = matcher(pattern, false) else {
+        return false;
+    };
+    if Path::new(pattern).is_absolute() {
+        return matcher.is_match(path.to_string_lossy().replace('\\', "/"));
+    }
+    paths::is_within(path, workspace)
+        && match_targets(path, workspace)
+            .first()
+            .is_some_and(|relative| matcher.is_match(relative))
+}
+
+/// Whether a deny entry covers `path`, ignoring case. An invalid glob denies
+/// everything.
+fn denied_by(pattern: &str, path: &Path, workspace: &Path) -> bool {
+    if !is_glob(pattern) {
+        return paths::is_within_ignoring_case(path, &workspace.join(pattern));
+    }
+    match matcher(pattern, true) {
+        Some(matcher) => match_targets(path, workspace)
+            .iter()
+            .any(|target| matcher.is_match(target)),
+        None => true,
+    }
 }
 
 // ============================================================================
@@ -73,7 +286,7 @@ pub enum NetworkCapability {
     #[default]
     None,
 
-    /// Access to specific domains only
+    /// Access matching these rules only (see [`crate::plugin_system::network`] for the syntax)
     DomainAllowlist(HashSet<String>),
 
     /// Unrestricted network access (use with caution)
@@ -81,15 +294,29 @@ pub enum NetworkCapability {
 }
 
 impl NetworkCapability {
-    /// Check if capability allows access to a domain
+    /// Check if any rule reaches a host. Full requests are checked with
+    /// [`crate::plugin_system::network::authorize`].
     pub fn can_access(&self, domain: &str) -> bool {
         match self {
             NetworkCapability::None => false,
-            NetworkCapability::DomainAllowlist(domains) => domains.contains(domain),
+            NetworkCapability::DomainAllowlist(rules) => rules
+                .iter()
+                .filter_map(|rule| NetworkRule::parse(rule).ok())
+                .any(|rule| rule.matches_host(domain)),
             NetworkCapability::Unrestricted => true,
         }
     }
 
+    /// Check that every allowlist entry parses as a network rule.
+    pub fn validate_rules(&self) -> Result<(), String> {
+        if let NetworkCapability::DomainAllowlist(rules) = self {
+            for rule in rules {
+                NetworkRule::parse(rule).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
     /// Add a domain to the allowlist
     pub fn add_domain(&mut self, domain: String) {
         match self {
@@ -116,6 +343,15 @@ pub struct CommandCapability {
 
     /// Whether to require user confirmation before execution
     pub require_confirmation: bool,
+
+    /// Argument rules from plugin.toml, keyed by command; built-in rules for
+    /// well-known tools apply on top (see [`crate::plugin_system::commands`])
+    #[serde(default)]
+    pub rules: HashMap<String, CommandRule>,
+
+    /// Absolute program paths, resolved from PATH when the capability is granted
+    #[serde(default)]
+    pub programs: HashMap<String, PathBuf>,
 }
 
 impl Default for CommandCapability {
@@ -123,6 +359,8 @@ impl Default for CommandCapability {
         CommandCapability {
             allowlist: HashSet::new(),
             require_confirmation: true,
+            rules: HashMap::new(),
+            programs: HashMap::new(),
         }
     }
 }
@@ -133,9 +371,36 @@ impl CommandCapability {
         CommandCapability {
             allowlist: allowlist.into_iter().collect(),
             require_confirmation: false,
+            ..Default::default()
         }
     }
 
+    /// Resolve every allowlisted command to an absolute path. Commands that
+    /// cannot be found stay unresolved and are refused at execution time.
+    pub fn resolve_programs(&mut self) {
+        self.programs = self
+            .allowlist
+            .iter()
+            .filter_map(|command| {
+                commands::find_program(command).map(|program| (command.clone(), program))
+            })
+            .collect();
+    }
+
+    /// Check that every argument rule compiles and names an allowlisted command.
+    pub fn validate_rules(&self) -> Result<(), String> {
+        for (command, rule) in &self.rules {
+            if !self.allowlist.contains(command) {
+                return Err(format!(
+                    "command rules for '{}', which is not in the allowlist",
+                    command
+                ));
+            }
+            rule.validate().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
     /// Check if a command is allowed
     pub fn can_execute(&self, command: &str) -> bool {
         self.allowlist.contains(command)
@@ -199,6 +464,17 @@ impl UiCapability {
     }
 }
 
+// ============================================================================
+// AUDIT CAPABILITIES
+// ============================================================================
+
+/// Access to the host's audit trail, for security monitor plugins
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditCapability {
+    /// Can subscribe to the live audit events of every plugin
+    pub read: bool,
+}
+
 // ============================================================================
 // PLUGIN CAPABILITIES
 // ============================================================================
@@ -217,6 +493,10 @@ pub struct PluginCapabilities {
 
     /// UI modification capabilities
     pub ui: UiCapability,
+
+    /// Audit trail access
+    #[serde(default)]
+    pub audit: AuditCapability,
 }
 
 impl Default for PluginCapabilities {
@@ -226,6 +506,7 @@ impl Default for PluginCapabilities {
             network: NetworkCapability::None,
             commands: CommandCapability::default(),
             ui: UiCapability::none(),
+            audit: AuditCapability::default(),
         }
     }
 }
@@ -243,6 +524,7 @@ impl PluginCapabilities {
             network: NetworkCapability::None,
             commands: CommandCapability::default(),
             ui: UiCapability::basic(),
+            audit: AuditCapability::default(),
         }
     }
 
@@ -253,6 +535,7 @@ impl PluginCapabilities {
             network: NetworkCapability::None,
             commands: CommandCapability::default(),
             ui: UiCapability::basic(),
+            audit: AuditCapability::default(),
         }
     }
 
@@ -271,8 +554,10 @@ impl PluginCapabilities {
                 .into_iter()
                 .collect(),
                 require_confirmation: false,
+                ..Default::default()
             },
             ui: UiCapability::all(),
+            audit: AuditCapability::default(),
         }
     }
 
@@ -293,14 +578,17 @@ impl PluginCapabilities {
                     FilesystemCapability::Scoped {
                         read: r1,
                         write: w1,
+                        deny: d1,
                     },
                     FilesystemCapability::Scoped {
                         read: r2,
                         write: w2,
+                        deny: d2,
                     },
                 ) => FilesystemCapability::Scoped {
                     read: r1.union(r2).cloned().collect(),
                     write: w1.union(w2).cloned().collect(),
+                    deny: d1.intersection(d2).cloned().collect(),
                 },
             },
             network: match (&self.network, &other.network) {
@@ -324,6 +612,21 @@ impl PluginCapabilities {
                     .collect(),
                 require_confirmation: self.commands.require_confirmation
                     && other.commands.require_confirmation,
+                // Argument rules and resolved paths: ours win where both sides have one
+                rules: other
+                    .commands
+                    .rules
+                    .iter()
+                    .chain(&self.commands.rules)
+                    .map(|(command, rule)| (command.clone(), rule.clone()))
+                    .collect(),
+                programs: other
+                    .commands
+                    .programs
+                    .iter()
+                    .chain(&self.commands.programs)
+                    .map(|(command, program)| (command.clone(), program.clone()))
+                    .collect(),
             },
             ui: UiCapability {
                 status_bar: self.ui.status_bar || other.ui.status_bar,
@@ -331,6 +634,9 @@ impl PluginCapabilities {
                 notifications: self.ui.notifications || other.ui.notifications,
                 webview: self.ui.webview || other.ui.webview,
             },
+            audit: AuditCapability {
+                read: self.audit.read || other.audit.read,
+            },
         }
     }
 
@@ -350,12 +656,14 @@ impl PluginCapabilities {
                 FilesystemCapability::Scoped {
                     read: r1,
                     write: w1,
+                    deny: d1,
                 },
                 FilesystemCapability::Scoped {
                     read: r2,
                     write: w2,
+                    deny: d2,
                 },
-            ) => r1.is_subset(r2) && w1.is_subset(w2),
+            ) => r1.is_subset(r2) && w1.is_subset(w2) && d2.is_subset(d1),
             _ => false,
         };
 
@@ -378,7 +686,10 @@ impl PluginCapabilities {
             && (!self.ui.notifications || other.ui.notifications)
             && (!self.ui.webview || other.ui.webview);
 
-        fs_ok && net_ok && cmd_ok && ui_ok
+        // Audit check
+        let audit_ok = !self.audit.read || other.audit.read;
+
+        fs_ok && net_ok && cmd_ok && ui_ok && audit_ok
     }
 }
 
@@ -407,11 +718,20 @@ mod tests {
         assert!(!cap.can_write("/other/file.txt", "/workspace"));
     }
 
+    #[test]
+    fn test_filesystem_rejects_unresolved_traversal() {
+        let cap = FilesystemCapability::WorkspaceReadWrite;
+        assert!(!cap.can_read("/workspace/../home/.ssh/id_rsa", "/workspace"));
+        assert!(!cap.can_write("/workspace/./../etc/passwd", "/workspace"));
+        assert!(!cap.can_read("workspace/file.txt", "workspace"));
+    }
+
     #[test]
     fn test_filesystem_scoped() {
         let cap = FilesystemCapability::Scoped {
             read: vec!["/workspace/read".to_string()].into_iter().collect(),
             write: vec!["/workspace/write".to_string()].into_iter().collect(),
+            deny: HashSet::new(),
         };
 
         assert!(cap.can_read("/workspace/read/file.txt", "/workspace"));
@@ -422,6 +742,174 @@ mod tests {
         assert!(!cap.can_write("/workspace/writer/file.txt", "/workspace"));
     }
 
+    #[test]
+    fn test_filesystem_scoped_globs() {
+        let cap = FilesystemCapability::Scoped {
+            read: vec!["src/**/*.rs".to_string(), "Cargo.toml".to_string()]
+                .into_iter()
+                .collect(),
+            write: vec!["target/*.txt".to_string()].into_iter().collect(),
+            deny: HashSet::new(),
+        };
+
+        assert!(cap.can_read("/workspace/src/main.rs", "/workspace"));
+        assert!(cap.can_read("/workspace/src/a/b/lib.rs", "/workspace"));
+        assert!(cap.can_read("/workspace/Cargo.toml", "/workspace"));
+        assert!(!cap.can_read("/workspace/src/notes.md", "/workspace"));
+        assert!(!cap.can_read("/workspace/build.rs", "/workspace"));
+        // Relative globs never match outside the workspace
+        assert!(!cap.can_read("/other/src/main.rs", "/workspace"));
+        assert!(cap.can_write("/workspace/target/out.txt", "/workspace"));
+        assert!(!cap.can_write("/workspace/target/sub/out.txt", "/workspace"));
+    }
+
+    #[test]
+    fn test_filesystem_deny_overrides_allow() {
+        let cap = FilesystemCapability::Scoped {
+            read: vec!["**".to_string()].into_iter().collect(),
+            write: vec!["src".to_string()].into_iter().collect(),
+            deny: vec!["fixtures/secrets".to_string(), "**/*.sqlite".to_string()]
+                .into_iter()
+                .collect(),
+        };
+
+        assert!(cap.can_read("/workspace/src/lib.rs", "/workspace"));
+        assert!(!cap.can_read("/workspace/fixtures/secrets/token.txt", "/workspace"));
+        assert!(!cap.can_read("/workspace/data/app.sqlite", "/workspace"));
+        assert!(!cap.can_write("/workspace/src/cache.sqlite", "/workspace"));
+        assert!(cap.denies("/workspace/data/app.sqlite", "/workspace"));
+    }
+
+    #[test]
+    fn test_filesystem_default_deny_applies_to_workspace_access() {
+        let cap = FilesystemCapability::WorkspaceReadWrite;
+        for secret in [
+            "/workspace/.env",
+            "/workspace/.env.production",
+            "/workspace/config/server.pem",
+            "/workspace/.git/config",
+            "/workspace/deploy/id_rsa",
+        ] {
+            assert!(
+                !cap.can_read(secret, "/workspace"),
+                "{} was readable",
+                secret
+            );
+            assert!(
+                !cap.can_write(secret, "/workspace"),
+                "{} was writable",
+                secret
+            );
+        }
+        assert!(cap.can_read("/workspace/src/env.rs", "/workspace"));
+        assert!(cap.can_read("/workspace/.git/HEAD", "/workspace"));
+    }
+
+    #[test]
+    fn test_filesystem_git_metadata_is_read_only() {
+        let cap = FilesystemCapability::Scoped {
+            read: vec!["**".to_string()].into_iter().collect(),
+            write: vec!["/workspace".to_string()].into_iter().collect(),
+            deny: HashSet::new(),
+        };
+        for metadata in [
+            "/workspace/.git",
+            "/workspace/.git/hooks/pre-commit",
+            "/workspace/.git/HEAD",
+            "/workspace/vendor/lib/.git/hooks/post-checkout",
+            "/workspace/.GIT/hooks/pre-commit",
+        ] {
+            assert!(
+                !cap.can_write(metadata, "/workspace"),
+                "{} was writable",
+                metadata
+            );
+        }
+        assert!(cap.can_read("/workspace/.git/hooks/pre-commit", "/workspace"));
+        assert!(!cap.denies("/workspace/.git/HEAD", "/workspace"));
+        assert!(cap.can_write("/workspace/.gitignore", "/workspace"));
+        assert!(cap.can_write("/workspace/src/git/mod.rs", "/workspace"));
+    }
+
+    #[test]
+    fn test_filesystem_deny_ignores_case() {
+        let cap = FilesystemCapability::Scoped {
+            read: vec!["**".to_string()].into_iter().collect(),
+            write: HashSet::new(),
+            deny: vec!["fixtures/secrets".to_string(), "**/*.sqlite".to_string()]
+                .into_iter()
+                .collect(),
+        };
+        for path in [
+            "/workspace/.ENV",
+            "/workspace/Config/Server.PEM",
+            "/workspace/.Git/Config",
+            "/workspace/deploy/ID_RSA",
+            "/workspace/Fixtures/SECRETS/token.txt",
+            "/workspace/data/app.SQLite",
+        ] {
+            assert!(!cap.can_read(path, "/workspace"), "{} was readable", path);
+        }
+        // Allow rules keep matching case-sensitively
+        let scoped = FilesystemCapability::Scoped {
+            read: vec!["src/**/*.rs".to_string()].into_iter().collect(),
+            write: HashSet::new(),
+            deny: HashSet::new(),
+        };
+        assert!(!scoped.can_read("/workspace/SRC/main.rs", "/workspace"));
+    }
+
+    #[test]
+    fn test_filesystem_validate_patterns() {
+        let valid = FilesystemCapability::Scoped {
+            read: vec!["src/**/*.{rs,toml}".to_string()].into_iter().collect(),
+            write: HashSet::new(),
+            deny: vec![".git/config".to_string()].into_iter().collect(),
+        };
+        assert!(valid.validate_patterns().is_ok());
+
+        let invalid = FilesystemCapability::Scoped {
+            read: HashSet::new(),
+            write: HashSet::new(),
+            deny: vec!["src/[".to_string()].into_iter().collect(),
+        };
+        assert!(invalid.validate_patterns().is_err());
+        // An unparseable deny rule fails closed
+        assert!(invalid.denies("/workspace/src/lib.rs", "/workspace"));
+    }
+
+    #[test]
+    fn test_filesystem_grant() {
+        let mut cap = FilesystemCapability::None;
+        cap.grant("docs", false, "/workspace");
+        assert!(cap.can_read("/workspace/docs/a.md", "/workspace"));
+        assert!(!cap.can_write("/workspace/docs/a.md", "/workspace"));
+
+        // Already covered: the variant is kept
+        let mut cap = FilesystemCapability::WorkspaceRead;
+        cap.grant("src", false, "/workspace");
+        assert_eq!(cap, FilesystemCapability::WorkspaceRead);
+
+        // Workspace access plus one outside path keeps both
+        cap.grant("/opt/data", false, "/workspace");
+        assert!(cap.can_read("/workspace/src/main.rs", "/workspace"));
+        assert!(cap.can_read("/opt/data/x.csv", "/workspace"));
+        assert!(!cap.can_write("/workspace/src/main.rs", "/workspace"));
+
+        cap.grant("out", true, "/workspace");
+        assert!(cap.can_write("/workspace/out/build.log", "/workspace"));
+        assert!(!cap.can_write("/workspace/src/main.rs", "/workspace"));
+
+        // Existing deny rules survive a grant
+        let mut cap = FilesystemCapability::Scoped {
+            read: HashSet::new(),
+            write: HashSet::new(),
+            deny: vec!["secrets".to_string()].into_iter().collect(),
+        };
+        cap.grant("secrets", true, "/workspace");
+        assert!(!cap.can_read("/workspace/secrets/token", "/workspace"));
+    }
+
     #[test]
     fn test_network_allowlist() {
         let mut cap = NetworkCapability::DomainAllowlist(HashSet::new());
@@ -454,6 +942,7 @@ mod tests {
                 notifications: false,
                 webview: false,
             },
+            audit: AuditCapability::default(),
         };
 
         let cap2 = PluginCapabilities {
@@ -468,6 +957,7 @@ mod tests {
                 notifications: true,
                 webview: false,
             },
+            audit: AuditCapability { read: true },
         };
 
         let merged = cap1.merge(&cap2);
@@ -481,6 +971,7 @@ mod tests {
         assert!(merged.ui.status_bar);
         assert!(merged.ui.sidebar);
         assert!(merged.ui.notifications);
+        assert!(merged.audit.read);
     }
 
     #[test]
@@ -493,6 +984,13 @@ mod tests {
         assert!(basic.is_subset_of(&full));
         assert!(advanced.is_subset_of(&full));
         assert!(!full.is_subset_of(&basic));
+
+        let monitor = PluginCapabilities {
+            audit: AuditCapability { read: true },
+            ..PluginCapabilities::none()
+        };
+        assert!(!monitor.is_subset_of(&full));
+        assert!(full.is_subset_of(&full.merge(&monitor)));
     }
 
     #[test]
@@ -590,6 +1088,9 @@ mod tests {
             filesystem: FilesystemCapability::Scoped {
                 read: vec!["/a".to_string()].into_iter().collect(),
                 write: vec!["/b".to_string()].into_iter().collect(),
+                deny: vec!["/a/.cache".to_string(), "**/*.log".to_string()]
+                    .into_iter()
+                    .collect(),
             },
             ..Default::default()
         };
@@ -597,15 +1098,18 @@ mod tests {
             filesystem: FilesystemCapability::Scoped {
                 read: vec!["/c".to_string()].into_iter().collect(),
                 write: vec!["/d".to_string()].into_iter().collect(),
+                deny: vec!["**/*.log".to_string()].into_iter().collect(),
             },
             ..Default::default()
         };
         let merged = cap1.merge(&cap2);
-        if let FilesystemCapability::Scoped { read, write } = &merged.filesystem {
+        if let FilesystemCapability::Scoped { read, write, deny } = &merged.filesystem {
             assert!(read.contains("/a"));
             assert!(read.contains("/c"));
             assert!(write.contains("/b"));
             assert!(write.contains("/d"));
+            assert_eq!(deny.len(), 1);
+            assert!(deny.contains("**/*.log"));
         } else {
             panic!("Expected Scoped filesystem capability");
         }
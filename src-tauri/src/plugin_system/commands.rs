@@ -0,0 +1,594 @@
+// src-tauri/src/plugin_system/commands.rs
+//
+// Argument policies for `CommandCapability`. Allowlisting a program name is not
+// enough: `git -c core.sshCommand=...` or `cargo --config` turn a read-only tool
+// into arbitrary code execution. Every plugin command is checked against the
+// built-in rules for well-known tools plus the rules from plugin.toml, runs the
+// program path resolved when the capability was granted, and starts with a
+// scrubbed environment.
+
+use crate::plugin_system::capabilities::CommandCapability;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Environment variables passed through to plugin commands; everything else
+/// (`GIT_*`, `LD_PRELOAD`, `NODE_OPTIONS`, `RUSTC_WRAPPER`, ...) is dropped.
+const INHERITED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TERM",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "SYSTEMROOT",
+    "WINDIR",
+    "COMSPEC",
+    "PATHEXT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+];
+
+/// Command policy errors
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("Command not allowed: {0}")]
+    NotAllowed(String),
+
+    #[error("Command '{0}' was not found on PATH when the capability was granted")]
+    NotResolved(String),
+
+    #[error("Subcommand '{subcommand}' is not allowed for {command}")]
+    Subcommand { command: String, subcommand: String },
+
+    #[error("Options before the subcommand are not allowed for {0}")]
+    LeadingOption(String),
+
+    #[error("Flag '{flag}' is not allowed for {command}")]
+    ForbiddenFlag { command: String, flag: String },
+
+    #[error("Argument '{arg}' is not allowed for {command}")]
+    ForbiddenArgument { command: String, arg: String },
+
+    #[error("Invalid argument pattern '{pattern}': {reason}")]
+    InvalidPattern { pattern: String, reason: String },
+}
+
+/// Argument rules for one allowlisted command, from `[permissions.commands.rules.<name>]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandRule {
+    /// The first argument must be one of these (empty allows any)
+    pub subcommands: Vec<String>,
+    /// The first argument must not be one of these
+    pub forbidden_subcommands: Vec<String>,
+    /// Flags that may not appear, in `--flag`, `--flag=value` or abbreviated form
+    pub forbidden_flags: Vec<String>,
+    /// Regexes every argument after the subcommand must fully match (empty allows any)
+    pub allowed_args: Vec<String>,
+    /// Regexes no argument may match
+    pub forbidden_args: Vec<String>,
+}
+
+impl CommandRule {
+    /// Check that every regex compiles.
+    pub fn validate(&self) -> Result<(), CommandError> {
+        for pattern in self.allowed_args.iter().chain(&self.forbidden_args) {
+            compile(pattern)?;
+        }
+        Ok(())
+    }
+
+    fn constrains_subcommand(&self) -> bool {
+        !self.subcommands.is_empty() || !self.forbidden_subcommands.is_empty()
+    }
+
+    /// Combine with another rule; the result is at least as strict as both.
+    fn and(mut self, other: &CommandRule) -> CommandRule {
+        if self.subcommands.is_empty() {
+            self.subcommands = other.subcommands.clone();
+        } else if !other.subcommands.is_empty() {
+            self.subcommands.retain(|s| other.subcommands.contains(s));
+            if self.subcommands.is_empty() {
+                // Disjoint allowlists leave nothing; an empty list would mean "any"
+                self.subcommands.push(String::new());
+            }
+        }
+        self.forbidden_subcommands
+            .extend(other.forbidden_subcommands.iter().cloned());
+        self.forbidden_flags
+            .extend(other.forbidden_flags.iter().cloned());
+        self.allowed_args.extend(other.allowed_args.iter().cloned());
+        self.forbidden_args
+            .extend(other.forbidden_args.iter().cloned());
+        self
+    }
+
+    fn check(&self, command: &str, args: &[String]) -> Result<(), CommandError> {
+        let positional_from = if self.constrains_subcommand() {
+            match args.first() {
+                Some(first) if first.starts_with('-') => {
+                    // `git -C dir config ...` would hide the real subcommand
+                    if args.iter().any(|arg| !arg.starts_with('-')) {
+                        return Err(CommandError::LeadingOption(command.to_string()));
+                    }
+                    0
+                }
+                Some(subcommand) => {
+                    let allowed = self.subcommands.is_empty()
+                        || self.subcommands.iter().any(|s| s == subcommand);
+                    if !allowed || self.forbidden_subcommands.iter().any(|s| s == subcommand) {
+                        return Err(CommandError::Subcommand {
+                            command: command.to_string(),
+                            subcommand: subcommand.clone(),
+                        });
+                    }
+                    1
+                }
+                None => 0,
+            }
+        } else {
+            0
+        };
+
+        let mut options_ended = false;
+        for arg in args {
+            if arg == "--" {
+                options_ended = true;
+                continue;
+            }
+            if !options_ended {
+                if let Some(flag) = self
+                    .forbidden_flags
+                    .iter()
+                    .find(|flag| flag_matches(flag, arg))
+                {
+                    return Err(CommandError::ForbiddenFlag {
+                        command: command.to_string(),
+                        flag: flag.clone(),
+                    });
+                }
+            }
+        }
+
+        let forbidden_arg = |arg: &String| CommandError::ForbiddenArgument {
+            command: command.to_string(),
+            arg: arg.clone(),
+        };
+        let forbidden = self
+            .forbidden_args
+            .iter()
+            .map(|p| compile(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(arg) = args
+            .iter()
+            .find(|arg| forbidden.iter().any(|re| re.is_match(arg)))
+        {
+            return Err(forbidden_arg(arg));
+        }
+        if !self.allowed_args.is_empty() {
+            let allowed = self
+                .allowed_args
+                .iter()
+                .map(|p| compile(&format!("^(?:{})$", p)))
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(arg) = args[positional_from..]
+                .iter()
+                .find(|arg| !allowed.iter().any(|re| re.is_match(arg)))
+            {
+                return Err(forbidden_arg(arg));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, CommandError> {
+    Regex::new(pattern).map_err(|e| CommandError::InvalidPattern {
+        pattern: pattern.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// `-c` matches `-c` and `-cvalue`; `--config` matches `--config`, `--config=x`
+/// and any abbreviation such as `--co`, since git and cargo accept unambiguous
+/// prefixes and we cannot know which ones are unambiguous for every subcommand.
+fn flag_matches(flag: &str, arg: &str) -> bool {
+    if flag.starts_with("--") {
+        let name = arg.split_once('=').map_or(arg, |(name, _)| name);
+        name.len() > 2 && name.starts_with("--") && flag.starts_with(name)
+    } else {
+        arg.starts_with(flag) && !arg.starts_with("--")
+    }
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// Rules that always apply to well-known tools, whatever the manifest says.
+pub fn builtin_rule(command: &str) -> Option<CommandRule> {
+    let rule = match command {
+        "git" => CommandRule {
+            forbidden_subcommands: strings(&[
+                "config",
+                "submodule",
+                "filter-branch",
+                "bisect",
+                "difftool",
+                "mergetool",
+                "daemon",
+                "instaweb",
+                "credential",
+                "send-email",
+                "upload-pack",
+                "receive-pack",
+                // `git rebase -x <cmd>` runs <cmd> after each commit
+                "rebase",
+            ]),
+            forbidden_flags: strings(&[
+                "-c",
+                "--config-env",
+                "--exec-path",
+                "--upload-pack",
+                "--receive-pack",
+                "--exec",
+                "--ext-diff",
+                "--template",
+                "--output",
+                "--git-dir",
+                "--work-tree",
+                // `git grep -O<cmd>` runs <cmd> on the matching files
+                "-O",
+                "--open-files-in-pager",
+            ]),
+            forbidden_args: strings(&[r"^ext::", r"^fd::"]),
+            ..Default::default()
+        },
+        "cargo" => CommandRule {
+            forbidden_subcommands: strings(&["install", "publish", "login", "owner", "yank"]),
+            forbidden_flags: strings(&["--config", "-Z", "--manifest-path"]),
+            ..Default::default()
+        },
+        "npm" => CommandRule {
+            forbidden_subcommands: strings(&[
+                "exec", "x", "explore", "publish", "adduser", "login", "token",
+            ]),
+            forbidden_flags: strings(&["--script-shell", "--node-options", "--userconfig"]),
+            ..Default::default()
+        },
+        "node" => CommandRule {
+            forbidden_flags: strings(&[
+                "-e",
+                "--eval",
+                "-p",
+                "--print",
+                "-r",
+                "--require",
+                "--import",
+                "--loader",
+                "--experimental-loader",
+                "-i",
+                "--interactive",
+            ]),
+            ..Default::default()
+        },
+        _ => return None,
+    };
+    Some(rule)
+}
+
+/// Find `program` on PATH (honoring PATHEXT on Windows).
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    if program.is_empty() || program.contains(['/', '\\', ':']) {
+        return None;
+    }
+    let path = std::env::var_os("PATH")?;
+    let extensions: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+            .split(';')
+            .map(String::from)
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    std::env::split_paths(&path)
+        .filter(|dir| dir.is_absolute())
+        .flat_map(|dir| {
+            extensions
+                .iter()
+                .map(move |ext| dir.join(format!("{}{}", program, ext)))
+        })
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// A command that passed the plugin's policy.
+#[derive(Debug, Clone)]
+pub struct AuthorizedCommand {
+    /// Absolute program path resolved at grant time
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Working directory, checked against the plugin's read capability. The
+    /// policy engine sets it to the workspace root when the caller names none.
+    pub cwd: Option<PathBuf>,
+}
+
+/// Check `command args...` against the plugin's command capability.
+///
+/// `args` are checked and later passed to the program exactly as given: no
+/// shell is involved, so metacharacters are inert, and rewriting them would
+/// only make the checked argv differ from the one that runs.
+pub fn authorize(
+    capability: &CommandCapability,
+    command: &str,
+    args: &[String],
+) -> Result<AuthorizedCommand, CommandError> {
+    if command.contains(['/', '\\', ':']) || !capability.can_execute(command) {
+        return Err(CommandError::NotAllowed(command.to_string()));
+    }
+    let program = capability
+        .programs
+        .get(command)
+        .cloned()
+        .ok_or_else(|| CommandError::NotResolved(command.to_string()))?;
+
+    let manifest_rule = capability.rules.get(command);
+    let rule = match (builtin_rule(command), manifest_rule) {
+        (Some(builtin), Some(manifest)) => builtin.and(manifest),
+        (Some(builtin), None) => builtin,
+        (None, Some(manifest)) => manifest.clone(),
+        (None, None) => CommandRule::default(),
+    };
+    rule.check(command, args)?;
+
+    Ok(AuthorizedCommand {
+        program,
+        args: args.to_vec(),
+        cwd: None,
+    })
+}
+
+impl AuthorizedCommand {
+    /// Process builder with the scrubbed environment applied.
+    pub fn command(&self) -> std::process::Command {
+        self.command_with_env(std::env::vars_os())
+    }
+
+    /// Process builder that inherits the allowed variables from `env` instead of
+    /// the app's environment.
+    pub fn command_with_env(
+        &self,
+        env: impl IntoIterator<Item = (OsString, OsString)>,
+    ) -> std::process::Command {
+        let mut cmd = std::process::Command::new(&self.program);
+        cmd.args(&self.args).env_clear();
+        if let Some(cwd) = &self.cwd {
+            cmd.current_dir(cwd);
+        }
+        for (key, value) in env {
+            let inherited = key.to_str().is_some_and(|k| {
+                INHERITED_ENV
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(k))
+            });
+            if inherited {
+                cmd.env(key, value);
+            }
+        }
+        // Never block on a credential prompt nobody can answer
+        cmd.env("GIT_TERMINAL_PROMPT", "0");
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        strings(args)
+    }
+
+    fn capability(command: &str, rule: Option<CommandRule>) -> CommandCapability {
+        let mut capability = CommandCapability::new(vec![command.to_string()]);
+        capability
+            .programs
+            .insert(command.to_string(), PathBuf::from("/usr/bin").join(command));
+        if let Some(rule) = rule {
+            capability.rules.insert(command.to_string(), rule);
+        }
+        capability
+    }
+
+    #[test]
+    fn test_git_code_execution_vectors_are_blocked() {
+        let git = capability("git", None);
+        for attack in [
+            &["-c", "core.sshCommand=touch /tmp/pwned", "fetch"][..],
+            &["-ccore.pager=sh", "log"],
+            &["--config-env=core.sshCommand=EVIL", "status"],
+            &["-C", ".", "config", "core.hooksPath", "/tmp"],
+            &["config", "core.fsmonitor", "sh -c evil"],
+            &["fetch", "--upload-pack=touch /tmp/pwned", "origin"],
+            &["fetch", "--upload-pa=sh", "origin"],
+            &["clone", "ext::sh -c touch% /tmp/pwned", "x"],
+            &["rebase", "--exec", "sh", "main"],
+            &["rebase", "-x", "sh -c evil", "HEAD~1"],
+            &["rebase", "-xsh", "HEAD~1"],
+            &["-C", ".", "rebase", "-i", "HEAD~1"],
+            &["submodule", "foreach", "sh"],
+            &["diff", "--ext-diff"],
+            &["grep", "-Osh -c evil", "TODO"],
+            &["grep", "--open-files-in-pager=sh", "TODO"],
+            &["grep", "--open-files", "TODO"],
+        ] {
+            assert!(
+                authorize(&git, "git", &args(attack)).is_err(),
+                "git {:?} was allowed",
+                attack
+            );
+        }
+        assert!(authorize(&git, "git", &args(&["status", "--porcelain"])).is_ok());
+        assert!(authorize(&git, "git", &args(&["log", "--", "-c"])).is_ok());
+        assert!(authorize(&git, "git", &args(&["--version"])).is_ok());
+    }
+
+    #[test]
+    fn test_cargo_config_is_blocked() {
+        let cargo = capability("cargo", None);
+        assert!(authorize(&cargo, "cargo", &args(&["build", "--config", "x"])).is_err());
+        assert!(authorize(
+            &cargo,
+            "cargo",
+            &args(&["check", "--config=build.rustc=sh"])
+        )
+        .is_err());
+        assert!(authorize(&cargo, "cargo", &args(&["check", "--message-format=json"])).is_ok());
+    }
+
+    #[test]
+    fn test_manifest_rule_narrows_subcommands() {
+        let rule = CommandRule {
+            subcommands: strings(&["status", "diff", "config"]),
+            allowed_args: strings(&[r"--porcelain(=v[12])?", r"[\w./-]+"]),
+            ..Default::default()
+        };
+        let git = capability("git", Some(rule));
+        assert!(authorize(&git, "git", &args(&["status", "--porcelain=v2"])).is_ok());
+        assert!(authorize(&git, "git", &args(&["diff", "src/main.rs"])).is_ok());
+        assert!(matches!(
+            authorize(&git, "git", &args(&["push"])),
+            Err(CommandError::Subcommand { .. })
+        ));
+        // Built-in forbids still apply even though the manifest lists `config`
+        assert!(authorize(&git, "git", &args(&["config", "user.name"])).is_err());
+        assert!(matches!(
+            authorize(&git, "git", &args(&["diff", "a b"])),
+            Err(CommandError::ForbiddenArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn test_forbidden_args_regex() {
+        let rule = CommandRule {
+            forbidden_args: strings(&[r"^/etc/"]),
+            ..Default::default()
+        };
+        let cat = capability("cat", Some(rule));
+        assert!(authorize(&cat, "cat", &args(&["/etc/shadow"])).is_err());
+        assert!(authorize(&cat, "cat", &args(&["README.md"])).is_ok());
+    }
+
+    #[test]
+    fn test_unlisted_unresolved_and_path_commands() {
+        let git = capability("git", None);
+        assert!(matches!(
+            authorize(&git, "rm", &args(&["-rf", "/"])),
+            Err(CommandError::NotAllowed(_))
+        ));
+        assert!(matches!(
+            authorize(&git, "/usr/bin/git", &[]),
+            Err(CommandError::NotAllowed(_))
+        ));
+
+        let unresolved = CommandCapability::new(vec!["git".to_string()]);
+        assert!(matches!(
+            authorize(&unresolved, "git", &args(&["status"])),
+            Err(CommandError::NotResolved(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_reported() {
+        let rule = CommandRule {
+            allowed_args: strings(&["("]),
+            ..Default::default()
+        };
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_flag_matching() {
+        assert!(flag_matches("--config", "--config"));
+        assert!(flag_matches("--config", "--config=a=b"));
+        assert!(flag_matches("--config", "--conf"));
+        assert!(flag_matches("--config", "--co"));
+        assert!(!flag_matches("--config", "--"));
+        assert!(!flag_matches("--config", "--configure"));
+        assert!(flag_matches("-c", "-c"));
+        assert!(flag_matches("-c", "-ccore.pager=sh"));
+        assert!(!flag_matches("-c", "--cached"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_program_resolves_absolute_path() {
+        let sh = find_program("sh").expect("sh on PATH");
+        assert!(sh.is_absolute());
+        assert!(find_program("./sh").is_none());
+        assert!(find_program("definitely-not-a-real-program-xyz").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_environment_is_scrubbed() {
+        let env_program = find_program("env").expect("env on PATH");
+        let mut capability = CommandCapability::new(vec!["env".to_string()]);
+        capability.programs.insert("env".to_string(), env_program);
+
+        let env = [
+            ("SKRETCHPAD_TEST_SECRET", "leaked"),
+            ("LD_PRELOAD", "/tmp/evil.so"),
+            ("PATH", "/usr/bin:/bin"),
+        ]
+        .map(|(key, value)| (OsString::from(key), OsString::from(value)));
+        let output = authorize(&capability, "env", &[])
+            .unwrap()
+            .command_with_env(env)
+            .output()
+            .unwrap();
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!stdout.contains("SKRETCHPAD_TEST_SECRET"));
+        assert!(!stdout.contains("LD_PRELOAD"));
+        assert!(stdout.contains("GIT_TERMINAL_PROMPT=0"));
+        assert!(stdout.contains("PATH=/usr/bin:/bin"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_arguments_reach_the_program_verbatim() {
+        let echo = find_program("echo").expect("echo on PATH");
+        let mut capability = CommandCapability::new(vec!["echo".to_string()]);
+        capability.programs.insert("echo".to_string(), echo);
+
+        let injection = args(&["a; touch /tmp/pwned", "$(whoami)", "`id` | cat > out"]);
+        let authorized = authorize(&capability, "echo", &injection).unwrap();
+        assert_eq!(authorized.args, injection);
+        let output = authorized.command().output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "a; touch /tmp/pwned $(whoami) `id` | cat > out\n"
+        );
+    }
+}
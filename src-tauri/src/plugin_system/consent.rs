@@ -0,0 +1,467 @@
+// src-tauri/src/plugin_system/consent.rs
+//
+// Consent broker for commands whose capability has `require_confirmation`.
+// The caller suspends, the frontend receives a `plugin:consent-request` event,
+// and the call resumes when `respond_plugin_consent` answers it or the request
+// times out. "Allow always" answers are remembered per plugin and command line
+// (program plus arguments) and persisted next to the trusted keys.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Event emitted to the frontend for every consent request.
+pub const CONSENT_REQUEST_EVENT: &str = "plugin:consent-request";
+
+/// How long a request waits for an answer before it counts as denied.
+pub const CONSENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Consent errors
+#[derive(Debug, thiserror::Error)]
+pub enum ConsentError {
+    #[error("User denied '{command}' for plugin '{plugin_id}'")]
+    Denied { plugin_id: String, command: String },
+
+    #[error("No answer to the consent request within {}s", .timeout.as_secs())]
+    TimedOut { timeout: Duration },
+
+    #[error("Failed to ask for consent: {0}")]
+    Emit(String),
+}
+
+/// The user's answer to a consent request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConsentDecision {
+    AllowOnce,
+    AllowAlways,
+    Deny,
+}
+
+/// Payload of `plugin:consent-request`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsentRequest {
+    pub id: String,
+    pub plugin_id: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+}
+
+impl ConsentRequest {
+    pub fn new(plugin_id: &str, command: &str, args: &[String], cwd: Option<&Path>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            plugin_id: plugin_id.to_string(),
+            command: command.to_string(),
+            args: args.to_vec(),
+            cwd: cwd.map(|p| p.display().to_string()),
+        }
+    }
+}
+
+/// A command line the user allowed permanently.
+///
+/// Allowing `git status` must not also allow `git push --force`, so the
+/// arguments are part of the decision.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AllowedCommand {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl AllowedCommand {
+    fn matches(&self, command: &str, args: &[String]) -> bool {
+        self.command == command && self.args == args
+    }
+}
+
+type AllowedCommands = BTreeMap<String, BTreeSet<AllowedCommand>>;
+
+/// Pending consent requests plus the persisted "allow always" decisions.
+pub struct ConsentBroker {
+    pending: Mutex<HashMap<String, oneshot::Sender<ConsentDecision>>>,
+    /// plugin id -> command lines the user allowed permanently
+    always: Mutex<AllowedCommands>,
+    store: Option<PathBuf>,
+    timeout: Duration,
+}
+
+impl ConsentBroker {
+    /// Broker that keeps "allow always" decisions in memory only.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            always: Mutex::new(BTreeMap::new()),
+            store: None,
+            timeout,
+        }
+    }
+
+    /// Broker backed by `path`, loading any decisions saved there.
+    pub fn with_store(path: PathBuf, timeout: Duration) -> Result<Self, String> {
+        let mut broker = Self::new(timeout);
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read consent store '{}': {}", path.display(), e))?;
+            let always = match serde_json::from_str::<AllowedCommands>(&content) {
+                Ok(always) => always,
+                // Older stores keyed decisions on the program name alone, which
+                // allowed any arguments; those decisions are dropped, not widened
+                Err(_)
+                    if serde_json::from_str::<BTreeMap<String, BTreeSet<String>>>(&content)
+                        .is_ok() =>
+                {
+                    eprintln!(
+                        "Discarding consent decisions in '{}' that were not tied to arguments",
+                        path.display()
+                    );
+                    AllowedCommands::new()
+                }
+                Err(e) => {
+                    return Err(format!(
+                        "Failed to parse consent store '{}': {}",
+                        path.display(),
+                        e
+                    ))
+                }
+            };
+            broker.always = Mutex::new(always);
+        }
+        broker.store = Some(path);
+        Ok(broker)
+    }
+
+    /// Whether the user already allowed this exact command line for the plugin permanently.
+    pub fn is_always_allowed(&self, plugin_id: &str, command: &str, args: &[String]) -> bool {
+        self.always
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(plugin_id)
+            .is_some_and(|commands| commands.iter().any(|c| c.matches(command, args)))
+    }
+
+    /// Command lines the user allowed permanently for a plugin.
+    pub fn always_allowed(&self, plugin_id: &str) -> Vec<AllowedCommand> {
+        self.always
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(plugin_id)
+            .map(|commands| commands.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget permanent decisions for a plugin: all of them when `command` is None,
+    /// every command line of `command` when `args` is None, otherwise just that one.
+    pub fn revoke(
+        &self,
+        plugin_id: &str,
+        command: Option<&str>,
+        args: Option<&[String]>,
+    ) -> Result<(), String> {
+        {
+            let mut always = self.always.lock().unwrap_or_else(|e| e.into_inner());
+            match command {
+                Some(command) => {
+                    if let Some(commands) = always.get_mut(plugin_id) {
+                        commands.retain(|allowed| match args {
+                            Some(args) => !allowed.matches(command, args),
+                            None => allowed.command != command,
+                        });
+                        if commands.is_empty() {
+                            always.remove(plugin_id);
+                        }
+                    }
+                }
+                None => {
+                    always.remove(plugin_id);
+                }
+            }
+        }
+        self.save()
+    }
+
+    /// Answer a pending request. Returns false if it is unknown or already settled.
+    pub fn respond(&self, request_id: &str, decision: ConsentDecision) -> bool {
+        match self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(request_id)
+        {
+            Some(sender) => sender.send(decision).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Ask for consent without blocking the async runtime.
+    pub async fn request(
+        &self,
+        request: ConsentRequest,
+        emit: impl FnOnce(&ConsentRequest) -> Result<(), String>,
+    ) -> Result<(), ConsentError> {
+        if self.is_always_allowed(&request.plugin_id, &request.command, &request.args) {
+            return Ok(());
+        }
+        let decision = self.ask(&request.id, || emit(&request)).await?;
+        self.settle(&request, decision)
+    }
+
+    /// Wait for the answer to request `id`, which `emit` delivers to the frontend.
+    ///
+    /// Lower-level than [`ConsentBroker::request`]: nothing is remembered, the
+    /// caller decides what each answer means.
+    pub async fn ask(
+        &self,
+        id: &str,
+        emit: impl FnOnce() -> Result<(), String>,
+    ) -> Result<ConsentDecision, ConsentError> {
+        let receiver = self.begin(id, emit)?;
+        // A dropped sender (the broker forgot the request) counts as a denial
+        let answer = tokio::time::timeout(self.timeout, receiver)
+            .await
+            .map(Result::ok);
+        self.end(id, answer)
+    }
+
+    fn begin(
+        &self,
+        id: &str,
+        emit: impl FnOnce() -> Result<(), String>,
+    ) -> Result<oneshot::Receiver<ConsentDecision>, ConsentError> {
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.to_string(), sender);
+        if let Err(e) = emit() {
+            self.pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(id);
+            return Err(ConsentError::Emit(e));
+        }
+        Ok(receiver)
+    }
+
+    fn end(
+        &self,
+        id: &str,
+        answer: Result<Option<ConsentDecision>, tokio::time::error::Elapsed>,
+    ) -> Result<ConsentDecision, ConsentError> {
+        // A late answer must not find the request still pending
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+        match answer {
+            Ok(Some(decision)) => Ok(decision),
+            Ok(None) => Ok(ConsentDecision::Deny),
+            Err(_) => Err(ConsentError::TimedOut {
+                timeout: self.timeout,
+            }),
+        }
+    }
+
+    fn settle(
+        &self,
+        request: &ConsentRequest,
+        decision: ConsentDecision,
+    ) -> Result<(), ConsentError> {
+        match decision {
+            ConsentDecision::AllowOnce => Ok(()),
+            ConsentDecision::AllowAlways => {
+                self.always
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .entry(request.plugin_id.clone())
+                    .or_default()
+                    .insert(AllowedCommand {
+                        command: request.command.clone(),
+                        args: request.args.clone(),
+                    });
+                if let Err(e) = self.save() {
+                    eprintln!("{}", e);
+                }
+                Ok(())
+            }
+            ConsentDecision::Deny => Err(ConsentError::Denied {
+                plugin_id: request.plugin_id.clone(),
+                command: request.command.clone(),
+            }),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.store else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                format!(
+                    "Failed to create consent store directory '{}': {}",
+                    parent.display(),
+                    e
+                )
+            })?;
+        }
+        let content =
+            serde_json::to_string_pretty(&*self.always.lock().unwrap_or_else(|e| e.into_inner()))
+                .map_err(|e| format!("Failed to serialize consent decisions: {}", e))?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("Failed to write consent store '{}': {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn request(command: &str) -> ConsentRequest {
+        ConsentRequest::new("p", command, &["status".to_string()], None)
+    }
+
+    /// Answer every emitted request from another thread.
+    fn answering(
+        broker: &Arc<ConsentBroker>,
+        decision: ConsentDecision,
+    ) -> impl FnOnce(&ConsentRequest) -> Result<(), String> {
+        let broker = broker.clone();
+        move |req| {
+            let id = req.id.clone();
+            std::thread::spawn(move || assert!(broker.respond(&id, decision)));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_allow_once_is_not_remembered() {
+        let broker = Arc::new(ConsentBroker::new(Duration::from_secs(5)));
+        let emit = answering(&broker, ConsentDecision::AllowOnce);
+        assert!(broker.request(request("git"), emit).await.is_ok());
+        assert!(!broker.is_always_allowed("p", "git", &["status".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_deny() {
+        let broker = Arc::new(ConsentBroker::new(Duration::from_secs(5)));
+        let emit = answering(&broker, ConsentDecision::Deny);
+        assert!(matches!(
+            broker.request(request("git"), emit).await,
+            Err(ConsentError::Denied { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_denies_and_late_answer_is_ignored() {
+        let broker = ConsentBroker::new(Duration::from_millis(20));
+        let mut id = String::new();
+        let result = broker
+            .request(request("git"), |req| {
+                id = req.id.clone();
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(ConsentError::TimedOut { .. })));
+        assert!(!broker.respond(&id, ConsentDecision::AllowOnce));
+    }
+
+    #[tokio::test]
+    async fn test_emit_failure_denies() {
+        let broker = ConsentBroker::new(Duration::from_secs(5));
+        let result = broker
+            .request(request("git"), |_| Err("no window".to_string()))
+            .await;
+        assert!(matches!(result, Err(ConsentError::Emit(_))));
+        assert!(broker.pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_request_is_rejected() {
+        let broker = ConsentBroker::new(CONSENT_TIMEOUT);
+        assert!(!broker.respond("nope", ConsentDecision::AllowAlways));
+    }
+
+    #[tokio::test]
+    async fn test_allow_always_is_persisted_and_skips_the_prompt() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("consent.json");
+        let broker =
+            Arc::new(ConsentBroker::with_store(path.clone(), Duration::from_secs(5)).unwrap());
+
+        let status = ["status".to_string()];
+        let emit = answering(&broker, ConsentDecision::AllowAlways);
+        broker.request(request("git"), emit).await.unwrap();
+        assert!(broker.is_always_allowed("p", "git", &status));
+        assert!(!broker.is_always_allowed("p", "npm", &status));
+        assert!(!broker.is_always_allowed("other", "git", &status));
+
+        let reloaded = ConsentBroker::with_store(path.clone(), Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            reloaded.always_allowed("p"),
+            vec![AllowedCommand {
+                command: "git".to_string(),
+                args: status.to_vec(),
+            }]
+        );
+        reloaded
+            .request(request("git"), |_| panic!("prompted again"))
+            .await
+            .unwrap();
+
+        reloaded.revoke("p", Some("git"), Some(&status)).unwrap();
+        let reloaded = ConsentBroker::with_store(path, Duration::from_secs(5)).unwrap();
+        assert!(reloaded.always_allowed("p").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_allow_always_is_tied_to_the_arguments() {
+        let broker = Arc::new(ConsentBroker::new(Duration::from_secs(5)));
+        let emit = answering(&broker, ConsentDecision::AllowAlways);
+        broker.request(request("git"), emit).await.unwrap();
+
+        let push = ConsentRequest::new(
+            "p",
+            "git",
+            &["push".to_string(), "--force".to_string()],
+            None,
+        );
+        let mut prompted = false;
+        let result = broker
+            .request(push, |_| {
+                prompted = true;
+                Err("no window".to_string())
+            })
+            .await;
+        assert!(prompted);
+        assert!(matches!(result, Err(ConsentError::Emit(_))));
+
+        broker.revoke("p", Some("git"), None).unwrap();
+        assert!(broker.always_allowed("p").is_empty());
+    }
+
+    #[test]
+    fn test_store_without_arguments_is_discarded() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("consent.json");
+        std::fs::write(&path, r#"{"p": ["git"]}"#).unwrap();
+
+        let broker = ConsentBroker::with_store(path, CONSENT_TIMEOUT).unwrap();
+        assert!(broker.always_allowed("p").is_empty());
+        assert!(!broker.is_always_allowed("p", "git", &[]));
+    }
+
+    #[test]
+    fn test_decision_wire_format() {
+        assert_eq!(
+            serde_json::to_string(&ConsentDecision::AllowAlways).unwrap(),
+            "\"allow-always\""
+        );
+        let decision: ConsentDecision = serde_json::from_str("\"allow-once\"").unwrap();
+        assert_eq!(decision, ConsentDecision::AllowOnce);
+    }
+}
@@ -0,0 +1,505 @@
+// src-tauri/src/plugin_system/escalation.rs
+//
+// Re-consent for capability changes. The capabilities a user approved for a
+// plugin are remembered; when plugin.toml changes (hot reload, update) the
+// manager diffs the new set against the approved one and refuses to activate a
+// plugin that asks for more until the user approves the diff.
+
+use crate::plugin_system::capabilities::PluginCapabilities;
+use crate::plugin_system::grants;
+use crate::plugin_system::loader::PluginManifest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Event emitted with the [`CapabilityDiff`] when activation is blocked.
+pub const CAPABILITY_ESCALATION_EVENT: &str = "plugin:capability-escalation";
+
+/// One difference between the approved and the requested capabilities.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CapabilityChange {
+    Filesystem {
+        from: serde_json::Value,
+        to: serde_json::Value,
+    },
+    Network {
+        from: serde_json::Value,
+        to: serde_json::Value,
+    },
+    CommandAdded {
+        command: String,
+    },
+    CommandRemoved {
+        command: String,
+    },
+    /// Argument rules for a command were removed or replaced
+    CommandRulesChanged {
+        command: String,
+    },
+    /// Argument rules were added to a command
+    CommandRulesAdded {
+        command: String,
+    },
+    ConfirmationDropped,
+    ConfirmationRequired,
+    UiAdded {
+        feature: String,
+    },
+    UiRemoved {
+        feature: String,
+    },
+    AuditReadAdded,
+    AuditReadRemoved,
+}
+
+/// Structured diff between a plugin's approved and requested capabilities.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityDiff {
+    pub plugin_id: String,
+    pub approved_version: String,
+    pub version: String,
+    /// Changes that widen what the plugin can do; any of these blocks activation
+    pub escalations: Vec<CapabilityChange>,
+    /// Changes that narrow what the plugin can do
+    pub reductions: Vec<CapabilityChange>,
+}
+
+impl CapabilityDiff {
+    /// Diff `requested` against `approved`.
+    pub fn between(
+        plugin_id: &str,
+        approved: &ApprovedCapabilities,
+        requested_version: &str,
+        requested: &PluginCapabilities,
+    ) -> Self {
+        let previous = &approved.capabilities;
+        let mut diff = CapabilityDiff {
+            plugin_id: plugin_id.to_string(),
+            approved_version: approved.version.clone(),
+            version: requested_version.to_string(),
+            escalations: Vec::new(),
+            reductions: Vec::new(),
+        };
+
+        // Filesystem and network: compare each on its own with `is_subset_of`
+        if previous.filesystem != requested.filesystem {
+            let only = |caps: &PluginCapabilities| PluginCapabilities {
+                filesystem: caps.filesystem.clone(),
+                ..PluginCapabilities::none()
+            };
+            let change = CapabilityChange::Filesystem {
+                from: to_value(&previous.filesystem),
+                to: to_value(&requested.filesystem),
+            };
+            diff.record(only(requested).is_subset_of(&only(previous)), change);
+        }
+        if previous.network != requested.network {
+            let only = |caps: &PluginCapabilities| PluginCapabilities {
+                network: caps.network.clone(),
+                ..PluginCapabilities::none()
+            };
+            let change = CapabilityChange::Network {
+                from: to_value(&previous.network),
+                to: to_value(&requested.network),
+            };
+            diff.record(only(requested).is_subset_of(&only(previous)), change);
+        }
+
+        // Commands
+        let (before, after) = (&previous.commands, &requested.commands);
+        let mut added: Vec<_> = after.allowlist.difference(&before.allowlist).collect();
+        let mut removed: Vec<_> = before.allowlist.difference(&after.allowlist).collect();
+        added.sort();
+        removed.sort();
+        for command in added {
+            diff.escalations.push(CapabilityChange::CommandAdded {
+                command: command.clone(),
+            });
+        }
+        for command in removed {
+            diff.reductions.push(CapabilityChange::CommandRemoved {
+                command: command.clone(),
+            });
+        }
+        let mut kept: Vec<_> = after.allowlist.intersection(&before.allowlist).collect();
+        kept.sort();
+        for command in kept {
+            // Rules cannot be compared for strictness, so any change to an
+            // existing rule counts as widening
+            match (before.rules.get(command), after.rules.get(command)) {
+                (Some(old), Some(new)) if old == new => {}
+                (None, None) => {}
+                (None, Some(_)) => diff.reductions.push(CapabilityChange::CommandRulesAdded {
+                    command: command.clone(),
+                }),
+                _ => diff
+                    .escalations
+                    .push(CapabilityChange::CommandRulesChanged {
+                        command: command.clone(),
+                    }),
+            }
+        }
+        match (before.require_confirmation, after.require_confirmation) {
+            (true, false) => diff.escalations.push(CapabilityChange::ConfirmationDropped),
+            (false, true) => diff.reductions.push(CapabilityChange::ConfirmationRequired),
+            _ => {}
+        }
+
+        // UI
+        for (feature, was, is) in [
+            (
+                "status_bar",
+                previous.ui.status_bar,
+                requested.ui.status_bar,
+            ),
+            ("sidebar", previous.ui.sidebar, requested.ui.sidebar),
+            (
+                "notifications",
+                previous.ui.notifications,
+                requested.ui.notifications,
+            ),
+            ("webview", previous.ui.webview, requested.ui.webview),
+        ] {
+            let feature = feature.to_string();
+            match (was, is) {
+                (false, true) => diff.escalations.push(CapabilityChange::UiAdded { feature }),
+                (true, false) => diff
+                    .reductions
+                    .push(CapabilityChange::UiRemoved { feature }),
+                _ => {}
+            }
+        }
+
+        // Audit
+        match (previous.audit.read, requested.audit.read) {
+            (false, true) => diff.escalations.push(CapabilityChange::AuditReadAdded),
+            (true, false) => diff.reductions.push(CapabilityChange::AuditReadRemoved),
+            _ => {}
+        }
+
+        diff
+    }
+
+    /// Whether the requested capabilities need the user's approval.
+    pub fn escalates(&self) -> bool {
+        !self.escalations.is_empty()
+    }
+
+    /// Whether anything changed at all.
+    pub fn is_empty(&self) -> bool {
+        self.escalations.is_empty() && self.reductions.is_empty()
+    }
+
+    fn record(&mut self, narrower: bool, change: CapabilityChange) {
+        if narrower {
+            self.reductions.push(change);
+        } else {
+            self.escalations.push(change);
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+/// Capabilities the user approved for a plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovedCapabilities {
+    pub version: String,
+    pub capabilities: PluginCapabilities,
+    /// Unix seconds
+    pub approved_at: u64,
+}
+
+/// Approved capabilities per plugin, optionally persisted as JSON.
+#[derive(Default)]
+pub struct ApprovalStore {
+    approved: Mutex<BTreeMap<String, ApprovedCapabilities>>,
+    path: Option<PathBuf>,
+}
+
+impl ApprovalStore {
+    /// Store that keeps approvals in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store backed by `path`, loading any approvals saved there.
+    pub fn with_file(path: PathBuf) -> Result<Self, String> {
+        let mut approved = BTreeMap::new();
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read approvals '{}': {}", path.display(), e))?;
+            approved = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse approvals '{}': {}", path.display(), e))?;
+        }
+        Ok(Self {
+            approved: Mutex::new(approved),
+            path: Some(path),
+        })
+    }
+
+    pub fn get(&self, plugin_id: &str) -> Option<ApprovedCapabilities> {
+        self.approved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(plugin_id)
+            .cloned()
+    }
+
+    /// Record the manifest's capabilities as approved.
+    pub fn approve(&self, plugin_id: &str, manifest: &PluginManifest) -> Result<(), String> {
+        let mut capabilities = manifest.capabilities.clone();
+        // Resolved paths depend on the machine, not on what the user approved
+        capabilities.commands.programs.clear();
+        self.approved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                plugin_id.to_string(),
+                ApprovedCapabilities {
+                    version: manifest.version.clone(),
+                    capabilities,
+                    approved_at: grants::now_secs(),
+                },
+            );
+        self.save()
+    }
+
+    /// Compare the manifest with the approved capabilities. A plugin seen for
+    /// the first time, or one whose capabilities only shrank, is (re)approved as
+    /// is; an escalation is returned for the user to review.
+    ///
+    /// Audit read exposes every plugin's operations, fetched URLs included, so
+    /// it is never trusted on first use: unless the plugin is verified, the
+    /// user reviews it like an escalation.
+    pub fn review(&self, plugin_id: &str, manifest: &PluginManifest) -> Result<(), CapabilityDiff> {
+        let Some(approved) = self.get(plugin_id) else {
+            if manifest.capabilities.audit.read && !manifest.trust.is_trusted() {
+                let mut without_audit = manifest.capabilities.clone();
+                without_audit.audit.read = false;
+                let unapproved = ApprovedCapabilities {
+                    version: String::new(),
+                    capabilities: without_audit,
+                    approved_at: 0,
+                };
+                return Err(CapabilityDiff::between(
+                    plugin_id,
+                    &unapproved,
+                    &manifest.version,
+                    &manifest.capabilities,
+                ));
+            }
+            if let Err(e) = self.approve(plugin_id, manifest) {
+                eprintln!("{}", e);
+            }
+            return Ok(());
+        };
+        let diff = CapabilityDiff::between(
+            plugin_id,
+            &approved,
+            &manifest.version,
+            &manifest.capabilities,
+        );
+        if diff.escalates() {
+            return Err(diff);
+        }
+        if !diff.is_empty() {
+            if let Err(e) = self.approve(plugin_id, manifest) {
+                eprintln!("{}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                format!(
+                    "Failed to create approvals directory '{}': {}",
+                    parent.display(),
+                    e
+                )
+            })?;
+        }
+        let content =
+            serde_json::to_string_pretty(&*self.approved.lock().unwrap_or_else(|e| e.into_inner()))
+                .map_err(|e| format!("Failed to serialize approvals: {}", e))?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("Failed to write approvals '{}': {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_system::capabilities::{
+        CommandCapability, FilesystemCapability, NetworkCapability,
+    };
+    use crate::plugin_system::commands::CommandRule;
+    use crate::plugin_system::trust::TrustLevel;
+
+    fn manifest(version: &str, capabilities: PluginCapabilities) -> PluginManifest {
+        let mut manifest: PluginManifest = toml::from_str(&format!(
+            "name = \"p\"\nversion = \"{}\"\nmain = \"main.js\"\n",
+            version
+        ))
+        .unwrap();
+        manifest.capabilities = capabilities;
+        manifest
+    }
+
+    fn git_caps() -> PluginCapabilities {
+        PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceRead,
+            commands: CommandCapability {
+                allowlist: ["git".to_string()].into(),
+                require_confirmation: true,
+                ..Default::default()
+            },
+            ..PluginCapabilities::none()
+        }
+    }
+
+    #[test]
+    fn test_first_sighting_is_approved() {
+        let store = ApprovalStore::new();
+        assert!(store.review("p", &manifest("1.0.0", git_caps())).is_ok());
+        assert_eq!(store.get("p").unwrap().version, "1.0.0");
+    }
+
+    #[test]
+    fn test_audit_read_is_reviewed_on_first_sighting() {
+        let store = ApprovalStore::new();
+        let mut monitor = git_caps();
+        monitor.audit.read = true;
+        let community = manifest("1.0.0", monitor);
+
+        let diff = store.review("p", &community).unwrap_err();
+        assert_eq!(diff.approved_version, "");
+        assert_eq!(diff.escalations, vec![CapabilityChange::AuditReadAdded]);
+        assert!(store.get("p").is_none());
+
+        store.approve("p", &community).unwrap();
+        assert!(store.review("p", &community).is_ok());
+
+        // A verified plugin is trusted with it
+        let mut verified = community.clone();
+        verified.trust = TrustLevel::Verified;
+        assert!(store.review("q", &verified).is_ok());
+    }
+
+    #[test]
+    fn test_escalation_blocks_until_approved() {
+        let store = ApprovalStore::new();
+        store.approve("p", &manifest("1.0.0", git_caps())).unwrap();
+
+        let mut upgraded = git_caps();
+        upgraded.filesystem = FilesystemCapability::WorkspaceReadWrite;
+        upgraded.network = NetworkCapability::DomainAllowlist(["evil.example".to_string()].into());
+        upgraded.commands.allow_command("curl".to_string());
+        upgraded.ui.webview = true;
+        let next = manifest("1.1.0", upgraded);
+
+        let diff = store.review("p", &next).unwrap_err();
+        assert_eq!(diff.approved_version, "1.0.0");
+        assert_eq!(diff.version, "1.1.0");
+        assert!(diff.escalates());
+        assert_eq!(diff.escalations.len(), 4);
+        assert!(diff.escalations.contains(&CapabilityChange::CommandAdded {
+            command: "curl".to_string()
+        }));
+        // Still blocked on the next attempt
+        assert!(store.review("p", &next).is_err());
+        // The frontend hands the reviewed diff back with its approval
+        let echoed: CapabilityDiff = serde_json::from_value(serde_json::json!(diff)).unwrap();
+        assert_eq!(echoed, diff);
+
+        store.approve("p", &next).unwrap();
+        assert!(store.review("p", &next).is_ok());
+    }
+
+    #[test]
+    fn test_reduction_is_accepted_and_recorded() {
+        let store = ApprovalStore::new();
+        store.approve("p", &manifest("1.0.0", git_caps())).unwrap();
+
+        let next = manifest("1.0.1", PluginCapabilities::none());
+        assert!(store.review("p", &next).is_ok());
+        assert_eq!(store.get("p").unwrap().version, "1.0.1");
+
+        // Going back up to the old set is an escalation again
+        assert!(store.review("p", &manifest("1.0.2", git_caps())).is_err());
+    }
+
+    #[test]
+    fn test_dropping_confirmation_or_rules_escalates() {
+        let mut approved = git_caps();
+        approved.commands.rules.insert(
+            "git".to_string(),
+            CommandRule {
+                subcommands: vec!["status".to_string()],
+                ..Default::default()
+            },
+        );
+        let approved = ApprovedCapabilities {
+            version: "1.0.0".to_string(),
+            capabilities: approved,
+            approved_at: 0,
+        };
+
+        let mut no_confirmation = approved.capabilities.clone();
+        no_confirmation.commands.require_confirmation = false;
+        let diff = CapabilityDiff::between("p", &approved, "1.1.0", &no_confirmation);
+        assert_eq!(
+            diff.escalations,
+            vec![CapabilityChange::ConfirmationDropped]
+        );
+
+        let mut no_rules = approved.capabilities.clone();
+        no_rules.commands.rules.clear();
+        let diff = CapabilityDiff::between("p", &approved, "1.1.0", &no_rules);
+        assert_eq!(
+            diff.escalations,
+            vec![CapabilityChange::CommandRulesChanged {
+                command: "git".to_string()
+            }]
+        );
+
+        let mut audit_read = approved.capabilities.clone();
+        audit_read.audit.read = true;
+        let diff = CapabilityDiff::between("p", &approved, "1.1.0", &audit_read);
+        assert_eq!(diff.escalations, vec![CapabilityChange::AuditReadAdded]);
+    }
+
+    #[test]
+    fn test_resolved_programs_are_not_a_change() {
+        let store = ApprovalStore::new();
+        store.approve("p", &manifest("1.0.0", git_caps())).unwrap();
+        let mut resolved = git_caps();
+        resolved
+            .commands
+            .programs
+            .insert("git".to_string(), PathBuf::from("/usr/bin/git"));
+        let approved = store.get("p").unwrap();
+        let diff = CapabilityDiff::between("p", &approved, "1.0.0", &resolved);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_approvals_persist() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("approvals.json");
+        let store = ApprovalStore::with_file(path.clone()).unwrap();
+        store.approve("p", &manifest("1.0.0", git_caps())).unwrap();
+
+        let reloaded = ApprovalStore::with_file(path).unwrap();
+        assert_eq!(reloaded.get("p"), store.get("p"));
+    }
+}
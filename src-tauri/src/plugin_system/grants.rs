@@ -0,0 +1,705 @@
+// src-tauri/src/plugin_system/grants.rs
+//
+// Persistent permission grants. Capabilities in plugin.toml are what a plugin
+// asks for up front; grants are what the user handed out later, through
+// `grant_plugin_capability` or a plugin's runtime permission request. Each grant
+// is tied to a plugin and a workspace, may expire, and is kept after revocation
+// so `list_plugin_grants` shows the full history. "Allow once" answers become
+// session grants, which are listed and applied like the others but never saved.
+
+use crate::plugin_system::capabilities::{
+    FilesystemCapability, NetworkCapability, PluginCapabilities,
+};
+use crate::plugin_system::commands;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Event emitted to the frontend when a plugin asks for a permission at runtime.
+/// Answered through `respond_plugin_consent`, like command confirmations.
+pub const PERMISSION_REQUEST_EVENT: &str = "plugin:permission-request";
+
+/// UI features that can be granted one by one.
+const UI_FEATURES: &[&str] = &["status_bar", "sidebar", "notifications", "webview"];
+
+/// Grant errors
+#[derive(Debug, thiserror::Error)]
+pub enum GrantError {
+    #[error("Invalid permission: {0}")]
+    InvalidPermission(String),
+
+    #[error("Grant store error: {0}")]
+    Store(String),
+}
+
+/// A single permission, e.g. `{ "kind": "network", "target": "https://api.github.com" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "target", rename_all = "snake_case")]
+pub enum Permission {
+    /// Read a path or glob (relative entries are taken from the workspace root)
+    FilesystemRead(String),
+    /// Read and write a path or glob
+    FilesystemWrite(String),
+    /// A network rule (see [`crate::plugin_system::network`] for the syntax)
+    Network(String),
+    /// Run an allowlisted command by name
+    Command(String),
+    /// One of `status_bar`, `sidebar`, `notifications`, `webview`
+    Ui(String),
+}
+
+impl Permission {
+    /// Check the target before it is granted.
+    pub fn validate(&self) -> Result<(), GrantError> {
+        let invalid = |reason: String| Err(GrantError::InvalidPermission(reason));
+        match self {
+            Permission::FilesystemRead(pattern) | Permission::FilesystemWrite(pattern) => {
+                let escapes = Path::new(pattern)
+                    .components()
+                    .any(|c| matches!(c, Component::ParentDir));
+                if pattern.is_empty() || pattern.contains('\0') || escapes {
+                    return invalid(format!("filesystem path '{}'", pattern.escape_default()));
+                }
+                FilesystemCapability::Scoped {
+                    read: [pattern.clone()].into(),
+                    write: Default::default(),
+                    deny: Default::default(),
+                }
+                .validate_patterns()
+                .or_else(invalid)
+            }
+            Permission::Network(rule) => NetworkCapability::DomainAllowlist([rule.clone()].into())
+                .validate_rules()
+                .or_else(invalid),
+            Permission::Command(command) => {
+                if command.is_empty() || command.contains(['/', '\\', ':']) {
+                    return invalid(format!("command '{}'", command.escape_default()));
+                }
+                Ok(())
+            }
+            Permission::Ui(feature) => {
+                if !UI_FEATURES.contains(&feature.as_str()) {
+                    return invalid(format!("UI feature '{}'", feature.escape_default()));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Absolute program for a command permission, resolved now (at grant time).
+    pub fn resolve_program(&self) -> Result<Option<PathBuf>, GrantError> {
+        match self {
+            Permission::Command(command) => {
+                commands::find_program(command).map(Some).ok_or_else(|| {
+                    GrantError::InvalidPermission(format!(
+                        "command '{}' was not found on PATH",
+                        command
+                    ))
+                })
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Add this permission to `capabilities`.
+    pub fn apply(
+        &self,
+        capabilities: &mut PluginCapabilities,
+        workspace_root: &Path,
+        program: Option<&Path>,
+    ) {
+        let workspace = workspace_root.to_string_lossy();
+        match self {
+            Permission::FilesystemRead(pattern) => {
+                capabilities.filesystem.grant(pattern, false, &workspace)
+            }
+            Permission::FilesystemWrite(pattern) => {
+                capabilities.filesystem.grant(pattern, true, &workspace)
+            }
+            Permission::Network(rule) => capabilities.network.add_domain(rule.clone()),
+            Permission::Command(command) => {
+                capabilities.commands.allow_command(command.clone());
+                if let Some(program) = program {
+                    capabilities
+                        .commands
+                        .programs
+                        .insert(command.clone(), program.to_path_buf());
+                }
+            }
+            Permission::Ui(feature) => match feature.as_str() {
+                "status_bar" => capabilities.ui.status_bar = true,
+                "sidebar" => capabilities.ui.sidebar = true,
+                "notifications" => capabilities.ui.notifications = true,
+                "webview" => capabilities.ui.webview = true,
+                _ => {}
+            },
+        }
+    }
+
+    /// Whether `capabilities` already include this permission.
+    pub fn is_covered_by(&self, capabilities: &PluginCapabilities, workspace_root: &Path) -> bool {
+        let mut granted = capabilities.clone();
+        self.apply(&mut granted, workspace_root, None);
+        granted == *capabilities
+    }
+}
+
+/// Payload of `plugin:permission-request`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionRequest {
+    pub id: String,
+    pub plugin_id: String,
+    pub permission: Permission,
+    pub reason: Option<String>,
+}
+
+impl PermissionRequest {
+    pub fn new(plugin_id: &str, permission: Permission, reason: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            plugin_id: plugin_id.to_string(),
+            permission,
+            reason,
+        }
+    }
+}
+
+/// How long a grant outlives the app.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantScope {
+    /// Saved with the store and applied again after a restart
+    #[default]
+    Persistent,
+    /// Kept in memory until the app exits
+    Session,
+}
+
+/// A permission the user granted to a plugin in one workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grant {
+    pub id: String,
+    pub plugin_id: String,
+    pub workspace: PathBuf,
+    pub permission: Permission,
+    #[serde(default)]
+    pub scope: GrantScope,
+    /// Program resolved when a command permission was granted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program: Option<PathBuf>,
+    /// Unix seconds
+    pub granted_at: u64,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    #[serde(default)]
+    pub revoked_at: Option<u64>,
+}
+
+impl Grant {
+    /// Neither revoked nor expired at `now` (Unix seconds).
+    pub fn is_active(&self, now: u64) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires| now < expires)
+    }
+}
+
+/// Current time in Unix seconds.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// All grants ever made, optionally persisted as JSON.
+#[derive(Default)]
+pub struct GrantStore {
+    grants: Mutex<Vec<Grant>>,
+    path: Option<PathBuf>,
+}
+
+impl GrantStore {
+    /// Store that keeps grants in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store backed by `path`, loading any grants saved there.
+    pub fn with_file(path: PathBuf) -> Result<Self, String> {
+        let mut grants = Vec::new();
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read grants '{}': {}", path.display(), e))?;
+            grants = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse grants '{}': {}", path.display(), e))?;
+        }
+        Ok(Self {
+            grants: Mutex::new(grants),
+            path: Some(path),
+        })
+    }
+
+    /// Record a grant, valid for `ttl` when given.
+    pub fn grant(
+        &self,
+        plugin_id: &str,
+        workspace: &Path,
+        permission: Permission,
+        ttl: Option<Duration>,
+    ) -> Result<Grant, GrantError> {
+        self.record(
+            plugin_id,
+            workspace,
+            permission,
+            ttl,
+            GrantScope::Persistent,
+        )
+    }
+
+    /// Record a grant that lasts until the app exits and is never saved.
+    pub fn grant_for_session(
+        &self,
+        plugin_id: &str,
+        workspace: &Path,
+        permission: Permission,
+    ) -> Result<Grant, GrantError> {
+        self.record(plugin_id, workspace, permission, None, GrantScope::Session)
+    }
+
+    fn record(
+        &self,
+        plugin_id: &str,
+        workspace: &Path,
+        permission: Permission,
+        ttl: Option<Duration>,
+        scope: GrantScope,
+    ) -> Result<Grant, GrantError> {
+        permission.validate()?;
+        let now = now_secs();
+        let grant = Grant {
+            id: uuid::Uuid::new_v4().to_string(),
+            plugin_id: plugin_id.to_string(),
+            workspace: workspace.to_path_buf(),
+            program: permission.resolve_program()?,
+            permission,
+            scope,
+            granted_at: now,
+            expires_at: ttl.map(|ttl| now.saturating_add(ttl.as_secs().max(1))),
+            revoked_at: None,
+        };
+        self.grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(grant.clone());
+        if scope == GrantScope::Persistent {
+            self.save()?;
+        }
+        Ok(grant)
+    }
+
+    /// Revoke one grant. Returns the revoked grant, or None if it is unknown or
+    /// was already revoked.
+    pub fn revoke(&self, grant_id: &str) -> Result<Option<Grant>, GrantError> {
+        let revoked = {
+            let mut grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+            grants
+                .iter_mut()
+                .find(|g| g.id == grant_id && g.revoked_at.is_none())
+                .map(|grant| {
+                    grant.revoked_at = Some(now_secs());
+                    grant.clone()
+                })
+        };
+        if revoked.is_some() {
+            self.save()?;
+        }
+        Ok(revoked)
+    }
+
+    /// Revoke every active grant of `permission` for a plugin in a workspace.
+    pub fn revoke_permission(
+        &self,
+        plugin_id: &str,
+        workspace: &Path,
+        permission: &Permission,
+    ) -> Result<usize, GrantError> {
+        let now = now_secs();
+        let count = {
+            let mut grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+            let mut count = 0;
+            for grant in grants.iter_mut().filter(|g| {
+                g.plugin_id == plugin_id
+                    && g.workspace == workspace
+                    && g.permission == *permission
+                    && g.is_active(now)
+            }) {
+                grant.revoked_at = Some(now);
+                count += 1;
+            }
+            count
+        };
+        if count > 0 {
+            self.save()?;
+        }
+        Ok(count)
+    }
+
+    /// Every grant, active or not, optionally for one plugin, oldest first.
+    pub fn list(&self, plugin_id: Option<&str>) -> Vec<Grant> {
+        self.grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|g| plugin_id.is_none_or(|id| g.plugin_id == id))
+            .cloned()
+            .collect()
+    }
+
+    /// `base` plus the plugin's active grants for this workspace.
+    pub fn apply(
+        &self,
+        plugin_id: &str,
+        workspace: &Path,
+        base: &PluginCapabilities,
+    ) -> PluginCapabilities {
+        let now = now_secs();
+        let mut capabilities = base.clone();
+        for grant in self
+            .grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|g| g.plugin_id == plugin_id && g.workspace == workspace && g.is_active(now))
+        {
+            grant
+                .permission
+                .apply(&mut capabilities, workspace, grant.program.as_deref());
+        }
+        capabilities
+    }
+
+    /// Plugins with a grant that expired in `(since, now]`.
+    pub fn expired_between(&self, since: u64, now: u64) -> Vec<String> {
+        let mut plugins: Vec<String> = self
+            .grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|g| g.revoked_at.is_none())
+            .filter(|g| g.expires_at.is_some_and(|at| since < at && at <= now))
+            .map(|g| g.plugin_id.clone())
+            .collect();
+        plugins.sort();
+        plugins.dedup();
+        plugins
+    }
+
+    fn save(&self) -> Result<(), GrantError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                GrantError::Store(format!(
+                    "Failed to create grants directory '{}': {}",
+                    parent.display(),
+                    e
+                ))
+            })?;
+        }
+        let persistent: Vec<Grant> = self
+            .grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|g| g.scope == GrantScope::Persistent)
+            .cloned()
+            .collect();
+        let content = serde_json::to_string_pretty(&persistent)
+            .map_err(|e| GrantError::Store(format!("Failed to serialize grants: {}", e)))?;
+        write_atomically(path, content.as_bytes()).map_err(|e| {
+            GrantError::Store(format!(
+                "Failed to write grants '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Replace `path` with `content` so that a crash leaves either the old file or
+/// the new one, never a truncated mix: the content goes to a sibling temp file,
+/// which is flushed to disk and then renamed over the original.
+fn write_atomically(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let written = std::fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    let result = written.and_then(|()| std::fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> PathBuf {
+        PathBuf::from("/workspace")
+    }
+
+    #[test]
+    fn test_permission_wire_format() {
+        let permission: Permission =
+            serde_json::from_str(r#"{"kind":"filesystem_read","target":"docs"}"#).unwrap();
+        assert_eq!(permission, Permission::FilesystemRead("docs".to_string()));
+        assert_eq!(
+            serde_json::to_value(Permission::Ui("sidebar".to_string())).unwrap(),
+            serde_json::json!({"kind": "ui", "target": "sidebar"})
+        );
+    }
+
+    #[test]
+    fn test_permission_validation() {
+        for invalid in [
+            Permission::FilesystemRead("../outside".to_string()),
+            Permission::FilesystemWrite(String::new()),
+            Permission::FilesystemRead("src/[".to_string()),
+            Permission::Network("ftp://example.com".to_string()),
+            Permission::Command("/bin/sh".to_string()),
+            Permission::Ui("clipboard".to_string()),
+        ] {
+            assert!(invalid.validate().is_err(), "accepted {:?}", invalid);
+        }
+        for valid in [
+            Permission::FilesystemRead("docs/**/*.md".to_string()),
+            Permission::Network("GET https://api.github.com/*".to_string()),
+            Permission::Command("git".to_string()),
+            Permission::Ui("status_bar".to_string()),
+        ] {
+            assert!(valid.validate().is_ok(), "rejected {:?}", valid);
+        }
+    }
+
+    #[test]
+    fn test_apply_adds_to_manifest_capabilities() {
+        let store = GrantStore::new();
+        let base = PluginCapabilities::workspace_read();
+        store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::Network("api.github.com".to_string()),
+                None,
+            )
+            .unwrap();
+        store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::FilesystemWrite("out".to_string()),
+                None,
+            )
+            .unwrap();
+        store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::Ui("sidebar".to_string()),
+                None,
+            )
+            .unwrap();
+
+        let caps = store.apply("p", &workspace(), &base);
+        assert!(caps.network.can_access("api.github.com"));
+        assert!(caps
+            .filesystem
+            .can_write("/workspace/out/log.txt", "/workspace"));
+        assert!(caps
+            .filesystem
+            .can_read("/workspace/src/main.rs", "/workspace"));
+        assert!(caps.ui.sidebar);
+
+        // Other plugins and other workspaces see nothing
+        assert_eq!(store.apply("q", &workspace(), &base), base);
+        assert_eq!(store.apply("p", Path::new("/elsewhere"), &base), base);
+    }
+
+    #[test]
+    fn test_revoke_keeps_history() {
+        let store = GrantStore::new();
+        let grant = store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::Ui("webview".to_string()),
+                None,
+            )
+            .unwrap();
+        let base = PluginCapabilities::none();
+        assert!(store.apply("p", &workspace(), &base).ui.webview);
+
+        assert!(store.revoke(&grant.id).unwrap().is_some());
+        assert!(store.revoke(&grant.id).unwrap().is_none());
+        assert!(!store.apply("p", &workspace(), &base).ui.webview);
+
+        let history = store.list(Some("p"));
+        assert_eq!(history.len(), 1);
+        assert!(history[0].revoked_at.is_some());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let store = GrantStore::new();
+        let grant = store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::Ui("sidebar".to_string()),
+                Some(Duration::from_secs(60)),
+            )
+            .unwrap();
+        let expires = grant.expires_at.unwrap();
+        assert!(grant.is_active(expires - 1));
+        assert!(!grant.is_active(expires));
+        assert_eq!(store.expired_between(expires - 1, expires), vec!["p"]);
+        assert!(store.expired_between(expires, expires + 10).is_empty());
+    }
+
+    #[test]
+    fn test_revoke_permission() {
+        let store = GrantStore::new();
+        let permission = Permission::Network("example.com".to_string());
+        store
+            .grant("p", &workspace(), permission.clone(), None)
+            .unwrap();
+        store
+            .grant("p", &workspace(), permission.clone(), None)
+            .unwrap();
+        assert_eq!(
+            store
+                .revoke_permission("p", &workspace(), &permission)
+                .unwrap(),
+            2
+        );
+        assert!(!store
+            .apply("p", &workspace(), &PluginCapabilities::none())
+            .network
+            .can_access("example.com"));
+    }
+
+    #[test]
+    fn test_is_covered_by() {
+        let caps = PluginCapabilities::workspace_read();
+        let ws = workspace();
+        assert!(Permission::FilesystemRead("src".to_string()).is_covered_by(&caps, &ws));
+        assert!(!Permission::FilesystemWrite("src".to_string()).is_covered_by(&caps, &ws));
+        assert!(Permission::Ui("status_bar".to_string()).is_covered_by(&caps, &ws));
+        assert!(!Permission::Ui("webview".to_string()).is_covered_by(&caps, &ws));
+    }
+
+    #[test]
+    fn test_session_grants_are_listed_but_not_saved() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("grants.json");
+        let store = GrantStore::with_file(path.clone()).unwrap();
+        let grant = store
+            .grant_for_session(
+                "p",
+                &workspace(),
+                Permission::FilesystemRead("docs".to_string()),
+            )
+            .unwrap();
+        assert_eq!(grant.scope, GrantScope::Session);
+        assert_eq!(store.list(Some("p")), vec![grant.clone()]);
+        assert!(store
+            .apply("p", &workspace(), &PluginCapabilities::none())
+            .filesystem
+            .can_read("/workspace/docs/a.md", "/workspace"));
+
+        // Saving a persistent grant leaves the session grant out of the file
+        store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::Ui("sidebar".to_string()),
+                None,
+            )
+            .unwrap();
+        let reloaded = GrantStore::with_file(path).unwrap();
+        let saved = reloaded.list(Some("p"));
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].scope, GrantScope::Persistent);
+
+        assert!(store.revoke(&grant.id).unwrap().is_some());
+        assert!(!store
+            .apply("p", &workspace(), &PluginCapabilities::none())
+            .filesystem
+            .can_read("/workspace/docs/a.md", "/workspace"));
+    }
+
+    #[test]
+    fn test_saving_replaces_the_file_without_leaving_a_temp_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("grants.json");
+        std::fs::write(&path, "[]").unwrap();
+        let store = GrantStore::with_file(path.clone()).unwrap();
+        store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::Ui("sidebar".to_string()),
+                None,
+            )
+            .unwrap();
+
+        let names: Vec<_> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("grants.json")]);
+        assert_eq!(
+            GrantStore::with_file(path).unwrap().list(Some("p")).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_grants_persist() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("grants.json");
+        let store = GrantStore::with_file(path.clone()).unwrap();
+        let grant = store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::FilesystemRead("docs".to_string()),
+                Some(Duration::from_secs(3600)),
+            )
+            .unwrap();
+        store.revoke(&grant.id).unwrap();
+        store
+            .grant(
+                "p",
+                &workspace(),
+                Permission::Network("example.com".to_string()),
+                None,
+            )
+            .unwrap();
+
+        let reloaded = GrantStore::with_file(path).unwrap();
+        assert_eq!(reloaded.list(None), store.list(None));
+        assert!(reloaded
+            .apply("p", &workspace(), &PluginCapabilities::none())
+            .network
+            .can_access("example.com"));
+    }
+}
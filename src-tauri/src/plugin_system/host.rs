@@ -0,0 +1,312 @@
+// src-tauri/src/plugin_system/host.rs
+//
+// The application the plugin system runs in. Everything plugins show the user
+// (notifications, status bar items, panels, lifecycle and consent events) goes
+// out through a `PluginHost`, and the ops reach the app's shared policy engine,
+// consent broker and grant store through it. `TauriHost` is the desktop app;
+// `RecordingHost` keeps everything in memory, so the manager, sandboxes and
+// workers can run real plugins headless in tests and tools.
+
+use crate::plugin_system::consent::ConsentBroker;
+use crate::plugin_system::grants::GrantStore;
+use crate::plugin_system::policy::PolicyEngine;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const NOTIFICATION_EVENT: &str = "plugin:notification";
+pub const STATUS_BAR_ADD_EVENT: &str = "plugin:status_bar:add";
+pub const STATUS_BAR_REMOVE_EVENT: &str = "plugin:status_bar:remove";
+pub const PANEL_SHOW_EVENT: &str = "plugin:panel:show";
+pub const PANEL_HIDE_EVENT: &str = "plugin:panel:hide";
+
+/// A notification shown on behalf of a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    pub plugin_id: String,
+    pub title: String,
+    pub message: String,
+    /// "info", "warning", "error" or "success"
+    pub level: String,
+}
+
+/// A plugin's status bar item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusBarItem {
+    pub plugin_id: String,
+    pub id: String,
+    pub text: String,
+    pub tooltip: Option<String>,
+    pub priority: i32,
+}
+
+/// A plugin panel with sanitized HTML content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Panel {
+    pub plugin_id: String,
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    /// "sidebar", "bottom" or "modal"
+    pub position: String,
+}
+
+/// The application embedding the plugin system.
+///
+/// Implementors provide `emit` and the shared services; the UI methods are
+/// events with fixed payloads and only need overriding to render them another way.
+pub trait PluginHost: Send + Sync {
+    /// Send an event to the frontend.
+    fn emit(&self, event: &str, payload: serde_json::Value) -> Result<(), String>;
+
+    /// Policy engine shared with the Tauri command path; ops refuse every
+    /// privileged operation without one.
+    fn policy_engine(&self) -> Option<Arc<PolicyEngine>>;
+
+    /// Broker for runtime permission requests.
+    fn consent_broker(&self) -> Option<Arc<ConsentBroker>>;
+
+    /// Store that "allow always" permission answers are saved in.
+    fn grant_store(&self) -> Option<Arc<GrantStore>>;
+
+    fn show_notification(&self, notification: &Notification) -> Result<(), String> {
+        self.emit(NOTIFICATION_EVENT, serde_json::json!(notification))
+    }
+
+    fn add_status_bar_item(&self, item: &StatusBarItem) -> Result<(), String> {
+        self.emit(STATUS_BAR_ADD_EVENT, serde_json::json!(item))
+    }
+
+    fn remove_status_bar_item(&self, plugin_id: &str, id: &str) -> Result<(), String> {
+        self.emit(
+            STATUS_BAR_REMOVE_EVENT,
+            serde_json::json!({"plugin_id": plugin_id, "id": id}),
+        )
+    }
+
+    fn show_panel(&self, panel: &Panel) -> Result<(), String> {
+        self.emit(PANEL_SHOW_EVENT, serde_json::json!(panel))
+    }
+
+    fn hide_panel(&self, plugin_id: &str, id: &str) -> Result<(), String> {
+        self.emit(
+            PANEL_HIDE_EVENT,
+            serde_json::json!({"plugin_id": plugin_id, "id": id}),
+        )
+    }
+}
+
+// ============================================================================
+// TAURI HOST
+// ============================================================================
+
+/// The desktop app: events go to the webview, services are Tauri managed state.
+pub struct TauriHost {
+    app_handle: AppHandle,
+}
+
+impl TauriHost {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl PluginHost for TauriHost {
+    fn emit(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        self.app_handle
+            .emit(event, payload)
+            .map_err(|e| e.to_string())
+    }
+
+    fn policy_engine(&self) -> Option<Arc<PolicyEngine>> {
+        self.app_handle
+            .try_state::<Arc<PolicyEngine>>()
+            .map(|state| state.inner().clone())
+    }
+
+    fn consent_broker(&self) -> Option<Arc<ConsentBroker>> {
+        self.app_handle
+            .try_state::<Arc<ConsentBroker>>()
+            .map(|state| state.inner().clone())
+    }
+
+    fn grant_store(&self) -> Option<Arc<GrantStore>> {
+        self.app_handle
+            .try_state::<Arc<GrantStore>>()
+            .map(|state| state.inner().clone())
+    }
+}
+
+// ============================================================================
+// RECORDING HOST
+// ============================================================================
+
+/// An event sent to a [`RecordingHost`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostEvent {
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+/// In-memory host for running plugins without a GUI; records every event.
+#[derive(Default)]
+pub struct RecordingHost {
+    events: Mutex<Vec<HostEvent>>,
+    policy: Option<Arc<PolicyEngine>>,
+    consent: Option<Arc<ConsentBroker>>,
+    grants: Option<Arc<GrantStore>>,
+}
+
+impl RecordingHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_policy_engine(mut self, policy: Arc<PolicyEngine>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn with_consent_broker(mut self, consent: Arc<ConsentBroker>) -> Self {
+        self.consent = Some(consent);
+        self
+    }
+
+    pub fn with_grant_store(mut self, grants: Arc<GrantStore>) -> Self {
+        self.grants = Some(grants);
+        self
+    }
+
+    /// Every event sent so far, oldest first.
+    pub fn events(&self) -> Vec<HostEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Payloads of the events named `event`, oldest first.
+    pub fn payloads(&self, event: &str) -> Vec<serde_json::Value> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.event == event)
+            .map(|e| e.payload.clone())
+            .collect()
+    }
+
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.decode(NOTIFICATION_EVENT)
+    }
+
+    pub fn status_bar_items(&self) -> Vec<StatusBarItem> {
+        self.decode(STATUS_BAR_ADD_EVENT)
+    }
+
+    pub fn panels(&self) -> Vec<Panel> {
+        self.decode(PANEL_SHOW_EVENT)
+    }
+
+    fn decode<T: serde::de::DeserializeOwned>(&self, event: &str) -> Vec<T> {
+        self.payloads(event)
+            .into_iter()
+            .filter_map(|payload| serde_json::from_value(payload).ok())
+            .collect()
+    }
+}
+
+impl PluginHost for RecordingHost {
+    fn emit(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        self.events.lock().unwrap().push(HostEvent {
+            event: event.to_string(),
+            payload,
+        });
+        Ok(())
+    }
+
+    fn policy_engine(&self) -> Option<Arc<PolicyEngine>> {
+        self.policy.clone()
+    }
+
+    fn consent_broker(&self) -> Option<Arc<ConsentBroker>> {
+        self.consent.clone()
+    }
+
+    fn grant_store(&self) -> Option<Arc<GrantStore>> {
+        self.grants.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_host_records_ui_events() {
+        let host = RecordingHost::new();
+        host.show_notification(&Notification {
+            plugin_id: "p".to_string(),
+            title: "p".to_string(),
+            message: "hello".to_string(),
+            level: "info".to_string(),
+        })
+        .unwrap();
+        host.add_status_bar_item(&StatusBarItem {
+            plugin_id: "p".to_string(),
+            id: "branch".to_string(),
+            text: "main".to_string(),
+            tooltip: None,
+            priority: 0,
+        })
+        .unwrap();
+        host.hide_panel("p", "panel").unwrap();
+        host.emit("plugin:activated", serde_json::json!({"plugin_id": "p"}))
+            .unwrap();
+
+        let events: Vec<String> = host.events().into_iter().map(|e| e.event).collect();
+        assert_eq!(
+            events,
+            [
+                NOTIFICATION_EVENT,
+                STATUS_BAR_ADD_EVENT,
+                PANEL_HIDE_EVENT,
+                "plugin:activated"
+            ]
+        );
+        assert_eq!(host.notifications()[0].message, "hello");
+        assert_eq!(host.status_bar_items()[0].text, "main");
+        assert!(host.panels().is_empty());
+        assert_eq!(
+            host.payloads(PANEL_HIDE_EVENT)[0],
+            serde_json::json!({"plugin_id": "p", "id": "panel"})
+        );
+    }
+
+    #[test]
+    fn test_ui_payloads_match_frontend_events() {
+        // Field names are what src/lib/stores/plugins.ts listens for
+        let item = StatusBarItem {
+            plugin_id: "git".to_string(),
+            id: "git-branch".to_string(),
+            text: "git: main".to_string(),
+            tooltip: Some("Current git branch".to_string()),
+            priority: 0,
+        };
+        assert_eq!(
+            serde_json::json!(item),
+            serde_json::json!({
+                "plugin_id": "git",
+                "id": "git-branch",
+                "text": "git: main",
+                "tooltip": "Current git branch",
+                "priority": 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_recording_host_has_no_services_by_default() {
+        let host = RecordingHost::new();
+        assert!(host.policy_engine().is_none());
+        assert!(host.consent_broker().is_none());
+        assert!(host.grant_store().is_none());
+    }
+}
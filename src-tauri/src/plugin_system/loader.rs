@@ -48,6 +48,9 @@ pub struct PluginManifest {
     /// Raw UI section from TOML
     #[serde(default)]
     pub ui: Option<TomlUi>,
+    /// Requested resource limits; clamped by the user's `LimitsPolicy` at activation
+    #[serde(default)]
+    pub limits: Option<TomlLimits>,
     #[serde(default)]
     pub dependencies: Vec<String>,
     #[serde(default)]
@@ -76,6 +79,8 @@ pub struct TomlPermissions {
     pub network: Option<toml::Value>,
     #[serde(default)]
     pub commands: Option<toml::Value>,
+    #[serde(default)]
+    pub audit: Option<toml::Value>,
 }
 
 /// Raw UI section as it appears in plugin.toml
@@ -91,6 +96,35 @@ pub struct TomlUi {
     pub webview: bool,
 }
 
+/// Raw `[limits]` section as it appears in plugin.toml (unset keys keep the defaults)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TomlLimits {
+    pub memory_mb: Option<u64>,
+    pub cpu_time_ms: Option<u64>,
+    pub ops_per_second: Option<u64>,
+    pub fetch_body_mb: Option<u64>,
+    pub concurrent_commands: Option<u64>,
+}
+
+impl TomlLimits {
+    /// Reject zero values, which would make every call fail.
+    pub fn validate(&self) -> Result<(), String> {
+        for (key, value) in [
+            ("memory_mb", self.memory_mb),
+            ("cpu_time_ms", self.cpu_time_ms),
+            ("ops_per_second", self.ops_per_second),
+            ("fetch_body_mb", self.fetch_body_mb),
+            ("concurrent_commands", self.concurrent_commands),
+        ] {
+            if value == Some(0) {
+                return Err(format!("limits.{} must be greater than zero", key));
+            }
+        }
+        Ok(())
+    }
+}
+
 // Re-export PluginSignature from trust module
 pub use crate::plugin_system::trust::PluginSignature;
 
@@ -160,6 +194,9 @@ impl PluginLoader {
         let manifest_content = std::fs::read_to_string(&manifest_path)?;
         let mut manifest: PluginManifest = toml::from_str(&manifest_content)
             .map_err(|e| LoaderError::InvalidManifest(e.to_string()))?;
+        if let Some(ref limits) = manifest.limits {
+            limits.validate().map_err(LoaderError::InvalidManifest)?;
+        }
 
         // Set trust level based on plugin type
         manifest.trust = if self.first_party_plugins.contains(plugin_id) {
@@ -203,8 +240,30 @@ impl PluginLoader {
                             FilesystemCapability::None
                         }
                     }
+                    toml::Value::Table(t) => {
+                        // Handle { read = [...], write = [...], deny = [...] } style
+                        let patterns = |key: &str| -> std::collections::HashSet<String> {
+                            t.get(key)
+                                .and_then(|v| v.as_array())
+                                .map(|arr| {
+                                    arr.iter()
+                                        .filter_map(|v| v.as_str().map(String::from))
+                                        .collect()
+                                })
+                                .unwrap_or_default()
+                        };
+                        FilesystemCapability::Scoped {
+                            read: patterns("read"),
+                            write: patterns("write"),
+                            deny: patterns("deny"),
+                        }
+                    }
                     _ => FilesystemCapability::None,
                 };
+                capabilities
+                    .filesystem
+                    .validate_patterns()
+                    .map_err(LoaderError::InvalidManifest)?;
             }
 
             // Parse network capability
@@ -236,6 +295,10 @@ impl PluginLoader {
                     }
                     _ => NetworkCapability::None,
                 };
+                capabilities
+                    .network
+                    .validate_rules()
+                    .map_err(LoaderError::InvalidManifest)?;
             }
 
             // Parse command capability
@@ -255,9 +318,21 @@ impl PluginLoader {
                             .get("require_confirmation")
                             .and_then(|v| v.as_bool())
                             .unwrap_or(true);
+                        // Handle [permissions.commands.rules.<command>] tables
+                        let rules = match t.get("rules") {
+                            Some(rules) => rules.clone().try_into().map_err(|e| {
+                                LoaderError::InvalidManifest(format!(
+                                    "invalid command rules: {}",
+                                    e
+                                ))
+                            })?,
+                            None => Default::default(),
+                        };
                         CommandCapability {
                             allowlist,
                             require_confirmation,
+                            rules,
+                            ..Default::default()
                         }
                     }
                     toml::Value::Array(arr) => {
@@ -269,10 +344,26 @@ impl PluginLoader {
                         CommandCapability {
                             allowlist,
                             require_confirmation: true,
+                            ..Default::default()
                         }
                     }
                     _ => CommandCapability::default(),
                 };
+                capabilities
+                    .commands
+                    .validate_rules()
+                    .map_err(LoaderError::InvalidManifest)?;
+            }
+
+            // Parse audit capability: "read" or ["read"]
+            if let Some(ref audit_val) = perms.audit {
+                capabilities.audit = AuditCapability {
+                    read: match audit_val {
+                        toml::Value::String(s) => s == "read",
+                        toml::Value::Array(arr) => arr.iter().any(|v| v.as_str() == Some("read")),
+                        _ => false,
+                    },
+                };
             }
         }
 
@@ -304,7 +395,9 @@ impl PluginLoader {
             );
         }
 
-        let manifest = self.load_manifest(plugin_id)?;
+        let mut manifest = self.load_manifest(plugin_id)?;
+        // Pin allowlisted commands to the programs on PATH at grant time
+        manifest.capabilities.commands.resolve_programs();
 
         // Validate signature format if present.
         if let Some(ref sig) = manifest.signature {
@@ -348,6 +441,8 @@ impl PluginLoader {
             .plugins
             .get_mut(plugin_id)
             .ok_or_else(|| LoaderError::PluginNotFound(plugin_id.to_string()))?;
+        let mut capabilities = capabilities;
+        capabilities.commands.resolve_programs();
         info.manifest.capabilities = capabilities;
         Ok(())
     }
@@ -512,6 +607,51 @@ main = "index.js"
         assert_eq!(manifest.main, "index.js");
     }
 
+    #[test]
+    fn test_manifest_limits_section() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "indexer",
+            r#"
+name = "indexer"
+version = "1.0.0"
+
+[limits]
+memory_mb = 256
+cpu_time_ms = 20000
+concurrent_commands = 2
+"#,
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        let manifest = loader.load_manifest("indexer").unwrap();
+        let limits = manifest.limits.unwrap();
+        assert_eq!(limits.memory_mb, Some(256));
+        assert_eq!(limits.cpu_time_ms, Some(20000));
+        assert_eq!(limits.concurrent_commands, Some(2));
+        assert_eq!(limits.ops_per_second, None);
+    }
+
+    #[test]
+    fn test_manifest_limits_rejects_zero_and_unknown_keys() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "zero",
+            "name = \"zero\"\nversion = \"1.0.0\"\n\n[limits]\ncpu_time_ms = 0\n",
+        );
+        write_plugin(
+            tmp.path(),
+            "typo",
+            "name = \"typo\"\nversion = \"1.0.0\"\n\n[limits]\nmemory = 64\n",
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        assert!(loader.load_manifest("zero").is_err());
+        assert!(loader.load_manifest("typo").is_err());
+    }
+
     #[test]
     fn test_capabilities_from_workspace_read() {
         let tmp = TempDir::new().unwrap();
@@ -560,6 +700,63 @@ filesystem = "WorkspaceReadWrite"
         );
     }
 
+    #[test]
+    fn test_capabilities_from_filesystem_patterns() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "p",
+            r#"
+name = "p"
+version = "1.0.0"
+author = "test"
+
+[permissions.filesystem]
+read = ["src/**/*.rs", "Cargo.toml"]
+deny = ["src/generated"]
+"#,
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        let manifest = loader.load_manifest("p").unwrap();
+        let filesystem = &manifest.capabilities.filesystem;
+        assert_eq!(
+            filesystem,
+            &FilesystemCapability::Scoped {
+                read: ["src/**/*.rs", "Cargo.toml"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                write: Default::default(),
+                deny: ["src/generated".to_string()].into_iter().collect(),
+            }
+        );
+        assert!(filesystem.can_read("/ws/src/lint.rs", "/ws"));
+        assert!(!filesystem.can_read("/ws/src/generated/api.rs", "/ws"));
+        assert!(!filesystem.can_write("/ws/src/lint.rs", "/ws"));
+    }
+
+    #[test]
+    fn test_capabilities_rejects_invalid_filesystem_pattern() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "p",
+            r#"
+name = "p"
+version = "1.0.0"
+author = "test"
+
+[permissions.filesystem]
+read = ["src/{a,b"]
+"#,
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        let err = loader.load_manifest("p").unwrap_err();
+        assert!(err.to_string().contains("invalid filesystem pattern"));
+    }
+
     #[test]
     fn test_capabilities_from_command_allowlist() {
         let tmp = TempDir::new().unwrap();
@@ -611,6 +808,104 @@ domains = ["api.github.com", "registry.npmjs.org"]
         assert!(!manifest.capabilities.network.can_access("evil.com"));
     }
 
+    #[test]
+    fn test_capabilities_from_network_rules() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "p",
+            r#"
+name = "p"
+version = "1.0.0"
+author = "test"
+
+[permissions.network]
+type = "DomainAllowlist"
+domains = ["GET https://*.github.com/repos/*", "http://localhost:8080"]
+"#,
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        let network = loader.load_manifest("p").unwrap().capabilities.network;
+        assert!(network.can_access("api.github.com"));
+        assert!(network.can_access("uploads.github.com"));
+        assert!(network.can_access("localhost"));
+        assert!(!network.can_access("github.com"));
+    }
+
+    #[test]
+    fn test_capabilities_rejects_invalid_network_rule() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "p",
+            r#"
+name = "p"
+version = "1.0.0"
+author = "test"
+
+[permissions.network]
+type = "DomainAllowlist"
+domains = ["ftp://files.example.com"]
+"#,
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        let err = loader.load_manifest("p").unwrap_err();
+        assert!(err.to_string().contains("Invalid network rule"));
+    }
+
+    #[test]
+    fn test_capabilities_from_command_rules() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "p",
+            r#"
+name = "p"
+version = "1.0.0"
+author = "test"
+
+[permissions.commands]
+allowlist = ["git"]
+require_confirmation = false
+
+[permissions.commands.rules.git]
+subcommands = ["status", "diff"]
+forbidden_flags = ["--no-index"]
+allowed_args = ['--porcelain', '[\w./-]+']
+"#,
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        let commands = loader.load_manifest("p").unwrap().capabilities.commands;
+        let rule = &commands.rules["git"];
+        assert_eq!(rule.subcommands, vec!["status", "diff"]);
+        assert_eq!(rule.forbidden_flags, vec!["--no-index"]);
+        assert_eq!(rule.allowed_args.len(), 2);
+    }
+
+    #[test]
+    fn test_capabilities_rejects_invalid_command_rules() {
+        for rules in [
+            "[permissions.commands.rules.npm]\nsubcommands = [\"test\"]",
+            "[permissions.commands.rules.git]\nallowed_args = [\"(\"]",
+            "[permissions.commands.rules.git]\nunknown_key = true",
+        ] {
+            let tmp = TempDir::new().unwrap();
+            write_plugin(
+                tmp.path(),
+                "p",
+                &format!(
+                    "name = \"p\"\nversion = \"1.0.0\"\n\n[permissions.commands]\nallowlist = [\"git\"]\n\n{}\n",
+                    rules
+                ),
+            );
+            let loader = PluginLoader::new(tmp.path().to_path_buf());
+            assert!(loader.load_manifest("p").is_err(), "accepted: {}", rules);
+        }
+    }
+
     #[test]
     fn test_capabilities_from_ui_section() {
         let tmp = TempDir::new().unwrap();
@@ -637,6 +932,27 @@ notifications = true
         assert!(!manifest.capabilities.ui.webview);
     }
 
+    #[test]
+    fn test_capabilities_from_audit_permission() {
+        let tmp = TempDir::new().unwrap();
+        write_plugin(
+            tmp.path(),
+            "monitor",
+            r#"
+name = "monitor"
+version = "1.0.0"
+author = "test"
+
+[permissions]
+audit = ["read"]
+"#,
+        );
+
+        let loader = PluginLoader::new(tmp.path().to_path_buf());
+        let manifest = loader.load_manifest("monitor").unwrap();
+        assert!(manifest.capabilities.audit.read);
+    }
+
     #[test]
     fn test_capabilities_default_when_no_permissions() {
         let tmp = TempDir::new().unwrap();
@@ -656,6 +972,7 @@ author = "test"
         assert!(!manifest.capabilities.network.can_access("anything.com"));
         assert!(!manifest.capabilities.commands.can_execute("anything"));
         assert!(!manifest.capabilities.ui.status_bar);
+        assert!(!manifest.capabilities.audit.read);
     }
 
     #[test]
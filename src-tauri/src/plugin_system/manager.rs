@@ -1,17 +1,25 @@
 // src-tauri/src/plugin_system/manager.rs
 
 use crate::plugin_system::{
+    anomaly::{Anomaly, AnomalyDetector, AnomalyRules, PLUGIN_SUSPENDED_EVENT},
+    audit::{AuditEvent, AUDIT_HOOK},
     capabilities::PluginCapabilities,
+    escalation::{ApprovalStore, CapabilityDiff, CAPABILITY_ESCALATION_EVENT},
+    grants::{self, GrantStore},
+    host::PluginHost,
     loader::{LoaderError, PluginLoader},
+    module_loader::resolve_within_root,
     ops::EditorStateHandle,
-    sandbox::{PluginSandbox, SandboxRegistry},
-    trust::TrustLevel,
+    sandbox::{LimitsPolicy, PluginError, PluginSandbox, ResourceLimits, SandboxRegistry},
+    supervisor::{CrashDecision, CrashRecord, RestartPolicy},
+    trust::{build_plugin_signature_payload, TrustLevel, TrustVerifier},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::Arc;
-use tauri::AppHandle;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 
 // ============================================================================
 // ERROR TYPES
@@ -43,6 +51,15 @@ pub enum ManagerError {
     #[error("Sandbox error: {0}")]
     Sandbox(String),
 
+    #[error("Plugin requests capabilities that need approval: {0}")]
+    CapabilityEscalation(String),
+
+    #[error("Capabilities of {0} changed since they were reviewed; review them again")]
+    StaleApproval(String),
+
+    #[error("Signature rejected: {0}")]
+    Signature(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -89,6 +106,14 @@ pub struct PluginStatus {
     pub auto_approve: bool,
     pub capability_tier: String,
     pub commands: Vec<PluginCommandStatus>,
+    /// Automatic restarts after worker crashes since the plugin last ran stably
+    pub restarts: u32,
+    /// Why the plugin's worker last died, if it has
+    pub last_failure: Option<String>,
+    /// Capabilities plugin.toml asks for beyond what the user approved
+    pub capability_escalation: Option<CapabilityDiff>,
+    /// Anomaly rule that suspended the plugin, until it is activated again
+    pub anomaly: Option<Anomaly>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -100,6 +125,81 @@ pub struct PluginCommandStatus {
     pub category: Option<String>,
 }
 
+// ============================================================================
+// ACTIVATION
+// ============================================================================
+
+/// A plugin sandbox created by [`PluginManager::prepare_activation`] whose
+/// entry point has not run yet.
+pub struct PendingActivation {
+    plugin_id: String,
+    entry_point: String,
+    entry: PathBuf,
+    sandbox: PluginSandbox,
+}
+
+impl PendingActivation {
+    /// Initialize the sandbox, evaluate the entry point (which registers the
+    /// plugin's hooks) and call its activation hook.
+    pub async fn run(self) -> ActivationRun {
+        let PendingActivation {
+            plugin_id,
+            entry_point,
+            entry,
+            sandbox,
+        } = self;
+        let sandbox_error = |e: PluginError| {
+            let reported = ManagerError::Sandbox(e.to_string());
+            (e, reported)
+        };
+        let result = async {
+            sandbox.initialize().await.map_err(sandbox_error)?;
+            sandbox.load_module(entry).await.map_err(|e| {
+                let reported = ManagerError::Sandbox(format!(
+                    "Failed to execute entry point '{}': {}",
+                    entry_point, e
+                ));
+                (e, reported)
+            })?;
+            sandbox
+                .call_hook("activate", vec![serde_json::json!({})])
+                .await
+                .map_err(sandbox_error)?;
+            Ok::<(), (PluginError, ManagerError)>(())
+        }
+        .await;
+        ActivationRun {
+            plugin_id,
+            sandbox,
+            result,
+        }
+    }
+}
+
+/// Outcome of [`PendingActivation::run`], applied by
+/// [`PluginManager::finish_activation`].
+pub struct ActivationRun {
+    plugin_id: String,
+    sandbox: PluginSandbox,
+    result: std::result::Result<(), (PluginError, ManagerError)>,
+}
+
+/// What the supervisor found on its last check, collected by
+/// [`PluginManager::supervision_work`] under a read lock.
+#[derive(Debug, Default)]
+pub struct SupervisionWork {
+    /// Active plugins whose worker died, with the reason
+    crashed: Vec<(String, String)>,
+    /// Crashed plugins whose restart is due
+    due: Vec<String>,
+}
+
+impl SupervisionWork {
+    pub fn is_empty(&self) -> bool {
+        self.crashed.is_empty() && self.due.is_empty()
+    }
+}
+
 // ============================================================================
 // PLUGIN MANAGER
 // ============================================================================
@@ -123,11 +223,41 @@ pub struct PluginManager {
     /// Workspace root for capability validation
     workspace_root: PathBuf,
 
-    /// Tauri AppHandle for UI ops in plugin sandboxes
-    app_handle: AppHandle,
+    /// The app plugins run in: UI events and the services their ops use
+    host: Arc<dyn PluginHost>,
 
     /// Shared editor state for plugin ops
     editor_state: EditorStateHandle,
+
+    /// User-level ceilings for per-plugin resource limits
+    limits_policy: LimitsPolicy,
+
+    /// Restart policy for plugins whose worker died
+    restart_policy: RestartPolicy,
+
+    /// Worker crash history (plugin_id -> record)
+    crashes: HashMap<String, CrashRecord>,
+
+    /// Permissions the user granted on top of plugin.toml
+    grants: Arc<GrantStore>,
+
+    /// When grant expiry was last checked (Unix seconds)
+    grants_checked_at: AtomicU64,
+
+    /// Manifest capabilities the user approved, per plugin
+    approvals: Arc<ApprovalStore>,
+
+    /// Escalations blocking activation until approved (plugin_id -> diff)
+    pending_escalations: HashMap<String, CapabilityDiff>,
+
+    /// Rules checked against the audit stream, watching the active plugins
+    anomaly_detector: Arc<Mutex<AnomalyDetector>>,
+
+    /// Anomalies that suspended plugins (plugin_id -> anomaly)
+    anomalies: HashMap<String, Anomaly>,
+
+    /// Keys trusted to sign `verified` plugins
+    trust_verifier: Arc<RwLock<TrustVerifier>>,
 }
 
 impl PluginManager {
@@ -136,7 +266,7 @@ impl PluginManager {
         plugins_dir: PathBuf,
         sandbox_registry: Arc<SandboxRegistry>,
         workspace_root: PathBuf,
-        app_handle: AppHandle,
+        host: Arc<dyn PluginHost>,
         editor_state: EditorStateHandle,
     ) -> Self {
         PluginManager {
@@ -146,9 +276,111 @@ impl PluginManager {
             event_listeners: HashMap::new(),
             errors: HashMap::new(),
             workspace_root,
-            app_handle,
+            host,
             editor_state,
+            limits_policy: LimitsPolicy::default(),
+            restart_policy: RestartPolicy::default(),
+            crashes: HashMap::new(),
+            grants: Arc::new(GrantStore::new()),
+            grants_checked_at: AtomicU64::new(grants::now_secs()),
+            approvals: Arc::new(ApprovalStore::new()),
+            pending_escalations: HashMap::new(),
+            anomaly_detector: Default::default(),
+            anomalies: HashMap::new(),
+            trust_verifier: Arc::new(RwLock::new(TrustVerifier::new())),
+        }
+    }
+
+    /// Replace the anomaly rules checked by [`PluginManager::inspect_audit_event`].
+    pub fn set_anomaly_rules(&mut self, rules: AnomalyRules) {
+        self.anomaly_detector
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .set_rules(rules);
+    }
+
+    /// The detector checking the active plugins' audit events; check events
+    /// with it directly and call [`PluginManager::suspend`] on a match, so the
+    /// manager is only locked when a plugin has to be suspended.
+    pub fn anomaly_detector(&self) -> Arc<Mutex<AnomalyDetector>> {
+        self.anomaly_detector.clone()
+    }
+
+    /// Replace the grant store (in-memory by default).
+    pub fn set_grant_store(&mut self, grants: Arc<GrantStore>) {
+        self.grants = grants;
+    }
+
+    /// Permissions granted to plugins beyond their manifests.
+    pub fn grant_store(&self) -> &Arc<GrantStore> {
+        &self.grants
+    }
+
+    /// Replace the approval store (in-memory by default).
+    pub fn set_approval_store(&mut self, approvals: Arc<ApprovalStore>) {
+        self.approvals = approvals;
+    }
+
+    /// Replace the trusted signing keys (none by default, so `verified`
+    /// plugins do not activate).
+    pub fn set_trust_verifier(&mut self, verifier: Arc<RwLock<TrustVerifier>>) {
+        self.trust_verifier = verifier;
+    }
+
+    /// Capability escalation blocking the plugin's activation, if any.
+    pub fn capability_escalation(&self, plugin_id: &str) -> Option<&CapabilityDiff> {
+        self.pending_escalations.get(plugin_id)
+    }
+
+    /// Approve the capabilities in the plugin's current manifest, unblocking
+    /// its next activation.
+    ///
+    /// `reviewed` is the diff the user was shown. If the manifest changed since
+    /// (a hot reload while the prompt was open), nothing is approved: the new
+    /// diff replaces the pending one and `ManagerError::StaleApproval` is returned.
+    pub fn approve_capabilities(
+        &mut self,
+        plugin_id: &str,
+        reviewed: &CapabilityDiff,
+    ) -> Result<()> {
+        let plugin_info = self
+            .loader
+            .get(plugin_id)
+            .ok_or_else(|| ManagerError::PluginNotLoaded(plugin_id.to_string()))?;
+        if let Err(current) = self.approvals.review(plugin_id, &plugin_info.manifest) {
+            if current != *reviewed {
+                let _ = self
+                    .host
+                    .emit(CAPABILITY_ESCALATION_EVENT, serde_json::json!(current));
+                self.pending_escalations
+                    .insert(plugin_id.to_string(), current);
+                return Err(ManagerError::StaleApproval(plugin_id.to_string()));
+            }
+            self.approvals
+                .approve(plugin_id, &plugin_info.manifest)
+                .map_err(ManagerError::Internal)?;
         }
+        if self.pending_escalations.remove(plugin_id).is_some() {
+            self.errors.remove(plugin_id);
+        }
+        Ok(())
+    }
+
+    /// Replace the restart policy used by [`PluginManager::supervise`].
+    pub fn set_restart_policy(&mut self, policy: RestartPolicy) {
+        self.restart_policy = policy;
+    }
+
+    /// Replace the user-level resource limit policy (applies on next activation).
+    pub fn set_limits_policy(&mut self, policy: LimitsPolicy) {
+        self.limits_policy = policy;
+    }
+
+    /// Resource limits the plugin runs with: its `[limits]` table clamped by the policy.
+    pub fn effective_limits(&self, plugin_id: &str) -> Option<ResourceLimits> {
+        self.loader
+            .get(plugin_id)
+            .map(|info| self.limits_policy.effective_limits(&info.manifest))
     }
 
     /// Discover all available plugins
@@ -157,7 +389,17 @@ impl PluginManager {
     }
 
     /// Load a plugin
+    ///
+    /// A plugin with a sandbox is not reloaded in place: the next capability
+    /// refresh would hand the running sandbox the new manifest's capabilities
+    /// without their escalation being reviewed. Use [`reload`](Self::reload).
     pub fn load(&mut self, plugin_id: &str) -> Result<()> {
+        if matches!(
+            self.active_plugins.get(plugin_id),
+            Some(PluginState::Active | PluginState::Activating | PluginState::Deactivating)
+        ) {
+            return Err(ManagerError::PluginAlreadyActive(plugin_id.to_string()));
+        }
         self.loader
             .load(plugin_id)
             .map_err(|e| ManagerError::Internal(e.to_string()))?;
@@ -171,15 +413,25 @@ impl PluginManager {
 
     /// Activate a plugin
     pub async fn activate(&mut self, plugin_id: &str) -> Result<()> {
+        let pending = self.prepare_activation(plugin_id)?;
+        let run = pending.run().await;
+        self.finish_activation(run).await
+    }
+
+    /// Check that a plugin may activate and create its sandbox, leaving it in
+    /// `PluginState::Activating`. The entry point runs in
+    /// [`PendingActivation::run`], which needs no access to the manager, so the
+    /// supervisor can restart plugins without holding the manager lock.
+    pub fn prepare_activation(&mut self, plugin_id: &str) -> Result<PendingActivation> {
         // Check if plugin is loaded
         let plugin_info = self
             .loader
             .get(plugin_id)
             .ok_or_else(|| ManagerError::PluginNotLoaded(plugin_id.to_string()))?;
 
-        // Check if already active
+        // Check if already active (or being activated)
         if let Some(state) = self.active_plugins.get(plugin_id) {
-            if *state == PluginState::Active {
+            if matches!(state, PluginState::Active | PluginState::Activating) {
                 return Err(ManagerError::PluginAlreadyActive(plugin_id.to_string()));
             }
         }
@@ -199,96 +451,154 @@ impl PluginManager {
             );
         }
 
+        // Every activation, restarts and reloads included, checks the files
+        // on disk against the signature
+        if plugin_info.manifest.trust.requires_signature() {
+            self.verify_signature(plugin_id)?;
+        }
+
         // Check dependencies (uses loader.verify_dependencies first)
         self.check_dependencies(plugin_id)?;
 
-        // Set state to activating
-        self.active_plugins
-            .insert(plugin_id.to_string(), PluginState::Activating);
+        // A manifest asking for more than the user approved (new version, hot
+        // reload) stays inactive until the user approves the diff
+        if !plugin_info.manifest.trust.auto_grant_permissions() {
+            if let Err(diff) = self.approvals.review(plugin_id, &plugin_info.manifest) {
+                println!(
+                    "[plugin] {} {} requests new capabilities; approval required",
+                    plugin_id, diff.version
+                );
+                let _ = self
+                    .host
+                    .emit(CAPABILITY_ESCALATION_EVENT, serde_json::json!(diff));
+                let error = ManagerError::CapabilityEscalation(plugin_id.to_string());
+                self.errors.insert(plugin_id.to_string(), error.to_string());
+                self.pending_escalations.insert(plugin_id.to_string(), diff);
+                return Err(error);
+            }
+        }
+        self.pending_escalations.remove(plugin_id);
+
+        // Resolve the entry point inside the canonical plugin root; the module
+        // loader applies the same confinement to every import.
+        let entry_point = plugin_info.manifest.main.clone();
+        let plugin_root = plugin_info.path.canonicalize().map_err(|e| {
+            ManagerError::Internal(format!(
+                "Failed to canonicalize plugin root '{}': {}",
+                plugin_info.path.display(),
+                e
+            ))
+        })?;
+        let entry =
+            resolve_within_root(&plugin_root, &plugin_root.join(&entry_point)).map_err(|e| {
+                ManagerError::Internal(format!(
+                    "Invalid plugin entry point '{}': {}",
+                    entry_point, e
+                ))
+            })?;
 
-        // Create sandbox with workspace root and app handle for ops
+        // Create sandbox with workspace root and app handle for ops; it runs
+        // with the manifest capabilities plus the user's active grants
+        let mut manifest = plugin_info.manifest.clone();
+        manifest.capabilities =
+            self.grants
+                .apply(plugin_id, &self.workspace_root, &manifest.capabilities);
         let sandbox = PluginSandbox::new(
-            plugin_info.manifest.clone(),
+            manifest,
+            self.limits_policy.effective_limits(&plugin_info.manifest),
+            plugin_root,
             self.workspace_root.clone(),
-            self.app_handle.clone(),
+            self.host.clone(),
             self.editor_state.clone(),
         )
         .map_err(|e| ManagerError::Sandbox(e.to_string()))?;
 
-        // Register sandbox
-        self.sandbox_registry
-            .register_sandbox(plugin_id.to_string(), sandbox)
-            .await;
-
-        // Get sandbox, load entry point, and activate
-        if let Some(sandbox) = self.sandbox_registry.get_sandbox(plugin_id).await {
-            let sandbox = sandbox.read().await;
-
-            if let Err(e) = sandbox.initialize().await {
-                self.errors.insert(plugin_id.to_string(), e.to_string());
-                self.active_plugins
-                    .insert(plugin_id.to_string(), PluginState::Error);
-                return Err(ManagerError::Sandbox(e.to_string()));
-            }
+        // Set state to activating
+        self.active_plugins
+            .insert(plugin_id.to_string(), PluginState::Activating);
 
-            // Load and execute the plugin's entry point script
-            // This registers hooks (onActivate, onDeactivate, registerHook)
-            let entry_point = &plugin_info.manifest.main;
-            let plugin_root = plugin_info.path.canonicalize().map_err(|e| {
-                ManagerError::Internal(format!(
-                    "Failed to canonicalize plugin root '{}': {}",
-                    plugin_info.path.display(),
-                    e
-                ))
-            })?;
-            let entry_path = plugin_info.path.join(entry_point);
-            let canonical_entry = entry_path.canonicalize().map_err(|e| {
-                ManagerError::Internal(format!(
-                    "Failed to read plugin entry point '{}': {}",
-                    entry_path.display(),
-                    e
-                ))
-            })?;
-            if !canonical_entry.starts_with(&plugin_root) {
-                return Err(ManagerError::Internal(format!(
-                    "Plugin entry point escapes plugin directory: '{}'",
-                    entry_point
-                )));
-            }
-            let script = std::fs::read_to_string(&canonical_entry).map_err(|e| {
-                ManagerError::Internal(format!(
-                    "Failed to read plugin entry point '{}': {}",
-                    canonical_entry.display(),
-                    e
-                ))
-            })?;
+        Ok(PendingActivation {
+            plugin_id: plugin_id.to_string(),
+            entry_point,
+            entry,
+            sandbox,
+        })
+    }
 
-            if let Err(e) = sandbox.execute(script).await {
-                self.errors.insert(plugin_id.to_string(), e.to_string());
-                self.active_plugins
-                    .insert(plugin_id.to_string(), PluginState::Error);
-                return Err(ManagerError::Sandbox(format!(
-                    "Failed to execute entry point '{}': {}",
-                    entry_point, e
-                )));
-            }
+    /// Check a `verified` plugin's signature against its files and the
+    /// trusted keys.
+    fn verify_signature(&self, plugin_id: &str) -> Result<()> {
+        let plugin_info = self
+            .loader
+            .get(plugin_id)
+            .ok_or_else(|| ManagerError::PluginNotLoaded(plugin_id.to_string()))?;
+        let Some(signature) = &plugin_info.manifest.signature else {
+            return Err(ManagerError::Signature(format!(
+                "Plugin '{}' requires a signature but none was provided",
+                plugin_id
+            )));
+        };
+        signature
+            .check_payload_version()
+            .map_err(|e| ManagerError::Signature(format!("Plugin '{}': {}", plugin_id, e)))?;
+        let payload = build_plugin_signature_payload(
+            plugin_id,
+            &plugin_info.path,
+            &plugin_info.manifest,
+            signature.timestamp,
+        )
+        .map_err(ManagerError::Signature)?;
+        let verified = self
+            .trust_verifier
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .verify_signature(signature, &payload);
+        if !verified {
+            return Err(ManagerError::Signature(format!(
+                "Plugin '{}' requires a trusted signature but verification failed",
+                plugin_id
+            )));
+        }
+        Ok(())
+    }
 
-            // Call activation hook (registered by the entry point script above)
-            if let Err(e) = sandbox
-                .call_hook("activate", vec![serde_json::json!({})])
-                .await
-            {
-                self.errors.insert(plugin_id.to_string(), e.to_string());
-                self.active_plugins
-                    .insert(plugin_id.to_string(), PluginState::Error);
-                return Err(ManagerError::Sandbox(e.to_string()));
-            }
+    /// Register the sandbox of a successful activation run, or record why it
+    /// failed. An activation overtaken by an unload is discarded.
+    pub async fn finish_activation(&mut self, run: ActivationRun) -> Result<()> {
+        let ActivationRun {
+            plugin_id,
+            sandbox,
+            result,
+        } = run;
+        if self.active_plugins.get(&plugin_id) != Some(&PluginState::Activating) {
+            return Err(ManagerError::Internal(format!(
+                "Activation of {} was cancelled",
+                plugin_id
+            )));
         }
+        if let Err((error, reported)) = result {
+            self.mark_failed(&plugin_id, &error);
+            return Err(reported);
+        }
+
+        // Register sandbox
+        self.sandbox_registry
+            .register_sandbox(plugin_id.clone(), sandbox)
+            .await;
 
         // Set state to active
         self.active_plugins
-            .insert(plugin_id.to_string(), PluginState::Active);
-        self.errors.remove(plugin_id);
+            .insert(plugin_id.clone(), PluginState::Active);
+        self.errors.remove(&plugin_id);
+        self.anomalies.remove(&plugin_id);
+        let network = self
+            .get_plugin_capabilities(&plugin_id)
+            .map(|caps| caps.network)
+            .unwrap_or_default();
+        self.anomaly_detector
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .watch(&plugin_id, network);
 
         // Emit lifecycle event
         self.emit_event(
@@ -320,6 +630,9 @@ impl PluginManager {
         for event_name in event_names {
             self.unregister_event_listener(plugin_id, &event_name);
         }
+        if let Some(policy) = self.host.policy_engine() {
+            policy.unsubscribe_audit_hook(plugin_id);
+        }
 
         // Call deactivation hook
         if let Some(sandbox) = self.sandbox_registry.get_sandbox(plugin_id).await {
@@ -336,6 +649,11 @@ impl PluginManager {
         self.active_plugins
             .insert(plugin_id.to_string(), PluginState::Loaded);
         self.errors.remove(plugin_id);
+        self.crashes.remove(plugin_id);
+        self.anomaly_detector
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .forget(plugin_id);
 
         // Emit lifecycle event
         self.emit_event(
@@ -368,7 +686,7 @@ impl PluginManager {
         let plugin_info = self.loader.get(plugin_id)?;
         let state = self.active_plugins.get(plugin_id).copied()?;
 
-        let caps = &plugin_info.manifest.capabilities;
+        let caps = &self.get_plugin_capabilities(plugin_id)?;
         let capability_tier = if caps.is_subset_of(&PluginCapabilities::none()) {
             "sandboxed"
         } else if caps.is_subset_of(&PluginCapabilities::workspace_read()) {
@@ -384,19 +702,24 @@ impl PluginManager {
             .duration_since(std::time::UNIX_EPOCH)
             .ok()
             .map(|d| d.as_millis() as u64);
+        let crash = self.crashes.get(plugin_id);
 
         Some(PluginStatus {
             id: plugin_id.to_string(),
             name: plugin_info.manifest.name.clone(),
             version: plugin_info.manifest.version.clone(),
             state,
-            capabilities: plugin_info.manifest.capabilities.clone(),
+            capabilities: caps.clone(),
             error: self.errors.get(plugin_id).cloned(),
             trust: plugin_info.manifest.trust.clone(),
             loaded_at,
             auto_approve: plugin_info.manifest.trust.auto_grant_permissions(),
             capability_tier: capability_tier.to_string(),
             commands: parse_manifest_commands(&plugin_info.manifest.commands, plugin_id),
+            restarts: crash.map_or(0, |c| c.restarts),
+            last_failure: crash.and_then(|c| c.last_failure.clone()),
+            capability_escalation: self.pending_escalations.get(plugin_id).cloned(),
+            anomaly: self.anomalies.get(plugin_id).cloned(),
         })
     }
 
@@ -409,6 +732,197 @@ impl PluginManager {
             .collect()
     }
 
+    /// Move a plugin to `PluginState::Error` after its sandbox failed, recording
+    /// the failure so it is reported through `get_status`.
+    pub fn mark_failed(&mut self, plugin_id: &str, error: &PluginError) {
+        self.errors.insert(plugin_id.to_string(), error.to_string());
+        self.active_plugins
+            .insert(plugin_id.to_string(), PluginState::Error);
+    }
+
+    /// Restart plugins whose worker died, with exponential backoff.
+    ///
+    /// Called every [`SUPERVISE_INTERVAL`](crate::plugin_system::supervisor::SUPERVISE_INTERVAL).
+    /// A dead worker moves its plugin to `PluginState::Error` until the restart is
+    /// due; once the restart budget is spent the plugin stays in `Error` and
+    /// `plugin:crash-looping` is emitted to the frontend.
+    ///
+    /// This runs every step under `&mut self`; the app's supervisor task calls
+    /// [`supervision_work`](Self::supervision_work),
+    /// [`begin_supervision`](Self::begin_supervision) and
+    /// [`finish_restart`](Self::finish_restart) itself so restarted plugins
+    /// activate without the manager lock held.
+    pub async fn supervise(&mut self) {
+        let now = Instant::now();
+        let work = self.supervision_work(now).await;
+        for pending in self.begin_supervision(work, now).await {
+            let run = pending.run().await;
+            self.finish_restart(run).await;
+        }
+    }
+
+    /// Active plugins whose worker died and crashed plugins whose restart is due.
+    pub async fn supervision_work(&self, now: Instant) -> SupervisionWork {
+        let mut work = SupervisionWork::default();
+        for (plugin_id, state) in &self.active_plugins {
+            if *state != PluginState::Active {
+                continue;
+            }
+            let Some(sandbox) = self.sandbox_registry.get_sandbox(plugin_id).await else {
+                continue;
+            };
+            let failure = sandbox.read().await.worker_failure();
+            if let Some(reason) = failure {
+                work.crashed.push((plugin_id.clone(), reason));
+            }
+        }
+        work.due = self.restarts_due(now);
+        work
+    }
+
+    /// Tear down the crashed plugins in `work` and create sandboxes for the
+    /// restarts that are due. The returned activations are run by the caller and
+    /// passed back to [`finish_restart`](Self::finish_restart).
+    pub async fn begin_supervision(
+        &mut self,
+        work: SupervisionWork,
+        now: Instant,
+    ) -> Vec<PendingActivation> {
+        for (plugin_id, reason) in work.crashed {
+            // The plugin may have been deactivated since the check
+            if self.is_active(&plugin_id) {
+                self.handle_worker_crash(&plugin_id, reason, now).await;
+            }
+        }
+
+        let mut restarts = Vec::new();
+        for plugin_id in self.restarts_due(now) {
+            let Some(record) = self.crashes.get_mut(&plugin_id) else {
+                continue;
+            };
+            record.begin_restart();
+            match self.prepare_activation(&plugin_id) {
+                Ok(pending) => restarts.push(pending),
+                Err(e) => {
+                    self.handle_worker_crash(&plugin_id, e.to_string(), Instant::now())
+                        .await;
+                }
+            }
+        }
+        restarts
+    }
+
+    /// Apply a restart started by [`begin_supervision`](Self::begin_supervision).
+    pub async fn finish_restart(&mut self, run: ActivationRun) {
+        let plugin_id = run.plugin_id.clone();
+        match self.finish_activation(run).await {
+            Ok(()) => {
+                let restarts = self
+                    .crashes
+                    .get(&plugin_id)
+                    .map(|record| record.restarts)
+                    .unwrap_or_default();
+                println!(
+                    "[plugin] Restarted {} after worker crash (restart {})",
+                    plugin_id, restarts
+                );
+                let _ = self.host.emit(
+                    "plugin:restarted",
+                    serde_json::json!({"plugin_id": plugin_id, "restarts": restarts}),
+                );
+            }
+            // Deactivated or unloaded while the restart ran
+            Err(_) if !self.crashes.contains_key(&plugin_id) => {}
+            Err(e) => {
+                self.handle_worker_crash(&plugin_id, e.to_string(), Instant::now())
+                    .await;
+            }
+        }
+    }
+
+    fn restarts_due(&self, now: Instant) -> Vec<String> {
+        self.crashes
+            .iter()
+            .filter(|(_, record)| record.restart_due(now))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Tear down a plugin whose worker died and schedule a restart or give up.
+    async fn handle_worker_crash(&mut self, plugin_id: &str, reason: String, now: Instant) {
+        eprintln!("[plugin] {} worker died: {}", plugin_id, reason);
+        self.sandbox_registry.remove_sandbox(plugin_id).await;
+        self.anomaly_detector
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .forget(plugin_id);
+        self.active_plugins
+            .insert(plugin_id.to_string(), PluginState::Error);
+
+        let record = self.crashes.entry(plugin_id.to_string()).or_default();
+        match record.record_crash(&self.restart_policy, reason.clone(), now) {
+            CrashDecision::Restart { after } => {
+                self.errors.insert(
+                    plugin_id.to_string(),
+                    format!("Worker crashed, restarting in {:?}: {}", after, reason),
+                );
+            }
+            CrashDecision::GiveUp => {
+                let message = format!(
+                    "Crash looping: worker died {} times; last failure: {}",
+                    record.restarts + 1,
+                    reason
+                );
+                self.errors.insert(plugin_id.to_string(), message.clone());
+                let _ = self.host.emit(
+                    "plugin:crash-looping",
+                    serde_json::json!({"plugin_id": plugin_id, "reason": message}),
+                );
+            }
+        }
+    }
+
+    /// Check an audit event against the anomaly rules and suspend its plugin
+    /// on a match. Events from plugins that are not active are ignored.
+    pub async fn inspect_audit_event(&mut self, event: &AuditEvent) {
+        let anomaly = self
+            .anomaly_detector
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .inspect(event);
+        if let Some(anomaly) = anomaly {
+            self.suspend(anomaly).await;
+        }
+    }
+
+    /// Deactivate a plugin that matched an anomaly rule, recording the rule in
+    /// its status until the user activates it again. A plugin that stopped in
+    /// the meantime is left alone.
+    pub async fn suspend(&mut self, anomaly: Anomaly) {
+        let plugin_id = anomaly.plugin_id.clone();
+        if !self.is_active(&plugin_id) {
+            return;
+        }
+        eprintln!(
+            "[plugin] Suspending {}: anomaly rule '{}' matched: {}",
+            plugin_id, anomaly.rule, anomaly.detail
+        );
+        if let Err(e) = self.deactivate(&plugin_id).await {
+            eprintln!("[plugin] Failed to suspend {}: {}", plugin_id, e);
+        }
+        self.errors.insert(
+            plugin_id.clone(),
+            format!(
+                "Suspended by anomaly rule '{}': {}",
+                anomaly.rule, anomaly.detail
+            ),
+        );
+        let _ = self
+            .host
+            .emit(PLUGIN_SUSPENDED_EVENT, serde_json::json!(anomaly));
+        self.anomalies.insert(plugin_id, anomaly);
+    }
+
     /// Check if plugin is active
     pub fn is_active(&self, plugin_id: &str) -> bool {
         matches!(
@@ -417,11 +931,50 @@ impl PluginManager {
         )
     }
 
-    /// Get plugin capabilities
+    /// Get plugin capabilities: the manifest's plus the user's active grants
     pub fn get_plugin_capabilities(&self, plugin_id: &str) -> Option<PluginCapabilities> {
-        self.loader
-            .get(plugin_id)
-            .map(|info| info.manifest.capabilities.clone())
+        self.loader.get(plugin_id).map(|info| {
+            self.grants
+                .apply(plugin_id, &self.workspace_root, &info.manifest.capabilities)
+        })
+    }
+
+    /// Push the current capabilities to the plugin's running sandbox, if any,
+    /// so grants and revocations apply without a restart.
+    pub async fn refresh_capabilities(&self, plugin_id: &str) -> Result<()> {
+        let Some(capabilities) = self.get_plugin_capabilities(plugin_id) else {
+            return Err(ManagerError::PluginNotLoaded(plugin_id.to_string()));
+        };
+        if let Some(sandbox) = self.sandbox_registry.get_sandbox(plugin_id).await {
+            if self.is_active(plugin_id) {
+                self.anomaly_detector
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .watch(plugin_id, capabilities.network.clone());
+            }
+            sandbox
+                .write()
+                .await
+                .set_capabilities(capabilities)
+                .await
+                .map_err(|e| ManagerError::Sandbox(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Refresh plugins whose grants expired since the last check.
+    pub async fn refresh_expired_grants(&self) {
+        let now = grants::now_secs();
+        let since = self.grants_checked_at.swap(now, Ordering::SeqCst);
+        let expired = self.grants.expired_between(since, now);
+        for plugin_id in expired {
+            if let Err(e) = self.refresh_capabilities(&plugin_id).await {
+                eprintln!(
+                    "[plugin] Failed to apply expired grants for {}: {}",
+                    plugin_id, e
+                );
+            }
+        }
     }
 
     /// Set plugin capabilities after dynamic permission grants.
@@ -470,6 +1023,36 @@ impl PluginManager {
         }
     }
 
+    /// Call the `audit` hook of every active plugin that subscribed to the audit
+    /// stream. Events from subscribers are not passed on, so monitors never see
+    /// the operations of their own (or each other's) hooks and cannot feed each
+    /// other an endless stream of events.
+    pub async fn dispatch_audit_event(&self, event: &AuditEvent) {
+        let Some(policy) = self.host.policy_engine() else {
+            return;
+        };
+        let subscribers = policy.audit_hook_subscribers();
+        if subscribers.contains(&event.plugin_id) {
+            return;
+        }
+        for plugin_id in subscribers {
+            // The capability may have been revoked since subscribing
+            if !self.is_active(&plugin_id)
+                || !self
+                    .get_plugin_capabilities(&plugin_id)
+                    .is_some_and(|capabilities| capabilities.audit.read)
+            {
+                continue;
+            }
+            if let Some(sandbox) = self.sandbox_registry.get_sandbox(&plugin_id).await {
+                let sandbox = sandbox.read().await;
+                let _ = sandbox
+                    .call_hook(AUDIT_HOOK, vec![serde_json::json!(event)])
+                    .await;
+            }
+        }
+    }
+
     /// Check plugin dependencies
     fn check_dependencies(&self, plugin_id: &str) -> Result<()> {
         // First check that all dependencies are loaded (via loader)
@@ -535,6 +1118,8 @@ impl PluginManager {
         // Remove from active plugins map
         self.active_plugins.remove(plugin_id);
         self.errors.remove(plugin_id);
+        self.crashes.remove(plugin_id);
+        self.anomalies.remove(plugin_id);
 
         println!("[plugin] Unloaded plugin: {}", plugin_id);
         Ok(())
@@ -601,10 +1186,664 @@ fn parse_manifest_commands(
 mod tests {
     use super::*;
 
-    // Note: Manager tests that require activate/deactivate need an AppHandle,
-    // which is only available in a running Tauri app. Loader-level and
-    // capabilities tests are in their respective modules.
-    // These tests validate the non-Tauri portions (state, events).
+    use crate::plugin_system::audit::{AuditLogger, DEFAULT_MAX_EVENTS};
+    use crate::plugin_system::consent::{
+        ConsentBroker, ConsentDecision, CONSENT_REQUEST_EVENT, CONSENT_TIMEOUT,
+    };
+    use crate::plugin_system::grants::{GrantScope, PERMISSION_REQUEST_EVENT};
+    use crate::plugin_system::host::RecordingHost;
+    use crate::plugin_system::policy::PolicyEngine;
+    use std::time::Duration;
+
+    /// A manager running real plugins headless: a scratch plugins directory and
+    /// workspace, with a `RecordingHost` in place of the Tauri app.
+    struct Headless {
+        dir: tempfile::TempDir,
+        manager: PluginManager,
+        host: Arc<RecordingHost>,
+        audit: Arc<AuditLogger>,
+        consent: Arc<ConsentBroker>,
+    }
+
+    /// Write plugin.toml: the standard header, then `tables`.
+    fn write_manifest(plugin_dir: &std::path::Path, id: &str, version: &str, tables: &str) {
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            format!(
+                "name = \"{}\"\nversion = \"{}\"\nauthor = \"test\"\n\n{}",
+                id, version, tables
+            ),
+        )
+        .unwrap();
+    }
+
+    /// `plugins` holds (id, plugin.toml tables after the header, main.js).
+    fn headless(plugins: &[(&str, &str, &str)]) -> Headless {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::write(workspace.join("src/main.rs"), "fn main() {}\n").unwrap();
+        for (id, tables, script) in plugins {
+            let plugin_dir = dir.path().join("plugins").join(id);
+            std::fs::create_dir_all(&plugin_dir).unwrap();
+            write_manifest(&plugin_dir, id, "1.0.0", tables);
+            std::fs::write(plugin_dir.join("main.js"), script).unwrap();
+        }
+
+        let audit = Arc::new(AuditLogger::new(DEFAULT_MAX_EVENTS));
+        let consent = Arc::new(ConsentBroker::new(CONSENT_TIMEOUT));
+        let grants = Arc::new(GrantStore::new());
+        let host = Arc::new(
+            RecordingHost::new()
+                .with_policy_engine(Arc::new(PolicyEngine::new(audit.clone(), consent.clone())))
+                .with_consent_broker(consent.clone())
+                .with_grant_store(grants.clone()),
+        );
+        let mut manager = PluginManager::new(
+            dir.path().join("plugins"),
+            Arc::new(SandboxRegistry::new()),
+            workspace.canonicalize().unwrap(),
+            host.clone(),
+            Default::default(),
+        );
+        manager.set_grant_store(grants);
+        for (id, _, _) in plugins {
+            manager.load(id).unwrap();
+        }
+        Headless {
+            dir,
+            manager,
+            host,
+            audit,
+            consent,
+        }
+    }
+
+    /// Answer the first `event` request `delay` after it was emitted.
+    fn answer_after(
+        h: &Headless,
+        event: &'static str,
+        delay: Duration,
+        decision: ConsentDecision,
+    ) -> std::thread::JoinHandle<()> {
+        let host = h.host.clone();
+        let consent = h.consent.clone();
+        std::thread::spawn(move || {
+            let request = loop {
+                if let Some(request) = host.payloads(event).pop() {
+                    break request;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            };
+            std::thread::sleep(delay);
+            assert!(consent.respond(request["id"].as_str().unwrap(), decision));
+        })
+    }
+
+    const GREETER: &str = r#"
+onActivate(function () {
+  skretchpad.ui.showNotification('hello from greeter', 'info');
+  skretchpad.ui.setStatusBarItem('greeter', 'Hello', 'Greeter plugin');
+});
+"#;
+
+    #[tokio::test]
+    async fn test_headless_plugin_runs_end_to_end() {
+        let mut h = headless(&[(
+            "greeter",
+            "[ui]\nnotifications = true\nstatus_bar = true\n",
+            GREETER,
+        )]);
+
+        h.manager.activate("greeter").await.unwrap();
+        assert!(h.manager.is_active("greeter"));
+
+        let notifications = h.host.notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].plugin_id, "greeter");
+        assert_eq!(notifications[0].message, "hello from greeter");
+        let items = h.host.status_bar_items();
+        assert_eq!(items[0].id, "greeter");
+        assert_eq!(items[0].tooltip.as_deref(), Some("Greeter plugin"));
+
+        let events = h.audit.get_events_for_plugin("greeter").await;
+        assert!(events
+            .iter()
+            .any(|e| e.operation == "ui.notification" && e.success));
+
+        h.manager.deactivate("greeter").await.unwrap();
+        assert_eq!(
+            h.manager.get_status("greeter").unwrap().state,
+            PluginState::Loaded
+        );
+    }
+
+    #[tokio::test]
+    async fn test_headless_plugin_is_held_to_its_capabilities() {
+        let script = r#"
+onActivate(function () {
+  try {
+    skretchpad.fs.readFile('src/main.rs');
+    skretchpad.ui.showNotification('read succeeded', 'info');
+  } catch (e) {
+    skretchpad.ui.showNotification(String(e), 'error');
+  }
+});
+"#;
+        let mut h = headless(&[("snoop", "[ui]\nnotifications = true\n", script)]);
+
+        h.manager.activate("snoop").await.unwrap();
+
+        let notifications = h.host.notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].level, "error");
+        assert!(notifications[0].message.contains("Permission denied"));
+        let events = h.audit.get_events_for_plugin("snoop").await;
+        let read = events.iter().find(|e| e.operation == "fs.read").unwrap();
+        assert!(!read.success);
+    }
+
+    #[tokio::test]
+    async fn test_ops_are_audited_through_the_policy_engine() {
+        let script = r#"
+onActivate(function () {
+  skretchpad.fs.readFile('src/../src/main.rs');
+  try {
+    skretchpad.fs.readFile('../outside.txt');
+  } catch (e) {}
+});
+"#;
+        let mut h = headless(&[(
+            "reader",
+            "[permissions]\nfilesystem = \"WorkspaceRead\"\n",
+            script,
+        )]);
+        std::fs::write(h.dir.path().join("outside.txt"), "secret\n").unwrap();
+        let mut live = h.host.policy_engine().unwrap().subscribe();
+
+        h.manager.activate("reader").await.unwrap();
+
+        let events = h.audit.get_events_for_plugin("reader").await;
+        let reads: Vec<_> = events.iter().filter(|e| e.operation == "fs.read").collect();
+        assert_eq!(reads.len(), 2);
+        // An allowed read is logged under the path that was actually opened
+        let canonical = h.manager.workspace_root.join("src").join("main.rs");
+        assert!(reads[0].success);
+        assert_eq!(reads[0].resource, canonical.display().to_string());
+        // The denied read still emits an event, with the reason
+        assert!(!reads[1].success);
+        assert_eq!(reads[1].resource, "../outside.txt");
+        assert!(reads[1].error.is_some());
+        assert_eq!(live.try_recv().unwrap().resource, reads[0].resource);
+        assert!(!live.try_recv().unwrap().success);
+    }
+
+    #[tokio::test]
+    async fn test_audit_hook_receives_other_plugins_events() {
+        let monitor = r#"
+onActivate(function () {
+  skretchpad.audit.subscribe(function (event) {
+    skretchpad.ui.showNotification(event.plugin_id + ' ' + event.operation, 'info');
+  });
+});
+"#;
+        let mut h = headless(&[
+            (
+                "monitor",
+                "[permissions]\naudit = [\"read\"]\n\n[ui]\nnotifications = true\n",
+                monitor,
+            ),
+            ("greeter", "[ui]\nnotifications = true\n", GREETER),
+        ]);
+
+        // Reading every plugin's audit events is not trusted on first use
+        assert!(matches!(
+            h.manager.activate("monitor").await,
+            Err(ManagerError::CapabilityEscalation(_))
+        ));
+        let diff = h.manager.capability_escalation("monitor").unwrap().clone();
+        h.manager.approve_capabilities("monitor", &diff).unwrap();
+        h.manager.activate("monitor").await.unwrap();
+
+        let policy = h.host.policy_engine().unwrap();
+        let mut events = policy.subscribe();
+        h.manager.activate("greeter").await.unwrap();
+        while let Ok(event) = events.try_recv() {
+            h.manager.dispatch_audit_event(&event).await;
+        }
+
+        let seen: Vec<String> = h
+            .host
+            .notifications()
+            .into_iter()
+            .filter(|n| n.plugin_id == "monitor")
+            .map(|n| n.message)
+            .collect();
+        assert!(seen.contains(&"greeter ui.notification".to_string()));
+        // The monitor's own notifications are not fed back to it
+        assert!(!seen.iter().any(|m| m.starts_with("monitor ")));
+
+        h.manager.deactivate("monitor").await.unwrap();
+        assert!(policy.audit_hook_subscribers().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_consent_prompt_does_not_count_against_cpu_time() {
+        let script = r#"
+onActivate(async function () {
+  const out = await skretchpad.commands.execute('echo', ['consented']);
+  skretchpad.ui.showNotification(out.stdout.trim(), 'info');
+});
+"#;
+        let mut h = headless(&[(
+            "asker",
+            "[permissions]\ncommands = { allowlist = [\"echo\"], require_confirmation = true }\n\n\
+             [ui]\nnotifications = true\n\n[limits]\ncpu_time_ms = 200\n",
+            script,
+        )]);
+
+        // The user takes three times the plugin's CPU budget to answer
+        let answer = answer_after(
+            &h,
+            CONSENT_REQUEST_EVENT,
+            Duration::from_millis(600),
+            ConsentDecision::AllowOnce,
+        );
+        h.manager.activate("asker").await.unwrap();
+        answer.join().unwrap();
+
+        let notifications = h.host.notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].message, "consented");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sync_execute_returns_the_output_directly() {
+        let script = r#"
+onActivate(function () {
+  const out = skretchpad.commands.execute('echo', ['sync']);
+  skretchpad.ui.showNotification(out.stdout.trim(), 'info');
+});
+"#;
+        let h = headless(&[(
+            "runner",
+            "[permissions]\ncommands = { allowlist = [\"echo\"] }\n\n[ui]\nnotifications = true\n",
+            script,
+        )]);
+
+        h.manager.activate("runner").await.unwrap();
+
+        let notifications = h.host.notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].message, "sync");
+    }
+
+    #[tokio::test]
+    async fn test_allow_once_permission_is_a_session_grant() {
+        let script = r#"
+onActivate(async function () {
+  if (await skretchpad.permissions.request('filesystem_read', 'src', 'index sources')) {
+    skretchpad.ui.showNotification(skretchpad.fs.readFile('src/main.rs').trim(), 'info');
+  }
+});
+"#;
+        let mut h = headless(&[(
+            "reader",
+            "[ui]\nnotifications = true\n\n[limits]\ncpu_time_ms = 200\n",
+            script,
+        )]);
+
+        let answer = answer_after(
+            &h,
+            PERMISSION_REQUEST_EVENT,
+            Duration::from_millis(600),
+            ConsentDecision::AllowOnce,
+        );
+        h.manager.activate("reader").await.unwrap();
+        answer.join().unwrap();
+        assert_eq!(h.host.notifications()[0].message, "fn main() {}");
+
+        let grants = h.manager.grant_store().list(Some("reader"));
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].scope, GrantScope::Session);
+
+        // Recomputing the sandbox's capabilities keeps the session grant
+        h.manager.refresh_capabilities("reader").await.unwrap();
+        let workspace = h.manager.workspace_root().display().to_string();
+        assert!(h
+            .manager
+            .get_plugin_capabilities("reader")
+            .unwrap()
+            .filesystem
+            .can_read(&format!("{}/src/main.rs", workspace), &workspace));
+    }
+
+    #[tokio::test]
+    async fn test_escalated_manifest_stays_inactive_until_the_reviewed_diff_is_approved() {
+        let mut h = headless(&[(
+            "greeter",
+            "[ui]\nnotifications = true\nstatus_bar = true\n",
+            GREETER,
+        )]);
+        h.manager.activate("greeter").await.unwrap();
+        h.manager.deactivate("greeter").await.unwrap();
+
+        let plugin_dir = h.dir.path().join("plugins/greeter");
+        let update = |version: &str, tables: &str, manager: &mut PluginManager| {
+            write_manifest(&plugin_dir, "greeter", version, tables);
+            manager.load("greeter").unwrap();
+        };
+        update(
+            "1.1.0",
+            "[ui]\nnotifications = true\nstatus_bar = true\nsidebar = true\n",
+            &mut h.manager,
+        );
+        let err = h.manager.activate("greeter").await.unwrap_err();
+        assert!(
+            matches!(err, ManagerError::CapabilityEscalation(_)),
+            "{:?}",
+            err
+        );
+        let reviewed = h.manager.capability_escalation("greeter").unwrap().clone();
+        assert_eq!(reviewed.version, "1.1.0");
+
+        // The manifest escalates further while the user is looking at the prompt
+        update(
+            "1.2.0",
+            "[ui]\nnotifications = true\nstatus_bar = true\nsidebar = true\nwebview = true\n",
+            &mut h.manager,
+        );
+        let err = h
+            .manager
+            .approve_capabilities("greeter", &reviewed)
+            .unwrap_err();
+        assert!(matches!(err, ManagerError::StaleApproval(_)), "{:?}", err);
+        let current = h.manager.capability_escalation("greeter").unwrap().clone();
+        assert_eq!(current.version, "1.2.0");
+        assert!(matches!(
+            h.manager.activate("greeter").await,
+            Err(ManagerError::CapabilityEscalation(_))
+        ));
+        assert!(!h.manager.is_active("greeter"));
+
+        h.manager.approve_capabilities("greeter", &current).unwrap();
+        assert!(h.manager.capability_escalation("greeter").is_none());
+        h.manager.activate("greeter").await.unwrap();
+        assert!(h.manager.is_active("greeter"));
+    }
+
+    #[tokio::test]
+    async fn test_active_plugin_is_not_reloaded_in_place() {
+        let mut h = headless(&[("greeter", "[ui]\nnotifications = true\n", GREETER)]);
+        h.manager.activate("greeter").await.unwrap();
+
+        write_manifest(
+            &h.dir.path().join("plugins/greeter"),
+            "greeter",
+            "1.1.0",
+            "[permissions]\nfilesystem = \"WorkspaceReadWrite\"\n\n[ui]\nnotifications = true\n",
+        );
+        assert!(matches!(
+            h.manager.load("greeter"),
+            Err(ManagerError::PluginAlreadyActive(_))
+        ));
+        assert!(h.manager.is_active("greeter"));
+
+        // A later grant refresh still pushes only the approved manifest
+        h.manager.refresh_capabilities("greeter").await.unwrap();
+        let capabilities = h.manager.get_plugin_capabilities("greeter").unwrap();
+        assert!(matches!(
+            capabilities.filesystem,
+            crate::plugin_system::capabilities::FilesystemCapability::None
+        ));
+
+        // Reloading goes through the escalation review
+        assert!(matches!(
+            h.manager.reload("greeter").await,
+            Err(ManagerError::CapabilityEscalation(_))
+        ));
+        assert!(!h.manager.is_active("greeter"));
+    }
+
+    #[tokio::test]
+    async fn test_headless_heap_exhaustion_moves_plugin_to_error() {
+        let script = r#"
+onActivate(function () {
+  const hoard = [];
+  while (true) { hoard.push(new Array(100000).fill(1)); }
+});
+"#;
+        let mut h = headless(&[("hoarder", "[limits]\nmemory_mb = 16\n", script)]);
+
+        let err = h.manager.activate("hoarder").await.unwrap_err();
+        assert!(matches!(err, ManagerError::Sandbox(_)), "{:?}", err);
+        let status = h.manager.get_status("hoarder").unwrap();
+        assert_eq!(status.state, PluginState::Error);
+        assert!(status.error.unwrap().contains("Memory limit exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_suspends_running_plugin() {
+        let mut h = headless(&[(
+            "greeter",
+            "[ui]\nnotifications = true\nstatus_bar = true\n",
+            GREETER,
+        )]);
+        h.manager.activate("greeter").await.unwrap();
+
+        let now = std::time::SystemTime::now();
+        for (operation, resource) in [
+            ("fs.read", "/home/user/.ssh/id_rsa"),
+            ("network.fetch", "https://attacker.example/upload"),
+        ] {
+            h.manager
+                .inspect_audit_event(&AuditEvent {
+                    plugin_id: "greeter".to_string(),
+                    operation: operation.to_string(),
+                    resource: resource.to_string(),
+                    timestamp: now,
+                    success: true,
+                    error: None,
+                })
+                .await;
+        }
+
+        assert!(!h.manager.is_active("greeter"));
+        let status = h.manager.get_status("greeter").unwrap();
+        assert_eq!(status.anomaly.unwrap().rule, "read-then-fetch");
+        assert!(status.error.unwrap().contains("read-then-fetch"));
+        assert_eq!(h.host.payloads(PLUGIN_SUSPENDED_EVENT).len(), 1);
+
+        // Activating again is the user's decision and clears the record
+        h.manager.activate("greeter").await.unwrap();
+        assert!(h.manager.get_status("greeter").unwrap().anomaly.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_detector_checks_events_without_the_manager() {
+        let mut h = headless(&[(
+            "greeter",
+            "[ui]\nnotifications = true\nstatus_bar = true\n",
+            GREETER,
+        )]);
+        h.manager.activate("greeter").await.unwrap();
+
+        // As in the app: events reach the detector on a channel that never
+        // drops any, and the manager is only needed once a rule matches
+        let detector = h.manager.anomaly_detector();
+        let mut inspected = h.audit.inspect();
+        let policy = h.host.policy_engine().unwrap();
+        let now = std::time::SystemTime::now();
+        for (operation, resource) in [
+            ("fs.read", "/home/user/.ssh/id_rsa"),
+            ("network.fetch", "https://attacker.example/upload"),
+        ] {
+            policy.record("greeter", operation, resource, now, None);
+        }
+        let mut anomalies = Vec::new();
+        while let Ok(event) = inspected.try_recv() {
+            if let Some(anomaly) = detector.lock().unwrap().inspect(&event) {
+                anomalies.push(anomaly);
+            }
+        }
+        assert_eq!(anomalies.len(), 1);
+        h.manager.suspend(anomalies.remove(0)).await;
+        assert!(!h.manager.is_active("greeter"));
+
+        // A suspended plugin is no longer watched
+        policy.record("greeter", "fs.read", "/home/user/.ssh/id_rsa", now, None);
+        let event = inspected.try_recv().unwrap();
+        assert!(detector.lock().unwrap().inspect(&event).is_none());
+    }
+
+    async fn kill_worker(manager: &PluginManager, plugin_id: &str) {
+        let sandbox = manager
+            .sandbox_registry()
+            .get_sandbox(plugin_id)
+            .await
+            .unwrap();
+        sandbox.read().await.kill_worker("simulated crash");
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_restarts_crashed_plugin_until_budget_is_spent() {
+        let mut h = headless(&[(
+            "greeter",
+            "[ui]\nnotifications = true\nstatus_bar = true\n",
+            GREETER,
+        )]);
+        h.manager.set_restart_policy(RestartPolicy {
+            max_restarts: 2,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            reset_after: Duration::from_secs(3600),
+        });
+        h.manager.activate("greeter").await.unwrap();
+
+        for restart in 1..=2 {
+            kill_worker(&h.manager, "greeter").await;
+            h.manager.supervise().await;
+            assert!(h.manager.is_active("greeter"));
+            let status = h.manager.get_status("greeter").unwrap();
+            assert_eq!(status.restarts, restart);
+            assert_eq!(status.last_failure.as_deref(), Some("simulated crash"));
+        }
+        assert_eq!(h.host.payloads("plugin:restarted").len(), 2);
+        // The activate hook ran in every new isolate
+        assert_eq!(h.host.notifications().len(), 3);
+
+        kill_worker(&h.manager, "greeter").await;
+        h.manager.supervise().await;
+        h.manager.supervise().await;
+        let status = h.manager.get_status("greeter").unwrap();
+        assert_eq!(status.state, PluginState::Error);
+        assert!(status.error.unwrap().contains("Crash looping"));
+        assert_eq!(h.host.payloads("plugin:crash-looping").len(), 1);
+        assert_eq!(h.host.payloads("plugin:restarted").len(), 2);
+        assert!(h
+            .manager
+            .sandbox_registry()
+            .get_sandbox("greeter")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_every_activation_checks_the_signature() {
+        use crate::plugin_system::trust::{encode_public_key, sign_plugin, PluginSignature};
+
+        let mut h = headless(&[(
+            "signed",
+            "trust = \"verified\"\n\n[ui]\nnotifications = true\nstatus_bar = true\n",
+            GREETER,
+        )]);
+        let plugin_dir = h.dir.path().join("plugins/signed");
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let manifest = h.manager.loader().get("signed").unwrap().manifest.clone();
+        let signature = sign_plugin("signed", &plugin_dir, &manifest, &key).unwrap();
+        #[derive(Serialize)]
+        struct SignatureTable<'a> {
+            signature: &'a PluginSignature,
+        }
+        let table = toml::to_string(&SignatureTable {
+            signature: &signature,
+        })
+        .unwrap();
+        let content = std::fs::read_to_string(plugin_dir.join("plugin.toml")).unwrap();
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            format!("{}\n{}", content, table),
+        )
+        .unwrap();
+        h.manager.load("signed").unwrap();
+
+        // No trusted keys yet
+        assert!(matches!(
+            h.manager.activate("signed").await,
+            Err(ManagerError::Signature(_))
+        ));
+        let mut verifier = TrustVerifier::new();
+        verifier
+            .add_trusted_key(encode_public_key(&key.verifying_key()))
+            .unwrap();
+        h.manager
+            .set_trust_verifier(Arc::new(RwLock::new(verifier)));
+        h.manager.activate("signed").await.unwrap();
+
+        // The files change on disk while the plugin runs; a supervised restart
+        // must not bring it back
+        std::fs::write(
+            plugin_dir.join("main.js"),
+            format!("{}\n// tampered\n", GREETER),
+        )
+        .unwrap();
+        h.manager.set_restart_policy(RestartPolicy {
+            max_restarts: 2,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            reset_after: Duration::from_secs(3600),
+        });
+        kill_worker(&h.manager, "signed").await;
+        h.manager.supervise().await;
+        h.manager.supervise().await;
+        assert!(!h.manager.is_active("signed"));
+        let status = h.manager.get_status("signed").unwrap();
+        assert!(status.error.unwrap().contains("Signature rejected"));
+        assert!(h.host.payloads("plugin:restarted").is_empty());
+
+        assert!(matches!(
+            h.manager.reload("signed").await,
+            Err(ManagerError::Signature(_))
+        ));
+        assert!(!h.manager.is_active("signed"));
+    }
+
+    #[tokio::test]
+    async fn test_activation_overtaken_by_unload_is_discarded() {
+        let mut h = headless(&[(
+            "greeter",
+            "[ui]\nnotifications = true\nstatus_bar = true\n",
+            GREETER,
+        )]);
+
+        let pending = h.manager.prepare_activation("greeter").unwrap();
+        assert!(matches!(
+            h.manager.activate("greeter").await,
+            Err(ManagerError::PluginAlreadyActive(_))
+        ));
+        let run = pending.run().await;
+        h.manager.unload("greeter").await.unwrap();
+
+        assert!(h.manager.finish_activation(run).await.is_err());
+        assert!(h
+            .manager
+            .sandbox_registry()
+            .get_sandbox("greeter")
+            .await
+            .is_none());
+    }
 
     #[test]
     fn test_event_listeners_standalone() {
@@ -781,6 +2020,10 @@ mod tests {
             auto_approve: false,
             capability_tier: "sandboxed".to_string(),
             commands: vec![],
+            restarts: 0,
+            last_failure: None,
+            capability_escalation: None,
+            anomaly: None,
         };
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("\"id\":\"test\""));
@@ -806,12 +2049,42 @@ mod tests {
             auto_approve: false,
             capability_tier: "sandboxed".to_string(),
             commands: vec![],
+            restarts: 0,
+            last_failure: None,
+            capability_escalation: None,
+            anomaly: None,
         };
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("\"state\":\"error\""));
         assert!(json.contains("sandbox timeout"));
     }
 
+    #[test]
+    fn test_plugin_status_reports_crash_history() {
+        use crate::plugin_system::capabilities::*;
+        use crate::plugin_system::trust::TrustLevel;
+        let status = PluginStatus {
+            id: "test".to_string(),
+            name: "Test Plugin".to_string(),
+            version: "1.0.0".to_string(),
+            state: PluginState::Error,
+            capabilities: PluginCapabilities::default(),
+            error: Some("Crash looping: worker died 4 times".to_string()),
+            trust: TrustLevel::Community,
+            loaded_at: None,
+            auto_approve: false,
+            capability_tier: "sandboxed".to_string(),
+            commands: vec![],
+            restarts: 3,
+            last_failure: Some("Plugin worker panicked: boom".to_string()),
+            capability_escalation: None,
+            anomaly: None,
+        };
+        let json = serde_json::to_string(&status).unwrap();
+        assert!(json.contains("\"restarts\":3"));
+        assert!(json.contains("\"last_failure\":\"Plugin worker panicked: boom\""));
+    }
+
     #[test]
     fn test_plugin_status_first_party_trust() {
         use crate::plugin_system::capabilities::*;
@@ -828,6 +2101,10 @@ mod tests {
             auto_approve: true,
             capability_tier: "full".to_string(),
             commands: vec![],
+            restarts: 0,
+            last_failure: None,
+            capability_escalation: None,
+            anomaly: None,
         };
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("\"trust\":\"first-party\""));
@@ -851,6 +2128,10 @@ mod tests {
             auto_approve: false,
             capability_tier: "read-only".to_string(),
             commands: vec![],
+            restarts: 0,
+            last_failure: None,
+            capability_escalation: None,
+            anomaly: None,
         };
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("\"trust\":\"verified\""));
@@ -938,6 +2219,10 @@ mod tests {
             auto_approve: false,
             capability_tier: "sandboxed".to_string(),
             commands: vec![],
+            restarts: 0,
+            last_failure: None,
+            capability_escalation: None,
+            anomaly: None,
         };
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("\"trust\":\"local\""));
@@ -0,0 +1,364 @@
+// src-tauri/src/plugin_system/module_loader.rs
+//
+// ES module loader for plugin code. Plugins may `import` their own files
+// (relative JavaScript/TypeScript modules and JSON) but nothing outside the
+// canonical plugin directory and nothing over the network.
+
+use crate::plugin_system::transpile::{transpile_typescript, SourceMapStore};
+use deno_core::error::{generic_error, AnyError};
+use deno_core::{
+    ModuleLoader, ModuleSource, ModuleSourceFuture, ModuleSpecifier, ModuleType, ResolutionKind,
+};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+/// Canonicalize `path` and ensure it stays inside the canonical plugin `root`.
+///
+/// Symlinks are resolved before the check, so a link pointing outside the
+/// plugin directory is rejected the same way as a `../` path.
+pub fn resolve_within_root(root: &Path, path: &Path) -> Result<PathBuf, String> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve '{}': {}", path.display(), e))?;
+    if !canonical.starts_with(root) {
+        return Err(format!(
+            "'{}' escapes the plugin directory '{}'",
+            path.display(),
+            root.display()
+        ));
+    }
+    Ok(canonical)
+}
+
+/// Module loader confined to a single plugin's directory.
+pub struct PluginModuleLoader {
+    root: PathBuf,
+    source_maps: SourceMapStore,
+}
+
+impl PluginModuleLoader {
+    /// `root` must already be canonical (see [`resolve_within_root`]). TypeScript
+    /// modules are recorded in `source_maps` as they are transpiled.
+    pub fn new(root: PathBuf, source_maps: SourceMapStore) -> Self {
+        Self { root, source_maps }
+    }
+
+    /// Map a specifier to a file inside the plugin root, rejecting anything else.
+    fn confine(&self, specifier: &ModuleSpecifier) -> Result<PathBuf, AnyError> {
+        if specifier.scheme() != "file" {
+            return Err(generic_error(format!(
+                "Import of '{}' is not allowed: plugins may only import files from their own directory",
+                specifier
+            )));
+        }
+        let path = specifier
+            .to_file_path()
+            .map_err(|_| generic_error(format!("Invalid module path: '{}'", specifier)))?;
+        resolve_within_root(&self.root, &path)
+            .map_err(|e| generic_error(format!("Import of '{}' is not allowed: {}", specifier, e)))
+    }
+
+    fn load_source(&self, specifier: &ModuleSpecifier) -> Result<ModuleSource, AnyError> {
+        let path = self.confine(specifier)?;
+        let kind = source_kind(&path)?;
+        let code = std::fs::read_to_string(&path).map_err(|e| {
+            generic_error(format!("Failed to read module '{}': {}", path.display(), e))
+        })?;
+        match kind {
+            SourceKind::JavaScript => Ok(ModuleSource::new(
+                ModuleType::JavaScript,
+                code.into(),
+                specifier,
+            )),
+            SourceKind::Json => Ok(ModuleSource::new(ModuleType::Json, code.into(), specifier)),
+            SourceKind::TypeScript => {
+                let module = transpile_typescript(specifier, code)?;
+                self.source_maps.insert(specifier, module.clone());
+                Ok(ModuleSource::new(
+                    ModuleType::JavaScript,
+                    module.code.clone().into(),
+                    specifier,
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SourceKind {
+    JavaScript,
+    Json,
+    TypeScript,
+}
+
+/// Source kind by file extension; only JavaScript, TypeScript and JSON are importable.
+fn source_kind(path: &Path) -> Result<SourceKind, AnyError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("js") | Some("mjs") => Ok(SourceKind::JavaScript),
+        Some("ts") | Some("mts") => Ok(SourceKind::TypeScript),
+        Some("json") => Ok(SourceKind::Json),
+        _ => Err(generic_error(format!(
+            "Unsupported module type: '{}' (expected .js, .mjs, .ts, .mts or .json)",
+            path.display()
+        ))),
+    }
+}
+
+impl ModuleLoader for PluginModuleLoader {
+    fn resolve(
+        &self,
+        specifier: &str,
+        referrer: &str,
+        _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, AnyError> {
+        let resolved = deno_core::resolve_import(specifier, referrer).map_err(|e| {
+            generic_error(format!(
+                "Cannot resolve import '{}' from '{}': {}",
+                specifier, referrer, e
+            ))
+        })?;
+        self.confine(&resolved)?;
+        Ok(resolved)
+    }
+
+    fn load(
+        &self,
+        module_specifier: &ModuleSpecifier,
+        _maybe_referrer: Option<&ModuleSpecifier>,
+        _is_dyn_import: bool,
+    ) -> Pin<Box<ModuleSourceFuture>> {
+        // Re-check at load time: the file may have been swapped for a symlink since resolve.
+        Box::pin(std::future::ready(self.load_source(module_specifier)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn plugin_dir() -> (TempDir, PathBuf) {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("plugin");
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(root.join("main.js"), "import './lib/helper.js';\n").unwrap();
+        std::fs::write(root.join("lib/helper.js"), "export const x = 1;\n").unwrap();
+        std::fs::write(root.join("data.json"), "{\"answer\": 42}\n").unwrap();
+        std::fs::write(tmp.path().join("secret.js"), "export default 'secret';\n").unwrap();
+        let root = root.canonicalize().unwrap();
+        (tmp, root)
+    }
+
+    fn main_url(root: &Path) -> String {
+        ModuleSpecifier::from_file_path(root.join("main.js"))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_resolve_within_root_accepts_nested_file() {
+        let (_tmp, root) = plugin_dir();
+        let resolved = resolve_within_root(&root, &root.join("lib/../lib/helper.js")).unwrap();
+        assert_eq!(resolved, root.join("lib/helper.js"));
+    }
+
+    #[test]
+    fn test_resolve_within_root_rejects_parent_escape() {
+        let (_tmp, root) = plugin_dir();
+        let err = resolve_within_root(&root, &root.join("../secret.js")).unwrap_err();
+        assert!(err.contains("escapes the plugin directory"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_within_root_rejects_symlink_escape() {
+        let (tmp, root) = plugin_dir();
+        std::os::unix::fs::symlink(tmp.path().join("secret.js"), root.join("link.js")).unwrap();
+        assert!(resolve_within_root(&root, &root.join("link.js")).is_err());
+    }
+
+    #[test]
+    fn test_loader_resolves_relative_imports() {
+        let (_tmp, root) = plugin_dir();
+        let loader = PluginModuleLoader::new(root.clone(), SourceMapStore::default());
+        let resolved = loader
+            .resolve("./lib/helper.js", &main_url(&root), ResolutionKind::Import)
+            .unwrap();
+        assert_eq!(resolved.to_file_path().unwrap(), root.join("lib/helper.js"));
+    }
+
+    #[test]
+    fn test_loader_rejects_imports_outside_root() {
+        let (_tmp, root) = plugin_dir();
+        let loader = PluginModuleLoader::new(root.clone(), SourceMapStore::default());
+        let err = loader
+            .resolve("../secret.js", &main_url(&root), ResolutionKind::Import)
+            .unwrap_err();
+        assert!(err.to_string().contains("not allowed"));
+    }
+
+    #[test]
+    fn test_loader_rejects_network_imports() {
+        let (_tmp, root) = plugin_dir();
+        let loader = PluginModuleLoader::new(root.clone(), SourceMapStore::default());
+        for specifier in [
+            "https://example.com/mod.js",
+            "http://localhost:8080/mod.js",
+            "data:text/javascript,export default 1",
+        ] {
+            let err = loader
+                .resolve(specifier, &main_url(&root), ResolutionKind::Import)
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("may only import files"),
+                "{}: {}",
+                specifier,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_loader_rejects_bare_specifiers() {
+        let (_tmp, root) = plugin_dir();
+        let loader = PluginModuleLoader::new(root.clone(), SourceMapStore::default());
+        assert!(loader
+            .resolve("lodash", &main_url(&root), ResolutionKind::Import)
+            .is_err());
+    }
+
+    #[test]
+    fn test_source_kind_for_extensions() {
+        assert_eq!(
+            source_kind(Path::new("a.js")).unwrap(),
+            SourceKind::JavaScript
+        );
+        assert_eq!(
+            source_kind(Path::new("src/index.ts")).unwrap(),
+            SourceKind::TypeScript
+        );
+        assert_eq!(source_kind(Path::new("a.json")).unwrap(), SourceKind::Json);
+        assert!(source_kind(Path::new("a.wasm")).is_err());
+    }
+
+    fn plugin_runtime(root: &Path) -> (tokio::runtime::Runtime, deno_core::JsRuntime) {
+        let tokio_rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let source_maps = SourceMapStore::default();
+        let runtime = {
+            let _guard = tokio_rt.enter();
+            deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+                module_loader: Some(std::rc::Rc::new(PluginModuleLoader::new(
+                    root.to_path_buf(),
+                    source_maps.clone(),
+                ))),
+                source_map_getter: Some(Box::new(source_maps)),
+                ..Default::default()
+            })
+        };
+        (tokio_rt, runtime)
+    }
+
+    fn evaluate_main(
+        tokio_rt: &tokio::runtime::Runtime,
+        runtime: &mut deno_core::JsRuntime,
+        entry: &Path,
+    ) -> Result<(), AnyError> {
+        let specifier = ModuleSpecifier::from_file_path(entry).unwrap();
+        let _guard = tokio_rt.enter();
+        tokio_rt.block_on(async {
+            let id = runtime.load_main_module(&specifier, None).await?;
+            let evaluation = runtime.mod_evaluate(id);
+            runtime.run_event_loop(false).await?;
+            evaluation.await
+        })
+    }
+
+    fn global_string(runtime: &mut deno_core::JsRuntime, name: &'static str) -> String {
+        let value = runtime
+            .execute_script("read_result.js", deno_core::FastString::Static(name))
+            .unwrap();
+        let scope = &mut runtime.handle_scope();
+        let local = deno_core::v8::Local::new(scope, value);
+        local.to_rust_string_lossy(scope)
+    }
+
+    #[test]
+    fn test_runtime_loads_multi_file_plugin() {
+        let (_tmp, root) = plugin_dir();
+        std::fs::write(
+            root.join("main.js"),
+            "import { greet } from './lib/greet.js';\n\
+             import data from './data.json' with { type: 'json' };\n\
+             globalThis.result = greet('plugin') + ':' + data.answer;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("lib/greet.js"),
+            "export function greet(name) { return 'hello ' + name; }\n",
+        )
+        .unwrap();
+
+        let (tokio_rt, mut runtime) = plugin_runtime(&root);
+        evaluate_main(&tokio_rt, &mut runtime, &root.join("main.js")).unwrap();
+        assert_eq!(
+            global_string(&mut runtime, "globalThis.result"),
+            "hello plugin:42"
+        );
+    }
+
+    #[test]
+    fn test_runtime_rejects_escaping_import() {
+        let (_tmp, root) = plugin_dir();
+        std::fs::write(root.join("main.js"), "import '../secret.js';\n").unwrap();
+
+        let (tokio_rt, mut runtime) = plugin_runtime(&root);
+        let err = evaluate_main(&tokio_rt, &mut runtime, &root.join("main.js")).unwrap_err();
+        assert!(err.to_string().contains("not allowed"));
+    }
+
+    #[test]
+    fn test_runtime_loads_typescript_entry_point() {
+        let (_tmp, root) = plugin_dir();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/index.ts"),
+            "import { label } from './label.ts';\n\
+             const count: number = 3;\n\
+             globalThis.result = label(count);\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/label.ts"),
+            "export function label(n: number): string {\n  return `items: ${n}`;\n}\n",
+        )
+        .unwrap();
+
+        let (tokio_rt, mut runtime) = plugin_runtime(&root);
+        evaluate_main(&tokio_rt, &mut runtime, &root.join("src/index.ts")).unwrap();
+        assert_eq!(global_string(&mut runtime, "globalThis.result"), "items: 3");
+    }
+
+    #[test]
+    fn test_typescript_errors_point_at_original_lines() {
+        let (_tmp, root) = plugin_dir();
+        // Lines 1-3 are type-only and vanish from the emitted JavaScript
+        std::fs::write(
+            root.join("main.ts"),
+            "interface Options {\n  name: string;\n}\n\
+             export function fail(opts: Options): never {\n\
+             \x20 throw new Error(`boom ${opts.name}`);\n\
+             }\n\
+             fail({ name: 'ts' });\n",
+        )
+        .unwrap();
+
+        let (tokio_rt, mut runtime) = plugin_runtime(&root);
+        let err = evaluate_main(&tokio_rt, &mut runtime, &root.join("main.ts")).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("boom ts"), "{}", message);
+        assert!(message.contains("main.ts:5:"), "{}", message);
+    }
+}
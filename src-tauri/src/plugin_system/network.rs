@@ -0,0 +1,761 @@
+// src-tauri/src/plugin_system/network.rs
+//
+// URL rules for `NetworkCapability::DomainAllowlist` and the checks every plugin
+// fetch goes through, shared by `op_plugin_fetch*` and `api.rs::plugin_fetch`.
+//
+// A rule is an optional comma-separated method list followed by a URL pattern:
+//
+//   api.github.com                         https, default port, any path, any method
+//   GET,HEAD https://*.github.com/repos/*  subdomains of github.com, read-only
+//   http://localhost:8080                  plain HTTP to a local dev server
+//
+// Plain HTTP, IP literals and localhost are refused unless a rule names them
+// explicitly. Hostnames must resolve to public addresses, and the request is
+// pinned to the addresses that were checked so a second DNS answer cannot
+// redirect it (DNS rebinding).
+
+use crate::plugin_system::capabilities::NetworkCapability;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use url::Url;
+
+/// Redirects followed per request (reqwest's default limit)
+const MAX_REDIRECTS: usize = 10;
+
+/// Pinned clients kept before the cache starts over.
+const MAX_CACHED_CLIENTS: usize = 64;
+
+thread_local! {
+    /// Pinned clients by what they were built for. A client carries its own
+    /// connection pool and TLS setup, so fetches to the same vetted addresses
+    /// reuse one instead of connecting from scratch. Kept per thread: pooled
+    /// connections are driven by the runtime that opened them.
+    static CLIENTS: RefCell<HashMap<ClientKey, reqwest::Client>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Everything a pinned client's behaviour depends on: the host and the
+/// addresses it connects to, plus the method and rules its redirect policy checks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    host: String,
+    addrs: Vec<SocketAddr>,
+    method: String,
+    /// Sorted allowlist rules; `None` for unrestricted access
+    rules: Option<Vec<String>>,
+}
+
+/// Network authorization errors
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkError {
+    #[error("Network access not granted")]
+    NoAccess,
+
+    #[error("Invalid URL '{url}': {reason}")]
+    InvalidUrl { url: String, reason: String },
+
+    #[error("Unsupported URL scheme '{0}'")]
+    UnsupportedScheme(String),
+
+    #[error("Invalid network rule '{rule}': {reason}")]
+    InvalidRule { rule: String, reason: String },
+
+    #[error("Host not allowed: {0}")]
+    HostNotAllowed(String),
+
+    #[error("Plain HTTP is not allowed for {0}; use https or add an explicit http:// rule")]
+    InsecureScheme(String),
+
+    #[error("URL not allowed: {0}")]
+    UrlNotAllowed(String),
+
+    #[error("Method {method} not allowed for {url}")]
+    MethodNotAllowed { method: String, url: String },
+
+    #[error("Failed to resolve '{host}': {reason}")]
+    Resolve { host: String, reason: String },
+
+    #[error("Host '{host}' resolves to non-public address {addr}")]
+    BlockedAddress { host: String, addr: IpAddr },
+}
+
+/// Host part of a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostPattern {
+    /// Exactly this host (domain, `localhost`, or IP literal)
+    Exact(String),
+    /// Any subdomain of this domain (`*.github.com`), not the domain itself
+    Subdomains(String),
+}
+
+/// One parsed `DomainAllowlist` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkRule {
+    /// Allowed methods (uppercase); `None` allows any
+    methods: Option<Vec<String>>,
+    scheme: String,
+    host: HostPattern,
+    /// `None` means the scheme's default port; `Some(None)` means any port
+    port: Option<Option<u16>>,
+    /// Path pattern where `*` matches any run of characters; `None` allows any path
+    path: Option<String>,
+}
+
+impl NetworkRule {
+    /// Parse a rule string (see the module docs for the syntax).
+    pub fn parse(rule: &str) -> Result<Self, NetworkError> {
+        let invalid = |reason: &str| NetworkError::InvalidRule {
+            rule: rule.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let trimmed = rule.trim();
+        let (methods, pattern) = match trimmed.split_once(char::is_whitespace) {
+            Some((methods, pattern)) => {
+                let methods = methods
+                    .split(',')
+                    .map(|m| {
+                        let m = m.trim();
+                        if m.is_empty() || !m.chars().all(|c| c.is_ascii_alphabetic()) {
+                            Err(invalid(
+                                "methods must be a comma-separated list like GET,HEAD",
+                            ))
+                        } else {
+                            Ok(m.to_ascii_uppercase())
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                (Some(methods), pattern.trim())
+            }
+            None => (None, trimmed),
+        };
+
+        let (scheme, rest) = match pattern.split_once("://") {
+            Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+            None => ("https".to_string(), pattern),
+        };
+        if scheme != "https" && scheme != "http" {
+            return Err(invalid("scheme must be http or https"));
+        }
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], Some(&rest[index..])),
+            None => (rest, None),
+        };
+        let (host, port) = split_authority(authority).ok_or_else(|| invalid("bad host or port"))?;
+        let host = host.to_ascii_lowercase();
+        let host = match host.strip_prefix("*.") {
+            Some(domain) if !domain.is_empty() && !domain.contains('*') => {
+                if parse_ip_literal(domain).is_some() {
+                    return Err(invalid("wildcards cannot match IP addresses"));
+                }
+                HostPattern::Subdomains(domain.to_string())
+            }
+            Some(_) => return Err(invalid("bad wildcard host")),
+            None if host.is_empty() || host.contains('*') => {
+                return Err(invalid("host must be a name, *.domain, or an IP address"))
+            }
+            None => HostPattern::Exact(host),
+        };
+
+        Ok(Self {
+            methods,
+            scheme,
+            host,
+            port,
+            path: path.filter(|p| !p.is_empty()).map(String::from),
+        })
+    }
+
+    /// Whether the rule's host pattern covers `host` (as returned by `Url::host_str`).
+    pub fn matches_host(&self, host: &str) -> bool {
+        match &self.host {
+            HostPattern::Exact(exact) => exact == host,
+            HostPattern::Subdomains(domain) => {
+                !is_local_host(host)
+                    && host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+            }
+        }
+    }
+
+    fn matches_location(&self, url: &Url) -> bool {
+        let port_ok = match self.port {
+            Some(None) => true,
+            Some(Some(port)) => url.port_or_known_default() == Some(port),
+            None => url.port().is_none(),
+        };
+        url.scheme() == self.scheme
+            && url.host_str().is_some_and(|host| self.matches_host(host))
+            && port_ok
+            && self
+                .path
+                .as_deref()
+                .is_none_or(|pattern| wildcard_match(pattern, url.path()))
+    }
+
+    fn allows_method(&self, method: &str) -> bool {
+        self.methods
+            .as_ref()
+            .is_none_or(|methods| methods.iter().any(|m| m == method))
+    }
+
+    /// Whether this rule explicitly names a local host (localhost or an IP literal)
+    fn names_local_host(&self) -> bool {
+        matches!(&self.host, HostPattern::Exact(host) if is_local_host(host))
+    }
+}
+
+/// `host[:port]`, with `[v6]` literals and `*` for any port.
+fn split_authority(authority: &str) -> Option<(&str, Option<Option<u16>>)> {
+    let (host, port) = if authority.starts_with('[') {
+        let end = authority.find(']')?;
+        let port = match &authority[end + 1..] {
+            "" => None,
+            rest => Some(rest.strip_prefix(':')?),
+        };
+        (&authority[..=end], port)
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match port {
+        None => None,
+        Some("*") => Some(None),
+        Some(port) => Some(Some(port.parse().ok()?)),
+    };
+    Some((host, port))
+}
+
+/// `*` matches any (possibly empty) run of characters; everything else is literal.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn parse_ip_literal(host: &str) -> Option<IpAddr> {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// `localhost`, `*.localhost`, or an IP literal
+fn is_local_host(host: &str) -> bool {
+    host == "localhost" || host.ends_with(".localhost") || parse_ip_literal(host).is_some()
+}
+
+/// Whether `addr` is routable on the public internet.
+pub fn is_public_address(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => is_public_v4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(v6),
+        },
+    }
+}
+
+fn is_public_v4(addr: Ipv4Addr) -> bool {
+    let [a, b, c, _] = addr.octets();
+    !(addr.is_unspecified()
+        || addr.is_loopback()
+        || addr.is_private()
+        || addr.is_link_local()
+        || addr.is_broadcast()
+        || addr.is_multicast()
+        || addr.is_documentation()
+        || a == 0
+        || a >= 240
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b == 18 || b == 19)))
+}
+
+fn is_public_v6(addr: Ipv6Addr) -> bool {
+    let first = addr.segments()[0];
+    !(addr.is_unspecified()
+        || addr.is_loopback()
+        || addr.is_multicast()
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || (first == 0x2001 && addr.segments()[1] == 0x0db8)
+        || addr.to_ipv4().is_some())
+}
+
+/// A request that passed the capability's rules but has not been resolved yet.
+#[derive(Debug, Clone)]
+pub struct AuthorizedRequest {
+    pub url: Url,
+    pub method: String,
+    host: String,
+    port: u16,
+    /// The matched rule names this local host explicitly
+    allow_local: bool,
+}
+
+/// A request pinned to the addresses it was vetted against.
+#[derive(Debug, Clone)]
+pub struct ResolvedRequest {
+    pub url: Url,
+    pub method: String,
+    host: String,
+    /// Empty for IP literals, which need no resolution
+    addrs: Vec<SocketAddr>,
+}
+
+/// Check `url` and `method` against a network capability without touching DNS.
+pub fn authorize(
+    capability: &NetworkCapability,
+    url: &str,
+    method: &str,
+) -> Result<AuthorizedRequest, NetworkError> {
+    let parsed = Url::parse(url).map_err(|e| NetworkError::InvalidUrl {
+        url: url.to_string(),
+        reason: e.to_string(),
+    })?;
+    if parsed.scheme() != "https" && parsed.scheme() != "http" {
+        return Err(NetworkError::UnsupportedScheme(parsed.scheme().to_string()));
+    }
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| NetworkError::InvalidUrl {
+            url: url.to_string(),
+            reason: "no host".to_string(),
+        })?
+        .to_string();
+    let port = parsed
+        .port_or_known_default()
+        .ok_or_else(|| NetworkError::UnsupportedScheme(parsed.scheme().to_string()))?;
+    let method = method.to_ascii_uppercase();
+
+    let allow_local = match capability {
+        NetworkCapability::None => return Err(NetworkError::NoAccess),
+        NetworkCapability::Unrestricted => {
+            if is_local_host(&host) {
+                return Err(NetworkError::HostNotAllowed(host));
+            }
+            if parsed.scheme() == "http" {
+                return Err(NetworkError::InsecureScheme(host));
+            }
+            false
+        }
+        NetworkCapability::DomainAllowlist(entries) => {
+            // Entries are validated when the manifest loads; anything unparseable grants nothing
+            let rules: Vec<NetworkRule> = entries
+                .iter()
+                .filter_map(|entry| NetworkRule::parse(entry).ok())
+                .collect();
+            let for_host: Vec<&NetworkRule> = rules
+                .iter()
+                .filter(|rule| rule.matches_host(&host))
+                .collect();
+            if for_host.is_empty() {
+                return Err(NetworkError::HostNotAllowed(host));
+            }
+            if !for_host.iter().any(|rule| rule.scheme == parsed.scheme()) {
+                return Err(NetworkError::InsecureScheme(host));
+            }
+            let located: Vec<&&NetworkRule> = for_host
+                .iter()
+                .filter(|rule| rule.matches_location(&parsed))
+                .collect();
+            if located.is_empty() {
+                return Err(NetworkError::UrlNotAllowed(parsed.to_string()));
+            }
+            let Some(rule) = located.iter().find(|rule| rule.allows_method(&method)) else {
+                return Err(NetworkError::MethodNotAllowed {
+                    method,
+                    url: parsed.to_string(),
+                });
+            };
+            rule.names_local_host()
+        }
+    };
+
+    Ok(AuthorizedRequest {
+        url: parsed,
+        method,
+        host,
+        port,
+        allow_local,
+    })
+}
+
+impl AuthorizedRequest {
+    fn vet(self, resolved: Vec<SocketAddr>) -> Result<ResolvedRequest, NetworkError> {
+        if resolved.is_empty() {
+            return Err(NetworkError::Resolve {
+                host: self.host,
+                reason: "no addresses".to_string(),
+            });
+        }
+        if !self.allow_local {
+            if let Some(addr) = resolved.iter().find(|a| !is_public_address(a.ip())) {
+                return Err(NetworkError::BlockedAddress {
+                    host: self.host,
+                    addr: addr.ip(),
+                });
+            }
+        }
+        Ok(ResolvedRequest {
+            url: self.url,
+            method: self.method,
+            host: self.host,
+            addrs: resolved,
+        })
+    }
+
+    fn literal(self) -> Option<Result<ResolvedRequest, NetworkError>> {
+        let ip = parse_ip_literal(&self.host)?;
+        Some(if self.allow_local {
+            Ok(ResolvedRequest {
+                url: self.url,
+                method: self.method,
+                host: self.host,
+                addrs: Vec::new(),
+            })
+        } else {
+            Err(NetworkError::BlockedAddress {
+                host: self.host,
+                addr: ip,
+            })
+        })
+    }
+
+    /// Resolve the host and check every address it maps to.
+    pub async fn resolve(self) -> Result<ResolvedRequest, NetworkError> {
+        if let Some(result) = self.clone().literal() {
+            return result;
+        }
+        let resolved = tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .map_err(|e| NetworkError::Resolve {
+                host: self.host.clone(),
+                reason: e.to_string(),
+            })?
+            .collect();
+        self.vet(resolved)
+    }
+}
+
+impl ResolvedRequest {
+    /// Follow a redirect only if it stays on the vetted host and passes the rules again.
+    fn redirect_policy(&self, capability: &NetworkCapability) -> reqwest::redirect::Policy {
+        let capability = capability.clone();
+        let host = self.host.clone();
+        let method = self.method.clone();
+        reqwest::redirect::Policy::custom(move |attempt| {
+            let allowed = attempt.previous().len() < MAX_REDIRECTS
+                && authorize(&capability, attempt.url().as_str(), &method)
+                    .is_ok_and(|next| next.host == host);
+            if allowed {
+                attempt.follow()
+            } else {
+                attempt.stop()
+            }
+        })
+    }
+
+    /// HTTP client that connects only to the vetted addresses, shared with
+    /// earlier requests pinned the same way.
+    pub fn client(&self, capability: &NetworkCapability) -> reqwest::Result<reqwest::Client> {
+        let key = self.client_key(capability);
+        if let Some(client) = CLIENTS.with(|clients| clients.borrow().get(&key).cloned()) {
+            return Ok(client);
+        }
+
+        let mut builder = reqwest::Client::builder().redirect(self.redirect_policy(capability));
+        if !self.addrs.is_empty() {
+            builder = builder.resolve_to_addrs(&self.host, &self.addrs);
+        }
+        let client = builder.build()?;
+
+        CLIENTS.with(|clients| {
+            let mut clients = clients.borrow_mut();
+            if clients.len() >= MAX_CACHED_CLIENTS {
+                clients.clear();
+            }
+            clients.insert(key, client.clone());
+        });
+        Ok(client)
+    }
+
+    fn client_key(&self, capability: &NetworkCapability) -> ClientKey {
+        let mut addrs = self.addrs.clone();
+        addrs.sort();
+        let rules = match capability {
+            NetworkCapability::None => Some(Vec::new()),
+            NetworkCapability::DomainAllowlist(rules) => {
+                let mut rules: Vec<String> = rules.iter().cloned().collect();
+                rules.sort();
+                Some(rules)
+            }
+            NetworkCapability::Unrestricted => None,
+        };
+        ClientKey {
+            host: self.host.clone(),
+            addrs,
+            method: self.method.clone(),
+            rules,
+        }
+    }
+
+    /// Parsed request method
+    pub fn method(&self) -> reqwest::Method {
+        reqwest::Method::from_bytes(self.method.as_bytes()).unwrap_or(reqwest::Method::GET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist(rules: &[&str]) -> NetworkCapability {
+        NetworkCapability::DomainAllowlist(rules.iter().map(|r| r.to_string()).collect())
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rule = NetworkRule::parse("GET,head https://*.github.com/repos/*").unwrap();
+        assert_eq!(
+            rule.methods,
+            Some(vec!["GET".to_string(), "HEAD".to_string()])
+        );
+        assert_eq!(rule.host, HostPattern::Subdomains("github.com".to_string()));
+        assert_eq!(rule.path.as_deref(), Some("/repos/*"));
+
+        let bare = NetworkRule::parse("api.github.com").unwrap();
+        assert_eq!(bare.scheme, "https");
+        assert_eq!(bare.port, None);
+
+        let local = NetworkRule::parse("http://[::1]:8080").unwrap();
+        assert_eq!(local.host, HostPattern::Exact("[::1]".to_string()));
+        assert_eq!(local.port, Some(Some(8080)));
+
+        for bad in [
+            "ftp://example.com",
+            "*.*.com",
+            "api.*.com",
+            "*.10.0.0.1",
+            "G-T x.com",
+            "x.com:99999",
+        ] {
+            assert!(NetworkRule::parse(bad).is_err(), "{} parsed", bad);
+        }
+    }
+
+    #[test]
+    fn test_wildcard_subdomains() {
+        let cap = allowlist(&["https://*.github.com"]);
+        assert!(authorize(&cap, "https://api.github.com/user", "GET").is_ok());
+        assert!(authorize(&cap, "https://uploads.github.com/x", "POST").is_ok());
+        assert!(authorize(&cap, "https://a.b.github.com/", "GET").is_ok());
+        assert!(matches!(
+            authorize(&cap, "https://github.com/", "GET"),
+            Err(NetworkError::HostNotAllowed(_))
+        ));
+        assert!(matches!(
+            authorize(&cap, "https://evilgithub.com/", "GET"),
+            Err(NetworkError::HostNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_paths_and_methods() {
+        let cap = allowlist(&["GET https://*.github.com/repos/*"]);
+        assert!(authorize(&cap, "https://api.github.com/repos/a/b?page=2", "get").is_ok());
+        assert!(matches!(
+            authorize(&cap, "https://api.github.com/user", "GET"),
+            Err(NetworkError::UrlNotAllowed(_))
+        ));
+        assert!(matches!(
+            authorize(&cap, "https://api.github.com/repos/a/b", "DELETE"),
+            Err(NetworkError::MethodNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_plain_http_and_ports() {
+        let cap = allowlist(&["api.example.com", "http://localhost:8080"]);
+        assert!(matches!(
+            authorize(&cap, "http://api.example.com/", "GET"),
+            Err(NetworkError::InsecureScheme(_))
+        ));
+        assert!(matches!(
+            authorize(&cap, "https://api.example.com:8443/", "GET"),
+            Err(NetworkError::UrlNotAllowed(_))
+        ));
+        assert!(authorize(&cap, "https://api.example.com:443/", "GET").is_ok());
+        assert!(authorize(&cap, "http://localhost:8080/health", "GET").is_ok());
+        assert!(authorize(&cap, "http://localhost:9090/", "GET").is_err());
+
+        let any_port = allowlist(&["https://api.example.com:*"]);
+        assert!(authorize(&any_port, "https://api.example.com:8443/", "GET").is_ok());
+    }
+
+    #[test]
+    fn test_local_hosts_need_explicit_rules() {
+        let unrestricted = NetworkCapability::Unrestricted;
+        for url in [
+            "https://localhost/",
+            "https://127.0.0.1/",
+            "https://2130706433/",
+            "https://[::1]/",
+            "https://169.254.169.254/latest/meta-data",
+            "https://8.8.8.8/",
+            "http://example.com/",
+        ] {
+            assert!(
+                authorize(&unrestricted, url, "GET").is_err(),
+                "{} allowed",
+                url
+            );
+        }
+        assert!(authorize(&unrestricted, "https://example.com/", "POST").is_ok());
+
+        // Wildcards never reach local hosts
+        let cap = allowlist(&["https://*.localhost"]);
+        assert!(authorize(&cap, "https://app.localhost/", "GET").is_err());
+
+        let cap = allowlist(&["https://127.0.0.1"]);
+        assert!(
+            authorize(&cap, "https://127.0.0.1/", "GET")
+                .unwrap()
+                .allow_local
+        );
+    }
+
+    #[test]
+    fn test_none_denies() {
+        assert!(matches!(
+            authorize(&NetworkCapability::None, "https://example.com", "GET"),
+            Err(NetworkError::NoAccess)
+        ));
+        assert!(matches!(
+            authorize(
+                &NetworkCapability::Unrestricted,
+                "file:///etc/passwd",
+                "GET"
+            ),
+            Err(NetworkError::UnsupportedScheme(_))
+        ));
+    }
+
+    #[test]
+    fn test_public_addresses() {
+        for private in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_address(private.parse().unwrap()), "{}", private);
+        }
+        for public in ["93.184.216.34", "140.82.112.3", "2606:4700::1111"] {
+            assert!(is_public_address(public.parse().unwrap()), "{}", public);
+        }
+    }
+
+    #[test]
+    fn test_rebinding_to_private_address_is_blocked() {
+        let request = authorize(
+            &allowlist(&["api.example.com"]),
+            "https://api.example.com/",
+            "GET",
+        )
+        .unwrap();
+        let rebound = vec![
+            "93.184.216.34:443".parse().unwrap(),
+            "127.0.0.1:443".parse().unwrap(),
+        ];
+        assert!(matches!(
+            request.clone().vet(rebound),
+            Err(NetworkError::BlockedAddress { .. })
+        ));
+
+        let pinned = request
+            .vet(vec!["93.184.216.34:443".parse().unwrap()])
+            .unwrap();
+        assert_eq!(pinned.addrs, vec!["93.184.216.34:443".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_explicit_localhost_may_resolve_to_loopback() {
+        let request = authorize(
+            &allowlist(&["http://localhost:3000"]),
+            "http://localhost:3000/",
+            "GET",
+        )
+        .unwrap();
+        assert!(request.vet(vec!["127.0.0.1:3000".parse().unwrap()]).is_ok());
+    }
+
+    #[test]
+    fn test_pinned_clients_are_reused() {
+        let capability = allowlist(&["cache.example.com"]);
+        let pinned = |method: &str, addrs: &[&str]| {
+            authorize(&capability, "https://cache.example.com/", method)
+                .unwrap()
+                .vet(addrs.iter().map(|a| a.parse().unwrap()).collect())
+                .unwrap()
+        };
+        let cached = || CLIENTS.with(|clients| clients.borrow().len());
+
+        let first = pinned("GET", &["93.184.216.34:443", "93.184.216.35:443"]);
+        let reordered = pinned("GET", &["93.184.216.35:443", "93.184.216.34:443"]);
+        assert_eq!(
+            first.client_key(&capability),
+            reordered.client_key(&capability)
+        );
+        first.client(&capability).unwrap();
+        reordered.client(&capability).unwrap();
+        assert_eq!(cached(), 1);
+
+        // A different method or rule set changes the redirect policy
+        pinned("POST", &["93.184.216.34:443", "93.184.216.35:443"])
+            .client(&capability)
+            .unwrap();
+        assert_ne!(
+            first.client_key(&capability),
+            first.client_key(&NetworkCapability::Unrestricted)
+        );
+        assert_eq!(cached(), 2);
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("/repos/*", "/repos/a/b"));
+        assert!(!wildcard_match("/repos/*", "/repo"));
+        assert!(wildcard_match("/a/*/c", "/a/b/c"));
+        assert!(!wildcard_match("/a/*/c", "/a/b/d"));
+        assert!(wildcard_match("/exact", "/exact"));
+        assert!(!wildcard_match("/exact", "/exact/more"));
+        assert!(wildcard_match("*.json", "/x/y.json"));
+    }
+}
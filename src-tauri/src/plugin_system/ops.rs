@@ -2,16 +2,34 @@
 //
 // deno_core ops that bridge plugin JavaScript API calls to actual Rust operations.
 // Each op validates plugin capabilities before performing the operation.
-// Ops run synchronously on the worker thread.
+// Sync ops run directly on the worker thread; `*_async` ops return Promises
+// and are driven by the worker's event loop. The sync fetch and command ops
+// hold the worker thread, and every isolate on it, until they return.
 
+use crate::plugin_system::audit::AuditEvent;
 use crate::plugin_system::capabilities::PluginCapabilities;
+use crate::plugin_system::consent::{
+    ConsentDecision, ConsentError, ConsentRequest, CONSENT_REQUEST_EVENT,
+};
+use crate::plugin_system::grants::{
+    GrantError, Permission, PermissionRequest, PERMISSION_REQUEST_EVENT,
+};
+use crate::plugin_system::host::{Notification, PluginHost, StatusBarItem};
+use crate::plugin_system::network::AuthorizedRequest;
+use crate::plugin_system::policy::{self, PolicyContext, PolicyEngine, PolicyError};
+use crate::plugin_system::sandbox::ResourceLimits;
+use crate::plugin_system::worker::{BudgetPause, ExecutionBudget};
 use deno_core::op2;
 use deno_core::OpState;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter};
+use std::time::SystemTime;
+use tokio::sync::broadcast;
 
 /// Shared editor state accessible from both frontend (via Tauri commands) and plugin ops.
 /// Frontend pushes updates; plugin ops read from it synchronously.
@@ -28,11 +46,67 @@ pub type EditorStateHandle = Arc<Mutex<SharedEditorState>>;
 pub struct PluginOpState {
     pub plugin_id: String,
     pub capabilities: PluginCapabilities,
+    /// Effective limits for this plugin (fetch body size, concurrent commands)
+    pub limits: ResourceLimits,
     pub workspace_root: PathBuf,
-    pub app_handle: AppHandle,
+    /// The app the plugin runs in (UI events, shared services)
+    pub host: Arc<dyn PluginHost>,
     pub editor_state: EditorStateHandle,
 }
 
+impl PluginOpState {
+    /// Start auditing an op made by this plugin; it is recorded through the
+    /// host's policy engine, like the Tauri commands.
+    fn begin_audit(&self) -> OpAudit {
+        OpAudit {
+            policy: self.host.policy_engine(),
+            plugin_id: self.plugin_id.clone(),
+            started: SystemTime::now(),
+            resolved: None,
+        }
+    }
+
+    /// The calling plugin, as the policy engine sees it
+    fn policy_context(&self) -> PolicyContext<'_> {
+        PolicyContext {
+            plugin_id: &self.plugin_id,
+            capabilities: &self.capabilities,
+            workspace_root: &self.workspace_root,
+            limits: &self.limits,
+        }
+    }
+
+    /// The policy engine shared with the Tauri command path; without one every
+    /// privileged op is refused.
+    fn policy(&self) -> Result<Arc<PolicyEngine>, deno_core::error::AnyError> {
+        self.host.policy_engine().ok_or_else(|| {
+            deno_core::error::generic_error("Permission denied: no policy engine available")
+        })
+    }
+}
+
+fn policy_error(e: PolicyError) -> deno_core::error::AnyError {
+    deno_core::error::generic_error(e.to_string())
+}
+
+/// Wait for `future` from a sync op. It runs on a scoped thread with its own
+/// runtime, since the worker thread may already be inside the pool's `block_on`.
+fn run_blocking<T: Send>(
+    future: impl Future<Output = Result<T, deno_core::error::AnyError>> + Send,
+) -> Result<T, deno_core::error::AnyError> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?
+                    .block_on(future)
+            })
+            .join()
+            .unwrap_or_else(|_| Err(deno_core::error::generic_error("Sync op panicked")))
+    })
+}
+
 // ============================================================================
 // EXTENSION DEFINITION
 // ============================================================================
@@ -41,17 +115,69 @@ deno_core::extension!(
     skretchpad_plugin_ops,
     ops = [
         op_plugin_read_file,
+        op_plugin_read_file_async,
         op_plugin_write_file,
         op_plugin_list_files,
         op_plugin_fetch,
+        op_plugin_fetch_async,
         op_plugin_execute_command,
+        op_plugin_execute_command_async,
+        op_plugin_request_permission,
         op_plugin_show_notification,
         op_plugin_set_status_bar,
         op_plugin_get_editor_content,
         op_plugin_get_active_file,
+        op_plugin_audit_subscribe,
+        op_plugin_audit_poll,
     ],
 );
 
+// ============================================================================
+// AUDIT
+// ============================================================================
+
+/// An op in progress, added to the audit trail once it finishes
+struct OpAudit {
+    /// `None` only when the host has no policy engine, which refuses every op
+    policy: Option<Arc<PolicyEngine>>,
+    plugin_id: String,
+    started: SystemTime,
+    /// Canonical path the op was authorized for
+    resolved: Option<PathBuf>,
+}
+
+impl OpAudit {
+    /// Record `path` as the op's resource instead of the plugin-supplied one.
+    fn resolved(&mut self, path: &Path) {
+        self.resolved = Some(path.to_path_buf());
+    }
+
+    /// Record the op's outcome; denied and failed ops keep the error as the reason.
+    fn finish<T>(
+        self,
+        operation: &str,
+        resource: &str,
+        result: &Result<T, deno_core::error::AnyError>,
+    ) {
+        self.finish_with(
+            operation,
+            resource,
+            result.as_ref().err().map(|e| e.to_string()),
+        );
+    }
+
+    fn finish_with(self, operation: &str, resource: &str, error: Option<String>) {
+        let Some(policy) = self.policy else {
+            return;
+        };
+        let resource = match &self.resolved {
+            Some(path) => path.display().to_string(),
+            None => resource.to_string(),
+        };
+        policy.record(&self.plugin_id, operation, &resource, self.started, error);
+    }
+}
+
 // ============================================================================
 // FILESYSTEM OPS
 // ============================================================================
@@ -63,29 +189,59 @@ pub fn op_plugin_read_file(
     #[string] path: String,
 ) -> Result<String, deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
-    let workspace = plugin_state.workspace_root.to_string_lossy().to_string();
-
-    // Canonicalize path for security
-    let canonical = std::fs::canonicalize(&path)
-        .map_err(|e| deno_core::error::generic_error(format!("Invalid path '{}': {}", path, e)))?;
-    let canonical_str = canonical.to_string_lossy().to_string();
+    let mut audit = plugin_state.begin_audit();
+    let result = read_file(plugin_state, &mut audit, &path);
+    audit.finish("fs.read", &path, &result);
+    result
+}
 
-    // Validate capability
-    if !plugin_state
-        .capabilities
-        .filesystem
-        .can_read(&canonical_str, &workspace)
-    {
-        return Err(deno_core::error::generic_error(format!(
-            "Permission denied: plugin '{}' cannot read '{}'",
-            plugin_state.plugin_id, path
-        )));
-    }
+fn read_file(
+    plugin_state: &PluginOpState,
+    audit: &mut OpAudit,
+    path: &str,
+) -> Result<String, deno_core::error::AnyError> {
+    let canonical = plugin_state
+        .policy()?
+        .authorize_read(&plugin_state.policy_context(), path)
+        .map_err(policy_error)?;
+    audit.resolved(&canonical);
 
     std::fs::read_to_string(&canonical)
         .map_err(|e| deno_core::error::generic_error(format!("Failed to read '{}': {}", path, e)))
 }
 
+#[op2(async)]
+#[string]
+pub async fn op_plugin_read_file_async(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+) -> Result<String, deno_core::error::AnyError> {
+    let mut audit = state.borrow().borrow::<PluginOpState>().begin_audit();
+    let result = read_file_async(&state, &mut audit, &path).await;
+    audit.finish("fs.read", &path, &result);
+    result
+}
+
+async fn read_file_async(
+    state: &Rc<RefCell<OpState>>,
+    audit: &mut OpAudit,
+    path: &str,
+) -> Result<String, deno_core::error::AnyError> {
+    let canonical = {
+        let state = state.borrow();
+        let plugin_state = state.borrow::<PluginOpState>();
+        plugin_state
+            .policy()?
+            .authorize_read(&plugin_state.policy_context(), path)
+            .map_err(policy_error)?
+    };
+    audit.resolved(&canonical);
+
+    tokio::fs::read_to_string(&canonical)
+        .await
+        .map_err(|e| deno_core::error::generic_error(format!("Failed to read '{}': {}", path, e)))
+}
+
 #[op2(fast)]
 pub fn op_plugin_write_file(
     state: &mut OpState,
@@ -93,45 +249,26 @@ pub fn op_plugin_write_file(
     #[string] content: String,
 ) -> Result<(), deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
-    let workspace = plugin_state.workspace_root.to_string_lossy().to_string();
-
-    // For new files, canonicalize the parent directory
-    let raw_path = PathBuf::from(&path);
-    let canonical = if raw_path.exists() {
-        std::fs::canonicalize(&raw_path).map_err(|e| {
-            deno_core::error::generic_error(format!("Invalid path '{}': {}", path, e))
-        })?
-    } else {
-        let parent = raw_path
-            .parent()
-            .ok_or_else(|| deno_core::error::generic_error(format!("Invalid path: {}", path)))?;
-        if !parent.exists() {
-            return Err(deno_core::error::generic_error(format!(
-                "Parent directory does not exist: {}",
-                parent.display()
-            )));
-        }
-        let canonical_parent = std::fs::canonicalize(parent)
-            .map_err(|e| deno_core::error::generic_error(format!("Invalid parent path: {}", e)))?;
-        canonical_parent.join(raw_path.file_name().ok_or_else(|| {
-            deno_core::error::generic_error(format!("Invalid filename in path: {}", path))
-        })?)
-    };
-    let canonical_str = canonical.to_string_lossy().to_string();
-
-    // Validate capability
-    if !plugin_state
-        .capabilities
-        .filesystem
-        .can_write(&canonical_str, &workspace)
-    {
-        return Err(deno_core::error::generic_error(format!(
-            "Permission denied: plugin '{}' cannot write to '{}'",
-            plugin_state.plugin_id, path
-        )));
-    }
+    let mut audit = plugin_state.begin_audit();
+    let result = write_file(plugin_state, &mut audit, &path, &content);
+    audit.finish("fs.write", &path, &result);
+    result
+}
 
-    std::fs::write(&canonical, &content)
+fn write_file(
+    plugin_state: &PluginOpState,
+    audit: &mut OpAudit,
+    path: &str,
+    content: &str,
+) -> Result<(), deno_core::error::AnyError> {
+    // New files resolve through their deepest existing ancestor
+    let canonical = plugin_state
+        .policy()?
+        .authorize_write(&plugin_state.policy_context(), path)
+        .map_err(policy_error)?;
+    audit.resolved(&canonical);
+
+    std::fs::write(&canonical, content)
         .map_err(|e| deno_core::error::generic_error(format!("Failed to write '{}': {}", path, e)))
 }
 
@@ -151,29 +288,36 @@ pub fn op_plugin_list_files(
     #[string] directory: String,
 ) -> Result<Vec<OpDirectoryEntry>, deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
-    let workspace = plugin_state.workspace_root.to_string_lossy().to_string();
-
-    let canonical = std::fs::canonicalize(&directory).map_err(|e| {
-        deno_core::error::generic_error(format!("Invalid path '{}': {}", directory, e))
-    })?;
-    let canonical_str = canonical.to_string_lossy().to_string();
+    let mut audit = plugin_state.begin_audit();
+    let result = list_files(plugin_state, &mut audit, &directory);
+    audit.finish("fs.list", &directory, &result);
+    result
+}
 
-    if !plugin_state
-        .capabilities
-        .filesystem
-        .can_read(&canonical_str, &workspace)
-    {
-        return Err(deno_core::error::generic_error(format!(
-            "Permission denied: plugin '{}' cannot read '{}'",
-            plugin_state.plugin_id, directory
-        )));
-    }
+fn list_files(
+    plugin_state: &PluginOpState,
+    audit: &mut OpAudit,
+    directory: &str,
+) -> Result<Vec<OpDirectoryEntry>, deno_core::error::AnyError> {
+    let canonical = plugin_state
+        .policy()?
+        .authorize_read(&plugin_state.policy_context(), directory)
+        .map_err(policy_error)?;
+    audit.resolved(&canonical);
+    let workspace = plugin_state.workspace_root.to_string_lossy().to_string();
 
     let mut entries = Vec::new();
     for entry in std::fs::read_dir(&canonical).map_err(|e| {
         deno_core::error::generic_error(format!("Failed to list '{}': {}", directory, e))
     })? {
         let entry = entry.map_err(|e| deno_core::error::generic_error(e.to_string()))?;
+        if plugin_state
+            .capabilities
+            .filesystem
+            .denies(&entry.path().to_string_lossy(), &workspace)
+        {
+            continue;
+        }
         let metadata = entry
             .metadata()
             .map_err(|e| deno_core::error::generic_error(e.to_string()))?;
@@ -212,6 +356,10 @@ pub struct OpFetchParams {
     pub body: Option<String>,
 }
 
+fn fetch_error(e: impl std::fmt::Display) -> deno_core::error::AnyError {
+    deno_core::error::generic_error(format!("Fetch failed: {}", e))
+}
+
 #[op2]
 #[serde]
 pub fn op_plugin_fetch(
@@ -219,32 +367,85 @@ pub fn op_plugin_fetch(
     #[serde] params: OpFetchParams,
 ) -> Result<OpFetchResponse, deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
+    let audit = plugin_state.begin_audit();
+    let url = params.url.clone();
+    let result = fetch(plugin_state, params);
+    audit.finish("network.fetch", &url, &result);
+    result
+}
 
-    // Parse URL and validate domain
-    let url_parsed = url::Url::parse(&params.url).map_err(|e| {
-        deno_core::error::generic_error(format!("Invalid URL '{}': {}", params.url, e))
-    })?;
-    let domain = url_parsed.host_str().ok_or_else(|| {
-        deno_core::error::generic_error(format!("No host in URL: {}", params.url))
-    })?;
+fn fetch(
+    plugin_state: &PluginOpState,
+    params: OpFetchParams,
+) -> Result<OpFetchResponse, deno_core::error::AnyError> {
+    let policy = plugin_state.policy()?;
+    let ctx = plugin_state.policy_context();
+    let method = params.method.as_deref().unwrap_or("GET");
+    let authorized = policy
+        .authorize_fetch(&ctx, &params.url, method)
+        .map_err(policy_error)?;
+    run_blocking(send_fetch(&policy, &ctx, authorized, params))
+}
 
-    if !plugin_state.capabilities.network.can_access(domain) {
-        return Err(deno_core::error::generic_error(format!(
-            "Permission denied: plugin '{}' cannot access domain '{}'",
-            plugin_state.plugin_id, domain
-        )));
-    }
+#[op2(async)]
+#[serde]
+pub async fn op_plugin_fetch_async(
+    state: Rc<RefCell<OpState>>,
+    #[serde] params: OpFetchParams,
+) -> Result<OpFetchResponse, deno_core::error::AnyError> {
+    let audit = state.borrow().borrow::<PluginOpState>().begin_audit();
+    let url = params.url.clone();
+    let result = fetch_async(&state, params).await;
+    audit.finish("network.fetch", &url, &result);
+    result
+}
 
-    // Build and execute request using blocking client
-    let client = reqwest::blocking::Client::new();
-    let method = params
-        .method
-        .as_deref()
-        .unwrap_or("GET")
-        .parse::<reqwest::Method>()
-        .map_err(|e| deno_core::error::generic_error(format!("Invalid method: {}", e)))?;
+async fn fetch_async(
+    state: &Rc<RefCell<OpState>>,
+    params: OpFetchParams,
+) -> Result<OpFetchResponse, deno_core::error::AnyError> {
+    let (policy, authorized, plugin_id, capabilities, workspace_root, limits) = {
+        let state = state.borrow();
+        let plugin_state = state.borrow::<PluginOpState>();
+        let policy = plugin_state.policy()?;
+        let method = params.method.as_deref().unwrap_or("GET");
+        let authorized = policy
+            .authorize_fetch(&plugin_state.policy_context(), &params.url, method)
+            .map_err(policy_error)?;
+        (
+            policy,
+            authorized,
+            plugin_state.plugin_id.clone(),
+            plugin_state.capabilities.clone(),
+            plugin_state.workspace_root.clone(),
+            plugin_state.limits.clone(),
+        )
+    };
+    let ctx = PolicyContext {
+        plugin_id: &plugin_id,
+        capabilities: &capabilities,
+        workspace_root: &workspace_root,
+        limits: &limits,
+    };
+    send_fetch(&policy, &ctx, authorized, params).await
+}
 
-    let mut request = client.request(method, &params.url);
+/// Resolve and send an authorized request, reading at most the plugin's
+/// `max_fetch_body_size` of the response.
+async fn send_fetch(
+    policy: &PolicyEngine,
+    ctx: &PolicyContext<'_>,
+    authorized: AuthorizedRequest,
+    params: OpFetchParams,
+) -> Result<OpFetchResponse, deno_core::error::AnyError> {
+    let target = authorized.resolve().await.map_err(fetch_error)?;
+
+    let client = target
+        .client(&ctx.capabilities.network)
+        .map_err(fetch_error)?;
+    let mut request = client
+        .request(target.method(), target.url.clone())
+        .timeout(policy.timeout());
 
     if let Some(headers) = params.headers {
         for (key, value) in headers {
@@ -256,9 +457,7 @@ pub fn op_plugin_fetch(
         request = request.body(body);
     }
 
-    let response = request
-        .send()
-        .map_err(|e| deno_core::error::generic_error(format!("Fetch failed: {}", e)))?;
+    let response = request.send().await.map_err(fetch_error)?;
 
     let status = response.status().as_u16();
     let headers: HashMap<String, String> = response
@@ -266,9 +465,10 @@ pub fn op_plugin_fetch(
         .iter()
         .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
         .collect();
-    let body = response.text().map_err(|e| {
-        deno_core::error::generic_error(format!("Failed to read response body: {}", e))
-    })?;
+    let body = policy
+        .read_body(ctx, response)
+        .await
+        .map_err(policy_error)?;
 
     Ok(OpFetchResponse {
         status,
@@ -277,17 +477,6 @@ pub fn op_plugin_fetch(
     })
 }
 
-// ============================================================================
-// ARGUMENT SANITIZATION
-// ============================================================================
-
-/// Sanitize command arguments by removing shell metacharacters
-pub(crate) fn sanitize_args(args: &[String]) -> Vec<String> {
-    args.iter()
-        .map(|arg| arg.replace(&['|', '&', ';', '>', '<', '`', '$', '\n', '\r'][..], ""))
-        .collect()
-}
-
 // ============================================================================
 // COMMAND EXECUTION OPS
 // ============================================================================
@@ -300,42 +489,242 @@ pub struct OpCommandOutput {
     pub status: i32,
 }
 
+impl From<std::process::Output> for OpCommandOutput {
+    fn from(output: std::process::Output) -> Self {
+        Self {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            status: output.status.code().unwrap_or(-1),
+        }
+    }
+}
+
+fn emit_consent_request(host: &dyn PluginHost, request: &ConsentRequest) -> Result<(), String> {
+    host.emit(CONSENT_REQUEST_EVENT, serde_json::json!(request))
+}
+
+/// Stop the isolate's CPU clock while the op waits on the user.
+fn pause_execution_budget(state: &Rc<RefCell<OpState>>) -> Option<BudgetPause> {
+    state
+        .borrow()
+        .try_borrow::<ExecutionBudget>()
+        .map(ExecutionBudget::pause)
+}
+
+fn consent_error(e: ConsentError) -> deno_core::error::AnyError {
+    deno_core::error::generic_error(format!("Permission denied: {}", e))
+}
+
+fn command_error(command: &str, e: std::io::Error) -> deno_core::error::AnyError {
+    deno_core::error::generic_error(format!("Failed to execute '{}': {}", command, e))
+}
+
 #[op2]
 #[serde]
 pub fn op_plugin_execute_command(
     state: &mut OpState,
     #[string] command: String,
     #[serde] args: Vec<String>,
+) -> Result<OpCommandOutput, deno_core::error::AnyError> {
+    let audit = state.borrow::<PluginOpState>().begin_audit();
+    let result = execute_command(state, &command, &args);
+    audit.finish("command.execute", &command, &result);
+    result
+}
+
+fn execute_command(
+    state: &OpState,
+    command: &str,
+    args: &[String],
 ) -> Result<OpCommandOutput, deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
+    let policy = plugin_state.policy()?;
+    let ctx = plugin_state.policy_context();
+    let authorized = policy
+        .authorize_command(&ctx, command, args, None)
+        .map_err(policy_error)?;
+    let slot = policy.command_slot(&ctx).map_err(policy_error)?;
+    let host = plugin_state.host.clone();
+    let paused = state
+        .try_borrow::<ExecutionBudget>()
+        .map(ExecutionBudget::pause);
+    // Holds this plugin's thread until the user answers
+    run_blocking(async {
+        policy
+            .confirm(&ctx, &authorized, command, |req| {
+                emit_consent_request(host.as_ref(), req)
+            })
+            .await
+            .map_err(policy_error)
+    })?;
+    drop(paused);
 
-    // Validate command is in allowlist
-    if !plugin_state.capabilities.commands.can_execute(&command) {
-        return Err(deno_core::error::generic_error(format!(
-            "Permission denied: plugin '{}' cannot execute command '{}'",
-            plugin_state.plugin_id, command
-        )));
-    }
+    let output = run_blocking(async {
+        policy
+            .output(&authorized, &slot)
+            .await
+            .map_err(|e| command_error(command, e))
+    })?;
+    Ok(output.into())
+}
 
-    // Sanitize arguments (prevent injection)
-    let sanitized_args = sanitize_args(&args);
+#[op2(async)]
+#[serde]
+pub async fn op_plugin_execute_command_async(
+    state: Rc<RefCell<OpState>>,
+    #[string] command: String,
+    #[serde] args: Vec<String>,
+) -> Result<OpCommandOutput, deno_core::error::AnyError> {
+    let audit = state.borrow().borrow::<PluginOpState>().begin_audit();
+    let result = execute_command_async(&state, &command, &args).await;
+    audit.finish("command.execute", &command, &result);
+    result
+}
 
-    let output = std::process::Command::new(&command)
-        .args(&sanitized_args)
-        .output()
-        .map_err(|e| {
-            deno_core::error::generic_error(format!("Failed to execute '{}': {}", command, e))
-        })?;
+async fn execute_command_async(
+    state: &Rc<RefCell<OpState>>,
+    command: &str,
+    args: &[String],
+) -> Result<OpCommandOutput, deno_core::error::AnyError> {
+    let (policy, authorized, plugin_id, capabilities, workspace_root, limits, host) = {
+        let state = state.borrow();
+        let plugin_state = state.borrow::<PluginOpState>();
+        let policy = plugin_state.policy()?;
+        let authorized = policy
+            .authorize_command(&plugin_state.policy_context(), command, args, None)
+            .map_err(policy_error)?;
+        (
+            policy,
+            authorized,
+            plugin_state.plugin_id.clone(),
+            plugin_state.capabilities.clone(),
+            plugin_state.workspace_root.clone(),
+            plugin_state.limits.clone(),
+            plugin_state.host.clone(),
+        )
+    };
+    let ctx = PolicyContext {
+        plugin_id: &plugin_id,
+        capabilities: &capabilities,
+        workspace_root: &workspace_root,
+        limits: &limits,
+    };
+    let slot = policy.command_slot(&ctx).map_err(policy_error)?;
+    let paused = pause_execution_budget(state);
+    policy
+        .confirm(&ctx, &authorized, command, |req| {
+            emit_consent_request(host.as_ref(), req)
+        })
+        .await
+        .map_err(policy_error)?;
+    drop(paused);
+
+    let output = policy
+        .output(&authorized, &slot)
+        .await
+        .map_err(|e| command_error(command, e))?;
+    Ok(output.into())
+}
 
-    Ok(OpCommandOutput {
-        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-        status: output.status.code().unwrap_or(-1),
-    })
+// ============================================================================
+// PERMISSION OPS
+// ============================================================================
+
+/// Ask the user for a permission the plugin does not have yet. Resolves to
+/// whether the plugin has it afterwards; "allow always" is saved in the grant
+/// store and "allow once" is kept there as a session grant.
+#[op2(async)]
+pub async fn op_plugin_request_permission(
+    state: Rc<RefCell<OpState>>,
+    #[serde] permission: Permission,
+    #[string] reason: String,
+) -> Result<bool, deno_core::error::AnyError> {
+    let audit = state.borrow().borrow::<PluginOpState>().begin_audit();
+    let resource = serde_json::to_string(&permission).unwrap_or_default();
+    let result = request_permission(&state, permission, reason).await;
+    let error = match &result {
+        Ok(true) => None,
+        Ok(false) => Some("Permission not granted".to_string()),
+        Err(e) => Some(e.to_string()),
+    };
+    audit.finish_with("permission.request", &resource, error);
+    result
+}
+
+async fn request_permission(
+    state: &Rc<RefCell<OpState>>,
+    permission: Permission,
+    reason: String,
+) -> Result<bool, deno_core::error::AnyError> {
+    let permission_error = |e: GrantError| deno_core::error::generic_error(e.to_string());
+    permission.validate().map_err(permission_error)?;
+
+    let (request, broker, grants, host, workspace_root) = {
+        let state = state.borrow();
+        let plugin_state = state.borrow::<PluginOpState>();
+        if permission.is_covered_by(&plugin_state.capabilities, &plugin_state.workspace_root) {
+            return Ok(true);
+        }
+        let host = plugin_state.host.clone();
+        let (Some(broker), Some(grants)) = (host.consent_broker(), host.grant_store()) else {
+            return Err(deno_core::error::generic_error(
+                "Permission requests are not available",
+            ));
+        };
+        (
+            PermissionRequest::new(
+                &plugin_state.plugin_id,
+                permission.clone(),
+                Some(reason).filter(|r| !r.is_empty()),
+            ),
+            broker,
+            grants,
+            host,
+            plugin_state.workspace_root.clone(),
+        )
+    };
+
+    let paused = pause_execution_budget(state);
+    let decision = match broker
+        .ask(&request.id, || {
+            host.emit(PERMISSION_REQUEST_EVENT, serde_json::json!(request))
+        })
+        .await
+    {
+        Ok(decision) => decision,
+        Err(ConsentError::TimedOut { .. }) => return Ok(false),
+        Err(e) => return Err(consent_error(e)),
+    };
+    drop(paused);
+
+    // Both answers are recorded so the grant shows in `list_plugin_grants` and is
+    // kept when the sandbox's capabilities are recomputed
+    let grant = match decision {
+        ConsentDecision::Deny => return Ok(false),
+        ConsentDecision::AllowOnce => {
+            grants.grant_for_session(&request.plugin_id, &workspace_root, permission.clone())
+        }
+        ConsentDecision::AllowAlways => grants.grant(
+            &request.plugin_id,
+            &workspace_root,
+            permission.clone(),
+            None,
+        ),
+    }
+    .map_err(permission_error)?;
+
+    let mut state = state.borrow_mut();
+    let plugin_state = state.borrow_mut::<PluginOpState>();
+    permission.apply(
+        &mut plugin_state.capabilities,
+        &workspace_root,
+        grant.program.as_deref(),
+    );
+    Ok(true)
 }
 
 // ============================================================================
-// UI OPS (fire-and-forget via AppHandle.emit)
+// UI OPS (fire-and-forget via the plugin host)
 // ============================================================================
 
 #[op2(fast)]
@@ -345,25 +734,30 @@ pub fn op_plugin_show_notification(
     #[string] level: String,
 ) -> Result<(), deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
+    let audit = plugin_state.begin_audit();
+    let result = show_notification(plugin_state, &message, &level);
+    audit.finish("ui.notification", &level, &result);
+    result
+}
 
-    if !plugin_state.capabilities.ui.notifications {
-        return Err(deno_core::error::generic_error(format!(
-            "Permission denied: plugin '{}' does not have notification capability",
-            plugin_state.plugin_id
-        )));
-    }
+fn show_notification(
+    plugin_state: &PluginOpState,
+    message: &str,
+    level: &str,
+) -> Result<(), deno_core::error::AnyError> {
+    plugin_state
+        .policy()?
+        .authorize_ui(&plugin_state.policy_context(), "notification")
+        .map_err(policy_error)?;
 
     plugin_state
-        .app_handle
-        .emit(
-            "plugin:notification",
-            serde_json::json!({
-                "plugin_id": plugin_state.plugin_id,
-                "title": plugin_state.plugin_id,
-                "message": message,
-                "level": level,
-            }),
-        )
+        .host
+        .show_notification(&Notification {
+            plugin_id: plugin_state.plugin_id.clone(),
+            title: plugin_state.plugin_id.clone(),
+            message: message.to_string(),
+            level: level.to_string(),
+        })
         .map_err(|e| {
             deno_core::error::generic_error(format!("Failed to emit notification: {}", e))
         })?;
@@ -379,26 +773,32 @@ pub fn op_plugin_set_status_bar(
     #[string] tooltip: String,
 ) -> Result<(), deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
+    let audit = plugin_state.begin_audit();
+    let result = set_status_bar(plugin_state, &id, &text, &tooltip);
+    audit.finish("ui.status_bar", &id, &result);
+    result
+}
 
-    if !plugin_state.capabilities.ui.status_bar {
-        return Err(deno_core::error::generic_error(format!(
-            "Permission denied: plugin '{}' does not have status_bar capability",
-            plugin_state.plugin_id
-        )));
-    }
+fn set_status_bar(
+    plugin_state: &PluginOpState,
+    id: &str,
+    text: &str,
+    tooltip: &str,
+) -> Result<(), deno_core::error::AnyError> {
+    plugin_state
+        .policy()?
+        .authorize_ui(&plugin_state.policy_context(), "status_bar")
+        .map_err(policy_error)?;
 
     plugin_state
-        .app_handle
-        .emit(
-            "plugin:status_bar:add",
-            serde_json::json!({
-                "plugin_id": plugin_state.plugin_id,
-                "id": id,
-                "text": text,
-                "tooltip": tooltip,
-                "priority": 0,
-            }),
-        )
+        .host
+        .add_status_bar_item(&StatusBarItem {
+            plugin_id: plugin_state.plugin_id.clone(),
+            id: id.to_string(),
+            text: text.to_string(),
+            tooltip: Some(tooltip.to_string()),
+            priority: 0,
+        })
         .map_err(|e| {
             deno_core::error::generic_error(format!("Failed to emit status bar update: {}", e))
         })?;
@@ -416,6 +816,19 @@ pub fn op_plugin_get_editor_content(
     state: &mut OpState,
 ) -> Result<serde_json::Value, deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
+    let audit = plugin_state.begin_audit();
+    let result = get_editor_content(plugin_state);
+    audit.finish("editor.get_content", "", &result);
+    result
+}
+
+fn get_editor_content(
+    plugin_state: &PluginOpState,
+) -> Result<serde_json::Value, deno_core::error::AnyError> {
+    plugin_state
+        .policy()?
+        .authorize_editor(&plugin_state.policy_context())
+        .map_err(policy_error)?;
 
     // Read from shared state -- no event round-trip needed
     let editor_state = plugin_state.editor_state.lock().map_err(|e| {
@@ -434,6 +847,19 @@ pub fn op_plugin_get_active_file(
     state: &mut OpState,
 ) -> Result<serde_json::Value, deno_core::error::AnyError> {
     let plugin_state = state.borrow::<PluginOpState>();
+    let audit = plugin_state.begin_audit();
+    let result = get_active_file(plugin_state);
+    audit.finish("editor.get_active_file", "", &result);
+    result
+}
+
+fn get_active_file(
+    plugin_state: &PluginOpState,
+) -> Result<serde_json::Value, deno_core::error::AnyError> {
+    plugin_state
+        .policy()?
+        .authorize_editor(&plugin_state.policy_context())
+        .map_err(policy_error)?;
 
     // Read from shared state -- no event round-trip needed
     let editor_state = plugin_state.editor_state.lock().map_err(|e| {
@@ -447,82 +873,82 @@ pub fn op_plugin_get_active_file(
 }
 
 // ============================================================================
-// TESTS
+// AUDIT OPS (security monitor plugins, behind the audit read capability)
 // ============================================================================
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    // ---- sanitize_args tests ----
-
-    #[test]
-    fn sanitize_strips_pipe() {
-        let args = vec!["git|rm".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["gitrm"]);
-    }
-
-    #[test]
-    fn sanitize_strips_semicolon() {
-        let args = vec!["git; rm -rf /".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["git rm -rf /"]);
-    }
-
-    #[test]
-    fn sanitize_strips_ampersand() {
-        let args = vec!["cmd && evil".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["cmd  evil"]);
-    }
-
-    #[test]
-    fn sanitize_strips_redirect_gt() {
-        let args = vec!["echo > /etc/passwd".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["echo  /etc/passwd"]);
-    }
+/// Most events one poll returns; the rest wait for the next poll
+const AUDIT_POLL_MAX: usize = 256;
 
-    #[test]
-    fn sanitize_strips_redirect_lt() {
-        let args = vec!["cmd < input".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["cmd  input"]);
-    }
+/// A plugin's subscription to the live audit stream
+struct AuditFeed(broadcast::Receiver<AuditEvent>);
 
-    #[test]
-    fn sanitize_strips_backtick() {
-        let args = vec!["`whoami`".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["whoami"]);
-    }
+/// Events returned by `op_plugin_audit_poll`
+#[derive(Debug, Serialize)]
+pub struct OpAuditBatch {
+    pub events: Vec<AuditEvent>,
+    /// Events missed because the plugin fell too far behind
+    pub dropped: u64,
+}
 
-    #[test]
-    fn sanitize_strips_dollar() {
-        let args = vec!["$HOME".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["HOME"]);
-    }
+/// Start receiving every plugin's audit events; replaces an earlier subscription.
+/// Events can be polled, and are also passed to the plugin's `audit` hook.
+#[op2(fast)]
+pub fn op_plugin_audit_subscribe(state: &mut OpState) -> Result<(), deno_core::error::AnyError> {
+    let receiver = {
+        let plugin_state = state.borrow::<PluginOpState>();
+        let audit = plugin_state.begin_audit();
+        let result = plugin_state.policy().and_then(|policy| {
+            policy
+                .authorize_audit_read(&plugin_state.policy_context())
+                .map_err(policy_error)?;
+            policy.subscribe_audit_hook(&plugin_state.plugin_id);
+            Ok(policy.subscribe())
+        });
+        audit.finish("audit.subscribe", "", &result);
+        result?
+    };
+    state.put(AuditFeed(receiver));
+    Ok(())
+}
 
-    #[test]
-    fn sanitize_strips_newlines() {
-        let args = vec!["line1\nline2\rline3".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["line1line2line3"]);
-    }
+/// Take the events recorded since the last poll without waiting. Polls are not
+/// audited themselves, or a monitor would mostly see its own polling.
+#[op2]
+#[serde]
+pub fn op_plugin_audit_poll(
+    state: &mut OpState,
+) -> Result<OpAuditBatch, deno_core::error::AnyError> {
+    // The capability may have been revoked since subscribing
+    policy::check_audit_read(&state.borrow::<PluginOpState>().capabilities)
+        .map_err(policy_error)?;
+    let AuditFeed(receiver) = state.try_borrow_mut::<AuditFeed>().ok_or_else(|| {
+        deno_core::error::generic_error("Not subscribed to audit events; call audit.subscribe()")
+    })?;
 
-    #[test]
-    fn sanitize_preserves_clean_arguments() {
-        let args = vec!["--branch".to_string(), "main".to_string(), "-v".to_string()];
-        assert_eq!(sanitize_args(&args), vec!["--branch", "main", "-v"]);
+    let mut batch = OpAuditBatch {
+        events: Vec::new(),
+        dropped: 0,
+    };
+    while batch.events.len() < AUDIT_POLL_MAX {
+        match receiver.try_recv() {
+            Ok(event) => batch.events.push(event),
+            Err(broadcast::error::TryRecvError::Lagged(missed)) => batch.dropped += missed,
+            Err(_) => break,
+        }
     }
+    Ok(batch)
+}
 
-    #[test]
-    fn sanitize_combined_injection() {
-        let args = vec!["git; rm -rf /".to_string()];
-        let sanitized = sanitize_args(&args);
-        assert!(!sanitized[0].contains(';'));
-        assert_eq!(sanitized[0], "git rm -rf /");
-    }
+// ============================================================================
+// TESTS
+// ============================================================================
 
-    #[test]
-    fn sanitize_empty_args() {
-        let args: Vec<String> = vec![];
-        assert_eq!(sanitize_args(&args), Vec::<String>::new());
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_system::audit::AuditLogger;
+    use crate::plugin_system::consent::ConsentBroker;
+    use std::time::Duration;
 
     // ---- OpDirectoryEntry tests ----
 
@@ -563,4 +989,63 @@ mod tests {
         assert_eq!(json["status"], 0);
         assert_eq!(json["stdout"], "hello\n");
     }
+
+    // ---- OpAudit tests ----
+
+    fn op_audit(logger: &Arc<AuditLogger>) -> OpAudit {
+        OpAudit {
+            policy: Some(Arc::new(PolicyEngine::new(
+                logger.clone(),
+                Arc::new(ConsentBroker::new(Duration::from_secs(1))),
+            ))),
+            plugin_id: "test-plugin".to_string(),
+            started: SystemTime::now(),
+            resolved: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn op_audit_records_allowed_op() {
+        let logger = Arc::new(AuditLogger::new(10));
+        let result: Result<String, deno_core::error::AnyError> = Ok("contents".to_string());
+        op_audit(&logger).finish("fs.read", "README.md", &result);
+
+        let events = logger.get_events_for_plugin("test-plugin").await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].operation, "fs.read");
+        assert_eq!(events[0].resource, "README.md");
+        assert!(events[0].success);
+        assert!(events[0].error.is_none());
+    }
+
+    #[tokio::test]
+    async fn op_audit_records_the_resolved_path() {
+        let logger = Arc::new(AuditLogger::new(10));
+        let mut audit = op_audit(&logger);
+        audit.resolved(Path::new("/workspace/src/main.rs"));
+        let result: Result<String, deno_core::error::AnyError> = Ok(String::new());
+        audit.finish("fs.read", "src/../src/main.rs", &result);
+
+        let events = logger.get_events().await;
+        assert_eq!(events[0].resource, "/workspace/src/main.rs");
+    }
+
+    #[tokio::test]
+    async fn op_audit_records_denial_with_reason() {
+        let logger = Arc::new(AuditLogger::new(10));
+        let denied = PolicyError::PermissionDenied {
+            operation: "command execution".to_string(),
+            capability: "commands.allowlist".to_string(),
+        };
+        let result: Result<OpCommandOutput, _> = Err(policy_error(denied));
+        op_audit(&logger).finish("command.execute", "rm", &result);
+
+        let events = logger.get_events().await;
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].success);
+        assert_eq!(
+            events[0].error.as_deref(),
+            Some("Permission denied: command execution requires commands.allowlist")
+        );
+    }
 }
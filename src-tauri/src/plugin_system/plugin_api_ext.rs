@@ -0,0 +1,10 @@
+// src-tauri/src/plugin_system/plugin_api_ext.rs
+//
+// The plugin API (js/plugin_api.js) as a JS-only extension. This file is also
+// compiled into build.rs, which runs the extension once to create the plugin
+// startup snapshot, so it must not depend on the rest of the crate.
+
+deno_core::extension!(
+    skretchpad_plugin_api,
+    js = [dir "../../js", "plugin_api.js"],
+);
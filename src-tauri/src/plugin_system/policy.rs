@@ -0,0 +1,1247 @@
+// src-tauri/src/plugin_system/policy.rs
+//
+// One policy engine for both ways a plugin reaches a privileged operation: the
+// Tauri commands in `api.rs` and the deno ops in `ops.rs`. Each path describes
+// the calling plugin with a `PolicyContext` and asks the engine to authorize the
+// operation. The engine applies the capability checks, the plugin's operation
+// rate limit, command confirmation, concurrency and timeouts and the fetch body
+// limit, and writes the audit trail, so a capability means the same thing
+// whichever way it is exercised.
+
+use crate::plugin_system::audit::{AuditEvent, AuditLogger};
+use crate::plugin_system::capabilities::{FilesystemCapability, PluginCapabilities};
+use crate::plugin_system::commands::{self, AuthorizedCommand, CommandError};
+use crate::plugin_system::consent::{ConsentBroker, ConsentError, ConsentRequest};
+use crate::plugin_system::network::{self, AuthorizedRequest, NetworkError};
+use crate::plugin_system::paths::{self, PathAccess};
+use crate::plugin_system::sandbox::{OperationTracker, PluginError, ResourceLimits};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+
+/// Upper bound for a plugin command or fetch.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Why the policy refused an operation
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    #[error("Permission denied: {operation} requires {capability}")]
+    PermissionDenied {
+        operation: String,
+        capability: String,
+    },
+
+    #[error("Invalid path '{path}': {reason}")]
+    InvalidPath { path: String, reason: String },
+
+    #[error("Path not allowed: {path}")]
+    PathNotAllowed { path: String },
+
+    #[error("Domain not allowed: {0}")]
+    DomainNotAllowed(String),
+
+    #[error("Network request not allowed: {0}")]
+    Network(NetworkError),
+
+    #[error("Command not allowed: {0}")]
+    CommandNotAllowed(String),
+
+    #[error("Command rejected: {0}")]
+    Command(CommandError),
+
+    #[error("Fetch failed: {0}")]
+    Fetch(String),
+
+    #[error(transparent)]
+    Resource(#[from] PluginError),
+
+    #[error(transparent)]
+    Consent(#[from] ConsentError),
+}
+
+/// The calling plugin, as seen by the policy
+#[derive(Debug, Clone, Copy)]
+pub struct PolicyContext<'a> {
+    pub plugin_id: &'a str,
+    pub capabilities: &'a PluginCapabilities,
+    pub workspace_root: &'a Path,
+    pub limits: &'a ResourceLimits,
+}
+
+// ============================================================================
+// CAPABILITY CHECKS
+// ============================================================================
+
+/// Check a resolved path against the plugin's filesystem read capability.
+pub fn check_read(
+    capabilities: &PluginCapabilities,
+    path: &Path,
+    workspace_root: &Path,
+) -> Result<(), PolicyError> {
+    if capabilities.filesystem == FilesystemCapability::None {
+        return Err(PolicyError::PermissionDenied {
+            operation: "read".to_string(),
+            capability: "filesystem read".to_string(),
+        });
+    }
+    if capabilities
+        .filesystem
+        .can_read(&path.to_string_lossy(), &workspace_root.to_string_lossy())
+    {
+        Ok(())
+    } else {
+        Err(PolicyError::PathNotAllowed {
+            path: path.display().to_string(),
+        })
+    }
+}
+
+/// Check a resolved path against the plugin's filesystem write capability.
+pub fn check_write(
+    capabilities: &PluginCapabilities,
+    path: &Path,
+    workspace_root: &Path,
+) -> Result<(), PolicyError> {
+    if matches!(
+        capabilities.filesystem,
+        FilesystemCapability::None | FilesystemCapability::WorkspaceRead
+    ) {
+        return Err(PolicyError::PermissionDenied {
+            operation: "write".to_string(),
+            capability: "filesystem write".to_string(),
+        });
+    }
+    if capabilities
+        .filesystem
+        .can_write(&path.to_string_lossy(), &workspace_root.to_string_lossy())
+    {
+        Ok(())
+    } else {
+        Err(PolicyError::PathNotAllowed {
+            path: path.display().to_string(),
+        })
+    }
+}
+
+/// Check a request against the network rules (scheme, host, port, path, method).
+pub fn check_fetch(
+    capabilities: &PluginCapabilities,
+    url: &str,
+    method: &str,
+) -> Result<AuthorizedRequest, PolicyError> {
+    network::authorize(&capabilities.network, url, method).map_err(|e| match e {
+        NetworkError::NoAccess => PolicyError::PermissionDenied {
+            operation: "network request".to_string(),
+            capability: "network access".to_string(),
+        },
+        NetworkError::HostNotAllowed(domain) => PolicyError::DomainNotAllowed(domain),
+        other => PolicyError::Network(other),
+    })
+}
+
+/// Check a command against the allowlist and argument policy. `args` is the
+/// plugin's raw argv, which is validated as given and runs unchanged.
+pub fn check_command(
+    capabilities: &PluginCapabilities,
+    command: &str,
+    args: &[String],
+) -> Result<AuthorizedCommand, PolicyError> {
+    commands::authorize(&capabilities.commands, command, args).map_err(|e| match e {
+        CommandError::NotAllowed(command) => PolicyError::CommandNotAllowed(command),
+        other => PolicyError::Command(other),
+    })
+}
+
+/// Check a UI feature: `status_bar`, `sidebar`, `notification` or `webview`.
+pub fn check_ui(capabilities: &PluginCapabilities, feature: &str) -> Result<(), PolicyError> {
+    let allowed = match feature {
+        "status_bar" => capabilities.ui.status_bar,
+        "sidebar" => capabilities.ui.sidebar,
+        "notification" => capabilities.ui.notifications,
+        "webview" => capabilities.ui.webview,
+        _ => false,
+    };
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(PolicyError::PermissionDenied {
+            operation: feature.to_string(),
+            capability: format!("UI {}", feature),
+        })
+    }
+}
+
+/// Check access to the live audit events of every plugin.
+pub fn check_audit_read(capabilities: &PluginCapabilities) -> Result<(), PolicyError> {
+    if capabilities.audit.read {
+        Ok(())
+    } else {
+        Err(PolicyError::PermissionDenied {
+            operation: "audit subscription".to_string(),
+            capability: "audit read".to_string(),
+        })
+    }
+}
+
+// ============================================================================
+// POLICY ENGINE
+// ============================================================================
+
+/// Authorizes, rate-limits, confirms, times out and audits plugin operations
+pub struct PolicyEngine {
+    audit: Arc<AuditLogger>,
+    consent: Arc<ConsentBroker>,
+    /// Privileged operations per plugin in the current one-second window
+    trackers: Mutex<HashMap<String, OperationTracker>>,
+    /// Commands running per plugin
+    running_commands: Mutex<HashMap<String, Arc<AtomicUsize>>>,
+    /// Plugins whose `audit` hook is called with each recorded event
+    audit_hooks: Mutex<BTreeSet<String>>,
+    timeout: Duration,
+}
+
+impl PolicyEngine {
+    pub fn new(audit: Arc<AuditLogger>, consent: Arc<ConsentBroker>) -> Self {
+        Self {
+            audit,
+            consent,
+            trackers: Mutex::new(HashMap::new()),
+            running_commands: Mutex::new(HashMap::new()),
+            audit_hooks: Mutex::new(BTreeSet::new()),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Replace the command and fetch timeout (30 seconds by default).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Authorize reading (or listing, or watching) a plugin-supplied path.
+    pub fn authorize_read(&self, ctx: &PolicyContext, path: &str) -> Result<PathBuf, PolicyError> {
+        self.account(ctx)?;
+        let resolved = resolve(ctx, path, PathAccess::Existing)?;
+        check_read(ctx.capabilities, &resolved, ctx.workspace_root)?;
+        Ok(resolved)
+    }
+
+    /// Authorize writing a plugin-supplied path, which may not exist yet.
+    pub fn authorize_write(&self, ctx: &PolicyContext, path: &str) -> Result<PathBuf, PolicyError> {
+        self.account(ctx)?;
+        let resolved = resolve(ctx, path, PathAccess::WriteTarget)?;
+        check_write(ctx.capabilities, &resolved, ctx.workspace_root)?;
+        Ok(resolved)
+    }
+
+    pub fn authorize_fetch(
+        &self,
+        ctx: &PolicyContext,
+        url: &str,
+        method: &str,
+    ) -> Result<AuthorizedRequest, PolicyError> {
+        self.account(ctx)?;
+        check_fetch(ctx.capabilities, url, method)
+    }
+
+    /// Authorize a command with the plugin's raw arguments and an
+    /// optional working directory, which must be readable by the plugin.
+    /// Without one the command runs in the workspace root.
+    pub fn authorize_command(
+        &self,
+        ctx: &PolicyContext,
+        command: &str,
+        args: &[String],
+        cwd: Option<&str>,
+    ) -> Result<AuthorizedCommand, PolicyError> {
+        self.account(ctx)?;
+        let mut authorized = check_command(ctx.capabilities, command, args)?;
+        let cwd = match cwd {
+            Some(cwd) => {
+                let resolved = resolve(ctx, cwd, PathAccess::Existing)?;
+                check_read(ctx.capabilities, &resolved, ctx.workspace_root)?;
+                resolved
+            }
+            None => ctx.workspace_root.to_path_buf(),
+        };
+        authorized.cwd = Some(cwd);
+        Ok(authorized)
+    }
+
+    pub fn authorize_ui(&self, ctx: &PolicyContext, feature: &str) -> Result<(), PolicyError> {
+        self.account(ctx)?;
+        check_ui(ctx.capabilities, feature)
+    }
+
+    /// Authorize reading or replacing the editor's content or active file.
+    pub fn authorize_editor(&self, ctx: &PolicyContext) -> Result<(), PolicyError> {
+        self.account(ctx)?;
+        check_ui(ctx.capabilities, "webview")
+    }
+
+    pub fn authorize_audit_read(&self, ctx: &PolicyContext) -> Result<(), PolicyError> {
+        self.account(ctx)?;
+        check_audit_read(ctx.capabilities)
+    }
+
+    /// Ask the user before running `authorized` when the plugin's command
+    /// capability requires confirmation; `emit` shows the request.
+    pub async fn confirm(
+        &self,
+        ctx: &PolicyContext<'_>,
+        authorized: &AuthorizedCommand,
+        command: &str,
+        emit: impl FnOnce(&ConsentRequest) -> Result<(), String>,
+    ) -> Result<(), PolicyError> {
+        match confirmation_request(ctx, authorized, command) {
+            Some(request) => Ok(self.consent.request(request, emit).await?),
+            None => Ok(()),
+        }
+    }
+
+    /// Take one of the plugin's `max_concurrent_commands` slots for a command
+    /// about to run.
+    pub fn command_slot(&self, ctx: &PolicyContext) -> Result<CommandSlot, PolicyError> {
+        let running = self
+            .running_commands
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(ctx.plugin_id.to_string())
+            .or_default()
+            .clone();
+        let limit = ctx.limits.max_concurrent_commands;
+        CommandSlot::try_acquire(&running, limit).map_err(|running| {
+            PolicyError::Resource(PluginError::CommandLimitExceeded { running, limit })
+        })
+    }
+
+    /// Run an authorized command, killing it once the timeout elapses. The
+    /// command counts against the plugin's concurrency limit through `_slot`.
+    pub async fn output(
+        &self,
+        authorized: &AuthorizedCommand,
+        _slot: &CommandSlot,
+    ) -> std::io::Result<Output> {
+        let mut cmd = tokio::process::Command::from(authorized.command());
+        cmd.kill_on_drop(true);
+        tokio::time::timeout(self.timeout, cmd.output())
+            .await
+            .map_err(|_| timed_out(self.timeout))?
+    }
+
+    /// Read a fetch response body, refusing one larger than the plugin's
+    /// `max_fetch_body_size` without buffering more than that.
+    pub async fn read_body(
+        &self,
+        ctx: &PolicyContext<'_>,
+        mut response: reqwest::Response,
+    ) -> Result<String, PolicyError> {
+        let limit = ctx.limits.max_fetch_body_size;
+        let too_large = || PolicyError::Resource(PluginError::FetchBodyTooLarge { limit });
+        if response.content_length().unwrap_or(0) > limit as u64 {
+            return Err(too_large());
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| PolicyError::Fetch(format!("Failed to read response body: {}", e)))?
+        {
+            if bytes.len() + chunk.len() > limit {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Add an operation to the audit trail.
+    pub fn record(
+        &self,
+        plugin_id: &str,
+        operation: &str,
+        resource: &str,
+        started: SystemTime,
+        error: Option<String>,
+    ) {
+        self.audit.record(AuditEvent {
+            plugin_id: plugin_id.to_string(),
+            operation: operation.to_string(),
+            resource: resource.to_string(),
+            timestamp: started,
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    /// Receive every operation recorded from now on, for security monitor plugins.
+    pub fn subscribe(&self) -> broadcast::Receiver<AuditEvent> {
+        self.audit.subscribe()
+    }
+
+    /// Call `plugin_id`'s `audit` hook with every event recorded from now on
+    /// (see `PluginManager::dispatch_audit_event`).
+    pub fn subscribe_audit_hook(&self, plugin_id: &str) {
+        self.audit_hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(plugin_id.to_string());
+    }
+
+    pub fn unsubscribe_audit_hook(&self, plugin_id: &str) {
+        self.audit_hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(plugin_id);
+    }
+
+    /// Plugins whose `audit` hook receives events.
+    pub fn audit_hook_subscribers(&self) -> Vec<String> {
+        self.audit_hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Count one privileged operation against the plugin's per-second budget.
+    fn account(&self, ctx: &PolicyContext) -> Result<(), PolicyError> {
+        let mut trackers = self.trackers.lock().unwrap_or_else(|e| e.into_inner());
+        trackers
+            .entry(ctx.plugin_id.to_string())
+            .or_insert_with(OperationTracker::new)
+            .record_call(ctx.limits)?;
+        Ok(())
+    }
+}
+
+/// One of a plugin's `max_concurrent_commands` slots, released on drop.
+pub struct CommandSlot(Arc<AtomicUsize>);
+
+impl CommandSlot {
+    /// Take a slot if fewer than `limit` are held; otherwise return the current count.
+    fn try_acquire(running: &Arc<AtomicUsize>, limit: usize) -> Result<Self, usize> {
+        running.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            (n < limit).then_some(n + 1)
+        })?;
+        Ok(Self(running.clone()))
+    }
+}
+
+impl Drop for CommandSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Resolve a plugin-supplied path against the workspace root (see [`paths::resolve`]).
+fn resolve(ctx: &PolicyContext, raw: &str, access: PathAccess) -> Result<PathBuf, PolicyError> {
+    paths::resolve(raw, ctx.workspace_root, access).map_err(|e| PolicyError::InvalidPath {
+        path: raw.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+fn confirmation_request(
+    ctx: &PolicyContext,
+    authorized: &AuthorizedCommand,
+    command: &str,
+) -> Option<ConsentRequest> {
+    ctx.capabilities.commands.require_confirmation.then(|| {
+        ConsentRequest::new(
+            ctx.plugin_id,
+            command,
+            &authorized.args,
+            Some(authorized.cwd.as_deref().unwrap_or(ctx.workspace_root)),
+        )
+    })
+}
+
+fn timed_out(timeout: Duration) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!("timed out after {}s", timeout.as_secs()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_system::capabilities::{
+        AuditCapability, CommandCapability, NetworkCapability, UiCapability,
+    };
+    use std::collections::HashSet;
+
+    // ============================================================================
+    // check_read tests
+    // ============================================================================
+
+    #[test]
+    fn test_check_read_workspace_read_allows_in_workspace() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceRead,
+            ..Default::default()
+        };
+        assert!(check_read(&capabilities, &workspace.join("file.txt"), &workspace).is_ok());
+    }
+
+    #[test]
+    fn test_check_read_workspace_read_denies_outside() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceRead,
+            ..Default::default()
+        };
+        assert!(check_read(&capabilities, &PathBuf::from("/etc/passwd"), &workspace).is_err());
+    }
+
+    #[test]
+    fn test_check_read_none_denies_all() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::None,
+            ..Default::default()
+        };
+        assert!(check_read(&capabilities, &workspace.join("file.txt"), &workspace).is_err());
+    }
+
+    #[test]
+    fn test_check_read_scoped_allows_in_scope() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::Scoped {
+                read: vec!["/workspace/src".to_string()].into_iter().collect(),
+                write: HashSet::new(),
+                deny: HashSet::new(),
+            },
+            ..Default::default()
+        };
+        assert!(check_read(
+            &capabilities,
+            &PathBuf::from("/workspace/src/main.rs"),
+            &workspace
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_check_read_scoped_denies_out_of_scope() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::Scoped {
+                read: vec!["/workspace/src".to_string()].into_iter().collect(),
+                write: HashSet::new(),
+                deny: HashSet::new(),
+            },
+            ..Default::default()
+        };
+        assert!(check_read(
+            &capabilities,
+            &PathBuf::from("/workspace/secrets/key.pem"),
+            &workspace
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_read_readwrite_allows() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceReadWrite,
+            ..Default::default()
+        };
+        assert!(check_read(&capabilities, &workspace.join("file.txt"), &workspace).is_ok());
+    }
+
+    // ============================================================================
+    // check_write tests
+    // ============================================================================
+
+    #[test]
+    fn test_check_write_none_denies() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::None,
+            ..Default::default()
+        };
+        assert!(check_write(&capabilities, &workspace.join("file.txt"), &workspace).is_err());
+    }
+
+    #[test]
+    fn test_check_write_workspace_read_denies() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceRead,
+            ..Default::default()
+        };
+        assert!(check_write(&capabilities, &workspace.join("file.txt"), &workspace).is_err());
+    }
+
+    #[test]
+    fn test_check_write_readwrite_allows_in_workspace() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceReadWrite,
+            ..Default::default()
+        };
+        assert!(check_write(&capabilities, &workspace.join("file.txt"), &workspace).is_ok());
+    }
+
+    #[test]
+    fn test_check_write_readwrite_denies_outside() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceReadWrite,
+            ..Default::default()
+        };
+        assert!(check_write(&capabilities, &PathBuf::from("/etc/passwd"), &workspace).is_err());
+    }
+
+    #[test]
+    fn test_check_write_scoped_allows_in_scope() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::Scoped {
+                read: HashSet::new(),
+                write: vec!["/workspace/out".to_string()].into_iter().collect(),
+                deny: HashSet::new(),
+            },
+            ..Default::default()
+        };
+        assert!(check_write(
+            &capabilities,
+            &PathBuf::from("/workspace/out/result.txt"),
+            &workspace
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_check_write_scoped_denies_out_of_scope() {
+        let workspace = PathBuf::from("/workspace");
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::Scoped {
+                read: HashSet::new(),
+                write: vec!["/workspace/out".to_string()].into_iter().collect(),
+                deny: HashSet::new(),
+            },
+            ..Default::default()
+        };
+        assert!(check_write(
+            &capabilities,
+            &PathBuf::from("/workspace/src/main.rs"),
+            &workspace
+        )
+        .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_read_follows_symlink_out_of_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let workspace = tmp.path().join("project");
+        std::fs::create_dir_all(tmp.path().join(".ssh")).unwrap();
+        std::fs::write(tmp.path().join(".ssh/id_rsa"), "secret").unwrap();
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::os::unix::fs::symlink(tmp.path().join(".ssh"), workspace.join("keys")).unwrap();
+        let capabilities = PluginCapabilities {
+            filesystem: FilesystemCapability::WorkspaceReadWrite,
+            ..Default::default()
+        };
+
+        let path = paths::resolve("keys/id_rsa", &workspace, PathAccess::Existing).unwrap();
+        assert!(check_read(&capabilities, &path, &workspace).is_err());
+        let path = paths::resolve("keys/new_key", &workspace, PathAccess::WriteTarget).unwrap();
+        assert!(check_write(&capabilities, &path, &workspace).is_err());
+        let path = paths::resolve("notes.txt", &workspace, PathAccess::WriteTarget).unwrap();
+        assert!(check_write(&capabilities, &path, &workspace).is_ok());
+    }
+
+    // ============================================================================
+    // check_fetch tests
+    // ============================================================================
+
+    #[test]
+    fn test_check_fetch_none_denies() {
+        let capabilities = PluginCapabilities {
+            network: NetworkCapability::None,
+            ..Default::default()
+        };
+        assert!(check_fetch(&capabilities, "https://example.com", "GET").is_err());
+    }
+
+    #[test]
+    fn test_check_fetch_unrestricted_allows() {
+        let capabilities = PluginCapabilities {
+            network: NetworkCapability::Unrestricted,
+            ..Default::default()
+        };
+        assert!(check_fetch(&capabilities, "https://anything.com/data", "GET").is_ok());
+    }
+
+    #[test]
+    fn test_check_fetch_allowlist_allows() {
+        let capabilities = PluginCapabilities {
+            network: NetworkCapability::DomainAllowlist(
+                vec!["api.example.com".to_string()].into_iter().collect(),
+            ),
+            ..Default::default()
+        };
+        assert!(check_fetch(&capabilities, "https://api.example.com/data", "GET").is_ok());
+    }
+
+    #[test]
+    fn test_check_fetch_allowlist_denies() {
+        let capabilities = PluginCapabilities {
+            network: NetworkCapability::DomainAllowlist(
+                vec!["api.example.com".to_string()].into_iter().collect(),
+            ),
+            ..Default::default()
+        };
+        assert!(check_fetch(&capabilities, "https://evil.com/data", "GET").is_err());
+    }
+
+    #[test]
+    fn test_check_fetch_invalid_url_errors() {
+        let capabilities = PluginCapabilities {
+            network: NetworkCapability::DomainAllowlist(HashSet::new()),
+            ..Default::default()
+        };
+        assert!(check_fetch(&capabilities, "not-a-url", "GET").is_err());
+    }
+
+    // ============================================================================
+    // check_command tests
+    // ============================================================================
+
+    fn git_capabilities() -> PluginCapabilities {
+        let mut commands = CommandCapability {
+            allowlist: vec!["git".to_string()].into_iter().collect(),
+            require_confirmation: false,
+            ..Default::default()
+        };
+        commands
+            .programs
+            .insert("git".to_string(), PathBuf::from("/usr/bin/git"));
+        PluginCapabilities {
+            commands,
+            ..Default::default()
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_check_command_allows_allowlisted() {
+        let capabilities = git_capabilities();
+        let authorized = check_command(&capabilities, "git", &args(&["status"])).unwrap();
+        assert_eq!(authorized.program, PathBuf::from("/usr/bin/git"));
+    }
+
+    #[test]
+    fn test_check_command_denies_unlisted() {
+        let capabilities = git_capabilities();
+        assert!(matches!(
+            check_command(&capabilities, "rm", &[]),
+            Err(PolicyError::CommandNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_command_denies_path_like_binary() {
+        let capabilities = git_capabilities();
+        assert!(check_command(&capabilities, "/usr/bin/git", &[]).is_err());
+    }
+
+    #[test]
+    fn test_check_command_denies_unresolved_program() {
+        let mut capabilities = git_capabilities();
+        capabilities.commands.programs.clear();
+        assert!(matches!(
+            check_command(&capabilities, "git", &args(&["status"])),
+            Err(PolicyError::Command(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_command_denies_git_config_injection() {
+        let capabilities = git_capabilities();
+        for attack in [
+            &["-c", "core.sshCommand=touch /tmp/pwned", "fetch"][..],
+            &["fetch", "--upload-pack=touch /tmp/pwned"][..],
+            &["config", "alias.x", "!sh"][..],
+        ] {
+            assert!(
+                check_command(&capabilities, "git", &args(attack)).is_err(),
+                "allowed: {:?}",
+                attack
+            );
+        }
+    }
+
+    // ============================================================================
+    // check_ui tests
+    // ============================================================================
+
+    #[test]
+    fn test_check_ui_status_bar() {
+        let capabilities = PluginCapabilities {
+            ui: UiCapability {
+                status_bar: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(check_ui(&capabilities, "status_bar").is_ok());
+    }
+
+    #[test]
+    fn test_check_ui_sidebar() {
+        let capabilities = PluginCapabilities {
+            ui: UiCapability {
+                sidebar: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(check_ui(&capabilities, "sidebar").is_ok());
+    }
+
+    #[test]
+    fn test_check_ui_notification() {
+        let capabilities = PluginCapabilities {
+            ui: UiCapability {
+                notifications: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(check_ui(&capabilities, "notification").is_ok());
+    }
+
+    #[test]
+    fn test_check_ui_webview() {
+        let capabilities = PluginCapabilities {
+            ui: UiCapability {
+                webview: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(check_ui(&capabilities, "webview").is_ok());
+    }
+
+    #[test]
+    fn test_check_ui_denied_when_false() {
+        let capabilities = PluginCapabilities::default();
+        assert!(check_ui(&capabilities, "status_bar").is_err());
+        assert!(check_ui(&capabilities, "sidebar").is_err());
+        assert!(check_ui(&capabilities, "notification").is_err());
+        assert!(check_ui(&capabilities, "webview").is_err());
+    }
+
+    #[test]
+    fn test_check_ui_unknown_operation() {
+        let capabilities = PluginCapabilities {
+            ui: UiCapability::all(),
+            ..Default::default()
+        };
+        assert!(check_ui(&capabilities, "unknown_op").is_err());
+    }
+
+    // ============================================================================
+    // PolicyEngine tests
+    // ============================================================================
+
+    use crate::plugin_system::consent::ConsentDecision;
+
+    fn engine() -> (PolicyEngine, Arc<AuditLogger>, Arc<ConsentBroker>) {
+        let audit = Arc::new(AuditLogger::new(100));
+        let consent = Arc::new(ConsentBroker::new(Duration::from_millis(50)));
+        (
+            PolicyEngine::new(audit.clone(), consent.clone()),
+            audit,
+            consent,
+        )
+    }
+
+    fn context<'a>(
+        capabilities: &'a PluginCapabilities,
+        workspace: &'a Path,
+        limits: &'a ResourceLimits,
+    ) -> PolicyContext<'a> {
+        PolicyContext {
+            plugin_id: "test",
+            capabilities,
+            workspace_root: workspace,
+            limits,
+        }
+    }
+
+    /// An operation as either path issues it
+    enum Op<'a> {
+        Read(&'a str),
+        Write(&'a str),
+        Fetch(&'a str, &'a str),
+        Execute(&'a str, &'a [&'a str]),
+        Ui(&'a str),
+        Editor,
+        AuditRead,
+    }
+
+    fn authorize(engine: &PolicyEngine, ctx: &PolicyContext, op: &Op) -> Result<(), PolicyError> {
+        match op {
+            Op::Read(path) => engine.authorize_read(ctx, path).map(drop),
+            Op::Write(path) => engine.authorize_write(ctx, path).map(drop),
+            Op::Fetch(url, method) => engine.authorize_fetch(ctx, url, method).map(drop),
+            Op::Execute(command, argv) => engine
+                .authorize_command(ctx, command, &args(argv), None)
+                .map(drop),
+            Op::Ui(feature) => engine.authorize_ui(ctx, feature),
+            Op::Editor => engine.authorize_editor(ctx),
+            Op::AuditRead => engine.authorize_audit_read(ctx),
+        }
+    }
+
+    #[test]
+    fn test_capability_matrix() {
+        let tmp = tempfile::tempdir().unwrap();
+        let workspace = tmp.path().canonicalize().unwrap();
+        std::fs::write(workspace.join("notes.txt"), "notes").unwrap();
+        std::fs::write(workspace.join(".env"), "SECRET=1").unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+        let outside_file = outside.path().join("secret.txt").display().to_string();
+
+        let network = PluginCapabilities {
+            network: NetworkCapability::DomainAllowlist(
+                ["api.example.com".to_string()].into_iter().collect(),
+            ),
+            ..PluginCapabilities::none()
+        };
+        let ui = PluginCapabilities {
+            ui: UiCapability {
+                notifications: true,
+                ..Default::default()
+            },
+            ..PluginCapabilities::none()
+        };
+        let security_monitor = PluginCapabilities {
+            audit: AuditCapability { read: true },
+            ..PluginCapabilities::none()
+        };
+        let tiers = [
+            ("none", PluginCapabilities::none()),
+            ("workspace_read", PluginCapabilities::workspace_read()),
+            (
+                "workspace_read_write",
+                PluginCapabilities::workspace_read_write(),
+            ),
+            ("network", network),
+            ("git", git_capabilities()),
+            ("ui", ui),
+            ("security_monitor", security_monitor),
+        ];
+        let ops = [
+            ("read", Op::Read("notes.txt")),
+            ("read .env", Op::Read(".env")),
+            ("read outside", Op::Read(&outside_file)),
+            ("write", Op::Write("new.txt")),
+            (
+                "fetch allowed",
+                Op::Fetch("https://api.example.com/v1", "GET"),
+            ),
+            ("fetch other", Op::Fetch("https://evil.example/", "GET")),
+            ("git status", Op::Execute("git", &["status"])),
+            (
+                "git config injection",
+                Op::Execute("git", &["-c", "core.pager=sh", "log"]),
+            ),
+            ("rm", Op::Execute("rm", &["-rf", "/"])),
+            ("notification", Op::Ui("notification")),
+            ("webview", Op::Ui("webview")),
+            ("editor", Op::Editor),
+            ("audit read", Op::AuditRead),
+        ];
+        let expected: &[(&str, &[&str])] = &[
+            ("none", &[]),
+            ("workspace_read", &["read", "notification"]),
+            ("workspace_read_write", &["read", "write", "notification"]),
+            ("network", &["fetch allowed"]),
+            ("git", &["git status"]),
+            ("ui", &["notification"]),
+            ("security_monitor", &["audit read"]),
+        ];
+
+        let (engine, _, _) = engine();
+        let limits = ResourceLimits {
+            max_operations: u64::MAX,
+            ..ResourceLimits::default()
+        };
+        for ((tier, capabilities), (expected_tier, allowed)) in tiers.iter().zip(expected) {
+            assert_eq!(tier, expected_tier);
+            let ctx = context(capabilities, &workspace, &limits);
+            for (name, op) in &ops {
+                let result = authorize(&engine, &ctx, op);
+                assert_eq!(
+                    result.is_ok(),
+                    allowed.contains(name),
+                    "{} / {}: {:?}",
+                    tier,
+                    name,
+                    result
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_is_per_plugin() {
+        let (engine, _, _) = engine();
+        let capabilities = PluginCapabilities::none();
+        let workspace = PathBuf::from("/workspace");
+        let limits = ResourceLimits {
+            max_operations: 2,
+            ..ResourceLimits::default()
+        };
+        let ctx = context(&capabilities, &workspace, &limits);
+        assert!(engine.authorize_ui(&ctx, "status_bar").is_err());
+        assert!(engine.authorize_ui(&ctx, "status_bar").is_err());
+        assert!(matches!(
+            engine.authorize_ui(&ctx, "status_bar"),
+            Err(PolicyError::Resource(PluginError::RateLimitExceeded { .. }))
+        ));
+
+        let other = PolicyContext {
+            plugin_id: "other",
+            ..ctx
+        };
+        assert!(matches!(
+            engine.authorize_ui(&other, "status_bar"),
+            Err(PolicyError::PermissionDenied { .. })
+        ));
+    }
+
+    #[test]
+    fn test_command_cwd_must_be_readable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let workspace = tmp.path().canonicalize().unwrap();
+        std::fs::create_dir(workspace.join("src")).unwrap();
+        let (engine, _, _) = engine();
+        let limits = ResourceLimits::default();
+
+        let capabilities = git_capabilities();
+        let ctx = context(&capabilities, &workspace, &limits);
+        assert!(matches!(
+            engine.authorize_command(&ctx, "git", &args(&["status"]), Some("src")),
+            Err(PolicyError::PermissionDenied { .. })
+        ));
+
+        let mut capabilities = git_capabilities();
+        capabilities.filesystem = FilesystemCapability::WorkspaceRead;
+        let ctx = context(&capabilities, &workspace, &limits);
+        let authorized = engine
+            .authorize_command(&ctx, "git", &args(&["status; rm -rf /"]), Some("src"))
+            .unwrap();
+        assert_eq!(authorized.cwd, Some(workspace.join("src")));
+        // No shell runs the command, so the argument is passed through untouched
+        assert_eq!(authorized.args, vec!["status; rm -rf /"]);
+
+        // Without a cwd the command runs where the consent prompt says it does
+        let authorized = engine
+            .authorize_command(&ctx, "git", &args(&["status"]), None)
+            .unwrap();
+        assert_eq!(
+            authorized.command().get_current_dir(),
+            Some(workspace.as_path())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_confirmation() {
+        let (engine, _, consent) = engine();
+        let mut capabilities = git_capabilities();
+        let workspace = PathBuf::from("/workspace");
+        let limits = ResourceLimits::default();
+        let authorized = check_command(&capabilities, "git", &args(&["status"])).unwrap();
+
+        // Not required: nobody is asked
+        let ctx = context(&capabilities, &workspace, &limits);
+        assert!(engine
+            .confirm(&ctx, &authorized, "git", |_| panic!("asked"))
+            .await
+            .is_ok());
+
+        capabilities.commands.require_confirmation = true;
+        let ctx = context(&capabilities, &workspace, &limits);
+        assert!(engine
+            .confirm(&ctx, &authorized, "git", |request| {
+                assert_eq!(request.cwd.as_deref(), Some("/workspace"));
+                consent.respond(&request.id, ConsentDecision::AllowOnce);
+                Ok(())
+            })
+            .await
+            .is_ok());
+        assert!(matches!(
+            engine.confirm(&ctx, &authorized, "git", |_| Ok(())).await,
+            Err(PolicyError::Consent(ConsentError::TimedOut { .. }))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_times_out() {
+        let (engine, _, _) = engine();
+        let engine = engine.with_timeout(Duration::from_millis(100));
+        let Some(sleep) = commands::find_program("sleep") else {
+            return;
+        };
+        let authorized = AuthorizedCommand {
+            program: sleep,
+            args: args(&["5"]),
+            cwd: None,
+        };
+        let started = std::time::Instant::now();
+        let slot = engine
+            .command_slot(&context(
+                &PluginCapabilities::none(),
+                Path::new("/workspace"),
+                &ResourceLimits::default(),
+            ))
+            .unwrap();
+        let err = engine.output(&authorized, &slot).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_collects_stdout() {
+        let (engine, _, _) = engine();
+        let Some(echo) = commands::find_program("echo") else {
+            return;
+        };
+        let authorized = AuthorizedCommand {
+            program: echo,
+            args: args(&["hello"]),
+            cwd: None,
+        };
+        let slot = engine
+            .command_slot(&context(
+                &PluginCapabilities::none(),
+                Path::new("/workspace"),
+                &ResourceLimits::default(),
+            ))
+            .unwrap();
+        let output = engine.output(&authorized, &slot).await.unwrap();
+        assert_eq!(output.stdout, b"hello\n");
+    }
+
+    #[test]
+    fn test_command_slots_are_limited_per_plugin_and_released() {
+        let (engine, _, _) = engine();
+        let capabilities = PluginCapabilities::none();
+        let workspace = PathBuf::from("/workspace");
+        let limits = ResourceLimits {
+            max_concurrent_commands: 2,
+            ..ResourceLimits::default()
+        };
+        let ctx = context(&capabilities, &workspace, &limits);
+        let first = engine.command_slot(&ctx).unwrap();
+        let second = engine.command_slot(&ctx).unwrap();
+        assert!(matches!(
+            engine.command_slot(&ctx),
+            Err(PolicyError::Resource(PluginError::CommandLimitExceeded {
+                running: 2,
+                limit: 2
+            }))
+        ));
+        let other = PolicyContext {
+            plugin_id: "other",
+            ..ctx
+        };
+        assert!(engine.command_slot(&other).is_ok());
+
+        drop(first);
+        let third = engine.command_slot(&ctx).unwrap();
+        drop(second);
+        drop(third);
+        let _slots = [
+            engine.command_slot(&ctx).unwrap(),
+            engine.command_slot(&ctx).unwrap(),
+        ];
+    }
+
+    #[tokio::test]
+    async fn test_read_body_stops_at_the_fetch_limit() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // A body without a content length, so the limit applies while streaming
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let _ = socket
+                    .write_all(b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n")
+                    .await;
+                let _ = socket.write_all(&[b'x'; 64]).await;
+            }
+        });
+
+        let (engine, _, _) = engine();
+        let capabilities = PluginCapabilities::none();
+        let workspace = PathBuf::from("/workspace");
+        for (limit, fits) in [(64, true), (63, false)] {
+            let limits = ResourceLimits {
+                max_fetch_body_size: limit,
+                ..ResourceLimits::default()
+            };
+            let ctx = context(&capabilities, &workspace, &limits);
+            let response = reqwest::get(format!("http://{}/", addr)).await.unwrap();
+            let body = engine.read_body(&ctx, response).await;
+            if fits {
+                assert_eq!(body.unwrap().len(), 64);
+            } else {
+                assert!(matches!(
+                    body,
+                    Err(PolicyError::Resource(PluginError::FetchBodyTooLarge {
+                        limit: 63
+                    }))
+                ));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_writes_audit_event() {
+        let (engine, audit, _) = engine();
+        let mut subscriber = engine.subscribe();
+        engine.record(
+            "test",
+            "fs.read",
+            "/workspace/a.txt",
+            SystemTime::now(),
+            Some("denied".to_string()),
+        );
+        let events = audit.get_events_for_plugin("test").await;
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].success);
+        assert_eq!(events[0].error.as_deref(), Some("denied"));
+        assert_eq!(subscriber.try_recv().unwrap().resource, "/workspace/a.txt");
+    }
+}
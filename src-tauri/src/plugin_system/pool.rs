@@ -0,0 +1,905 @@
+// src-tauri/src/plugin_system/pool.rs
+//
+// Shared worker pool: a fixed number of OS threads, each hosting the isolates
+// of many plugins. Every thread owns one Tokio runtime and one watchdog; an
+// isolate is only entered while it handles a message or has its event loop
+// polled, and isolates that sit idle are parked (their garbage collected with a
+// low-memory notification). Calls waiting on async work (a fetch, a child
+// process, a consent prompt) are polled alongside the other isolates' messages
+// instead of blocking the thread, and timers or ops a call leaves running keep
+// being polled after it is answered.
+// An isolate whose factory or message handling panics is dropped on its own and
+// the reason is kept on its slot, so the rest of the thread keeps running.
+
+use crate::plugin_system::sandbox::ResourceLimits;
+use crate::plugin_system::worker::{
+    answer_all, panic_message, IsolateLimits, PendingCall, PluginWorker, Watchdog, WorkerMessage,
+    WorkerResponse,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Interval, MissedTickBehavior};
+
+/// Upper bound on pool threads when sizing from the available parallelism.
+pub const MAX_POOL_THREADS: usize = 4;
+
+/// How long an isolate may sit idle before it is parked.
+pub const IDLE_PARK_AFTER: Duration = Duration::from_secs(30);
+
+/// How often a pool thread with no messages checks for isolates to park.
+const PARK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often pending calls have their deadlines checked. Deadlines are measured
+/// on each isolate's budget clock, which pauses, so no timer can wake on them.
+const SETTLE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Builds a plugin's `JsRuntime` on the pool thread that will host it.
+pub type IsolateFactory = Box<dyn FnOnce() -> Result<deno_core::JsRuntime, String> + Send>;
+
+/// Why a slot's isolate died, written by its pool thread.
+type FailureCell = Arc<Mutex<Option<String>>>;
+
+static WORKER_POOL: LazyLock<WorkerPool> = LazyLock::new(|| {
+    WorkerPool::new(default_thread_count(), IDLE_PARK_AFTER)
+        .expect("Failed to start plugin worker pool")
+});
+
+/// Number of pool threads: the available parallelism, capped at [`MAX_POOL_THREADS`].
+pub fn default_thread_count() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_POOL_THREADS)
+}
+
+enum PoolMessage {
+    /// Create an isolate for `key` on this thread
+    Spawn {
+        key: u64,
+        plugin_id: String,
+        limits: ResourceLimits,
+        factory: IsolateFactory,
+        failure: FailureCell,
+    },
+    /// Deliver a worker message to the isolate for `key`
+    Run { key: u64, message: WorkerMessage },
+    /// Report the isolates hosted on this thread
+    Stats {
+        response_tx: oneshot::Sender<Vec<IsolateStats>>,
+    },
+    /// Drop every hosted isolate and stop the thread
+    Shutdown,
+}
+
+/// What woke a pool thread up.
+enum PoolEvent {
+    Message(PoolMessage),
+    /// Time to look for isolates to park
+    Tick,
+    /// Every sender is gone
+    Closed,
+}
+
+/// Memory and activity of one hosted isolate.
+#[derive(Debug, Clone, Serialize)]
+pub struct IsolateStats {
+    pub plugin_id: String,
+    pub thread: usize,
+    pub heap_used: usize,
+    pub idle_ms: u64,
+    pub parked: bool,
+}
+
+/// Snapshot of the whole pool, reported by `get_worker_info`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub threads: usize,
+    pub isolates: usize,
+    pub parked: usize,
+    pub heap_used: usize,
+    pub per_isolate: Vec<IsolateStats>,
+}
+
+struct PoolThread {
+    sender: mpsc::UnboundedSender<PoolMessage>,
+    isolates: Arc<AtomicUsize>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+/// Fixed set of threads that plugin isolates are multiplexed over.
+///
+/// Messages for isolates on the same thread run one at a time, so a plugin
+/// that uses its whole `max_cpu_time` delays the others sharing its thread.
+/// Time a call spends awaiting an op does not hold the thread.
+pub struct WorkerPool {
+    threads: Vec<PoolThread>,
+    next_key: AtomicU64,
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize, park_after: Duration) -> std::io::Result<Self> {
+        let threads = (0..threads.max(1))
+            .map(|index| {
+                let (tx, rx) = mpsc::unbounded_channel();
+                let isolates = Arc::new(AtomicUsize::new(0));
+                let thread_isolates = isolates.clone();
+                let handle = thread::Builder::new()
+                    .name(format!("plugin-pool-{}", index))
+                    .spawn(move || run_pool_thread(index, rx, thread_isolates, park_after))?;
+                Ok(PoolThread {
+                    sender: tx,
+                    isolates,
+                    handle: Some(handle),
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            threads,
+            next_key: AtomicU64::new(0),
+        })
+    }
+
+    /// The process-wide pool used by plugin sandboxes.
+    pub fn global() -> &'static WorkerPool {
+        &WORKER_POOL
+    }
+
+    /// Create an isolate on the least loaded thread and return its slot.
+    pub fn spawn(
+        &self,
+        plugin_id: String,
+        limits: ResourceLimits,
+        factory: IsolateFactory,
+    ) -> PoolSlot {
+        let thread = self
+            .threads
+            .iter()
+            .min_by_key(|t| t.isolates.load(Ordering::SeqCst))
+            .expect("worker pool has at least one thread");
+        let key = self.next_key.fetch_add(1, Ordering::SeqCst);
+        let failure = FailureCell::default();
+
+        thread.isolates.fetch_add(1, Ordering::SeqCst);
+        if thread
+            .sender
+            .send(PoolMessage::Spawn {
+                key,
+                plugin_id,
+                limits,
+                factory,
+                failure: failure.clone(),
+            })
+            .is_err()
+        {
+            thread.isolates.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        PoolSlot {
+            key,
+            sender: thread.sender.clone(),
+            failure,
+        }
+    }
+
+    /// Collect thread, isolate and heap figures from every pool thread.
+    pub async fn stats(&self) -> PoolStats {
+        let mut per_isolate = Vec::new();
+        for thread in &self.threads {
+            let (tx, rx) = oneshot::channel();
+            if thread
+                .sender
+                .send(PoolMessage::Stats { response_tx: tx })
+                .is_ok()
+            {
+                if let Ok(stats) = rx.await {
+                    per_isolate.extend(stats);
+                }
+            }
+        }
+
+        PoolStats {
+            threads: self.threads.len(),
+            isolates: per_isolate.len(),
+            parked: per_isolate.iter().filter(|s| s.parked).count(),
+            heap_used: per_isolate.iter().map(|s| s.heap_used).sum(),
+            per_isolate,
+        }
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        for thread in &self.threads {
+            let _ = thread.sender.send(PoolMessage::Shutdown);
+        }
+        for thread in &mut self.threads {
+            if let Some(handle) = thread.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+/// A plugin's place in the pool. Dropping the slot drops the isolate.
+pub struct PoolSlot {
+    key: u64,
+    sender: mpsc::UnboundedSender<PoolMessage>,
+    failure: FailureCell,
+}
+
+impl PoolSlot {
+    /// Queue a message for this slot's isolate. Returns `false` if the pool thread is gone.
+    pub fn send(&self, message: WorkerMessage) -> bool {
+        self.sender
+            .send(PoolMessage::Run {
+                key: self.key,
+                message,
+            })
+            .is_ok()
+    }
+
+    /// Why the isolate died (failed to start or panicked), if it has.
+    pub fn failure(&self) -> Option<String> {
+        self.failure
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+impl PoolSlot {
+    /// Drop the isolate and record `reason` as if it had panicked.
+    pub(crate) fn kill(&self, reason: &str) {
+        record_failure(&self.failure, reason.to_string());
+        self.send(WorkerMessage::Shutdown);
+    }
+}
+
+/// Record why an isolate died so its slot can report it.
+fn record_failure(cell: &FailureCell, reason: String) {
+    *cell.lock().unwrap_or_else(|e| e.into_inner()) = Some(reason);
+}
+
+impl Drop for PoolSlot {
+    fn drop(&mut self) {
+        self.send(WorkerMessage::Shutdown);
+    }
+}
+
+/// A plugin isolate living on a pool thread.
+///
+/// V8 keeps a per-thread stack of entered isolates and requires an isolate to be
+/// the current one when it is disposed. Hosted isolates are therefore exited as
+/// soon as they are created and only entered around the work done on them.
+struct HostedIsolate {
+    plugin_id: String,
+    runtime: deno_core::JsRuntime,
+    guard: IsolateLimits,
+    limits: ResourceLimits,
+    failure: FailureCell,
+    /// Calls whose results are still settling on the event loop
+    calls: Vec<PendingCall>,
+    /// Timers or ops (from answered calls too) were still running when the
+    /// event loop was last polled
+    background: bool,
+    last_used: Instant,
+    parked: bool,
+}
+
+impl HostedIsolate {
+    fn create(
+        plugin_id: String,
+        limits: ResourceLimits,
+        factory: IsolateFactory,
+        failure: FailureCell,
+    ) -> Result<Self, String> {
+        let mut runtime = panic::catch_unwind(AssertUnwindSafe(factory)).map_err(|payload| {
+            format!(
+                "Plugin worker panicked: {}",
+                panic_message(payload.as_ref())
+            )
+        })??;
+        let guard = IsolateLimits::install(&mut runtime);
+        // SAFETY: the isolate was entered on creation and nothing else is entered above it.
+        unsafe { runtime.v8_isolate().exit() };
+
+        Ok(Self {
+            plugin_id,
+            runtime,
+            guard,
+            limits,
+            failure,
+            calls: Vec::new(),
+            background: false,
+            last_used: Instant::now(),
+            parked: false,
+        })
+    }
+
+    fn entered<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        // SAFETY: pool threads keep no isolate entered between messages, so
+        // enter/exit always pair up on an empty stack.
+        unsafe { self.runtime.v8_isolate().enter() };
+        let result = f(self);
+        unsafe { self.runtime.v8_isolate().exit() };
+        result
+    }
+
+    /// Handle one message; an `Err` means the message handling panicked and the
+    /// isolate must be dropped.
+    fn handle(
+        &mut self,
+        tokio_rt: &tokio::runtime::Runtime,
+        watchdog: &Watchdog,
+        message: WorkerMessage,
+    ) -> Result<(), String> {
+        let outcome = self.entered(|hosted| {
+            PluginWorker::handle_message(
+                &mut hosted.runtime,
+                tokio_rt,
+                watchdog,
+                &hosted.guard,
+                &hosted.limits,
+                message,
+            )
+        });
+        self.last_used = Instant::now();
+        self.parked = false;
+        // The message may have started timers or ops; the next poll tells
+        self.background = true;
+        if let Some(call) = outcome? {
+            self.calls.push(call);
+        }
+        Ok(())
+    }
+
+    /// Whether calls are waiting on this isolate's event loop.
+    fn busy(&self) -> bool {
+        !self.calls.is_empty()
+    }
+
+    /// Whether the event loop has anything to drive: pending calls, or timers
+    /// and ops left running by earlier ones.
+    fn active(&self) -> bool {
+        self.busy() || self.background
+    }
+
+    /// Drive the event loop and the pending calls; an `Err` means polling
+    /// panicked and the isolate must be dropped.
+    fn poll(&mut self, watchdog: &Watchdog, cx: &mut Context<'_>) -> Result<(), String> {
+        let outcome = self.entered(|hosted| {
+            let HostedIsolate {
+                plugin_id,
+                runtime,
+                guard,
+                limits,
+                calls,
+                background,
+                ..
+            } = hosted;
+            let had_calls = !calls.is_empty();
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                guard.run(watchdog, runtime, limits, |rt| {
+                    PluginWorker::poll_calls(rt, calls, limits, cx)
+                })
+            }));
+            *background = false;
+            match outcome {
+                Ok(Ok(Poll::Pending)) => {
+                    *background = true;
+                    Ok(())
+                }
+                Ok(Ok(Poll::Ready(Ok(())))) => Ok(()),
+                // With no call to fail, a background task's error is only logged
+                Ok(Ok(Poll::Ready(Err(e)))) => {
+                    if !had_calls {
+                        log::warn!("Plugin '{}' background task failed: {}", plugin_id, e);
+                    }
+                    Ok(())
+                }
+                // The isolate was terminated; whatever ran belonged to these
+                // calls, and its background work waits for the next message
+                Ok(Err(response)) => {
+                    answer_all(calls, &response);
+                    Ok(())
+                }
+                Err(payload) => {
+                    watchdog.disarm();
+                    let reason = format!(
+                        "Plugin worker panicked: {}",
+                        panic_message(payload.as_ref())
+                    );
+                    answer_all(calls, &WorkerResponse::Crashed(reason.clone()));
+                    Err(reason)
+                }
+            }
+        });
+        self.last_used = Instant::now();
+        outcome
+    }
+
+    fn park(&mut self) {
+        self.entered(|hosted| hosted.runtime.v8_isolate().low_memory_notification());
+        self.parked = true;
+    }
+
+    fn stats(&mut self, thread: usize) -> IsolateStats {
+        let heap_used = self.entered(|hosted| PluginWorker::heap_used(&mut hosted.runtime));
+        IsolateStats {
+            plugin_id: self.plugin_id.clone(),
+            thread,
+            heap_used,
+            idle_ms: self.last_used.elapsed().as_millis() as u64,
+            parked: self.parked,
+        }
+    }
+}
+
+impl Drop for HostedIsolate {
+    fn drop(&mut self) {
+        // The runtime exits its isolate when dropped, so it has to be the current one
+        unsafe { self.runtime.v8_isolate().enter() };
+        // Pending calls hold handles into the isolate; release them first
+        self.calls.clear();
+    }
+}
+
+fn run_pool_thread(
+    index: usize,
+    mut rx: mpsc::UnboundedReceiver<PoolMessage>,
+    isolates: Arc<AtomicUsize>,
+    park_after: Duration,
+) {
+    // Async ops (fetch, process, fs) register with this runtime's reactor,
+    // so it must outlive every isolate hosted on the thread.
+    let tokio_rt = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(rt) => rt,
+        Err(e) => {
+            log::error!("Failed to create plugin pool runtime: {}", e);
+            return;
+        }
+    };
+    let _rt_guard = tokio_rt.enter();
+
+    let watchdog = match Watchdog::spawn() {
+        Ok(watchdog) => watchdog,
+        Err(e) => {
+            log::error!("Failed to start plugin watchdog: {}", e);
+            return;
+        }
+    };
+
+    let mut park_check = tokio::time::interval(PARK_CHECK_INTERVAL);
+    let mut settle_check = tokio::time::interval(SETTLE_CHECK_INTERVAL);
+    settle_check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut hosted: HashMap<u64, HostedIsolate> = HashMap::new();
+    loop {
+        // Messages are handled outside `block_on`: loading a module blocks on
+        // the runtime itself
+        let event = tokio_rt.block_on(std::future::poll_fn(|cx| {
+            poll_pool(
+                cx,
+                &mut rx,
+                &mut hosted,
+                &watchdog,
+                &isolates,
+                &mut park_check,
+                &mut settle_check,
+            )
+        }));
+        match event {
+            PoolEvent::Message(PoolMessage::Spawn {
+                key,
+                plugin_id,
+                limits,
+                factory,
+                failure,
+            }) => {
+                match HostedIsolate::create(plugin_id.clone(), limits, factory, failure.clone()) {
+                    Ok(isolate) => {
+                        hosted.insert(key, isolate);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to start isolate for plugin '{}': {}", plugin_id, e);
+                        record_failure(&failure, e);
+                        isolates.fetch_sub(1, Ordering::SeqCst);
+                    }
+                }
+            }
+            PoolEvent::Message(PoolMessage::Run {
+                key,
+                message: WorkerMessage::Shutdown,
+            }) => {
+                if hosted.remove(&key).is_some() {
+                    isolates.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+            PoolEvent::Message(PoolMessage::Run { key, message }) => {
+                // Messages for a dead isolate are dropped; the caller then reports
+                // the failure recorded on its slot.
+                let Some(isolate) = hosted.get_mut(&key) else {
+                    continue;
+                };
+                if let Err(reason) = isolate.handle(&tokio_rt, &watchdog, message) {
+                    log::error!("Plugin '{}' crashed: {}", isolate.plugin_id, reason);
+                    record_failure(&isolate.failure, reason);
+                    hosted.remove(&key);
+                    isolates.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+            PoolEvent::Message(PoolMessage::Stats { response_tx }) => {
+                let stats = hosted.values_mut().map(|i| i.stats(index)).collect();
+                let _ = response_tx.send(stats);
+            }
+            PoolEvent::Message(PoolMessage::Shutdown) | PoolEvent::Closed => break,
+            PoolEvent::Tick => {}
+        }
+
+        for isolate in hosted.values_mut() {
+            if !isolate.busy() && !isolate.parked && isolate.last_used.elapsed() >= park_after {
+                isolate.park();
+            }
+        }
+    }
+
+    isolates.fetch_sub(hosted.len(), Ordering::SeqCst);
+}
+
+/// Wait for the next message while driving the calls and background work of
+/// every hosted isolate. Also wakes up when a call finishes or a park check is due, so idle
+/// isolates are parked before the next message is handled.
+fn poll_pool(
+    cx: &mut Context<'_>,
+    rx: &mut mpsc::UnboundedReceiver<PoolMessage>,
+    hosted: &mut HashMap<u64, HostedIsolate>,
+    watchdog: &Watchdog,
+    isolates: &AtomicUsize,
+    park_check: &mut Interval,
+    settle_check: &mut Interval,
+) -> Poll<PoolEvent> {
+    if let Poll::Ready(message) = rx.poll_recv(cx) {
+        return Poll::Ready(message.map_or(PoolEvent::Closed, PoolEvent::Message));
+    }
+
+    let mut finished = false;
+    let mut crashed = Vec::new();
+    for (key, isolate) in hosted.iter_mut().filter(|(_, i)| i.active()) {
+        let was_busy = isolate.busy();
+        if let Err(reason) = isolate.poll(watchdog, cx) {
+            log::error!("Plugin '{}' crashed: {}", isolate.plugin_id, reason);
+            record_failure(&isolate.failure, reason);
+            crashed.push(*key);
+        }
+        finished |= was_busy && !isolate.busy();
+    }
+    for key in crashed {
+        hosted.remove(&key);
+        isolates.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    if hosted.values().any(HostedIsolate::busy) {
+        while settle_check.poll_tick(cx).is_ready() {}
+    }
+    if finished || park_check.poll_tick(cx).is_ready() {
+        return Poll::Ready(PoolEvent::Tick);
+    }
+    Poll::Pending
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_system::capabilities::PluginCapabilities;
+    use crate::plugin_system::sandbox::PluginError;
+
+    fn test_limits() -> ResourceLimits {
+        ResourceLimits {
+            max_memory: 64 * 1024 * 1024,
+            max_cpu_time: Duration::from_millis(500),
+            ..ResourceLimits::default()
+        }
+    }
+
+    fn plain_worker(pool: &WorkerPool, id: &str) -> PluginWorker {
+        let limits = test_limits();
+        let params_limits = limits.clone();
+        PluginWorker::spawn_on(
+            pool,
+            id.to_string(),
+            PluginCapabilities::default(),
+            limits,
+            Box::new(move || {
+                Ok(deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+                    create_params: Some(PluginWorker::create_params(&params_limits)),
+                    ..Default::default()
+                }))
+            }),
+        )
+    }
+
+    /// Permits for `op_test_wait_for_release`, handed out by the test.
+    static RELEASE: LazyLock<tokio::sync::Semaphore> =
+        LazyLock::new(|| tokio::sync::Semaphore::new(0));
+
+    #[deno_core::op2(async)]
+    async fn op_test_wait_for_release() -> Result<(), deno_core::error::AnyError> {
+        RELEASE.acquire().await?.forget();
+        Ok(())
+    }
+
+    /// A short timer, for scripts that keep work running in the background.
+    #[deno_core::op2(async)]
+    async fn op_test_tick() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    deno_core::extension!(
+        pool_test_ops,
+        ops = [op_test_wait_for_release, op_test_tick]
+    );
+
+    /// A worker whose scripts can call `op_test_wait_for_release` and `op_test_tick`.
+    fn waiting_worker(pool: &WorkerPool, id: &str) -> PluginWorker {
+        let limits = ResourceLimits {
+            max_cpu_time: Duration::from_secs(10),
+            ..test_limits()
+        };
+        let params_limits = limits.clone();
+        PluginWorker::spawn_on(
+            pool,
+            id.to_string(),
+            PluginCapabilities::default(),
+            limits,
+            Box::new(move || {
+                Ok(deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+                    extensions: vec![pool_test_ops::init_ops_and_esm()],
+                    create_params: Some(PluginWorker::create_params(&params_limits)),
+                    ..Default::default()
+                }))
+            }),
+        )
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_default_thread_count_is_bounded() {
+        let threads = default_thread_count();
+        assert!((1..=MAX_POOL_THREADS).contains(&threads));
+    }
+
+    #[test]
+    fn test_isolates_on_one_thread_keep_separate_globals() {
+        let pool = WorkerPool::new(1, IDLE_PARK_AFTER).unwrap();
+        let workers: Vec<_> = (0..3)
+            .map(|i| plain_worker(&pool, &format!("plugin-{}", i)))
+            .collect();
+
+        block_on(async {
+            for (i, worker) in workers.iter().enumerate() {
+                worker
+                    .execute(format!("globalThis.n = {}; n", i))
+                    .await
+                    .unwrap();
+            }
+            for (i, worker) in workers.iter().enumerate() {
+                let n = worker.execute("globalThis.n".to_string()).await.unwrap();
+                assert_eq!(n, serde_json::json!(i));
+            }
+
+            let stats = pool.stats().await;
+            assert_eq!(stats.threads, 1);
+            assert_eq!(stats.isolates, 3);
+            assert!(stats.heap_used > 0);
+        });
+    }
+
+    #[test]
+    fn test_isolates_can_be_dropped_in_any_order() {
+        let pool = WorkerPool::new(1, IDLE_PARK_AFTER).unwrap();
+        let first = plain_worker(&pool, "first");
+        let second = plain_worker(&pool, "second");
+        let third = plain_worker(&pool, "third");
+
+        block_on(async {
+            first.execute("1".to_string()).await.unwrap();
+            first.shutdown();
+            assert_eq!(
+                second.execute("'still here'".to_string()).await.unwrap(),
+                "still here"
+            );
+
+            let stats = pool.stats().await;
+            let ids: Vec<_> = stats
+                .per_isolate
+                .iter()
+                .map(|s| s.plugin_id.as_str())
+                .collect();
+            assert_eq!(stats.isolates, 2);
+            assert!(!ids.contains(&"first"));
+        });
+
+        drop(third);
+        drop(second);
+        assert_eq!(block_on(pool.stats()).isolates, 0);
+    }
+
+    #[test]
+    fn test_spawn_balances_across_threads() {
+        let pool = WorkerPool::new(2, IDLE_PARK_AFTER).unwrap();
+        let _workers: Vec<_> = (0..4)
+            .map(|i| plain_worker(&pool, &format!("plugin-{}", i)))
+            .collect();
+
+        let stats = block_on(pool.stats());
+        assert_eq!(stats.threads, 2);
+        for thread in 0..2 {
+            assert_eq!(
+                stats
+                    .per_isolate
+                    .iter()
+                    .filter(|s| s.thread == thread)
+                    .count(),
+                2
+            );
+        }
+    }
+
+    #[test]
+    fn test_timeout_on_shared_thread_does_not_affect_neighbour() {
+        let pool = WorkerPool::new(1, IDLE_PARK_AFTER).unwrap();
+        let looping = plain_worker(&pool, "looping");
+        let neighbour = plain_worker(&pool, "neighbour");
+
+        block_on(async {
+            let err = looping
+                .execute("while (true) {}".to_string())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, PluginError::Timeout { .. }));
+
+            assert_eq!(neighbour.execute("6 * 7".to_string()).await.unwrap(), 42);
+            assert_eq!(looping.execute("1 + 1".to_string()).await.unwrap(), 2);
+        });
+    }
+
+    #[test]
+    fn test_call_awaiting_an_op_does_not_hold_the_thread() {
+        let pool = WorkerPool::new(1, IDLE_PARK_AFTER).unwrap();
+        let waiting = waiting_worker(&pool, "waiting");
+        let neighbour = plain_worker(&pool, "neighbour");
+
+        block_on(async {
+            let pending = waiting.execute(
+                "Deno.core.opAsync('op_test_wait_for_release').then(() => 'released')".to_string(),
+            );
+            tokio::pin!(pending);
+
+            // The neighbour is answered while the first call is still waiting
+            tokio::select! {
+                response = &mut pending => panic!("Call settled early: {:?}", response),
+                n = neighbour.execute("6 * 7".to_string()) => assert_eq!(n.unwrap(), 42),
+            }
+            assert_eq!(pool.stats().await.isolates, 2);
+
+            RELEASE.add_permits(1);
+            assert_eq!(pending.await.unwrap(), "released");
+        });
+    }
+
+    #[test]
+    fn test_call_is_answered_while_its_background_work_keeps_running() {
+        let pool = WorkerPool::new(1, IDLE_PARK_AFTER).unwrap();
+        let worker = waiting_worker(&pool, "ticking");
+
+        block_on(async {
+            let started = Instant::now();
+            let response = worker
+                .execute(
+                    "globalThis.ticks = 0;
+                     (async () => {
+                       while (true) {
+                         await Deno.core.opAsync('op_test_tick');
+                         ticks++;
+                       }
+                     })();
+                     'started'"
+                        .to_string(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response, "started");
+            assert!(started.elapsed() < Duration::from_secs(5));
+
+            // The loop keeps ticking between messages
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let ticks = worker.execute("ticks".to_string()).await.unwrap();
+            assert!(ticks.as_f64().unwrap() > 1.0, "ticks: {}", ticks);
+        });
+    }
+
+    #[test]
+    fn test_idle_isolates_are_parked_and_resume() {
+        let pool = WorkerPool::new(1, Duration::ZERO).unwrap();
+        let worker = plain_worker(&pool, "idle");
+
+        block_on(async {
+            worker
+                .execute("globalThis.kept = 'value'".to_string())
+                .await
+                .unwrap();
+            let stats = pool.stats().await;
+            assert!(stats.per_isolate[0].parked);
+            assert_eq!(stats.parked, 1);
+
+            assert_eq!(
+                worker.execute("globalThis.kept".to_string()).await.unwrap(),
+                "value"
+            );
+        });
+    }
+
+    #[test]
+    fn test_failed_isolate_reports_its_failure() {
+        let pool = WorkerPool::new(1, IDLE_PARK_AFTER).unwrap();
+        let worker = PluginWorker::spawn_on(
+            &pool,
+            "broken".to_string(),
+            PluginCapabilities::default(),
+            test_limits(),
+            Box::new(|| Err("Failed to inject plugin API: no runtime".to_string())),
+        );
+
+        let err = block_on(worker.execute("1".to_string())).unwrap_err();
+        assert!(
+            matches!(&err, PluginError::WorkerCrashed(reason) if reason.contains("no runtime")),
+            "Expected WorkerCrashed, got {:?}",
+            err
+        );
+        assert!(worker.failure().is_some());
+        assert_eq!(block_on(pool.stats()).isolates, 0);
+    }
+
+    #[test]
+    fn test_panicking_factory_only_takes_down_its_isolate() {
+        let pool = WorkerPool::new(1, IDLE_PARK_AFTER).unwrap();
+        let healthy = plain_worker(&pool, "healthy");
+        let panicking = PluginWorker::spawn_on(
+            &pool,
+            "panicking".to_string(),
+            PluginCapabilities::default(),
+            test_limits(),
+            Box::new(|| panic!("extension init failed")),
+        );
+
+        block_on(async {
+            let err = panicking.execute("1".to_string()).await.unwrap_err();
+            assert!(
+                matches!(&err, PluginError::WorkerCrashed(reason) if reason.contains("extension init failed")),
+                "Expected WorkerCrashed, got {:?}",
+                err
+            );
+            assert_eq!(healthy.execute("2 + 2".to_string()).await.unwrap(), 4);
+            assert!(healthy.failure().is_none());
+        });
+    }
+}
@@ -1,17 +1,25 @@
 // src-tauri/src/plugin_system/sandbox.rs
 
 use crate::plugin_system::capabilities::PluginCapabilities;
-use crate::plugin_system::loader::PluginManifest;
+use crate::plugin_system::host::PluginHost;
+use crate::plugin_system::loader::{PluginManifest, TomlLimits};
 use crate::plugin_system::ops::EditorStateHandle;
+use crate::plugin_system::trust::TrustLevel;
 use crate::plugin_system::worker::PluginWorker;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
-use tauri::AppHandle;
 use tokio::sync::RwLock;
 
+/// Extra time the sandbox waits for a worker reply beyond `max_cpu_time`.
+///
+/// The worker's watchdog enforces `max_cpu_time` inside the isolate and reports a
+/// precise `Timeout`; this outer bound only catches a worker thread that stopped responding.
+/// Both are measured on the worker's execution budget, so consent prompts don't count.
+const WORKER_REPLY_GRACE: Duration = Duration::from_secs(1);
+
 pub struct PluginSandbox {
     id: String,
     capabilities: PluginCapabilities,
@@ -20,15 +28,193 @@ pub struct PluginSandbox {
     operation_tracker: std::sync::Mutex<OperationTracker>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ResourceLimits {
-    pub max_memory: usize,      // Bytes
-    pub max_cpu_time: Duration, // Per-operation timeout
-    pub max_operations: u64,    // Operations per second
+    pub max_memory: usize,              // Bytes
+    pub max_cpu_time: Duration,         // Per-operation timeout
+    pub max_operations: u64,            // Operations per second
+    pub max_fetch_body_size: usize,     // Bytes per response body
+    pub max_concurrent_commands: usize, // Child processes in flight
+}
+
+const MB: u64 = 1024 * 1024;
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_memory: 50 * 1024 * 1024, // 50MB
+            max_cpu_time: Duration::from_secs(5),
+            max_operations: 100,
+            max_fetch_body_size: 10 * 1024 * 1024, // 10MB
+            max_concurrent_commands: 4,
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Apply the values set in a `[limits]` table on top of these limits.
+    pub fn with_overrides(&self, overrides: &TomlLimits) -> Self {
+        let mut limits = self.clone();
+        if let Some(mb) = overrides.memory_mb {
+            limits.max_memory = mb_to_bytes(mb);
+        }
+        if let Some(ms) = overrides.cpu_time_ms {
+            limits.max_cpu_time = Duration::from_millis(ms);
+        }
+        if let Some(ops) = overrides.ops_per_second {
+            limits.max_operations = ops;
+        }
+        if let Some(mb) = overrides.fetch_body_mb {
+            limits.max_fetch_body_size = mb_to_bytes(mb);
+        }
+        if let Some(n) = overrides.concurrent_commands {
+            limits.max_concurrent_commands = usize::try_from(n).unwrap_or(usize::MAX);
+        }
+        limits
+    }
+
+    /// Lower each limit to `ceiling` where it exceeds it.
+    pub fn clamp_to(&self, ceiling: &ResourceLimits) -> Self {
+        Self {
+            max_memory: self.max_memory.min(ceiling.max_memory),
+            max_cpu_time: self.max_cpu_time.min(ceiling.max_cpu_time),
+            max_operations: self.max_operations.min(ceiling.max_operations),
+            max_fetch_body_size: self.max_fetch_body_size.min(ceiling.max_fetch_body_size),
+            max_concurrent_commands: self
+                .max_concurrent_commands
+                .min(ceiling.max_concurrent_commands),
+        }
+    }
+
+    /// JSON view used by the resource stats and worker info commands.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "max_memory": self.max_memory,
+            "max_cpu_time_ms": self.max_cpu_time.as_millis() as u64,
+            "max_operations": self.max_operations,
+            "max_fetch_body_size": self.max_fetch_body_size,
+            "max_concurrent_commands": self.max_concurrent_commands,
+        })
+    }
+}
+
+fn mb_to_bytes(mb: u64) -> usize {
+    usize::try_from(mb.saturating_mul(MB)).unwrap_or(usize::MAX)
+}
+
+/// User-level ceilings on what a plugin's `[limits]` table may request, by trust level.
+///
+/// Loaded from `plugin_limits.toml` in the app data directory; each table in that
+/// file uses the same keys as a manifest's `[limits]` section and overrides the
+/// built-in ceiling for that trust level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitsPolicy {
+    pub first_party: ResourceLimits,
+    pub verified: ResourceLimits,
+    pub community: ResourceLimits,
+    pub local: ResourceLimits,
+}
+
+/// Raw `plugin_limits.toml` contents
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct TomlLimitsPolicy {
+    first_party: TomlLimits,
+    verified: TomlLimits,
+    community: TomlLimits,
+    local: TomlLimits,
+}
+
+impl Default for LimitsPolicy {
+    fn default() -> Self {
+        let generous = ResourceLimits {
+            max_memory: 512 * 1024 * 1024,
+            max_cpu_time: Duration::from_secs(60),
+            max_operations: 1000,
+            max_fetch_body_size: 100 * 1024 * 1024,
+            max_concurrent_commands: 16,
+        };
+        Self {
+            first_party: generous.clone(),
+            verified: ResourceLimits {
+                max_memory: 256 * 1024 * 1024,
+                max_cpu_time: Duration::from_secs(30),
+                max_operations: 500,
+                max_fetch_body_size: 50 * 1024 * 1024,
+                max_concurrent_commands: 8,
+            },
+            community: ResourceLimits {
+                max_memory: 32 * 1024 * 1024,
+                max_cpu_time: Duration::from_secs(5),
+                max_operations: 100,
+                max_fetch_body_size: 5 * 1024 * 1024,
+                max_concurrent_commands: 2,
+            },
+            local: generous,
+        }
+    }
+}
+
+impl LimitsPolicy {
+    /// Parse a policy file's contents on top of the built-in ceilings.
+    pub fn from_toml_str(content: &str) -> Result<Self, String> {
+        let raw: TomlLimitsPolicy =
+            toml::from_str(content).map_err(|e| format!("Invalid limits policy: {}", e))?;
+        for (level, table) in [
+            ("first-party", &raw.first_party),
+            ("verified", &raw.verified),
+            ("community", &raw.community),
+            ("local", &raw.local),
+        ] {
+            table
+                .validate()
+                .map_err(|e| format!("Invalid limits policy [{}]: {}", level, e))?;
+        }
+
+        let defaults = Self::default();
+        Ok(Self {
+            first_party: defaults.first_party.with_overrides(&raw.first_party),
+            verified: defaults.verified.with_overrides(&raw.verified),
+            community: defaults.community.with_overrides(&raw.community),
+            local: defaults.local.with_overrides(&raw.local),
+        })
+    }
+
+    /// Load the policy file, falling back to the built-in ceilings if it does not exist.
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read limits policy '{}': {}", path.display(), e))?;
+        Self::from_toml_str(&content)
+    }
+
+    /// Ceiling for plugins at the given trust level.
+    pub fn ceiling(&self, trust: &TrustLevel) -> &ResourceLimits {
+        match trust {
+            TrustLevel::FirstParty => &self.first_party,
+            TrustLevel::Verified => &self.verified,
+            TrustLevel::Community => &self.community,
+            TrustLevel::Local => &self.local,
+        }
+    }
+
+    /// Limits a plugin actually runs with: defaults, then its `[limits]` table,
+    /// clamped to the ceiling for its trust level.
+    pub fn effective_limits(&self, manifest: &PluginManifest) -> ResourceLimits {
+        let requested = match &manifest.limits {
+            Some(overrides) => ResourceLimits::default().with_overrides(overrides),
+            None => ResourceLimits::default(),
+        };
+        requested.clamp_to(self.ceiling(&manifest.trust))
+    }
 }
 
+/// Operations per one-second window and the last memory reading; the sandbox
+/// tracks hook calls and the policy engine tracks privileged operations.
 #[derive(Debug, Clone)]
-struct OperationTracker {
+pub(crate) struct OperationTracker {
     window_started: Instant,
     operations_in_window: u64,
     last_operation: SystemTime,
@@ -36,7 +222,7 @@ struct OperationTracker {
 }
 
 impl OperationTracker {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             window_started: Instant::now(),
             operations_in_window: 0,
@@ -64,6 +250,12 @@ impl OperationTracker {
         Ok(stats)
     }
 
+    /// Count one operation without a new memory reading.
+    pub(crate) fn record_call(&mut self, limits: &ResourceLimits) -> Result<(), PluginError> {
+        let memory_used = self.memory_used;
+        self.record_operation(limits, memory_used).map(|_| ())
+    }
+
     fn set_memory_usage(
         &mut self,
         limits: &ResourceLimits,
@@ -88,16 +280,20 @@ impl OperationTracker {
 impl PluginSandbox {
     pub fn new(
         manifest: PluginManifest,
+        resource_limits: ResourceLimits,
+        plugin_root: PathBuf,
         workspace_root: PathBuf,
-        app_handle: AppHandle,
+        host: Arc<dyn PluginHost>,
         editor_state: EditorStateHandle,
     ) -> Result<Self, PluginError> {
         // Create worker for thread-safe JavaScript execution
         let worker = PluginWorker::new(
             manifest.name.clone(),
             manifest.capabilities.clone(),
+            resource_limits.clone(),
+            plugin_root,
             workspace_root,
-            app_handle,
+            host,
             editor_state,
         );
 
@@ -105,11 +301,7 @@ impl PluginSandbox {
             id: manifest.name.clone(),
             capabilities: manifest.capabilities,
             worker: Arc::new(worker),
-            resource_limits: ResourceLimits {
-                max_memory: 50 * 1024 * 1024, // 50MB
-                max_cpu_time: Duration::from_secs(5),
-                max_operations: 100,
-            },
+            resource_limits,
             operation_tracker: std::sync::Mutex::new(OperationTracker::new()),
         })
     }
@@ -134,30 +326,60 @@ impl PluginSandbox {
             .map_err(|e| PluginError::SerializationError(e.to_string()))?;
 
         // Use worker for thread-safe execution with timeout
-        let result = tokio::time::timeout(
-            self.resource_limits.max_cpu_time,
-            self.worker.call_hook(hook.to_string(), args_value),
-        )
-        .await
-        .map_err(|_| PluginError::Timeout {
-            duration: self.resource_limits.max_cpu_time,
-        })?;
+        let result = self
+            .worker
+            .budget()
+            .within(
+                self.resource_limits.max_cpu_time + WORKER_REPLY_GRACE,
+                self.worker.call_hook(hook.to_string(), args_value),
+            )
+            .await
+            .ok_or(PluginError::Timeout {
+                duration: self.resource_limits.max_cpu_time,
+            })?;
+
+        let memory_after = self.worker.get_memory_usage().await?;
+        self.update_memory_usage(memory_after)?;
+        result
+    }
+
+    /// Load the plugin's entry point (and its imports) as an ES module
+    pub async fn load_module(&self, entry: PathBuf) -> Result<(), PluginError> {
+        let memory_before = self.worker.get_memory_usage().await?;
+        self.record_resource_usage(memory_before)?;
+        let result = self
+            .worker
+            .budget()
+            .within(
+                self.resource_limits.max_cpu_time + WORKER_REPLY_GRACE,
+                self.worker.load_module(entry),
+            )
+            .await
+            .ok_or(PluginError::Timeout {
+                duration: self.resource_limits.max_cpu_time,
+            })?;
 
         let memory_after = self.worker.get_memory_usage().await?;
         self.update_memory_usage(memory_after)?;
         result
     }
 
-    /// Execute JavaScript code in the sandbox
+    /// Execute JavaScript code in the sandbox as a classic script
+    #[allow(dead_code)]
     pub async fn execute(&self, code: String) -> Result<serde_json::Value, PluginError> {
         let memory_before = self.worker.get_memory_usage().await?;
         self.record_resource_usage(memory_before)?;
-        let result =
-            tokio::time::timeout(self.resource_limits.max_cpu_time, self.worker.execute(code))
-                .await
-                .map_err(|_| PluginError::Timeout {
-                    duration: self.resource_limits.max_cpu_time,
-                })?;
+        let result = self
+            .worker
+            .budget()
+            .within(
+                self.resource_limits.max_cpu_time + WORKER_REPLY_GRACE,
+                self.worker.execute(code),
+            )
+            .await
+            .ok_or(PluginError::Timeout {
+                duration: self.resource_limits.max_cpu_time,
+            })?;
 
         let memory_after = self.worker.get_memory_usage().await?;
         self.update_memory_usage(memory_after)?;
@@ -174,6 +396,16 @@ impl PluginSandbox {
         &self.capabilities
     }
 
+    /// Replace the sandbox capabilities, including the ones its ops check
+    pub async fn set_capabilities(
+        &mut self,
+        capabilities: PluginCapabilities,
+    ) -> Result<(), PluginError> {
+        self.worker.set_capabilities(capabilities.clone()).await?;
+        self.capabilities = capabilities;
+        Ok(())
+    }
+
     /// Get resource limits
     pub fn resource_limits(&self) -> &ResourceLimits {
         &self.resource_limits
@@ -191,6 +423,17 @@ impl PluginSandbox {
             })
     }
 
+    /// Why the worker's isolate died, if it has; polled by the plugin supervisor.
+    pub fn worker_failure(&self) -> Option<String> {
+        self.worker.failure()
+    }
+
+    /// Kill the worker's isolate as a crash would, for supervisor tests.
+    #[cfg(test)]
+    pub(crate) fn kill_worker(&self, reason: &str) {
+        self.worker.kill(reason);
+    }
+
     /// Check if plugin is within resource limits
     pub fn check_resource_limits(&self) -> Result<(), PluginError> {
         let stats = self.get_resource_stats();
@@ -311,13 +554,32 @@ pub enum PluginError {
     #[error("Rate limit exceeded: current {current}, limit {limit}")]
     RateLimitExceeded { current: u64, limit: u64 },
 
+    #[error("Command limit exceeded: {running} running, limit {limit}")]
+    CommandLimitExceeded { running: usize, limit: usize },
+
+    #[error("Response body exceeds the fetch limit of {limit} bytes")]
+    FetchBodyTooLarge { limit: usize },
+
     #[error("Worker disconnected")]
     WorkerDisconnected,
 
+    #[error("Worker crashed: {0}")]
+    WorkerCrashed(String),
+
     #[error("Worker already exists")]
     WorkerAlreadyExists,
 }
 
+impl PluginError {
+    /// Whether the plugin was stopped for exceeding its CPU or memory budget.
+    pub fn is_resource_violation(&self) -> bool {
+        matches!(
+            self,
+            PluginError::Timeout { .. } | PluginError::MemoryLimitExceeded { .. }
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -370,22 +632,140 @@ mod tests {
         assert_eq!(err.to_string(), "Worker disconnected");
     }
 
+    #[test]
+    fn test_plugin_error_worker_crashed_display() {
+        let err = PluginError::WorkerCrashed("Failed to inject plugin API".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Worker crashed: Failed to inject plugin API"
+        );
+    }
+
     #[test]
     fn test_plugin_error_worker_already_exists() {
         let err = PluginError::WorkerAlreadyExists;
         assert_eq!(err.to_string(), "Worker already exists");
     }
 
+    #[test]
+    fn test_plugin_error_resource_violation() {
+        assert!(PluginError::Timeout {
+            duration: Duration::from_secs(5)
+        }
+        .is_resource_violation());
+        assert!(PluginError::MemoryLimitExceeded { used: 2, limit: 1 }.is_resource_violation());
+        assert!(!PluginError::ExecutionError("boom".to_string()).is_resource_violation());
+        assert!(!PluginError::RateLimitExceeded {
+            current: 2,
+            limit: 1
+        }
+        .is_resource_violation());
+    }
+
     #[test]
     fn test_resource_limits_default() {
-        let limits = ResourceLimits {
-            max_memory: 50 * 1024 * 1024,
-            max_cpu_time: Duration::from_secs(5),
-            max_operations: 100,
-        };
+        let limits = ResourceLimits::default();
         assert_eq!(limits.max_memory, 52_428_800);
         assert_eq!(limits.max_cpu_time, Duration::from_secs(5));
         assert_eq!(limits.max_operations, 100);
+        assert_eq!(limits.max_fetch_body_size, 10 * 1024 * 1024);
+        assert_eq!(limits.max_concurrent_commands, 4);
+    }
+
+    fn manifest_with(trust: TrustLevel, limits: Option<TomlLimits>) -> PluginManifest {
+        let mut manifest: PluginManifest =
+            toml::from_str("name = \"indexer\"\nversion = \"1.0.0\"").unwrap();
+        manifest.trust = trust;
+        manifest.limits = limits;
+        manifest
+    }
+
+    #[test]
+    fn test_resource_limits_with_overrides() {
+        let overrides = TomlLimits {
+            memory_mb: Some(128),
+            cpu_time_ms: Some(1500),
+            concurrent_commands: Some(1),
+            ..TomlLimits::default()
+        };
+        let limits = ResourceLimits::default().with_overrides(&overrides);
+        assert_eq!(limits.max_memory, 128 * 1024 * 1024);
+        assert_eq!(limits.max_cpu_time, Duration::from_millis(1500));
+        assert_eq!(limits.max_concurrent_commands, 1);
+        // Unset keys keep their defaults
+        assert_eq!(limits.max_operations, 100);
+        assert_eq!(limits.max_fetch_body_size, 10 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_effective_limits_without_manifest_limits() {
+        let policy = LimitsPolicy::default();
+        let manifest = manifest_with(TrustLevel::FirstParty, None);
+        assert_eq!(
+            policy.effective_limits(&manifest),
+            ResourceLimits::default()
+        );
+    }
+
+    #[test]
+    fn test_effective_limits_grants_headroom_to_trusted_plugins() {
+        let policy = LimitsPolicy::default();
+        let manifest = manifest_with(
+            TrustLevel::FirstParty,
+            Some(TomlLimits {
+                memory_mb: Some(256),
+                cpu_time_ms: Some(20_000),
+                ..TomlLimits::default()
+            }),
+        );
+        let limits = policy.effective_limits(&manifest);
+        assert_eq!(limits.max_memory, 256 * 1024 * 1024);
+        assert_eq!(limits.max_cpu_time, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_effective_limits_clamps_community_plugins() {
+        let policy = LimitsPolicy::default();
+        let manifest = manifest_with(
+            TrustLevel::Community,
+            Some(TomlLimits {
+                memory_mb: Some(1024),
+                concurrent_commands: Some(64),
+                ..TomlLimits::default()
+            }),
+        );
+        let limits = policy.effective_limits(&manifest);
+        assert_eq!(limits.max_memory, policy.community.max_memory);
+        assert_eq!(limits.max_concurrent_commands, 2);
+
+        // Community plugins get less than the default even without asking for more
+        let limits = policy.effective_limits(&manifest_with(TrustLevel::Community, None));
+        assert!(limits.max_memory < ResourceLimits::default().max_memory);
+    }
+
+    #[test]
+    fn test_limits_policy_from_toml_overrides_ceiling() {
+        let policy = LimitsPolicy::from_toml_str(
+            "[community]\nmemory_mb = 16\n\n[first-party]\ncpu_time_ms = 120000\n",
+        )
+        .unwrap();
+        assert_eq!(policy.community.max_memory, 16 * 1024 * 1024);
+        assert_eq!(policy.first_party.max_cpu_time, Duration::from_secs(120));
+        assert_eq!(policy.verified, LimitsPolicy::default().verified);
+    }
+
+    #[test]
+    fn test_limits_policy_rejects_unknown_and_zero_values() {
+        assert!(LimitsPolicy::from_toml_str("[community]\nmemroy_mb = 16\n").is_err());
+        assert!(LimitsPolicy::from_toml_str("[community]\nmemory_mb = 0\n").is_err());
+        assert!(LimitsPolicy::from_toml_str("[unknown]\nmemory_mb = 16\n").is_err());
+    }
+
+    #[test]
+    fn test_limits_policy_missing_file_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = LimitsPolicy::load_from_file(&dir.path().join("plugin_limits.toml")).unwrap();
+        assert_eq!(policy, LimitsPolicy::default());
     }
 
     #[test]
@@ -423,6 +803,7 @@ mod tests {
             max_memory: 50 * 1024 * 1024,
             max_cpu_time: Duration::from_secs(5),
             max_operations: 100,
+            ..ResourceLimits::default()
         };
         let stats = ResourceStats {
             memory_used: 0,
@@ -438,6 +819,7 @@ mod tests {
             max_memory: 50 * 1024 * 1024,
             max_cpu_time: Duration::from_secs(5),
             max_operations: 2,
+            ..ResourceLimits::default()
         };
         let stats = ResourceStats {
             memory_used: 0,
@@ -454,6 +836,7 @@ mod tests {
             max_memory: 10,
             max_cpu_time: Duration::from_secs(5),
             max_operations: 2,
+            ..ResourceLimits::default()
         };
         let mut tracker = OperationTracker::new();
         assert!(tracker.record_operation(&limits, 3).is_ok());
@@ -476,6 +859,7 @@ mod tests {
             max_memory: 20,
             max_cpu_time: Duration::from_secs(5),
             max_operations: 100,
+            ..ResourceLimits::default()
         };
         let mut tracker = OperationTracker::new();
 
@@ -544,6 +928,7 @@ mod tests {
             max_memory: usize::MAX,
             max_cpu_time: Duration::from_secs(3600),
             max_operations: u64::MAX,
+            ..ResourceLimits::default()
         };
         assert_eq!(limits.max_memory, usize::MAX);
         assert_eq!(limits.max_cpu_time, Duration::from_secs(3600));
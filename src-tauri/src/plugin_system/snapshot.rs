@@ -0,0 +1,146 @@
+// src-tauri/src/plugin_system/snapshot.rs
+//
+// V8 startup snapshot for plugin isolates. build.rs runs js/plugin_api.js once
+// and serializes the heap; every plugin runtime starts from that heap and only
+// binds the skretchpad_plugin_ops ops, instead of re-executing the API script.
+
+use crate::plugin_system::ops::skretchpad_plugin_ops;
+use crate::plugin_system::plugin_api_ext::skretchpad_plugin_api;
+
+static PLUGIN_SNAPSHOT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/PLUGIN_SNAPSHOT.bin"));
+
+/// Snapshot containing the plugin API globals (`skretchpad`, `registerHook`, `__hooks__`, ...).
+pub fn startup_snapshot() -> deno_core::Snapshot {
+    deno_core::Snapshot::Static(PLUGIN_SNAPSHOT)
+}
+
+/// Extensions for a runtime created from [`startup_snapshot`], in snapshot order.
+/// Their JavaScript is already in the snapshot, so only ops are initialized.
+pub fn extensions() -> Vec<deno_core::Extension> {
+    vec![
+        skretchpad_plugin_api::init_ops(),
+        skretchpad_plugin_ops::init_ops(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_system::module_loader::PluginModuleLoader;
+    use crate::plugin_system::transpile::SourceMapStore;
+    use std::path::{Path, PathBuf};
+
+    fn snapshot_runtime(root: Option<&Path>) -> deno_core::JsRuntime {
+        let module_loader = root.map(|root| {
+            std::rc::Rc::new(PluginModuleLoader::new(
+                root.to_path_buf(),
+                SourceMapStore::default(),
+            )) as std::rc::Rc<dyn deno_core::ModuleLoader>
+        });
+        deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+            module_loader,
+            startup_snapshot: Some(startup_snapshot()),
+            extensions: extensions(),
+            ..Default::default()
+        })
+    }
+
+    fn eval_bool(runtime: &mut deno_core::JsRuntime, code: &'static str) -> bool {
+        let value = runtime
+            .execute_script("snapshot_check.js", deno_core::FastString::Static(code))
+            .unwrap();
+        let scope = &mut runtime.handle_scope();
+        deno_core::v8::Local::new(scope, value).is_true()
+    }
+
+    #[test]
+    fn test_snapshot_contains_plugin_api() {
+        let mut runtime = snapshot_runtime(None);
+        assert!(eval_bool(
+            &mut runtime,
+            "typeof registerHook === 'function' && \
+             typeof skretchpad.fs.readFileAsync === 'function' && \
+             typeof skretchpad.permissions.request === 'function' && \
+             typeof skretchpad.audit.poll === 'function' && \
+             Object.getPrototypeOf(globalThis.__hooks__) === null",
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_runtime_binds_plugin_ops() {
+        let mut runtime = snapshot_runtime(None);
+        assert!(eval_bool(
+            &mut runtime,
+            "typeof Deno.core.ops.op_plugin_read_file === 'function' && \
+             typeof Deno.core.ops.op_plugin_execute_command_async === 'function' && \
+             typeof Deno.core.ops.op_plugin_request_permission === 'function' && \
+             typeof Deno.core.ops.op_plugin_audit_poll === 'function'",
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_runtimes_do_not_share_hooks() {
+        let mut first = snapshot_runtime(None);
+        first
+            .execute_script(
+                "register.js",
+                deno_core::FastString::Static("registerHook('on_file_save', () => 1);"),
+            )
+            .unwrap();
+
+        let mut second = snapshot_runtime(None);
+        assert!(eval_bool(
+            &mut second,
+            "!('on_file_save' in globalThis.__hooks__)"
+        ));
+    }
+
+    fn plugins_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../plugins")
+            .canonicalize()
+            .unwrap()
+    }
+
+    fn activate(
+        tokio_rt: &tokio::runtime::Runtime,
+        mut runtime: deno_core::JsRuntime,
+        entry: &Path,
+    ) -> deno_core::JsRuntime {
+        let specifier = deno_core::ModuleSpecifier::from_file_path(entry).unwrap();
+        tokio_rt
+            .block_on(async {
+                let id = runtime.load_main_module(&specifier, None).await?;
+                let evaluation = runtime.mod_evaluate(id);
+                runtime.run_event_loop(false).await?;
+                evaluation.await
+            })
+            .unwrap();
+        runtime
+    }
+
+    #[test]
+    fn test_bundled_plugins_activate_from_snapshot() {
+        let tokio_rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let _guard = tokio_rt.enter();
+        for plugin in ["git", "git-status"] {
+            let root = plugins_dir().join(plugin);
+            let mut runtime = activate(
+                &tokio_rt,
+                snapshot_runtime(Some(&root)),
+                &root.join("main.js"),
+            );
+            assert!(
+                eval_bool(
+                    &mut runtime,
+                    "typeof globalThis.__hooks__.activate === 'function'"
+                ),
+                "{} did not register its hooks",
+                plugin
+            );
+        }
+    }
+}
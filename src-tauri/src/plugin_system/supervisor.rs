@@ -0,0 +1,196 @@
+// src-tauri/src/plugin_system/supervisor.rs
+//
+// Restart bookkeeping for plugins whose worker died. The app's supervisor task
+// finds dead workers with `PluginManager::supervision_work` and tears them down
+// with `PluginManager::begin_supervision`; this module decides whether and when
+// to restart them.
+
+use std::time::{Duration, Instant};
+
+/// How often the supervisor checks active plugins for dead workers.
+pub const SUPERVISE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How the supervisor restarts plugins whose worker died.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartPolicy {
+    /// Restarts allowed before the plugin is considered crash looping
+    pub max_restarts: u32,
+    /// Delay before the first restart; doubled for each further restart
+    pub initial_backoff: Duration,
+    /// Upper bound on the restart delay
+    pub max_backoff: Duration,
+    /// A plugin that runs this long without crashing gets its restart budget back
+    pub reset_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            reset_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restart number `restarts + 1`.
+    pub fn backoff(&self, restarts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(restarts);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// What to do after a worker crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashDecision {
+    /// Restart the plugin once `after` has elapsed
+    Restart { after: Duration },
+    /// The restart budget is spent; leave the plugin in `PluginState::Error`
+    GiveUp,
+}
+
+/// Crash history of one plugin.
+#[derive(Debug, Clone, Default)]
+pub struct CrashRecord {
+    /// Restarts attempted since the plugin last ran stably
+    pub restarts: u32,
+    /// Reason the worker died most recently
+    pub last_failure: Option<String>,
+    last_crash: Option<Instant>,
+    next_restart: Option<Instant>,
+}
+
+impl CrashRecord {
+    /// Record a crash at `now` and decide whether to restart.
+    pub fn record_crash(
+        &mut self,
+        policy: &RestartPolicy,
+        reason: String,
+        now: Instant,
+    ) -> CrashDecision {
+        if self
+            .last_crash
+            .is_some_and(|last| now.saturating_duration_since(last) >= policy.reset_after)
+        {
+            self.restarts = 0;
+        }
+        self.last_crash = Some(now);
+        self.last_failure = Some(reason);
+
+        if self.restarts >= policy.max_restarts {
+            self.next_restart = None;
+            return CrashDecision::GiveUp;
+        }
+        let after = policy.backoff(self.restarts);
+        self.next_restart = Some(now + after);
+        CrashDecision::Restart { after }
+    }
+
+    /// Whether a scheduled restart is due at `now`.
+    pub fn restart_due(&self, now: Instant) -> bool {
+        self.next_restart.is_some_and(|at| now >= at)
+    }
+
+    /// Claim a due restart, counting it against the budget.
+    pub fn begin_restart(&mut self) {
+        self.next_restart = None;
+        self.restarts += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RestartPolicy {
+        RestartPolicy {
+            max_restarts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            reset_after: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_is_capped() {
+        let policy = policy();
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(3), Duration::from_secs(5));
+        assert_eq!(policy.backoff(40), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_crash_loop_gives_up_after_max_restarts() {
+        let policy = policy();
+        let mut record = CrashRecord::default();
+        let mut now = Instant::now();
+
+        for expected in [1, 2, 4] {
+            let decision = record.record_crash(&policy, "panicked".to_string(), now);
+            assert_eq!(
+                decision,
+                CrashDecision::Restart {
+                    after: Duration::from_secs(expected)
+                }
+            );
+            assert!(!record.restart_due(now));
+            now += Duration::from_secs(expected);
+            assert!(record.restart_due(now));
+            record.begin_restart();
+            assert!(!record.restart_due(now));
+        }
+
+        assert_eq!(
+            record.record_crash(&policy, "panicked again".to_string(), now),
+            CrashDecision::GiveUp
+        );
+        assert_eq!(record.restarts, 3);
+        assert_eq!(record.last_failure.as_deref(), Some("panicked again"));
+        assert!(!record.restart_due(now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_stable_run_resets_restart_budget() {
+        let policy = policy();
+        let mut record = CrashRecord::default();
+        let start = Instant::now();
+
+        record.record_crash(&policy, "first".to_string(), start);
+        record.begin_restart();
+        record.record_crash(&policy, "second".to_string(), start);
+        record.begin_restart();
+        assert_eq!(record.restarts, 2);
+
+        let decision = record.record_crash(
+            &policy,
+            "much later".to_string(),
+            start + policy.reset_after,
+        );
+        assert_eq!(
+            decision,
+            CrashDecision::Restart {
+                after: policy.initial_backoff
+            }
+        );
+        assert_eq!(record.restarts, 0);
+    }
+
+    #[test]
+    fn test_zero_restarts_gives_up_immediately() {
+        let policy = RestartPolicy {
+            max_restarts: 0,
+            ..policy()
+        };
+        let mut record = CrashRecord::default();
+        assert_eq!(
+            record.record_crash(&policy, "boom".to_string(), Instant::now()),
+            CrashDecision::GiveUp
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// src-tauri/src/plugin_system/transpile.rs
+//
+// TypeScript support for plugin modules: type-stripping via deno_ast, a
+// process-wide cache keyed by content hash, and a source map store so errors
+// from transpiled code point at the original TypeScript lines.
+
+use deno_core::error::{generic_error, AnyError};
+use deno_core::{ModuleSpecifier, SourceMapGetter};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, LazyLock, Mutex};
+
+/// Upper bound on cached transpile results; the cache is cleared when exceeded.
+const MAX_CACHED_MODULES: usize = 256;
+
+/// Transpiled output for one TypeScript module.
+#[derive(Debug)]
+pub struct TranspiledModule {
+    /// Original TypeScript source (used to print the offending line in errors)
+    pub source: String,
+    /// Emitted JavaScript
+    pub code: String,
+    /// Source map from `code` back to `source`
+    pub source_map: Option<Vec<u8>>,
+}
+
+/// Transpile results keyed by a SHA-256 of the module specifier and its source,
+/// so unchanged files are not re-transpiled when a plugin reloads.
+static TRANSPILE_CACHE: LazyLock<Mutex<HashMap<String, Arc<TranspiledModule>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn cache_key(specifier: &ModuleSpecifier, source: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(specifier.as_str().as_bytes());
+    hasher.update([0u8]);
+    hasher.update(source.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Strip TypeScript types from `source`, returning the cached result when the
+/// same file content was transpiled before.
+pub fn transpile_typescript(
+    specifier: &ModuleSpecifier,
+    source: String,
+) -> Result<Arc<TranspiledModule>, AnyError> {
+    let key = cache_key(specifier, &source);
+    if let Some(cached) = TRANSPILE_CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&key).cloned())
+    {
+        return Ok(cached);
+    }
+
+    let parsed = deno_ast::parse_module(deno_ast::ParseParams {
+        specifier: specifier.to_string(),
+        text_info: deno_ast::SourceTextInfo::from_string(source.clone()),
+        media_type: deno_ast::MediaType::TypeScript,
+        capture_tokens: false,
+        scope_analysis: false,
+        maybe_syntax: None,
+    })
+    .map_err(|e| generic_error(format!("Failed to parse '{}': {}", specifier, e)))?;
+
+    let emitted = parsed
+        .transpile(&deno_ast::EmitOptions {
+            source_map: true,
+            inline_source_map: false,
+            inline_sources: true,
+            ..Default::default()
+        })
+        .map_err(|e| generic_error(format!("Failed to transpile '{}': {}", specifier, e)))?;
+
+    let module = Arc::new(TranspiledModule {
+        source,
+        code: emitted.text,
+        source_map: emitted.source_map.map(String::into_bytes),
+    });
+
+    if let Ok(mut cache) = TRANSPILE_CACHE.lock() {
+        if cache.len() >= MAX_CACHED_MODULES {
+            cache.clear();
+        }
+        cache.insert(key, module.clone());
+    }
+    Ok(module)
+}
+
+/// Source maps for the TypeScript modules loaded into one plugin runtime.
+///
+/// Shared between the module loader (which records modules as it transpiles them)
+/// and the runtime's `source_map_getter`.
+#[derive(Clone, Default)]
+pub struct SourceMapStore(Rc<RefCell<HashMap<String, Arc<TranspiledModule>>>>);
+
+impl SourceMapStore {
+    pub fn insert(&self, specifier: &ModuleSpecifier, module: Arc<TranspiledModule>) {
+        self.0.borrow_mut().insert(specifier.to_string(), module);
+    }
+}
+
+impl SourceMapGetter for SourceMapStore {
+    fn get_source_map(&self, file_name: &str) -> Option<Vec<u8>> {
+        self.0.borrow().get(file_name)?.source_map.clone()
+    }
+
+    fn get_source_line(&self, file_name: &str, line_number: usize) -> Option<String> {
+        let modules = self.0.borrow();
+        let module = modules.get(file_name)?;
+        module.source.lines().nth(line_number).map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specifier(name: &str) -> ModuleSpecifier {
+        ModuleSpecifier::parse(&format!("file:///plugins/ts/{}", name)).unwrap()
+    }
+
+    #[test]
+    fn test_transpile_strips_types() {
+        let module = transpile_typescript(
+            &specifier("strip.ts"),
+            "interface Options { name: string }\n\
+             export const greet = (opts: Options): string => `hi ${opts.name}`;\n"
+                .to_string(),
+        )
+        .unwrap();
+        assert!(!module.code.contains("interface"));
+        assert!(!module.code.contains(": string"));
+        assert!(module.code.contains("greet"));
+        assert!(module.source_map.is_some());
+    }
+
+    #[test]
+    fn test_transpile_reuses_cached_output() {
+        let spec = specifier("cached.ts");
+        let source = "export const n: number = 1;\n".to_string();
+        let first = transpile_typescript(&spec, source.clone()).unwrap();
+        let second = transpile_typescript(&spec, source).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let changed =
+            transpile_typescript(&spec, "export const n: number = 2;\n".to_string()).unwrap();
+        assert!(!Arc::ptr_eq(&first, &changed));
+    }
+
+    #[test]
+    fn test_transpile_reports_syntax_errors() {
+        let err = transpile_typescript(&specifier("broken.ts"), "export const = ;".to_string())
+            .unwrap_err();
+        assert!(err.to_string().contains("broken.ts"));
+    }
+
+    #[test]
+    fn test_source_map_store_serves_original_lines() {
+        let spec = specifier("store.ts");
+        let module = transpile_typescript(
+            &spec,
+            "type Id = string;\nexport const id: Id = 'a';\n".to_string(),
+        )
+        .unwrap();
+        let store = SourceMapStore::default();
+        store.insert(&spec, module);
+
+        assert!(store.get_source_map(spec.as_str()).is_some());
+        assert_eq!(
+            store.get_source_line(spec.as_str(), 1).as_deref(),
+            Some("export const id: Id = 'a';")
+        );
+        assert!(store
+            .get_source_map("file:///plugins/ts/other.ts")
+            .is_none());
+    }
+}
@@ -1,7 +1,7 @@
 // src-tauri/src/plugin_system/trust.rs
 
 use base64::Engine;
-use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
@@ -39,11 +39,22 @@ impl TrustLevel {
     }
 }
 
+/// Version of the payload `sign_plugin` signs. Version 1 hashed plugin.toml
+/// byte-for-byte and only the entrypoint; version 2 hashes every plugin file.
+pub const SIGNATURE_PAYLOAD_VERSION: u8 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginSignature {
     pub public_key: String,
     pub signature: Vec<u8>,
     pub timestamp: SystemTime,
+    /// Signatures written before the payload was versioned are version 1
+    #[serde(default = "legacy_payload_version")]
+    pub payload_version: u8,
+}
+
+fn legacy_payload_version() -> u8 {
+    1
 }
 
 impl PluginSignature {
@@ -53,7 +64,21 @@ impl PluginSignature {
             public_key,
             signature,
             timestamp: SystemTime::now(),
+            payload_version: SIGNATURE_PAYLOAD_VERSION,
+        }
+    }
+
+    /// Refuse a signature over an older payload format, which can never
+    /// verify against the current one, with an explicit re-sign message.
+    pub fn check_payload_version(&self) -> Result<(), String> {
+        if self.payload_version == SIGNATURE_PAYLOAD_VERSION {
+            return Ok(());
         }
+        Err(format!(
+            "Signature uses payload version {}, but version {} is required; \
+             re-sign required (`skretchpad-plugin sign`)",
+            self.payload_version, SIGNATURE_PAYLOAD_VERSION
+        ))
     }
 
     pub fn is_valid(&self) -> bool {
@@ -78,8 +103,14 @@ struct SignaturePayload<'a> {
     source: &'a str,
     trust: &'a TrustLevel,
     timestamp_secs: u64,
-    plugin_toml_sha256: String,
-    entrypoint_sha256: String,
+    files: Vec<FileDigest>,
+}
+
+/// One file of a signed plugin, by its `/`-separated path under the plugin root
+#[derive(Serialize)]
+struct FileDigest {
+    path: String,
+    sha256: String,
 }
 
 pub fn build_plugin_signature_payload(
@@ -88,26 +119,8 @@ pub fn build_plugin_signature_payload(
     manifest: &crate::plugin_system::loader::PluginManifest,
     timestamp: SystemTime,
 ) -> Result<Vec<u8>, String> {
-    let plugin_toml_path = plugin_root.join("plugin.toml");
-    let entrypoint_path = plugin_root.join(&manifest.main);
-
-    let plugin_toml_bytes = std::fs::read(&plugin_toml_path).map_err(|e| {
-        format!(
-            "Failed to read plugin manifest for signature payload '{}': {}",
-            plugin_toml_path.display(),
-            e
-        )
-    })?;
-    let entrypoint_bytes = std::fs::read(&entrypoint_path).map_err(|e| {
-        format!(
-            "Failed to read plugin entrypoint for signature payload '{}': {}",
-            entrypoint_path.display(),
-            e
-        )
-    })?;
-
     let payload = SignaturePayload {
-        version: 1,
+        version: SIGNATURE_PAYLOAD_VERSION,
         plugin_id,
         name: &manifest.name,
         plugin_version: &manifest.version,
@@ -118,14 +131,44 @@ pub fn build_plugin_signature_payload(
             .duration_since(SystemTime::UNIX_EPOCH)
             .map_err(|e| format!("Invalid signature timestamp: {}", e))?
             .as_secs(),
-        plugin_toml_sha256: hex_sha256(&plugin_toml_bytes),
-        entrypoint_sha256: hex_sha256(&entrypoint_bytes),
+        files: plugin_file_digests(plugin_root)?,
     };
 
     serde_json::to_vec(&payload)
         .map_err(|e| format!("Failed to serialize signature payload: {}", e))
 }
 
+/// Sign every file of a plugin as it is now with `signing_key`.
+///
+/// The returned signature belongs in the manifest's `[signature]` table; that
+/// table is left out of the manifest hash, so writing it does not invalidate it.
+pub fn sign_plugin(
+    plugin_id: &str,
+    plugin_root: &Path,
+    manifest: &crate::plugin_system::loader::PluginManifest,
+    signing_key: &SigningKey,
+) -> Result<PluginSignature, String> {
+    // The payload carries whole seconds, so the signature's timestamp must too
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| format!("Invalid signature timestamp: {}", e))?
+        .as_secs();
+    let timestamp = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+    let payload = build_plugin_signature_payload(plugin_id, plugin_root, manifest, timestamp)?;
+
+    Ok(PluginSignature {
+        public_key: encode_public_key(&signing_key.verifying_key()),
+        signature: signing_key.sign(&payload).to_bytes().to_vec(),
+        timestamp,
+        payload_version: SIGNATURE_PAYLOAD_VERSION,
+    })
+}
+
+/// Base64 form of a public key, as stored in signatures and trusted key lists.
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    base64::engine::general_purpose::STANDARD.encode(key.to_bytes())
+}
+
 pub struct TrustVerifier {
     trusted_keys: HashSet<String>,
 }
@@ -239,6 +282,81 @@ impl TrustVerifier {
     }
 }
 
+/// Hash every file under `plugin_root`, sorted by relative path, so modules
+/// the entrypoint imports are signed too. plugin.toml is hashed without its
+/// `[signature]` table; symlinks are refused rather than followed.
+fn plugin_file_digests(plugin_root: &Path) -> Result<Vec<FileDigest>, String> {
+    let read_error = |path: &Path, e: std::io::Error| {
+        format!(
+            "Failed to read '{}' for signature payload: {}",
+            path.display(),
+            e
+        )
+    };
+    let mut files = Vec::new();
+    let mut pending = vec![plugin_root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(|e| read_error(&dir, e))? {
+            let path = entry.map_err(|e| read_error(&dir, e))?.path();
+            let file_type = std::fs::symlink_metadata(&path)
+                .map_err(|e| read_error(&path, e))?
+                .file_type();
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            if file_type.is_symlink() {
+                return Err(format!(
+                    "Signed plugins cannot contain symlinks: '{}'",
+                    path.display()
+                ));
+            }
+            let relative = path
+                .strip_prefix(plugin_root)
+                .map_err(|e| format!("Failed to sign '{}': {}", path.display(), e))?
+                .components()
+                .map(|c| {
+                    c.as_os_str().to_str().ok_or_else(|| {
+                        format!("Plugin file name is not valid UTF-8: '{}'", path.display())
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .join("/");
+            let mut bytes = std::fs::read(&path).map_err(|e| read_error(&path, e))?;
+            if relative == "plugin.toml" {
+                bytes = unsigned_manifest_bytes(&bytes)?;
+            }
+            files.push(FileDigest {
+                path: relative,
+                sha256: hex_sha256(&bytes),
+            });
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// plugin.toml without its `[signature]` table, which cannot sign itself.
+fn unsigned_manifest_bytes(plugin_toml: &[u8]) -> Result<Vec<u8>, String> {
+    let content = std::str::from_utf8(plugin_toml)
+        .map_err(|e| format!("Plugin manifest is not valid UTF-8: {}", e))?;
+    let mut table: toml::Table = toml::from_str(content).map_err(|e| {
+        format!(
+            "Failed to parse plugin manifest for signature payload: {}",
+            e
+        )
+    })?;
+    table.remove("signature");
+    toml::to_string(&table)
+        .map(String::into_bytes)
+        .map_err(|e| {
+            format!(
+                "Failed to serialize plugin manifest for signature payload: {}",
+                e
+            )
+        })
+}
+
 fn hex_sha256(input: &[u8]) -> String {
     let digest = Sha256::digest(input);
     digest.iter().map(|b| format!("{:02x}", b)).collect()
@@ -421,6 +539,7 @@ trust = "verified"
             capabilities: PluginCapabilities::default(),
             permissions: None,
             ui: None,
+            limits: None,
             dependencies: vec![],
             source: "https://example.com/signed-plugin".to_string(),
             signature: None,
@@ -450,6 +569,124 @@ trust = "verified"
         assert_ne!(payload_a, payload_b);
     }
 
+    #[test]
+    fn test_build_plugin_signature_payload_binds_every_plugin_file() {
+        let tmp = TempDir::new().unwrap();
+        let plugin_dir = tmp.path().join("signed-plugin");
+        std::fs::create_dir_all(plugin_dir.join("lib")).unwrap();
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            "name = \"signed-plugin\"\nversion = \"1.0.0\"\ntrust = \"verified\"\n",
+        )
+        .unwrap();
+        std::fs::write(plugin_dir.join("main.js"), "import './lib/util.js';").unwrap();
+        std::fs::write(plugin_dir.join("lib/util.js"), "export const x = 1;").unwrap();
+        let loader = crate::plugin_system::loader::PluginLoader::new(tmp.path().to_path_buf());
+        let manifest = loader.load_manifest("signed-plugin").unwrap();
+        let payload = || {
+            build_plugin_signature_payload(
+                "signed-plugin",
+                &plugin_dir,
+                &manifest,
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(123),
+            )
+            .unwrap()
+        };
+
+        let signed = payload();
+        let listed: serde_json::Value = serde_json::from_slice(&signed).unwrap();
+        let paths: Vec<&str> = listed["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["lib/util.js", "main.js", "plugin.toml"]);
+
+        // An imported module is signed, not just the entrypoint
+        std::fs::write(plugin_dir.join("lib/util.js"), "export const x = 2;").unwrap();
+        assert_ne!(payload(), signed);
+        std::fs::write(plugin_dir.join("lib/util.js"), "export const x = 1;").unwrap();
+        assert_eq!(payload(), signed);
+
+        // So is a file added after signing
+        std::fs::write(plugin_dir.join("lib/extra.js"), "").unwrap();
+        assert_ne!(payload(), signed);
+    }
+
+    #[test]
+    fn test_signature_written_into_manifest_verifies() {
+        let tmp = TempDir::new().unwrap();
+        let plugin_dir = tmp.path().join("signed-plugin");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        let manifest_toml = "name = \"signed-plugin\"\nversion = \"1.0.0\"\ntrust = \"verified\"\n";
+        std::fs::write(plugin_dir.join("plugin.toml"), manifest_toml).unwrap();
+        std::fs::write(plugin_dir.join("main.js"), "onActivate(function () {});").unwrap();
+
+        let loader = crate::plugin_system::loader::PluginLoader::new(tmp.path().to_path_buf());
+        let manifest = loader.load_manifest("signed-plugin").unwrap();
+        let signing_key = SigningKey::from_bytes(&[21u8; 32]);
+        let sig = sign_plugin("signed-plugin", &plugin_dir, &manifest, &signing_key).unwrap();
+
+        #[derive(Serialize)]
+        struct SignatureTable<'a> {
+            signature: &'a PluginSignature,
+        }
+        let table = toml::to_string(&SignatureTable { signature: &sig }).unwrap();
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            format!("{}\n{}", manifest_toml, table),
+        )
+        .unwrap();
+
+        let signed = loader.load_manifest("signed-plugin").unwrap();
+        let stored = signed.signature.clone().unwrap();
+        assert_eq!(stored.timestamp, sig.timestamp);
+        let payload =
+            build_plugin_signature_payload("signed-plugin", &plugin_dir, &signed, stored.timestamp)
+                .unwrap();
+        let mut verifier = TrustVerifier::new();
+        verifier
+            .add_trusted_key(encode_public_key(&signing_key.verifying_key()))
+            .unwrap();
+        assert!(verifier.verify_signature(&stored, &payload));
+
+        // Editing the signed manifest still breaks the signature
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            format!("{}description = \"edited\"\n\n{}", manifest_toml, table),
+        )
+        .unwrap();
+        let edited = loader.load_manifest("signed-plugin").unwrap();
+        let payload =
+            build_plugin_signature_payload("signed-plugin", &plugin_dir, &edited, stored.timestamp)
+                .unwrap();
+        assert!(!verifier.verify_signature(&stored, &payload));
+    }
+
+    #[test]
+    fn test_unversioned_signature_requires_a_re_sign() {
+        #[derive(Deserialize)]
+        struct Manifest {
+            signature: PluginSignature,
+        }
+        let legacy: Manifest = toml::from_str(
+            r#"
+[signature]
+public_key = "key"
+signature = [1, 2, 3]
+timestamp = { secs_since_epoch = 123, nanos_since_epoch = 0 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.signature.payload_version, 1);
+        let err = legacy.signature.check_payload_version().unwrap_err();
+        assert!(err.contains("re-sign required"), "{}", err);
+
+        let current = PluginSignature::new("key".to_string(), vec![0; 64]);
+        assert!(current.check_payload_version().is_ok());
+    }
+
     #[test]
     fn test_trusted_keys_save_and_load_roundtrip() {
         let tmp = TempDir::new().unwrap();
@@ -494,6 +731,7 @@ trust = "verified"
             capabilities: PluginCapabilities::default(),
             permissions: None,
             ui: None,
+            limits: None,
             dependencies: vec![],
             source: "https://example.com/fixture-plugin".to_string(),
             signature: None,
@@ -515,6 +753,7 @@ trust = "verified"
             public_key: verify_key.clone(),
             signature: signature.to_bytes().to_vec(),
             timestamp,
+            payload_version: SIGNATURE_PAYLOAD_VERSION,
         };
 
         let mut verifier = TrustVerifier::new();
@@ -1,15 +1,24 @@
 // src-tauri/src/plugin_system/worker.rs
 
 use crate::plugin_system::capabilities::PluginCapabilities;
+use crate::plugin_system::host::PluginHost;
+use crate::plugin_system::module_loader::PluginModuleLoader;
 use crate::plugin_system::ops::{EditorStateHandle, PluginOpState};
+use crate::plugin_system::pool::{IsolateFactory, PoolSlot, WorkerPool};
 use crate::plugin_system::sandbox::{PluginError, ResourceLimits};
+use crate::plugin_system::snapshot;
+use crate::plugin_system::transpile::SourceMapStore;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll};
 use std::thread;
-use std::time::Duration;
-use tauri::AppHandle;
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
 
 /// Message sent to plugin worker
@@ -20,6 +29,11 @@ pub enum WorkerMessage {
         code: String,
         response_tx: oneshot::Sender<WorkerResponse>,
     },
+    /// Load and evaluate an ES module (the plugin entry point) and its imports
+    LoadModule {
+        path: PathBuf,
+        response_tx: oneshot::Sender<WorkerResponse>,
+    },
     /// Call a plugin hook
     CallHook {
         hook: String,
@@ -30,51 +44,474 @@ pub enum WorkerMessage {
     GetMemoryUsage {
         response_tx: oneshot::Sender<WorkerResponse>,
     },
+    /// Replace the capabilities ops check (after a grant, revocation or expiry)
+    SetCapabilities {
+        capabilities: PluginCapabilities,
+        response_tx: oneshot::Sender<WorkerResponse>,
+    },
     /// Shutdown the worker
     Shutdown,
 }
 
 /// Response from plugin worker
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WorkerResponse {
     Success(serde_json::Value),
     Error(String),
+    /// The script or hook settled, but its value has no JSON representation.
+    SerializationError(String),
+    /// Execution was terminated because it exceeded `max_cpu_time`.
+    Timeout {
+        duration: Duration,
+    },
+    /// Execution was terminated because the V8 heap reached `max_memory`.
+    MemoryLimitExceeded {
+        used: usize,
+        limit: usize,
+    },
+    /// Handling the message panicked; the isolate is dropped.
+    Crashed(String),
+}
+
+impl WorkerResponse {
+    fn into_result(self) -> Result<serde_json::Value, PluginError> {
+        match self {
+            WorkerResponse::Success(value) => Ok(value),
+            WorkerResponse::Error(err) => Err(PluginError::ExecutionError(err)),
+            WorkerResponse::SerializationError(err) => Err(PluginError::SerializationError(err)),
+            WorkerResponse::Timeout { duration } => Err(PluginError::Timeout { duration }),
+            WorkerResponse::MemoryLimitExceeded { used, limit } => {
+                Err(PluginError::MemoryLimitExceeded { used, limit })
+            }
+            WorkerResponse::Crashed(reason) => Err(PluginError::WorkerCrashed(reason)),
+        }
+    }
+}
+
+/// What a message left running on the isolate's event loop.
+pub(crate) enum PendingValue {
+    /// A script or hook result, which may be a promise
+    Value(deno_core::v8::Global<deno_core::v8::Value>),
+    /// A module graph's evaluation
+    Module(Pin<Box<dyn Future<Output = Result<(), deno_core::error::AnyError>>>>),
+}
+
+/// How far a message got when it was handled.
+pub(crate) enum Started {
+    /// The message was answered synchronously
+    Answered(WorkerResponse),
+    /// The result settles on the event loop, which the pool thread polls
+    Settling(PendingValue),
+}
+
+/// A script, module or hook call waiting on its isolate's event loop.
+///
+/// The pool thread polls these alongside the calls of the other isolates it
+/// hosts, so a call awaiting a fetch, a child process or a consent prompt does
+/// not hold the thread.
+pub(crate) struct PendingCall {
+    what: String,
+    value: PendingValue,
+    response_tx: oneshot::Sender<WorkerResponse>,
+    /// Budget time the call started at
+    started: Duration,
+}
+
+impl PendingCall {
+    fn new(
+        what: String,
+        value: PendingValue,
+        response_tx: oneshot::Sender<WorkerResponse>,
+        started: Duration,
+    ) -> Self {
+        Self {
+            what,
+            value,
+            response_tx,
+            started,
+        }
+    }
+}
+
+/// Answer every pending call with `response`.
+pub(crate) fn answer_all(calls: &mut Vec<PendingCall>, response: &WorkerResponse) {
+    for call in calls.drain(..) {
+        let _ = call.response_tx.send(response.clone());
+    }
 }
 
-/// Plugin worker that runs in its own thread
+/// Maximum length of a hook or event name accepted from plugins or the frontend.
+pub const MAX_HOOK_NAME_LEN: usize = 64;
+
+/// Validate a hook/event name before it is used for dispatch.
+///
+/// Names must start with an ASCII letter or `_` and may contain ASCII
+/// alphanumerics plus `_`, `.`, `:` and `-` (e.g. `on_file_save`, `command:git.status`).
+pub fn validate_hook_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_HOOK_NAME_LEN {
+        return Err(format!(
+            "Invalid hook name: must be 1-{} characters",
+            MAX_HOOK_NAME_LEN
+        ));
+    }
+    let mut chars = name.chars();
+    let first_ok = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    let rest_ok = chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-'));
+    if !first_ok || !rest_ok {
+        return Err(format!("Invalid hook name: '{}'", name.escape_default()));
+    }
+    Ok(())
+}
+
+/// Text of a caught panic payload.
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// How often a paused [`ExecutionBudget`] is checked again.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The clock `max_cpu_time` is measured on, one per isolate.
+///
+/// It stops while an op waits on the user (a consent or permission prompt can
+/// stay open for up to a minute), so only the time the plugin actually runs
+/// counts against its budget. The watchdog, the event-loop timeouts and the
+/// sandbox's reply timeout all read this clock.
+#[derive(Clone)]
+pub struct ExecutionBudget(Arc<Mutex<BudgetClock>>);
+
+struct BudgetClock {
+    /// Unpaused time up to `since`
+    active: Duration,
+    since: Instant,
+    /// Outstanding [`BudgetPause`] guards
+    pauses: usize,
+    /// End of the current run, in unpaused time
+    deadline: Option<Duration>,
+}
+
+impl BudgetClock {
+    fn active(&self, now: Instant) -> Duration {
+        if self.pauses > 0 {
+            self.active
+        } else {
+            self.active + now.saturating_duration_since(self.since)
+        }
+    }
+}
+
+impl Default for ExecutionBudget {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(BudgetClock {
+            active: Duration::ZERO,
+            since: Instant::now(),
+            pauses: 0,
+            deadline: None,
+        })))
+    }
+}
+
+impl ExecutionBudget {
+    fn clock(&self) -> std::sync::MutexGuard<'_, BudgetClock> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start a run that may use `budget` of unpaused time.
+    fn start(&self, budget: Duration) {
+        let mut clock = self.clock();
+        clock.deadline = Some(clock.active(Instant::now()) + budget);
+    }
+
+    /// Unpaused time used so far.
+    fn active(&self) -> Duration {
+        self.clock().active(Instant::now())
+    }
+
+    /// Unpaused time left in the current run; `None` while paused.
+    fn remaining(&self) -> Option<Duration> {
+        let clock = self.clock();
+        if clock.pauses > 0 {
+            return None;
+        }
+        let active = clock.active(Instant::now());
+        clock
+            .deadline
+            .map(|deadline| deadline.saturating_sub(active))
+    }
+
+    /// Stop the clock until the returned guard is dropped. Pauses nest, so
+    /// concurrent prompts from one plugin keep it stopped until all are answered.
+    pub fn pause(&self) -> BudgetPause {
+        let mut clock = self.clock();
+        if clock.pauses == 0 {
+            let now = Instant::now();
+            clock.active = clock.active(now);
+            clock.since = now;
+        }
+        clock.pauses += 1;
+        BudgetPause(self.clone())
+    }
+
+    /// Await `future` for at most `limit` of unpaused time; `None` if it ran out.
+    pub async fn within<F: std::future::Future>(
+        &self,
+        limit: Duration,
+        future: F,
+    ) -> Option<F::Output> {
+        let started = self.clock().active(Instant::now());
+        tokio::pin!(future);
+        loop {
+            let wait = {
+                let clock = self.clock();
+                let used = clock.active(Instant::now()).saturating_sub(started);
+                if clock.pauses > 0 {
+                    PAUSE_POLL_INTERVAL
+                } else if used >= limit {
+                    return None;
+                } else {
+                    limit - used
+                }
+            };
+            tokio::select! {
+                biased;
+                output = &mut future => return Some(output),
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    }
+}
+
+/// Keeps an [`ExecutionBudget`] paused while alive.
+pub struct BudgetPause(ExecutionBudget);
+
+impl Drop for BudgetPause {
+    fn drop(&mut self) {
+        let mut clock = self.0.clock();
+        clock.pauses -= 1;
+        if clock.pauses == 0 {
+            clock.since = Instant::now();
+        }
+    }
+}
+
+#[derive(Default)]
+struct WatchdogState {
+    run: Option<(ExecutionBudget, deno_core::v8::IsolateHandle)>,
+    fired: bool,
+    shutdown: bool,
+}
+
+/// Terminates an isolate when a single script or hook runs past its deadline.
+///
+/// `terminate_execution` is the only way to interrupt a synchronous loop such as
+/// `while (true) {}`, and it has to be called from another thread. Only one
+/// isolate runs at a time on a pool thread, so each pool thread shares one watchdog.
+pub(crate) struct Watchdog {
+    shared: Arc<(Mutex<WatchdogState>, Condvar)>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Watchdog {
+    pub(crate) fn spawn() -> std::io::Result<Self> {
+        let shared = Arc::new((Mutex::new(WatchdogState::default()), Condvar::new()));
+        let thread_shared = shared.clone();
+
+        let thread = thread::Builder::new()
+            .name("plugin-watchdog".to_string())
+            .spawn(move || {
+                let (lock, cvar) = &*thread_shared;
+                let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+                while !state.shutdown {
+                    let remaining = state.run.as_ref().map(|(budget, _)| budget.remaining());
+                    match remaining {
+                        None => {
+                            state = cvar.wait(state).unwrap_or_else(|e| e.into_inner());
+                        }
+                        Some(Some(remaining)) if remaining.is_zero() => {
+                            if let Some((_, isolate)) = state.run.take() {
+                                isolate.terminate_execution();
+                            }
+                            state.fired = true;
+                        }
+                        // The deadline may move while we sleep (a pause ended), so
+                        // it is recomputed on every wakeup
+                        Some(remaining) => {
+                            state = cvar
+                                .wait_timeout(state, remaining.unwrap_or(PAUSE_POLL_INTERVAL))
+                                .unwrap_or_else(|e| e.into_inner())
+                                .0;
+                        }
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// Terminate `isolate` once `budget` runs out.
+    fn arm(&self, isolate: deno_core::v8::IsolateHandle, budget: ExecutionBudget) {
+        let (lock, cvar) = &*self.shared;
+        let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+        state.run = Some((budget, isolate));
+        state.fired = false;
+        cvar.notify_one();
+    }
+
+    /// Disarm the deadline and report whether it fired. Once this returns, the
+    /// watchdog cannot terminate the isolate on behalf of the finished run.
+    fn disarm(&self) -> bool {
+        let (lock, cvar) = &*self.shared;
+        let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+        state.run = None;
+        let fired = std::mem::take(&mut state.fired);
+        cvar.notify_one();
+        fired
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        {
+            let (lock, cvar) = &*self.shared;
+            let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+            state.shutdown = true;
+            cvar.notify_one();
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Enforces `max_cpu_time` and `max_memory` on a worker's isolate.
+pub(crate) struct IsolateLimits {
+    isolate: deno_core::v8::IsolateHandle,
+    heap_exhausted: Arc<AtomicBool>,
+    budget: ExecutionBudget,
+}
+
+impl IsolateLimits {
+    /// Install the near-heap-limit callback.
+    ///
+    /// The runtime must have been created with [`PluginWorker::create_params`] so that
+    /// V8's heap limit matches `max_memory`.
+    pub(crate) fn install(runtime: &mut deno_core::JsRuntime) -> Self {
+        let limits = Self {
+            isolate: runtime.v8_isolate().thread_safe_handle(),
+            heap_exhausted: Arc::new(AtomicBool::new(false)),
+            budget: PluginWorker::budget_of(runtime),
+        };
+        limits.add_heap_callback(runtime);
+        limits
+    }
+
+    fn add_heap_callback(&self, runtime: &mut deno_core::JsRuntime) {
+        let flag = self.heap_exhausted.clone();
+        let isolate = self.isolate.clone();
+        runtime.add_near_heap_limit_callback(move |current_limit, _initial_limit| {
+            flag.store(true, Ordering::SeqCst);
+            isolate.terminate_execution();
+            // Give V8 headroom to unwind the terminated script instead of aborting
+            // the whole process with an out-of-memory crash.
+            current_limit.saturating_mul(2)
+        });
+    }
+
+    /// Drop the headroom granted by the callback so the next run is held to
+    /// `max_memory` again, and re-install the callback for it.
+    fn restore_heap_limit(&self, runtime: &mut deno_core::JsRuntime, limits: &ResourceLimits) {
+        runtime.remove_near_heap_limit_callback(limits.max_memory);
+        self.add_heap_callback(runtime);
+    }
+
+    /// Run `f` under the CPU deadline and heap limit, translating a terminated
+    /// isolate into `Timeout` / `MemoryLimitExceeded`.
+    pub(crate) fn run<T, F>(
+        &self,
+        watchdog: &Watchdog,
+        runtime: &mut deno_core::JsRuntime,
+        limits: &ResourceLimits,
+        f: F,
+    ) -> Result<T, WorkerResponse>
+    where
+        F: FnOnce(&mut deno_core::JsRuntime) -> T,
+    {
+        self.heap_exhausted.store(false, Ordering::SeqCst);
+        self.budget.start(limits.max_cpu_time);
+        watchdog.arm(self.isolate.clone(), self.budget.clone());
+
+        let response = f(runtime);
+
+        let timed_out = watchdog.disarm();
+        let out_of_memory = self.heap_exhausted.swap(false, Ordering::SeqCst);
+        if timed_out || out_of_memory {
+            // Termination is sticky; clear it so the next message can run.
+            runtime.v8_isolate().cancel_terminate_execution();
+        }
+
+        if out_of_memory {
+            let used = PluginWorker::heap_used(runtime);
+            self.restore_heap_limit(runtime, limits);
+            return Err(WorkerResponse::MemoryLimitExceeded {
+                used,
+                limit: limits.max_memory,
+            });
+        }
+        if timed_out {
+            return Err(WorkerResponse::Timeout {
+                duration: limits.max_cpu_time,
+            });
+        }
+        Ok(response)
+    }
+}
+
+/// Handle to a plugin's isolate, hosted on a thread of the shared [`WorkerPool`]
 pub struct PluginWorker {
     id: String,
     capabilities: PluginCapabilities,
     resource_limits: ResourceLimits,
-    sender: mpsc::Sender<WorkerMessage>,
-    handle: Option<thread::JoinHandle<()>>,
+    slot: PoolSlot,
+    budget: ExecutionBudget,
 }
 
 impl PluginWorker {
     pub fn new(
         id: String,
         capabilities: PluginCapabilities,
+        resource_limits: ResourceLimits,
+        plugin_root: PathBuf,
         workspace_root: PathBuf,
-        app_handle: AppHandle,
+        host: Arc<dyn PluginHost>,
         editor_state: EditorStateHandle,
     ) -> Self {
-        let (tx, rx) = mpsc::channel();
-
         let worker_id = id.clone();
         let worker_capabilities = capabilities.clone();
-        let worker_limits = ResourceLimits {
-            max_memory: 50 * 1024 * 1024, // 50MB
-            max_cpu_time: Duration::from_secs(5),
-            max_operations: 100,
-        };
+        let worker_limits = resource_limits.clone();
 
-        let handle = thread::spawn(move || {
-            // Create the plugin ops extension
-            let ext = crate::plugin_system::ops::skretchpad_plugin_ops::init_ops_and_esm();
+        let factory: IsolateFactory = Box::new(move || {
+            // Imports are confined to the plugin's own (canonical) directory;
+            // TypeScript source maps let errors point at the original .ts lines
+            let source_maps = SourceMapStore::default();
+            let module_loader =
+                std::rc::Rc::new(PluginModuleLoader::new(plugin_root, source_maps.clone()));
 
             let mut runtime = deno_core::JsRuntime::new(deno_core::RuntimeOptions {
-                module_loader: None,
-                extensions: vec![ext],
+                module_loader: Some(module_loader),
+                source_map_getter: Some(Box::new(source_maps)),
+                // The plugin API is already in the startup snapshot; only the ops are bound here
+                startup_snapshot: Some(snapshot::startup_snapshot()),
+                extensions: snapshot::extensions(),
+                create_params: Some(Self::create_params(&worker_limits)),
                 ..Default::default()
             });
 
@@ -85,70 +522,193 @@ impl PluginWorker {
                 state.put(PluginOpState {
                     plugin_id: worker_id.clone(),
                     capabilities: worker_capabilities,
+                    limits: worker_limits,
                     workspace_root,
-                    app_handle,
+                    host,
                     editor_state,
                 });
             }
 
-            // Inject plugin API
-            let api_script = include_str!("../../js/plugin_api.js");
-            if let Err(e) =
-                runtime.execute_script("plugin_api.js", deno_core::FastString::Static(api_script))
-            {
-                eprintln!("Failed to inject plugin API: {}", e);
-                return;
-            }
-
             // Set plugin ID in global scope
             let set_id_script = format!("globalThis.__PLUGIN_ID__ = '{}';", worker_id);
-            if let Err(e) =
-                runtime.execute_script("set_id", deno_core::FastString::Owned(set_id_script.into()))
-            {
-                eprintln!("Failed to set plugin ID: {}", e);
-                return;
-            }
+            runtime
+                .execute_script("set_id", deno_core::FastString::Owned(set_id_script.into()))
+                .map_err(|e| format!("Failed to set plugin ID: {}", e))?;
 
-            // Message loop
-            while let Ok(msg) = rx.recv() {
-                match msg {
-                    WorkerMessage::Execute { code, response_tx } => {
-                        let result = Self::execute_code_sync(&mut runtime, &code, &worker_limits);
-                        let _ = response_tx.send(result);
-                    }
-                    WorkerMessage::CallHook {
-                        hook,
-                        args,
-                        response_tx,
-                    } => {
-                        let result =
-                            Self::call_hook_sync(&mut runtime, &hook, &args, &worker_limits);
-                        let _ = response_tx.send(result);
-                    }
-                    WorkerMessage::GetMemoryUsage { response_tx } => {
-                        let result = Self::get_memory_usage_sync(&mut runtime);
-                        let _ = response_tx.send(result);
-                    }
-                    WorkerMessage::Shutdown => {
-                        break;
-                    }
-                }
-            }
+            Ok(runtime)
         });
 
+        Self::spawn_on(
+            WorkerPool::global(),
+            id,
+            capabilities,
+            resource_limits,
+            factory,
+        )
+    }
+
+    /// Host a worker on `pool`, building its runtime with `factory` on the pool thread.
+    pub(crate) fn spawn_on(
+        pool: &WorkerPool,
+        id: String,
+        capabilities: PluginCapabilities,
+        resource_limits: ResourceLimits,
+        factory: IsolateFactory,
+    ) -> Self {
+        let budget = ExecutionBudget::default();
+        let isolate_budget = budget.clone();
+        let factory: IsolateFactory = Box::new(move || {
+            let runtime = factory()?;
+            runtime.op_state().borrow_mut().put(isolate_budget);
+            Ok(runtime)
+        });
+        let slot = pool.spawn(id.clone(), resource_limits.clone(), factory);
         Self {
             id,
             capabilities,
-            resource_limits: ResourceLimits {
-                max_memory: 50 * 1024 * 1024,
-                max_cpu_time: Duration::from_secs(5),
-                max_operations: 100,
-            },
-            sender: tx,
-            handle: Some(handle),
+            resource_limits,
+            slot,
+            budget,
+        }
+    }
+
+    /// The clock this worker's `max_cpu_time` is measured on.
+    pub fn budget(&self) -> &ExecutionBudget {
+        &self.budget
+    }
+
+    /// The isolate's execution budget, installing one if the factory did not.
+    fn budget_of(runtime: &mut deno_core::JsRuntime) -> ExecutionBudget {
+        let state = runtime.op_state();
+        let mut state = state.borrow_mut();
+        if let Some(budget) = state.try_borrow::<ExecutionBudget>() {
+            return budget.clone();
+        }
+        let budget = ExecutionBudget::default();
+        state.put(budget.clone());
+        budget
+    }
+
+    /// Handle one message on the pool thread hosting this worker's isolate.
+    ///
+    /// Returns the call when its result still has to settle on the event loop;
+    /// the pool then drives it with [`PluginWorker::poll_calls`]. A panic while
+    /// handling the message is caught and returned as `Err` so the pool can drop
+    /// just this isolate. Panics inside ops unwind through V8 and still abort the
+    /// process.
+    pub(crate) fn handle_message(
+        runtime: &mut deno_core::JsRuntime,
+        tokio_rt: &tokio::runtime::Runtime,
+        watchdog: &Watchdog,
+        isolate_limits: &IsolateLimits,
+        limits: &ResourceLimits,
+        msg: WorkerMessage,
+    ) -> Result<Option<PendingCall>, String> {
+        let (what, response_tx, outcome) = match msg {
+            WorkerMessage::Execute { code, response_tx } => (
+                "Script".to_string(),
+                response_tx,
+                panic::catch_unwind(AssertUnwindSafe(|| {
+                    isolate_limits.run(watchdog, runtime, limits, |rt| {
+                        Self::execute_code(rt, &code)
+                    })
+                })),
+            ),
+            WorkerMessage::LoadModule { path, response_tx } => (
+                "Module".to_string(),
+                response_tx,
+                panic::catch_unwind(AssertUnwindSafe(|| {
+                    isolate_limits.run(watchdog, runtime, limits, |rt| {
+                        Self::load_module(rt, tokio_rt, &path, limits)
+                    })
+                })),
+            ),
+            WorkerMessage::CallHook {
+                hook,
+                args,
+                response_tx,
+            } => (
+                format!("Hook '{}'", hook),
+                response_tx,
+                panic::catch_unwind(AssertUnwindSafe(|| {
+                    isolate_limits.run(watchdog, runtime, limits, |rt| {
+                        Self::call_hook(rt, &hook, &args)
+                    })
+                })),
+            ),
+            WorkerMessage::GetMemoryUsage { response_tx } => (
+                "Memory usage".to_string(),
+                response_tx,
+                panic::catch_unwind(AssertUnwindSafe(|| {
+                    Ok(Started::Answered(Self::get_memory_usage_sync(runtime)))
+                })),
+            ),
+            WorkerMessage::SetCapabilities {
+                capabilities,
+                response_tx,
+            } => (
+                "Capabilities".to_string(),
+                response_tx,
+                panic::catch_unwind(AssertUnwindSafe(|| {
+                    Ok(Started::Answered(Self::set_capabilities_sync(
+                        runtime,
+                        capabilities,
+                    )))
+                })),
+            ),
+            // Shutdown is handled by the pool, which drops the isolate
+            WorkerMessage::Shutdown => return Ok(None),
+        };
+
+        match outcome {
+            Ok(Ok(Started::Settling(value))) => {
+                let started = Self::budget_of(runtime).active();
+                Ok(Some(PendingCall::new(what, value, response_tx, started)))
+            }
+            Ok(Ok(Started::Answered(response))) | Ok(Err(response)) => {
+                let _ = response_tx.send(response);
+                Ok(None)
+            }
+            Err(payload) => {
+                // The run never reached `disarm`; keep the deadline from firing later
+                watchdog.disarm();
+                let reason = format!(
+                    "Plugin worker panicked: {}",
+                    panic_message(payload.as_ref())
+                );
+                let _ = response_tx.send(WorkerResponse::Crashed(reason.clone()));
+                Err(reason)
+            }
+        }
+    }
+
+    fn send(&self, msg: WorkerMessage) -> Result<(), PluginError> {
+        if self.slot.send(msg) {
+            Ok(())
+        } else {
+            Err(self.disconnected())
         }
     }
 
+    /// Error for a worker that stopped answering, carrying the crash reason when known.
+    fn disconnected(&self) -> PluginError {
+        match self.slot.failure() {
+            Some(reason) => PluginError::WorkerCrashed(reason),
+            None => PluginError::WorkerDisconnected,
+        }
+    }
+
+    /// Why this worker's isolate died, if it has.
+    pub fn failure(&self) -> Option<String> {
+        self.slot.failure()
+    }
+
+    /// Kill the isolate as a crash would, for supervisor tests.
+    #[cfg(test)]
+    pub(crate) fn kill(&self, reason: &str) {
+        self.slot.kill(reason);
+    }
+
     /// Execute JavaScript code in the worker thread
     pub async fn execute(&self, code: String) -> Result<serde_json::Value, PluginError> {
         let (tx, rx) = oneshot::channel();
@@ -158,16 +718,25 @@ impl PluginWorker {
             response_tx: tx,
         };
 
-        self.sender
-            .send(msg)
-            .map_err(|_| PluginError::WorkerDisconnected)?;
+        self.send(msg)?;
 
-        let response = rx.await.map_err(|_| PluginError::WorkerDisconnected)?;
+        let response = rx.await.map_err(|_| self.disconnected())?;
+        response.into_result()
+    }
 
-        match response {
-            WorkerResponse::Success(value) => Ok(value),
-            WorkerResponse::Error(err) => Err(PluginError::ExecutionError(err)),
-        }
+    /// Load the plugin entry point as an ES module and evaluate it
+    pub async fn load_module(&self, path: PathBuf) -> Result<(), PluginError> {
+        let (tx, rx) = oneshot::channel();
+
+        let msg = WorkerMessage::LoadModule {
+            path,
+            response_tx: tx,
+        };
+
+        self.send(msg)?;
+
+        let response = rx.await.map_err(|_| self.disconnected())?;
+        response.into_result().map(|_| ())
     }
 
     /// Call a plugin hook in the worker thread
@@ -184,16 +753,10 @@ impl PluginWorker {
             response_tx: tx,
         };
 
-        self.sender
-            .send(msg)
-            .map_err(|_| PluginError::WorkerDisconnected)?;
-
-        let response = rx.await.map_err(|_| PluginError::WorkerDisconnected)?;
+        self.send(msg)?;
 
-        match response {
-            WorkerResponse::Success(value) => Ok(value),
-            WorkerResponse::Error(err) => Err(PluginError::ExecutionError(err)),
-        }
+        let response = rx.await.map_err(|_| self.disconnected())?;
+        response.into_result()
     }
 
     /// Read current V8 memory usage from the worker runtime.
@@ -201,126 +764,330 @@ impl PluginWorker {
         let (tx, rx) = oneshot::channel();
 
         let msg = WorkerMessage::GetMemoryUsage { response_tx: tx };
-        self.sender
-            .send(msg)
-            .map_err(|_| PluginError::WorkerDisconnected)?;
+        self.send(msg)?;
+
+        let response = rx.await.map_err(|_| self.disconnected())?;
+        response
+            .into_result()?
+            .as_u64()
+            .map(|v| v as usize)
+            .ok_or_else(|| PluginError::ExecutionError("Invalid memory usage payload".to_string()))
+    }
 
-        let response = rx.await.map_err(|_| PluginError::WorkerDisconnected)?;
-        match response {
-            WorkerResponse::Success(value) => value.as_u64().map(|v| v as usize).ok_or_else(|| {
-                PluginError::ExecutionError("Invalid memory usage payload".to_string())
-            }),
-            WorkerResponse::Error(err) => Err(PluginError::ExecutionError(err)),
+    /// Replace the capabilities the plugin's ops are checked against.
+    pub async fn set_capabilities(
+        &self,
+        capabilities: PluginCapabilities,
+    ) -> Result<(), PluginError> {
+        let (tx, rx) = oneshot::channel();
+
+        let msg = WorkerMessage::SetCapabilities {
+            capabilities,
+            response_tx: tx,
+        };
+        self.send(msg)?;
+
+        let response = rx.await.map_err(|_| self.disconnected())?;
+        response.into_result().map(|_| ())
+    }
+
+    fn set_capabilities_sync(
+        runtime: &mut deno_core::JsRuntime,
+        capabilities: PluginCapabilities,
+    ) -> WorkerResponse {
+        let op_state = runtime.op_state();
+        let mut state = op_state.borrow_mut();
+        match state.try_borrow_mut::<PluginOpState>() {
+            Some(plugin_state) => {
+                plugin_state.capabilities = capabilities;
+                WorkerResponse::Success(serde_json::Value::Null)
+            }
+            None => WorkerResponse::Error("Worker has no plugin op state".to_string()),
         }
     }
 
-    /// Pump the deno_core event loop to resolve pending promises/microtasks.
-    /// Runs with a timeout to prevent runaway async operations.
-    fn pump_event_loop(runtime: &mut deno_core::JsRuntime, timeout: Duration) {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build();
-
-        if let Ok(rt) = rt {
-            let _ = rt.block_on(async {
-                tokio::time::timeout(
-                    timeout,
-                    // deno_core 0.230: run_event_loop takes a bool (false = don't wait for inspector)
-                    runtime.run_event_loop(false),
-                )
-                .await
-            });
+    /// V8 isolate parameters that cap the heap at `max_memory`.
+    pub(crate) fn create_params(limits: &ResourceLimits) -> deno_core::v8::CreateParams {
+        deno_core::v8::CreateParams::default().heap_limits(0, limits.max_memory)
+    }
+
+    /// Used V8 heap plus external memory, in bytes.
+    pub(crate) fn heap_used(runtime: &mut deno_core::JsRuntime) -> usize {
+        let mut stats = deno_core::v8::HeapStatistics::default();
+        runtime.v8_isolate().get_heap_statistics(&mut stats);
+        stats
+            .used_heap_size()
+            .saturating_add(stats.external_memory())
+    }
+
+    /// Drive the isolate's event loop and answer the calls that have settled.
+    ///
+    /// A call is answered as soon as its promise (or module evaluation) settles;
+    /// timers and ops it left running keep going on later polls. A call still
+    /// pending after `max_cpu_time` of unpaused time times out. Values with no
+    /// JSON representation (functions, symbols, BigInts) produce a serialization
+    /// error. Returns the event loop's state: `Pending` while background work
+    /// remains.
+    pub(crate) fn poll_calls(
+        runtime: &mut deno_core::JsRuntime,
+        calls: &mut Vec<PendingCall>,
+        limits: &ResourceLimits,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), deno_core::error::AnyError>> {
+        // deno_core 0.230: poll_event_loop takes a bool (false = don't wait for inspector)
+        let event_loop = runtime.poll_event_loop(cx, false);
+        let active = Self::budget_of(runtime).active();
+
+        let mut index = 0;
+        while index < calls.len() {
+            match Self::poll_call(runtime, &mut calls[index], &event_loop, active, limits, cx) {
+                Some(response) => {
+                    let call = calls.remove(index);
+                    let _ = call.response_tx.send(response);
+                }
+                None => index += 1,
+            }
         }
+        event_loop
     }
 
-    /// Execute JavaScript code in the worker thread (internal)
-    fn execute_code_sync(
+    /// The answer for `call`, or `None` while it is still pending.
+    fn poll_call(
         runtime: &mut deno_core::JsRuntime,
-        code: &str,
-        _limits: &ResourceLimits,
+        call: &mut PendingCall,
+        event_loop: &Poll<Result<(), deno_core::error::AnyError>>,
+        active: Duration,
+        limits: &ResourceLimits,
+        cx: &mut Context<'_>,
+    ) -> Option<WorkerResponse> {
+        if let Some(response) = Self::poll_value(runtime, &mut call.value, &call.what, cx) {
+            return Some(response);
+        }
+        match event_loop {
+            Poll::Ready(Err(e)) => Some(WorkerResponse::Error(format!("Event loop error: {}", e))),
+            Poll::Ready(Ok(())) => Some(WorkerResponse::Error(format!(
+                "{} never settled: the event loop went idle while it was pending",
+                call.what
+            ))),
+            Poll::Pending if active.saturating_sub(call.started) < limits.max_cpu_time => None,
+            Poll::Pending => Some(WorkerResponse::Timeout {
+                duration: limits.max_cpu_time,
+            }),
+        }
+    }
+
+    /// Check whether a call's value has settled, converting it to its answer.
+    fn poll_value(
+        runtime: &mut deno_core::JsRuntime,
+        value: &mut PendingValue,
+        what: &str,
+        cx: &mut Context<'_>,
+    ) -> Option<WorkerResponse> {
+        use deno_core::v8;
+
+        let value = match value {
+            PendingValue::Value(value) => value,
+            PendingValue::Module(evaluation) => {
+                return match evaluation.as_mut().poll(cx) {
+                    Poll::Ready(Ok(())) => Some(WorkerResponse::Success(serde_json::Value::Null)),
+                    Poll::Ready(Err(e)) => {
+                        Some(WorkerResponse::Error(format!("Module error: {}", e)))
+                    }
+                    Poll::Pending => None,
+                };
+            }
+        };
+
+        let scope = &mut runtime.handle_scope();
+        let local = v8::Local::new(scope, &*value);
+        let Ok(promise) = v8::Local::<v8::Promise>::try_from(local) else {
+            return Some(Self::value_to_json(scope, local, what));
+        };
+        match promise.state() {
+            v8::PromiseState::Pending => None,
+            v8::PromiseState::Fulfilled => {
+                let result = promise.result(scope);
+                Some(Self::value_to_json(scope, result, what))
+            }
+            v8::PromiseState::Rejected => {
+                let exception = promise.result(scope);
+                let error = deno_core::error::JsError::from_v8_exception(scope, exception);
+                Some(WorkerResponse::Error(format!(
+                    "{} rejected: {}",
+                    what, error
+                )))
+            }
+        }
+    }
+
+    /// Convert a V8 value into JSON, rejecting values with no JSON representation.
+    fn value_to_json(
+        scope: &mut deno_core::v8::HandleScope,
+        value: deno_core::v8::Local<deno_core::v8::Value>,
+        what: &str,
     ) -> WorkerResponse {
-        let start = std::time::Instant::now();
+        let unsupported = if value.is_function() {
+            Some("function")
+        } else if value.is_symbol() {
+            Some("symbol")
+        } else if value.is_big_int() {
+            Some("bigint")
+        } else {
+            None
+        };
+        if let Some(kind) = unsupported {
+            return WorkerResponse::SerializationError(format!(
+                "{} returned a {}, which cannot be serialized to JSON",
+                what, kind
+            ));
+        }
 
+        match deno_core::serde_v8::from_v8::<serde_json::Value>(scope, value) {
+            Ok(json) => WorkerResponse::Success(json),
+            Err(e) => WorkerResponse::SerializationError(format!(
+                "{} returned a value that cannot be serialized to JSON: {}",
+                what, e
+            )),
+        }
+    }
+
+    /// Run a script; its result settles on the event loop.
+    fn execute_code(runtime: &mut deno_core::JsRuntime, code: &str) -> Started {
         match runtime.execute_script(
             "<plugin>",
             deno_core::FastString::Owned(code.to_string().into()),
         ) {
-            Ok(_result) => {
-                // Pump event loop to resolve any pending promises/microtasks
-                Self::pump_event_loop(runtime, Duration::from_secs(5));
+            Ok(result) => Started::Settling(PendingValue::Value(result)),
+            Err(e) => Started::Answered(WorkerResponse::Error(format!("Execution error: {}", e))),
+        }
+    }
 
-                // Check elapsed time after execution
-                if start.elapsed() > _limits.max_cpu_time {
-                    return WorkerResponse::Error("CPU time limit exceeded".to_string());
-                }
-                WorkerResponse::Success(serde_json::Value::Null)
+    /// Load a module graph; its evaluation settles on the event loop.
+    fn load_module(
+        runtime: &mut deno_core::JsRuntime,
+        tokio_rt: &tokio::runtime::Runtime,
+        path: &Path,
+        limits: &ResourceLimits,
+    ) -> Started {
+        let Ok(specifier) = deno_core::ModuleSpecifier::from_file_path(path) else {
+            return Started::Answered(WorkerResponse::Error(format!(
+                "Invalid module path: '{}'",
+                path.display()
+            )));
+        };
+
+        // Loading only reads and transpiles the plugin's own files
+        let budget = Self::budget_of(runtime);
+        let loaded = tokio_rt.block_on(budget.within(
+            limits.max_cpu_time,
+            runtime.load_main_module(&specifier, None),
+        ));
+        match loaded {
+            Some(Ok(id)) => {
+                Started::Settling(PendingValue::Module(Box::pin(runtime.mod_evaluate(id))))
+            }
+            Some(Err(e)) => {
+                Started::Answered(WorkerResponse::Error(format!("Module error: {}", e)))
             }
-            Err(e) => WorkerResponse::Error(format!("Execution error: {}", e)),
+            None => Started::Answered(WorkerResponse::Timeout {
+                duration: limits.max_cpu_time,
+            }),
         }
     }
 
-    /// Get or create a cached static hook script name
-    fn cached_hook_name(hook: &str) -> &'static str {
-        use std::collections::HashMap as StdHashMap;
-        use std::sync::LazyLock;
-        use std::sync::Mutex;
+    /// Look up `globalThis.__hooks__[hook]` and call it through a V8 function handle,
+    /// passing `args` as V8 values (a JSON array is spread into positional arguments).
+    /// Returns `Ok(None)` when the plugin has not registered the hook.
+    fn invoke_hook(
+        runtime: &mut deno_core::JsRuntime,
+        hook: &str,
+        args: &serde_json::Value,
+    ) -> Result<Option<deno_core::v8::Global<deno_core::v8::Value>>, String> {
+        use deno_core::v8;
 
-        static HOOK_NAMES: LazyLock<Mutex<StdHashMap<String, &'static str>>> =
-            LazyLock::new(|| Mutex::new(StdHashMap::new()));
+        let scope = &mut runtime.handle_scope();
+        let context = scope.get_current_context();
+        let global = context.global(scope);
 
-        let mut cache = HOOK_NAMES.lock().unwrap();
-        if let Some(&name) = cache.get(hook) {
-            return name;
+        let hooks_key = v8::String::new(scope, "__hooks__")
+            .ok_or_else(|| "Failed to allocate hook registry key".to_string())?;
+        let Some(hooks) = global.get(scope, hooks_key.into()) else {
+            return Ok(None);
+        };
+        let Ok(hooks) = v8::Local::<v8::Object>::try_from(hooks) else {
+            return Ok(None);
+        };
+
+        // Only own properties count, so inherited members such as `constructor`
+        // can never be dispatched as hooks.
+        let hook_key = v8::String::new(scope, hook)
+            .ok_or_else(|| format!("Failed to allocate hook name '{}'", hook))?;
+        if !hooks
+            .has_own_property(scope, hook_key.into())
+            .unwrap_or(false)
+        {
+            return Ok(None);
+        }
+        let Some(handler) = hooks.get(scope, hook_key.into()) else {
+            return Ok(None);
+        };
+        let Ok(handler) = v8::Local::<v8::Function>::try_from(handler) else {
+            return Ok(None);
+        };
+
+        let positional = match args {
+            serde_json::Value::Array(items) => items.clone(),
+            serde_json::Value::Null => Vec::new(),
+            other => vec![other.clone()],
+        };
+        let mut argv = Vec::with_capacity(positional.len());
+        for arg in &positional {
+            let value = deno_core::serde_v8::to_v8(scope, arg)
+                .map_err(|e| format!("Failed to convert argument for hook '{}': {}", hook, e))?;
+            argv.push(value);
+        }
+
+        let tc_scope = &mut v8::TryCatch::new(scope);
+        match handler.call(tc_scope, hooks.into(), &argv) {
+            Some(result) => Ok(Some(v8::Global::new(tc_scope, result))),
+            None => {
+                // JsError applies source maps, so TypeScript plugins report .ts locations
+                let message = tc_scope
+                    .exception()
+                    .map(|exception| {
+                        deno_core::error::JsError::from_v8_exception(tc_scope, exception)
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| "unknown exception".to_string());
+                Err(message)
+            }
         }
-        let leaked: &'static str = Box::leak(format!("hook_{}", hook).into_boxed_str());
-        cache.insert(hook.to_string(), leaked);
-        leaked
     }
 
-    /// Call a plugin hook in the worker thread (internal)
-    fn call_hook_sync(
+    /// Call a plugin hook; its result settles on the event loop.
+    fn call_hook(
         runtime: &mut deno_core::JsRuntime,
         hook: &str,
         args: &serde_json::Value,
-        _limits: &ResourceLimits,
-    ) -> WorkerResponse {
-        let start = std::time::Instant::now();
+    ) -> Started {
+        if let Err(e) = validate_hook_name(hook) {
+            return Started::Answered(WorkerResponse::Error(e));
+        }
 
         // Call hooks registered on globalThis.__hooks__ (set up by plugin code)
         // Falls back gracefully if hook is not registered
-        let script = format!(
-            "(function() {{ \
-                if (typeof globalThis.__hooks__ !== 'undefined' && typeof globalThis.__hooks__.{hook} === 'function') {{ \
-                    return globalThis.__hooks__.{hook}({args}); \
-                }} \
-                return null; \
-            }})()",
-            hook = hook,
-            args = serde_json::to_string(args).unwrap_or_else(|_| "null".to_string())
-        );
-
-        let hook_name = Self::cached_hook_name(hook);
-        match runtime.execute_script(hook_name, deno_core::FastString::Owned(script.into())) {
-            Ok(_result) => {
-                // Pump event loop to resolve any pending promises/microtasks
-                Self::pump_event_loop(runtime, Duration::from_secs(5));
-
-                if start.elapsed() > _limits.max_cpu_time {
-                    return WorkerResponse::Error("CPU time limit exceeded".to_string());
-                }
-                WorkerResponse::Success(serde_json::Value::Null)
-            }
-            Err(e) => WorkerResponse::Error(format!("Hook execution error: {}", e)),
+        match Self::invoke_hook(runtime, hook, args) {
+            Ok(Some(result)) => Started::Settling(PendingValue::Value(result)),
+            Ok(None) => Started::Answered(WorkerResponse::Success(serde_json::Value::Null)),
+            Err(e) => Started::Answered(WorkerResponse::Error(format!(
+                "Hook execution error: {}",
+                e
+            ))),
         }
     }
 
     fn get_memory_usage_sync(runtime: &mut deno_core::JsRuntime) -> WorkerResponse {
-        let scope = &mut runtime.handle_scope();
-        let mut stats = deno_core::v8::HeapStatistics::default();
-        scope.get_heap_statistics(&mut stats);
-        let used = stats
-            .used_heap_size()
-            .saturating_add(stats.external_memory());
+        let used = Self::heap_used(runtime);
         WorkerResponse::Success(serde_json::Value::from(used as u64))
     }
 
@@ -341,15 +1108,12 @@ impl PluginWorker {
 
     /// Send shutdown message without consuming self
     pub fn send_shutdown(&self) {
-        let _ = self.sender.send(WorkerMessage::Shutdown);
+        let _ = self.send(WorkerMessage::Shutdown);
     }
 
-    /// Shutdown the worker (consumes self, joins thread)
-    pub fn shutdown(mut self) {
-        let _ = self.sender.send(WorkerMessage::Shutdown);
-        if let Some(handle) = self.handle.take() {
-            let _ = handle.join();
-        }
+    /// Shutdown the worker (consumes self, releasing its isolate in the pool)
+    pub fn shutdown(self) {
+        drop(self);
     }
 }
 
@@ -369,8 +1133,10 @@ impl WorkerRegistry {
         &mut self,
         id: String,
         capabilities: PluginCapabilities,
+        resource_limits: ResourceLimits,
+        plugin_root: PathBuf,
         workspace_root: PathBuf,
-        app_handle: AppHandle,
+        host: Arc<dyn PluginHost>,
         editor_state: EditorStateHandle,
     ) -> Result<(), PluginError> {
         if self.workers.contains_key(&id) {
@@ -380,8 +1146,10 @@ impl WorkerRegistry {
         let worker = PluginWorker::new(
             id.clone(),
             capabilities,
+            resource_limits,
+            plugin_root,
             workspace_root,
-            app_handle,
+            host,
             editor_state,
         );
         self.workers.insert(id, worker);
@@ -461,26 +1229,205 @@ mod tests {
     }
 
     #[test]
-    fn test_cached_hook_name_returns_static_str() {
-        let name = PluginWorker::cached_hook_name("activate");
-        assert_eq!(name, "hook_activate");
+    fn test_validate_hook_name_accepts_plugin_hooks() {
+        for name in [
+            "activate",
+            "deactivate",
+            "on_file_save",
+            "command:git.status",
+            "plugin-event_1",
+        ] {
+            assert!(validate_hook_name(name).is_ok(), "{} should be valid", name);
+        }
+    }
+
+    #[test]
+    fn test_validate_hook_name_rejects_injection() {
+        for name in [
+            "",
+            "x; globalThis.pwned = true",
+            "a(1)",
+            "__hooks__['x']",
+            "1starts_with_digit",
+            "has space",
+            "line\nbreak",
+        ] {
+            assert!(
+                validate_hook_name(name).is_err(),
+                "{:?} should be invalid",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_hook_name_rejects_overlong_names() {
+        let name = "a".repeat(MAX_HOOK_NAME_LEN + 1);
+        assert!(validate_hook_name(&name).is_err());
+        assert!(validate_hook_name(&"a".repeat(MAX_HOOK_NAME_LEN)).is_ok());
+    }
+
+    fn hook_runtime(setup: &'static str) -> (tokio::runtime::Runtime, deno_core::JsRuntime) {
+        let tokio_rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut runtime = {
+            let _guard = tokio_rt.enter();
+            deno_core::JsRuntime::new(deno_core::RuntimeOptions::default())
+        };
+        runtime
+            .execute_script(
+                "hooks_setup.js",
+                deno_core::FastString::Static("globalThis.__hooks__ = Object.create(null);"),
+            )
+            .unwrap();
+        runtime
+            .execute_script("hooks_fixture.js", deno_core::FastString::Static(setup))
+            .unwrap();
+        (tokio_rt, runtime)
+    }
+
+    /// Drive `started` to its answer on `runtime`'s event loop, as the pool does.
+    fn settle(
+        runtime: &mut deno_core::JsRuntime,
+        tokio_rt: &tokio::runtime::Runtime,
+        started: Started,
+        what: &str,
+        limits: &ResourceLimits,
+    ) -> WorkerResponse {
+        let value = match started {
+            Started::Answered(response) => return response,
+            Started::Settling(value) => value,
+        };
+        let (response_tx, mut response_rx) = oneshot::channel();
+        let started = PluginWorker::budget_of(runtime).active();
+        let mut calls = vec![PendingCall::new(
+            what.to_string(),
+            value,
+            response_tx,
+            started,
+        )];
+        tokio_rt.block_on(async {
+            // Deadlines are read off the budget clock, so re-check them periodically
+            let mut recheck = tokio::time::interval(Duration::from_millis(10));
+            std::future::poll_fn(|cx| {
+                let _ = PluginWorker::poll_calls(runtime, &mut calls, limits, cx);
+                while recheck.poll_tick(cx).is_ready() {}
+                if calls.is_empty() {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await
+        });
+        response_rx.try_recv().unwrap()
+    }
+
+    fn call_hook(
+        runtime: &mut deno_core::JsRuntime,
+        tokio_rt: &tokio::runtime::Runtime,
+        hook: &str,
+        args: &serde_json::Value,
+    ) -> WorkerResponse {
+        let started = PluginWorker::call_hook(runtime, hook, args);
+        let what = format!("Hook '{}'", hook);
+        settle(runtime, tokio_rt, started, &what, &test_limits())
+    }
+
+    fn test_limits() -> ResourceLimits {
+        ResourceLimits {
+            max_memory: 50 * 1024 * 1024,
+            max_cpu_time: Duration::from_secs(2),
+            max_operations: 100,
+            ..ResourceLimits::default()
+        }
+    }
+
+    #[test]
+    fn test_call_hook_passes_args_positionally() {
+        let (tokio_rt, mut runtime) =
+            hook_runtime("globalThis.__hooks__.sum = (a, b) => ({ total: a.n + b.n });");
+        let _guard = tokio_rt.enter();
+        let response = call_hook(
+            &mut runtime,
+            &tokio_rt,
+            "sum",
+            &serde_json::json!([{"n": 2}, {"n": 3}]),
+        );
+        match response {
+            WorkerResponse::Success(v) => assert_eq!(v["total"], 5),
+            other => panic!("Expected Success, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_call_hook_unregistered_returns_null() {
+        let (tokio_rt, mut runtime) = hook_runtime("");
+        let _guard = tokio_rt.enter();
+        let response = call_hook(
+            &mut runtime,
+            &tokio_rt,
+            "on_file_save",
+            &serde_json::json!([]),
+        );
+        assert!(matches!(
+            response,
+            WorkerResponse::Success(serde_json::Value::Null)
+        ));
+    }
+
+    #[test]
+    fn test_call_hook_rejects_injected_name_without_running_it() {
+        let (tokio_rt, mut runtime) = hook_runtime("globalThis.__hooks__.x = () => 1;");
+        let _guard = tokio_rt.enter();
+        let response = call_hook(
+            &mut runtime,
+            &tokio_rt,
+            "x(); globalThis.pwned = true; (function(){})",
+            &serde_json::json!([]),
+        );
+        assert!(matches!(response, WorkerResponse::Error(_)));
+
+        let pwned = runtime
+            .execute_script(
+                "pwned_check.js",
+                deno_core::FastString::Static("globalThis.pwned === true"),
+            )
+            .unwrap();
+        let scope = &mut runtime.handle_scope();
+        let local = deno_core::v8::Local::new(scope, pwned);
+        assert!(!local.is_true());
     }
 
     #[test]
-    fn test_cached_hook_name_different_hooks() {
-        let name1 = PluginWorker::cached_hook_name("activate");
-        let name2 = PluginWorker::cached_hook_name("deactivate");
-        assert_ne!(name1, name2);
-        assert_eq!(name1, "hook_activate");
-        assert_eq!(name2, "hook_deactivate");
+    fn test_call_hook_ignores_inherited_members() {
+        let (tokio_rt, mut runtime) =
+            hook_runtime("globalThis.__hooks__ = {}; globalThis.__hooks__.real = () => 1;");
+        let _guard = tokio_rt.enter();
+        let response = call_hook(
+            &mut runtime,
+            &tokio_rt,
+            "constructor",
+            &serde_json::json!([]),
+        );
+        assert!(matches!(
+            response,
+            WorkerResponse::Success(serde_json::Value::Null)
+        ));
     }
 
     #[test]
-    fn test_cached_hook_name_same_hook_returns_same_pointer() {
-        let name1 = PluginWorker::cached_hook_name("on_save");
-        let name2 = PluginWorker::cached_hook_name("on_save");
-        // Same &'static str pointer
-        assert!(std::ptr::eq(name1, name2));
+    fn test_call_hook_surfaces_thrown_errors() {
+        let (tokio_rt, mut runtime) =
+            hook_runtime("globalThis.__hooks__.boom = () => { throw new Error('kaboom'); };");
+        let _guard = tokio_rt.enter();
+        let response = call_hook(&mut runtime, &tokio_rt, "boom", &serde_json::json!([]));
+        match response {
+            WorkerResponse::Error(msg) => assert!(msg.contains("kaboom")),
+            other => panic!("Expected Error, got {:?}", other),
+        }
     }
 
     #[test]
@@ -573,6 +1520,446 @@ mod tests {
                 assert!(used > 0);
             }
             WorkerResponse::Error(e) => panic!("Unexpected error: {}", e),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_settling_drives_microtasks_on_persistent_runtime() {
+        let tokio_rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let _guard = tokio_rt.enter();
+        let mut runtime = deno_core::JsRuntime::new(deno_core::RuntimeOptions::default());
+
+        for round in 0..2 {
+            let script = format!(
+                "globalThis.__done_{round} = false; \
+                 Promise.resolve().then(() => {{ globalThis.__done_{round} = true; }});"
+            );
+            let started = PluginWorker::execute_code(&mut runtime, &script);
+            let response = settle(&mut runtime, &tokio_rt, started, "Script", &test_limits());
+            assert!(matches!(response, WorkerResponse::Success(_)));
+
+            let check = format!("globalThis.__done_{round} === true");
+            let value = runtime
+                .execute_script("pump_check.js", deno_core::FastString::Owned(check.into()))
+                .unwrap();
+            let scope = &mut runtime.handle_scope();
+            let local = deno_core::v8::Local::new(scope, value);
+            assert!(local.is_true(), "microtasks not drained in round {}", round);
+        }
+    }
+
+    #[test]
+    fn test_plugin_api_exposes_async_variants() {
+        let ext = crate::plugin_system::ops::skretchpad_plugin_ops::init_ops_and_esm();
+        let mut runtime = deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+            extensions: vec![ext],
+            ..Default::default()
+        });
+        runtime
+            .execute_script(
+                "plugin_api.js",
+                deno_core::FastString::Static(include_str!("../../js/plugin_api.js")),
+            )
+            .unwrap();
+
+        let value = runtime
+            .execute_script(
+                "api_check.js",
+                deno_core::FastString::Static(
+                    "typeof skretchpad.fs.readFileAsync === 'function' && \
+                     typeof skretchpad.network.fetchAsync === 'function' && \
+                     typeof skretchpad.commands.executeAsync === 'function'",
+                ),
+            )
+            .unwrap();
+        let scope = &mut runtime.handle_scope();
+        let local = deno_core::v8::Local::new(scope, value);
+        assert!(local.is_true());
+    }
+
+    fn run_and_settle(source: &'static str) -> WorkerResponse {
+        let tokio_rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let _guard = tokio_rt.enter();
+        let mut runtime = deno_core::JsRuntime::new(deno_core::RuntimeOptions::default());
+        let value = runtime
+            .execute_script("settle_test.js", deno_core::FastString::Static(source))
+            .unwrap();
+        settle(
+            &mut runtime,
+            &tokio_rt,
+            Started::Settling(PendingValue::Value(value)),
+            "Hook 'test'",
+            &test_limits(),
+        )
+    }
+
+    #[test]
+    fn test_settling_returns_plain_values() {
+        match run_and_settle("({ files: ['a.rs'], count: 1 })") {
+            WorkerResponse::Success(v) => {
+                assert_eq!(v["files"][0], "a.rs");
+                assert_eq!(v["count"], 1);
+            }
+            other => panic!("Expected Success, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_settling_awaits_promises() {
+        match run_and_settle("Promise.resolve().then(() => ({ formatted: 'ok' }))") {
+            WorkerResponse::Success(v) => assert_eq!(v["formatted"], "ok"),
+            other => panic!("Expected Success, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_settling_undefined_is_null() {
+        match run_and_settle("undefined") {
+            WorkerResponse::Success(v) => assert!(v.is_null()),
+            other => panic!("Expected Success, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_settling_rejected_promise_is_error() {
+        match run_and_settle("Promise.reject(new Error('lint failed'))") {
+            WorkerResponse::Error(msg) => assert!(msg.contains("lint failed")),
+            other => panic!("Expected Error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_settling_function_is_serialization_error() {
+        match run_and_settle("(function () {})") {
+            WorkerResponse::SerializationError(msg) => {
+                assert!(msg.contains("Hook 'test'"));
+                assert!(msg.contains("function"));
+            }
+            other => panic!("Expected SerializationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_worker_response_serialization_error_maps_to_plugin_error() {
+        let err = WorkerResponse::SerializationError("bad value".to_string())
+            .into_result()
+            .unwrap_err();
+        assert!(matches!(err, PluginError::SerializationError(_)));
+    }
+
+    #[test]
+    fn test_worker_response_limit_variants_map_to_plugin_errors() {
+        let err = WorkerResponse::Timeout {
+            duration: Duration::from_millis(250),
+        }
+        .into_result()
+        .unwrap_err();
+        assert!(matches!(err, PluginError::Timeout { .. }));
+
+        let err = WorkerResponse::MemoryLimitExceeded {
+            used: 20,
+            limit: 10,
+        }
+        .into_result()
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            PluginError::MemoryLimitExceeded {
+                used: 20,
+                limit: 10
+            }
+        ));
+    }
+
+    fn limited_runtime(
+        limits: &ResourceLimits,
+    ) -> (
+        tokio::runtime::Runtime,
+        deno_core::JsRuntime,
+        IsolateLimits,
+        Watchdog,
+    ) {
+        let tokio_rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut runtime = {
+            let _guard = tokio_rt.enter();
+            deno_core::JsRuntime::new(deno_core::RuntimeOptions {
+                create_params: Some(PluginWorker::create_params(limits)),
+                ..Default::default()
+            })
+        };
+        let isolate_limits = IsolateLimits::install(&mut runtime);
+        (
+            tokio_rt,
+            runtime,
+            isolate_limits,
+            Watchdog::spawn().unwrap(),
+        )
+    }
+
+    /// Start a call under `isolate_limits` and settle it, as the pool does.
+    fn run_limited(
+        isolate_limits: &IsolateLimits,
+        watchdog: &Watchdog,
+        tokio_rt: &tokio::runtime::Runtime,
+        runtime: &mut deno_core::JsRuntime,
+        limits: &ResourceLimits,
+        f: impl FnOnce(&mut deno_core::JsRuntime) -> Started,
+    ) -> WorkerResponse {
+        match isolate_limits.run(watchdog, runtime, limits, f) {
+            Ok(started) => settle(runtime, tokio_rt, started, "Script", limits),
+            Err(response) => response,
+        }
+    }
+
+    #[test]
+    fn test_isolate_limits_terminate_infinite_loop() {
+        let limits = ResourceLimits {
+            max_memory: 64 * 1024 * 1024,
+            max_cpu_time: Duration::from_millis(200),
+            max_operations: 100,
+            ..ResourceLimits::default()
+        };
+        let (tokio_rt, mut runtime, isolate_limits, watchdog) = limited_runtime(&limits);
+        let _guard = tokio_rt.enter();
+
+        let started = Instant::now();
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| PluginWorker::execute_code(rt, "while (true) {}"),
+        );
+        assert!(
+            matches!(response, WorkerResponse::Timeout { duration } if duration == limits.max_cpu_time),
+            "Expected Timeout, got {:?}",
+            response
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // The isolate is usable again once termination has been cancelled
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| PluginWorker::execute_code(rt, "1 + 1"),
+        );
+        match response {
+            WorkerResponse::Success(v) => assert_eq!(v, 2),
+            other => panic!("Expected Success, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_isolate_limits_terminate_looping_hook() {
+        let limits = ResourceLimits {
+            max_memory: 64 * 1024 * 1024,
+            max_cpu_time: Duration::from_millis(200),
+            max_operations: 100,
+            ..ResourceLimits::default()
+        };
+        let (tokio_rt, mut runtime, isolate_limits, watchdog) = limited_runtime(&limits);
+        let _guard = tokio_rt.enter();
+        runtime
+            .execute_script(
+                "hooks_fixture.js",
+                deno_core::FastString::Static(
+                    "globalThis.__hooks__ = Object.create(null);\n\
+                     globalThis.__hooks__.activate = () => { for (;;) {} };",
+                ),
+            )
+            .unwrap();
+
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| PluginWorker::call_hook(rt, "activate", &serde_json::json!([])),
+        );
+        assert!(
+            matches!(response, WorkerResponse::Timeout { .. }),
+            "Expected Timeout, got {:?}",
+            response
+        );
+    }
+
+    #[test]
+    fn test_isolate_limits_fast_script_is_not_terminated() {
+        let limits = ResourceLimits {
+            max_memory: 64 * 1024 * 1024,
+            max_cpu_time: Duration::from_millis(200),
+            max_operations: 100,
+            ..ResourceLimits::default()
+        };
+        let (tokio_rt, mut runtime, isolate_limits, watchdog) = limited_runtime(&limits);
+        let _guard = tokio_rt.enter();
+
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| PluginWorker::execute_code(rt, "'done'"),
+        );
+        assert!(matches!(response, WorkerResponse::Success(_)));
+
+        // A deadline from a finished run must not fire into the next one
+        std::thread::sleep(Duration::from_millis(300));
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| PluginWorker::execute_code(rt, "'still alive'"),
+        );
+        match response {
+            WorkerResponse::Success(v) => assert_eq!(v, "still alive"),
+            other => panic!("Expected Success, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_isolate_limits_do_not_fire_while_paused() {
+        let limits = ResourceLimits {
+            max_memory: 64 * 1024 * 1024,
+            max_cpu_time: Duration::from_millis(200),
+            max_operations: 100,
+            ..ResourceLimits::default()
+        };
+        let (tokio_rt, mut runtime, isolate_limits, watchdog) = limited_runtime(&limits);
+        let _guard = tokio_rt.enter();
+
+        // An op waiting on a consent prompt holds a pause for longer than the budget
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| {
+                let paused = PluginWorker::budget_of(rt).pause();
+                std::thread::sleep(Duration::from_millis(400));
+                drop(paused);
+                PluginWorker::execute_code(rt, "'answered'")
+            },
+        );
+        match response {
+            WorkerResponse::Success(v) => assert_eq!(v, "answered"),
+            other => panic!("Expected Success, got {:?}", other),
         }
     }
+
+    #[test]
+    fn test_execution_budget_pause_stops_the_clock() {
+        let budget = ExecutionBudget::default();
+        budget.start(Duration::from_millis(100));
+
+        let paused = budget.pause();
+        let nested = budget.pause();
+        std::thread::sleep(Duration::from_millis(150));
+        drop(paused);
+        assert_eq!(budget.remaining(), None);
+        drop(nested);
+        assert!(budget.remaining().unwrap() > Duration::from_millis(50));
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(budget.remaining(), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_execution_budget_within_excludes_paused_time() {
+        let budget = ExecutionBudget::default();
+        let answered = budget
+            .within(Duration::from_millis(100), async {
+                let _paused = budget.pause();
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                "answered"
+            })
+            .await;
+        assert_eq!(answered, Some("answered"));
+
+        let expired = budget
+            .within(
+                Duration::from_millis(100),
+                tokio::time::sleep(Duration::from_millis(250)),
+            )
+            .await;
+        assert!(expired.is_none());
+    }
+
+    #[test]
+    fn test_isolate_limits_terminate_heap_exhaustion() {
+        let limits = ResourceLimits {
+            max_memory: 32 * 1024 * 1024,
+            max_cpu_time: Duration::from_secs(30),
+            max_operations: 100,
+            ..ResourceLimits::default()
+        };
+        let (tokio_rt, mut runtime, isolate_limits, watchdog) = limited_runtime(&limits);
+        let _guard = tokio_rt.enter();
+
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| {
+                PluginWorker::execute_code(
+                    rt,
+                    "const hoard = []; while (true) { hoard.push(new Array(100000).fill(1)); }",
+                )
+            },
+        );
+        match response {
+            WorkerResponse::MemoryLimitExceeded { limit, .. } => {
+                assert_eq!(limit, limits.max_memory)
+            }
+            other => panic!("Expected MemoryLimitExceeded, got {:?}", other),
+        }
+
+        // The headroom granted to unwind is taken back for the next run
+        let mut stats = deno_core::v8::HeapStatistics::default();
+        runtime.v8_isolate().get_heap_statistics(&mut stats);
+        assert!(
+            stats.heap_size_limit() < 2 * limits.max_memory,
+            "heap limit left at {} bytes",
+            stats.heap_size_limit()
+        );
+        let response = run_limited(
+            &isolate_limits,
+            &watchdog,
+            &tokio_rt,
+            &mut runtime,
+            &limits,
+            |rt| {
+                PluginWorker::execute_code(
+                    rt,
+                    "const more = []; while (true) { more.push(new Array(100000).fill(1)); }",
+                )
+            },
+        );
+        assert!(
+            matches!(response, WorkerResponse::MemoryLimitExceeded { .. }),
+            "Expected MemoryLimitExceeded, got {:?}",
+            response
+        );
+    }
 }
@@ -0,0 +1,5 @@
+// src-tauri/src/security/mod.rs
+//
+// Security regression suite for the plugin system. Compiled for tests only.
+
+pub mod threat_matrix;
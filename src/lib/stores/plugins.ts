@@ -32,6 +32,9 @@ export interface PluginCapabilities {
     notifications: boolean;
     webview: boolean;
   };
+  audit?: {
+    read: boolean;
+  };
 }
 
 export type PluginLifecycleState = 'loaded' | 'activating' | 'active' | 'deactivating' | 'error';